@@ -12,6 +12,16 @@ fn benchmark_capture_all(c: &mut Criterion) {
         });
     });
 
+    // Reuses the cached output state, so this shows the per-capture cost
+    // without connecting and re-reading the outputs each time.
+    group.bench_function("capture_all_reused_instance", |b| {
+        let mut grim = Grim::new().expect("Failed to create Grim");
+        b.iter(|| {
+            let result = grim.capture_all().expect("Failed to capture");
+            black_box(result);
+        });
+    });
+
     group.finish();
 }
 
@@ -69,11 +79,78 @@ fn benchmark_get_outputs(c: &mut Criterion) {
     group.finish();
 }
 
+fn benchmark_thumbnail(c: &mut Criterion) {
+    let mut group = c.benchmark_group("output_thumbnail");
+
+    let mut grim = Grim::new().expect("Failed to create Grim");
+    let output = grim
+        .get_outputs()
+        .expect("Failed to get outputs")
+        .into_iter()
+        .next()
+        .expect("No outputs");
+    let name = output.name().to_string();
+    let max_width = (output.geometry().width() as u32 * output.scale() as u32 / 10).max(1);
+    let max_height = (output.geometry().height() as u32 * output.scale() as u32 / 10).max(1);
+
+    // Both produce a ~10% image; the scaled capture also allocates a
+    // full-resolution RGBA copy before resizing it.
+    group.bench_function("capture_output_with_scale_0.1", |b| {
+        b.iter(|| {
+            let result = grim
+                .capture_output_with_scale(&name, 0.1)
+                .expect("Failed to capture");
+            black_box(result);
+        });
+    });
+
+    group.bench_function("capture_output_thumbnail", |b| {
+        b.iter(|| {
+            let result = grim
+                .capture_output_thumbnail(&name, max_width, max_height)
+                .expect("Failed to capture");
+            black_box(result);
+        });
+    });
+
+    group.finish();
+}
+
+#[cfg(feature = "dmabuf")]
+fn benchmark_shm_vs_dmabuf(c: &mut Criterion) {
+    let mut group = c.benchmark_group("shm_vs_dmabuf_4k");
+
+    // Needs an output of at least 3840x2160 at layout position (0, 0).
+    let region = GrimBox::new(0, 0, 3840, 2160);
+
+    for (name, dmabuf) in [("shm", false), ("dmabuf", true)] {
+        group.bench_function(name, |b| {
+            let mut grim = Grim::new().expect("Failed to create Grim");
+            grim.set_dmabuf_enabled(dmabuf);
+            b.iter(|| {
+                let result = grim.capture_region(region).expect("Failed to capture");
+                black_box(result);
+            });
+        });
+    }
+
+    group.finish();
+}
+
 criterion_group!(
     benches,
     benchmark_capture_all,
     benchmark_capture_with_scale,
     benchmark_capture_region,
-    benchmark_get_outputs
+    benchmark_get_outputs,
+    benchmark_thumbnail
 );
+
+#[cfg(feature = "dmabuf")]
+criterion_group!(dmabuf_benches, benchmark_shm_vs_dmabuf);
+
+#[cfg(feature = "dmabuf")]
+criterion_main!(benches, dmabuf_benches);
+
+#[cfg(not(feature = "dmabuf"))]
 criterion_main!(benches);
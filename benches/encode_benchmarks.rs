@@ -1,4 +1,6 @@
-use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use criterion::{
+    black_box, criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion, Throughput,
+};
 use grim_rs::Grim;
 
 fn generate_test_data(width: u32, height: u32) -> Vec<u8> {
@@ -140,22 +142,125 @@ fn benchmark_ppm_encoding(c: &mut Criterion) {
     group.finish();
 }
 
-#[cfg(feature = "jpeg")]
+#[cfg(feature = "qoi")]
+fn benchmark_qoi_vs_png(c: &mut Criterion) {
+    let mut group = c.benchmark_group("qoi_vs_png");
+
+    let sizes = [("1920x1080", 1920, 1080), ("3840x2160", 3840, 2160)];
+    let grim = Grim::new().expect("Failed to create Grim");
+
+    for (name, width, height) in sizes.iter() {
+        let data = generate_test_data(*width, *height);
+        group.throughput(Throughput::Bytes(data.len() as u64));
+
+        group.bench_with_input(BenchmarkId::new("qoi", name), &data, |b, data| {
+            b.iter(|| {
+                let result = grim
+                    .to_qoi(data, *width, *height)
+                    .expect("Failed to encode QOI");
+                black_box(result);
+            });
+        });
+        group.bench_with_input(BenchmarkId::new("png_level_1", name), &data, |b, data| {
+            b.iter(|| {
+                let result = grim
+                    .to_png_with_compression(data, *width, *height, 1)
+                    .expect("Failed to encode PNG");
+                black_box(result);
+            });
+        });
+    }
+
+    group.finish();
+}
+
+fn benchmark_owned_vs_borrowed(c: &mut Criterion) {
+    let mut group = c.benchmark_group("owned_vs_borrowed_3840x2160");
+
+    let width = 3840;
+    let height = 2160;
+    let data = generate_test_data(width, height);
+    let grim = Grim::new().expect("Failed to create Grim");
+
+    group.throughput(Throughput::Bytes(data.len() as u64));
+
+    // The owned variants receive a fresh buffer from the (untimed) setup
+    // closure, mirroring `CaptureResult::into_data()`. The borrowed variants
+    // have to copy or re-pack the whole buffer inside the timed section.
+    group.bench_function("ppm_borrowed", |b| {
+        b.iter(|| {
+            black_box(
+                grim.to_ppm(&data, width, height)
+                    .expect("Failed to encode PPM"),
+            )
+        });
+    });
+    group.bench_function("ppm_owned", |b| {
+        b.iter_batched(
+            || data.clone(),
+            |data| {
+                black_box(
+                    grim.to_ppm_owned(data, width, height)
+                        .expect("Failed to encode PPM"),
+                )
+            },
+            BatchSize::LargeInput,
+        );
+    });
+
+    #[cfg(feature = "jpeg")]
+    {
+        group.bench_function("jpeg_borrowed", |b| {
+            b.iter(|| {
+                black_box(
+                    grim.to_jpeg_with_quality(&data, width, height, 80)
+                        .expect("Failed to encode JPEG"),
+                )
+            });
+        });
+        group.bench_function("jpeg_owned", |b| {
+            b.iter_batched(
+                || data.clone(),
+                |data| {
+                    black_box(
+                        grim.to_jpeg_owned(data, width, height, 80)
+                            .expect("Failed to encode JPEG"),
+                    )
+                },
+                BatchSize::LargeInput,
+            );
+        });
+    }
+
+    group.finish();
+}
+
 criterion_group!(
     benches,
     benchmark_png_encoding,
     benchmark_png_compression_levels,
-    benchmark_jpeg_encoding,
-    benchmark_jpeg_quality_levels,
-    benchmark_ppm_encoding
+    benchmark_ppm_encoding,
+    benchmark_owned_vs_borrowed
 );
 
-#[cfg(not(feature = "jpeg"))]
+#[cfg(feature = "jpeg")]
 criterion_group!(
-    benches,
-    benchmark_png_encoding,
-    benchmark_png_compression_levels,
-    benchmark_ppm_encoding
+    jpeg_benches,
+    benchmark_jpeg_encoding,
+    benchmark_jpeg_quality_levels
 );
 
+#[cfg(feature = "qoi")]
+criterion_group!(qoi_benches, benchmark_qoi_vs_png);
+
+#[cfg(all(feature = "jpeg", feature = "qoi"))]
+criterion_main!(benches, jpeg_benches, qoi_benches);
+
+#[cfg(all(feature = "jpeg", not(feature = "qoi")))]
+criterion_main!(benches, jpeg_benches);
+
+#[cfg(all(not(feature = "jpeg"), feature = "qoi"))]
+criterion_main!(benches, qoi_benches);
+
+#[cfg(all(not(feature = "jpeg"), not(feature = "qoi")))]
 criterion_main!(benches);
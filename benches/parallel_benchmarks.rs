@@ -0,0 +1,48 @@
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use grim_rs::Grim;
+
+fn generate_test_data(width: u32, height: u32) -> Vec<u8> {
+    // A gradient compresses like a real screenshot rather than a solid fill.
+    (0..width * height * 4).map(|i| (i % 253) as u8).collect()
+}
+
+fn benchmark_png_parallel(c: &mut Criterion) {
+    let mut group = c.benchmark_group("png_parallel_3840x2160");
+
+    let width = 3840;
+    let height = 2160;
+    let data = generate_test_data(width, height);
+    let grim = Grim::new().expect("Failed to create Grim");
+
+    group.throughput(Throughput::Bytes(data.len() as u64));
+    group.sample_size(10);
+
+    group.bench_function("serial", |b| {
+        b.iter(|| {
+            black_box(
+                grim.to_png_with_compression(&data, width, height, 6)
+                    .expect("Failed to encode PNG"),
+            )
+        });
+    });
+
+    for threads in [2, 4, 8] {
+        group.bench_with_input(
+            BenchmarkId::new("parallel", threads),
+            &threads,
+            |b, &threads| {
+                b.iter(|| {
+                    black_box(
+                        grim.to_png_parallel(&data, width, height, 6, threads)
+                            .expect("Failed to encode PNG"),
+                    )
+                });
+            },
+        );
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, benchmark_png_parallel);
+criterion_main!(benches);
@@ -0,0 +1,38 @@
+use grim_rs::{AsyncGrim, CaptureParameters};
+use std::time::Duration;
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    env_logger::init();
+
+    let grim = AsyncGrim::new().await?;
+
+    // A ticker keeps running while the capture is in flight, showing that
+    // the runtime is not blocked by the Wayland dispatch loop.
+    let ticker = tokio::spawn(async {
+        let mut ticks = 0u32;
+        loop {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            ticks += 1;
+            if ticks.is_multiple_of(10) {
+                println!("runtime still responsive ({} ticks)", ticks);
+            }
+        }
+    });
+
+    let result = grim.capture_all_async().await?;
+    println!("Captured {}x{}", result.width(), result.height());
+
+    let outputs = grim.get_outputs_async().await?;
+    let parameters = outputs
+        .iter()
+        .map(|output| CaptureParameters::new(output.name()))
+        .collect();
+    let results = grim.capture_outputs_async(parameters).await?;
+    for (name, capture) in results.outputs() {
+        println!("{}: {}x{}", name, capture.width(), capture.height());
+    }
+
+    ticker.abort();
+    Ok(())
+}
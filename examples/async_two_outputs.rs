@@ -0,0 +1,40 @@
+use grim_rs::AsyncGrim;
+
+/// Capture the first two outputs at the same time.
+///
+/// Each `AsyncGrim` owns its own Wayland connection, so both frames are
+/// requested up front and the task awaits the compositor's replies on the
+/// two sockets at once, without tying up a thread per capture.
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    env_logger::init();
+
+    let first = AsyncGrim::new().await?;
+    let outputs = first.get_outputs_async().await?;
+    if outputs.len() < 2 {
+        eprintln!("This example needs at least two outputs");
+        return Ok(());
+    }
+    let second = AsyncGrim::new().await?;
+
+    let (left, right) = tokio::join!(
+        first.capture_output_async(outputs[0].name()),
+        second.capture_output_async(outputs[1].name()),
+    );
+
+    for (output, result) in outputs.iter().zip([left?, right?]) {
+        let filename = format!("{}.png", output.name());
+        first.shared().with(|grim| {
+            grim.save_png(result.data(), result.width(), result.height(), &filename)
+        })?;
+        println!(
+            "{}: {}x{} -> {}",
+            output.name(),
+            result.width(),
+            result.height(),
+            filename
+        );
+    }
+
+    Ok(())
+}
@@ -0,0 +1,72 @@
+use grim_rs::{Box, CaptureParameters, CaptureResult, Grim, Result};
+
+/// Capture every output separately and assemble them into one image using
+/// the placements recorded with the captures.
+fn main() -> Result<()> {
+    let mut grim = Grim::new()?;
+
+    let parameters = grim
+        .get_outputs()?
+        .iter()
+        .map(|output| CaptureParameters::new(output.name()))
+        .collect();
+    let results = grim.capture_outputs(parameters)?;
+
+    let geometries: Vec<Box> = results
+        .placements()
+        .values()
+        .map(|placement| *placement.geometry())
+        .collect();
+    let Some(layout) = Box::union_all(&geometries) else {
+        eprintln!("No outputs captured");
+        return Ok(());
+    };
+
+    let canvas_width = layout.width() as usize;
+    let canvas_height = layout.height() as usize;
+    let mut canvas = vec![0u8; canvas_width * canvas_height * 4];
+
+    for (name, capture) in results.outputs() {
+        let Some(placement) = results.placement(name) else {
+            continue;
+        };
+        let geometry = placement.geometry();
+        println!(
+            "{}: {}x{} at {},{} (scale {}), captured {}x{}",
+            name,
+            geometry.width(),
+            geometry.height(),
+            geometry.x(),
+            geometry.y(),
+            placement.scale(),
+            capture.width(),
+            capture.height()
+        );
+
+        // Bring HiDPI captures down to logical size so every output lines up.
+        let logical = capture.scale_to(geometry.width() as u32, geometry.height() as u32)?;
+        blit(
+            &mut canvas,
+            canvas_width,
+            &logical,
+            (geometry.x() - layout.x()) as usize,
+            (geometry.y() - layout.y()) as usize,
+        );
+    }
+
+    let filename = "multi_output_stitched.png";
+    grim.save_png(&canvas, canvas_width as u32, canvas_height as u32, filename)?;
+    println!(
+        "Saved {}x{} composite to {}",
+        canvas_width, canvas_height, filename
+    );
+    Ok(())
+}
+
+fn blit(canvas: &mut [u8], canvas_width: usize, capture: &CaptureResult, x: usize, y: usize) {
+    let row_len = capture.width() as usize * 4;
+    for (row, pixels) in capture.data().chunks_exact(row_len).enumerate() {
+        let start = ((y + row) * canvas_width + x) * 4;
+        canvas[start..start + row_len].copy_from_slice(pixels);
+    }
+}
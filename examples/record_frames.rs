@@ -0,0 +1,41 @@
+use grim_rs::{CaptureOptions, Error, Grim, Result};
+
+const FRAMES: usize = 30;
+
+/// Capture 30 frames of the first output into numbered PNG files.
+///
+/// Frames only arrive when the output changes, so move a window or the
+/// cursor around while this runs.
+fn main() -> Result<()> {
+    let mut grim = Grim::new()?;
+    let output = grim
+        .get_outputs()?
+        .into_iter()
+        .next()
+        .ok_or(Error::NoOutputs)?;
+    println!("Recording {} frames of {}", FRAMES, output.name());
+
+    let mut stream = grim.capture_stream(output.name(), &CaptureOptions::new())?;
+    let mut frames = Vec::with_capacity(FRAMES);
+    while frames.len() < FRAMES {
+        match stream.next_frame() {
+            Ok(frame) => frames.push(frame),
+            Err(Error::Timeout { .. }) => println!("No change on {}, still waiting", output.name()),
+            Err(e) => return Err(e),
+        }
+    }
+    drop(stream);
+
+    let start = frames[0].timestamp();
+    for (index, frame) in frames.iter().enumerate() {
+        let filename = format!("frame_{:03}.png", index);
+        let capture = frame.capture();
+        grim.save_png(capture.data(), capture.width(), capture.height(), &filename)?;
+        println!(
+            "{} at +{:.3}s",
+            filename,
+            frame.timestamp().saturating_sub(start).as_secs_f64()
+        );
+    }
+    Ok(())
+}
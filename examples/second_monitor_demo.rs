@@ -38,6 +38,16 @@ fn main() -> Result<()> {
             output.geometry().height()
         );
         println!("Scale: {}x", output.scale());
+        if let Some(refresh_mhz) = output.refresh_rate_mhz() {
+            println!("Refresh rate: {:.2} Hz", refresh_mhz as f64 / 1000.0);
+        }
+        if !output.make().is_empty() || !output.model().is_empty() {
+            println!("Monitor: {} {}", output.make(), output.model());
+        }
+        println!("Transform: {:?}", output.transform());
+        if let Some((width_mm, height_mm)) = output.physical_size_mm() {
+            println!("Physical size: {}x{} mm", width_mm, height_mm);
+        }
         if let Some(desc) = output.description() {
             println!("Description: {}", desc);
         }
@@ -62,7 +72,7 @@ fn main() -> Result<()> {
     let result = grim.capture_output(second_output.name())?;
     println!("Captured: {}x{} pixels", result.width(), result.height());
     let filename = generate_filename("full", "png");
-    grim.save_png(&result.data(), result.width(), result.height(), &filename)?;
+    grim.save_png(result.data(), result.width(), result.height(), &filename)?;
     println!("Saved: {}\n", filename);
 
     println!("Capturing second monitor with different scales...");
@@ -76,7 +86,7 @@ fn main() -> Result<()> {
     );
     let filename = generate_filename("half_scale", "png");
     grim.save_png(
-        &result_half.data(),
+        result_half.data(),
         result_half.width(),
         result_half.height(),
         &filename,
@@ -92,7 +102,7 @@ fn main() -> Result<()> {
     );
     let filename = generate_filename("quarter_scale", "png");
     grim.save_png(
-        &result_quarter.data(),
+        result_quarter.data(),
         result_quarter.width(),
         result_quarter.height(),
         &filename,
@@ -113,7 +123,7 @@ fn main() -> Result<()> {
     let result = grim.capture_region(region)?;
     println!("Captured: {}x{} pixels", result.width(), result.height());
     let filename = generate_filename("top_left", "png");
-    grim.save_png(&result.data(), result.width(), result.height(), &filename)?;
+    grim.save_png(result.data(), result.width(), result.height(), &filename)?;
     println!("Saved: {}\n", filename);
 
     println!("- Center region (800x600)...");
@@ -128,7 +138,7 @@ fn main() -> Result<()> {
     let result = grim.capture_region(region)?;
     println!("Captured: {}x{} pixels", result.width(), result.height());
     let filename = generate_filename("center", "png");
-    grim.save_png(&result.data(), result.width(), result.height(), &filename)?;
+    grim.save_png(result.data(), result.width(), result.height(), &filename)?;
     println!("Saved: {}\n", filename);
 
     println!("- Bottom-right corner (400x300)...");
@@ -143,7 +153,7 @@ fn main() -> Result<()> {
     let result = grim.capture_region(region)?;
     println!("Captured: {}x{} pixels", result.width(), result.height());
     let filename = generate_filename("bottom_right", "png");
-    grim.save_png(&result.data(), result.width(), result.height(), &filename)?;
+    grim.save_png(result.data(), result.width(), result.height(), &filename)?;
     println!("Saved: {}\n", filename);
 
     println!("Saving second monitor in different formats...");
@@ -151,31 +161,25 @@ fn main() -> Result<()> {
 
     println!("- PNG (default compression)...");
     let filename = generate_filename("format", "png");
-    grim.save_png(&result.data(), result.width(), result.height(), &filename)?;
+    grim.save_png(result.data(), result.width(), result.height(), &filename)?;
     println!("Saved: {}", filename);
 
     println!("- PNG (best compression)...");
     let filename = generate_filename("format_best_comp", "png");
-    grim.save_png_with_compression(
-        &result.data(),
-        result.width(),
-        result.height(),
-        &filename,
-        9,
-    )?;
+    grim.save_png_with_compression(result.data(), result.width(), result.height(), &filename, 9)?;
     println!("Saved: {}", filename);
 
     #[cfg(feature = "jpeg")]
     {
         println!("- JPEG (default quality)...");
         let filename = generate_filename("format", "jpg");
-        grim.save_jpeg(&result.data(), result.width(), result.height(), &filename)?;
+        grim.save_jpeg(result.data(), result.width(), result.height(), &filename)?;
         println!("Saved: {}", filename);
 
         println!("- JPEG (quality 95)...");
         let filename = generate_filename("format_q95", "jpg");
         grim.save_jpeg_with_quality(
-            &result.data(),
+            result.data(),
             result.width(),
             result.height(),
             &filename,
@@ -186,7 +190,7 @@ fn main() -> Result<()> {
 
     println!("- PPM (uncompressed)...");
     let filename = generate_filename("format", "ppm");
-    grim.save_ppm(&result.data(), result.width(), result.height(), &filename)?;
+    grim.save_ppm(result.data(), result.width(), result.height(), &filename)?;
     println!("Saved: {}\n", filename);
 
     println!("Capturing scaled regions...");
@@ -203,7 +207,7 @@ fn main() -> Result<()> {
     let result = grim.capture_region_with_scale(region, 0.75)?;
     println!("Captured: {}x{} pixels", result.width(), result.height());
     let filename = generate_filename("center_scaled", "png");
-    grim.save_png(&result.data(), result.width(), result.height(), &filename)?;
+    grim.save_png(result.data(), result.width(), result.height(), &filename)?;
     println!("Saved: {}\n", filename);
     println!("Capturing horizontal strip from second monitor...");
     let strip_height = (200).min(geom.height());
@@ -216,7 +220,7 @@ fn main() -> Result<()> {
     let result = grim.capture_region(region)?;
     println!("Captured: {}x{} pixels", result.width(), result.height());
     let filename = generate_filename("horizontal_strip", "png");
-    grim.save_png(&result.data(), result.width(), result.height(), &filename)?;
+    grim.save_png(result.data(), result.width(), result.height(), &filename)?;
     println!("Saved: {}\n", filename);
     println!("Capturing vertical strip from second monitor...");
     let strip_width = (200).min(geom.width());
@@ -229,21 +233,21 @@ fn main() -> Result<()> {
     let result = grim.capture_region(region)?;
     println!("Captured: {}x{} pixels", result.width(), result.height());
     let filename = generate_filename("vertical_strip", "png");
-    grim.save_png(&result.data(), result.width(), result.height(), &filename)?;
+    grim.save_png(result.data(), result.width(), result.height(), &filename)?;
     println!("Saved: {}\n", filename);
     println!("Converting to different formats in memory...");
     let result = grim.capture_output(second_output.name())?;
 
-    let png_bytes = grim.to_png(&result.data(), result.width(), result.height())?;
+    let png_bytes = grim.to_png(result.data(), result.width(), result.height())?;
     println!("PNG bytes: {} bytes", png_bytes.len());
 
     #[cfg(feature = "jpeg")]
     {
-        let jpeg_bytes = grim.to_jpeg(&result.data(), result.width(), result.height())?;
+        let jpeg_bytes = grim.to_jpeg(result.data(), result.width(), result.height())?;
         println!("JPEG bytes: {} bytes", jpeg_bytes.len());
     }
 
-    let ppm_bytes = grim.to_ppm(&result.data(), result.width(), result.height())?;
+    let ppm_bytes = grim.to_ppm(result.data(), result.width(), result.height())?;
     println!("PPM bytes: {} bytes\n", ppm_bytes.len());
 
     println!("Creating grid of small captures (4x4)...");
@@ -261,7 +265,7 @@ fn main() -> Result<()> {
             );
             let result = grim.capture_region(region)?;
             let filename = generate_filename(&format!("grid_{}_{}", row, col), "png");
-            grim.save_png(&result.data(), result.width(), result.height(), &filename)?;
+            grim.save_png(result.data(), result.width(), result.height(), &filename)?;
         }
     }
     println!(
@@ -0,0 +1,259 @@
+use crate::wayland_capture::{FrameJob, FrameProgress};
+use crate::{
+    Box, CaptureOptions, CaptureParameters, CaptureResult, Error, Grim, MultiOutputCaptureResult,
+    Output, Result, SharedGrim,
+};
+use std::os::fd::{AsRawFd, RawFd};
+use std::sync::Arc;
+use tokio::io::unix::AsyncFd;
+use tokio::io::Interest;
+
+/// Asynchronous front-end to [`Grim`] for use inside a tokio runtime.
+///
+/// A capture waits for the compositor to copy each frame, which can take
+/// hundreds of milliseconds on large outputs. `AsyncGrim` sends the
+/// screencopy requests and then awaits readiness of the Wayland socket
+/// through the runtime's reactor ([`AsyncFd`]), dispatching the frame's
+/// events as they arrive, so a capture in progress doesn't occupy any
+/// thread. Work that has no non-blocking form still runs on tokio's
+/// blocking pool: re-reading the outputs after a hotplug, compositing and
+/// scaling the frames, dmabuf copies, captures on compositors that only
+/// offer ext-image-copy-capture, and [`capture_outputs_async`](Self::capture_outputs_async).
+///
+/// The handle is cheap to clone; all clones share one Wayland connection
+/// (see [`SharedGrim`]) and their captures are executed one after another.
+/// To capture several outputs in parallel, create one `AsyncGrim` per
+/// output with [`AsyncGrim::new`] so each gets its own connection.
+///
+/// The async methods coexist with the synchronous API on [`Grim`]; convert an
+/// existing instance with [`Grim::into_async`].
+///
+/// # Runtime requirements
+///
+/// Requires the `tokio` feature (also available as `async`). Every method
+/// must be awaited from within a tokio runtime with its IO and time drivers
+/// enabled (`#[tokio::main]` enables both). The socket is registered with
+/// the runtime of the first capture, so keep using a handle on that
+/// runtime. Both the current-thread and the multi-thread runtime flavours
+/// work.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use grim_rs::AsyncGrim;
+///
+/// #[tokio::main]
+/// async fn main() -> grim_rs::Result<()> {
+///     let grim = AsyncGrim::new().await?;
+///     let result = grim.capture_all_async().await?;
+///     println!("Captured {}x{}", result.width(), result.height());
+///     Ok(())
+/// }
+/// ```
+#[derive(Clone)]
+pub struct AsyncGrim {
+    /// The Wayland socket as registered with the runtime, set up by the
+    /// first capture. Holding the lock gives a capture sole use of it.
+    socket: Arc<tokio::sync::Mutex<Option<AsyncFd<ConnectionFd>>>>,
+    inner: SharedGrim,
+}
+
+impl AsyncGrim {
+    /// Connect to the Wayland compositor without blocking the calling task.
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`Grim::new`].
+    pub async fn new() -> Result<Self> {
+        let grim = run_blocking(Grim::new).await?;
+        Ok(Self::from_grim(grim))
+    }
+
+    /// Wrap an existing [`Grim`] instance.
+    pub fn from_grim(grim: Grim) -> Self {
+        Self::from_shared(SharedGrim::from_grim(grim))
+    }
+
+    /// Wrap an existing [`SharedGrim`] handle.
+    ///
+    /// Synchronous users of the same handle and this async front-end share
+    /// the connection and its lock.
+    pub fn from_shared(inner: SharedGrim) -> Self {
+        Self {
+            socket: Arc::new(tokio::sync::Mutex::new(None)),
+            inner,
+        }
+    }
+
+    /// Get the underlying [`SharedGrim`] handle.
+    pub fn shared(&self) -> &SharedGrim {
+        &self.inner
+    }
+
+    /// Get information about available display outputs.
+    ///
+    /// See [`Grim::get_outputs`].
+    pub async fn get_outputs_async(&self) -> Result<Vec<Output>> {
+        let inner = self.inner.clone();
+        run_blocking(move || inner.get_outputs()).await
+    }
+
+    /// Capture a screenshot as described by `options`.
+    ///
+    /// See [`Grim::capture`]. The capture timeout set with
+    /// [`Grim::set_capture_timeout`] applies to each frame as a whole.
+    pub async fn capture_async(&self, options: &CaptureOptions) -> Result<CaptureResult> {
+        let mut socket = self.socket.lock().await;
+        let inner = self.inner.clone();
+        let options = options.clone();
+        let plan =
+            run_blocking(move || inner.with(|grim| grim.platform_capture.plan_capture(&options)))
+                .await?;
+
+        let mut frames = Vec::new();
+        for job in plan.jobs() {
+            frames.push(self.capture_frame(&mut socket, job).await?);
+        }
+        run_blocking(move || {
+            let mut frames = frames.into_iter();
+            plan.assemble(|_| frames.next().ok_or(Error::CaptureFailed))
+        })
+        .await
+    }
+
+    /// Capture the entire screen (all outputs).
+    ///
+    /// See [`Grim::capture_all`].
+    pub async fn capture_all_async(&self) -> Result<CaptureResult> {
+        self.capture_async(&CaptureOptions::new()).await
+    }
+
+    /// Capture a specific output by name.
+    ///
+    /// See [`Grim::capture_output`].
+    pub async fn capture_output_async(&self, output_name: &str) -> Result<CaptureResult> {
+        self.capture_async(&CaptureOptions::new().output(output_name))
+            .await
+    }
+
+    /// Capture a specific region.
+    ///
+    /// See [`Grim::capture_region`].
+    pub async fn capture_region_async(&self, region: Box) -> Result<CaptureResult> {
+        self.capture_async(&CaptureOptions::new().region(region))
+            .await
+    }
+
+    /// Capture multiple outputs with different parameters.
+    ///
+    /// See [`Grim::capture_outputs`]. Unlike the other capture methods this
+    /// runs the blocking capture on tokio's blocking pool.
+    pub async fn capture_outputs_async(
+        &self,
+        parameters: Vec<CaptureParameters>,
+    ) -> Result<MultiOutputCaptureResult> {
+        let inner = self.inner.clone();
+        run_blocking(move || inner.capture_outputs(parameters)).await
+    }
+}
+
+impl AsyncGrim {
+    /// Copy one frame, awaiting the compositor's events on the socket.
+    async fn capture_frame(
+        &self,
+        socket: &mut Option<AsyncFd<ConnectionFd>>,
+        job: FrameJob,
+    ) -> Result<CaptureResult> {
+        let (pending, timeout) = {
+            let mut grim = self.inner.lock();
+            let capture = &mut grim.platform_capture;
+            if socket.is_none() {
+                let fd = ConnectionFd(capture.connection_fd());
+                match AsyncFd::with_interest(fd, Interest::READABLE) {
+                    Ok(fd) => *socket = Some(fd),
+                    Err(e) => log::debug!("Cannot watch the Wayland socket: {}", e),
+                }
+            }
+            let pending = match socket {
+                Some(_) => capture.begin_frame(&job)?,
+                None => None,
+            };
+            (pending, capture.capture_timeout())
+        };
+        let (socket, mut pending) = match (socket.as_ref(), pending) {
+            (Some(socket), Some(pending)) => (socket, pending),
+            _ => return self.capture_frame_blocking(job).await,
+        };
+
+        let deadline = tokio::time::Instant::now() + timeout;
+        loop {
+            let progress = self
+                .inner
+                .with(|grim| grim.platform_capture.poll_frame(&mut pending))?;
+            match progress {
+                FrameProgress::Done(capture) => return Ok(capture),
+                FrameProgress::NeedsBlocking => {
+                    drop(pending);
+                    return self.capture_frame_blocking(job).await;
+                }
+                FrameProgress::Pending => {}
+            }
+
+            let mut ready = match tokio::time::timeout_at(deadline, socket.readable()).await {
+                Ok(ready) => ready.map_err(Error::Io)?,
+                Err(_) => return Err(Error::Timeout { waited: timeout }),
+            };
+            // Hold the lock so a synchronous capture on the same connection
+            // isn't reading from the socket at the same time.
+            if !self.inner.with(|_| pending.read_events())? {
+                ready.clear_ready();
+            }
+        }
+    }
+
+    /// Copy one frame on the blocking pool.
+    async fn capture_frame_blocking(&self, job: FrameJob) -> Result<CaptureResult> {
+        let inner = self.inner.clone();
+        run_blocking(move || inner.with(|grim| grim.platform_capture.capture_job(&job))).await
+    }
+}
+
+/// The Wayland socket, borrowed so [`AsyncFd`] can watch it.
+///
+/// The connection owns the descriptor and closes it; `AsyncGrim` keeps the
+/// connection alive for as long as this is registered.
+struct ConnectionFd(RawFd);
+
+impl AsRawFd for ConnectionFd {
+    fn as_raw_fd(&self) -> RawFd {
+        self.0
+    }
+}
+
+impl From<Grim> for AsyncGrim {
+    fn from(grim: Grim) -> Self {
+        Self::from_grim(grim)
+    }
+}
+
+impl From<SharedGrim> for AsyncGrim {
+    fn from(inner: SharedGrim) -> Self {
+        Self::from_shared(inner)
+    }
+}
+
+/// Run `f` on tokio's blocking thread pool and wait for its result.
+///
+/// A panic inside `f` is resumed on the awaiting task, matching what the
+/// synchronous API would have done.
+async fn run_blocking<F, T>(f: F) -> Result<T>
+where
+    F: FnOnce() -> Result<T> + Send + 'static,
+    T: Send + 'static,
+{
+    match tokio::task::spawn_blocking(f).await {
+        Ok(result) => result,
+        Err(e) if e.is_panic() => std::panic::resume_unwind(e.into_panic()),
+        Err(_) => Err(Error::CaptureFailed),
+    }
+}
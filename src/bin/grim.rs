@@ -1,196 +1,74 @@
-use grim_rs::{Box as GrimBox, CaptureParameters, Grim};
+#[path = "grim/cli.rs"]
+mod cli;
+
+use cli::{CliError, FileType, Options};
+use grim_rs::{Grim, SelectionOptions};
 use std::env;
 use std::fs;
 use std::io::{self, BufRead};
 use std::path::{Path, PathBuf};
 
 fn main() -> grim_rs::Result<()> {
-    let args: Vec<String> = env::args().collect();
-    let mut opts = Options::default();
-    let mut output_file = None;
-    let mut arg_idx = 1;
-
-    while arg_idx < args.len() {
-        match args[arg_idx].as_str() {
-            "-h" | "--help" => {
-                print_help();
-                return Ok(());
-            }
-            "-s" => {
-                arg_idx += 1;
-                if arg_idx >= args.len() {
-                    eprintln!("Error: -s requires an argument");
-                    std::process::exit(1);
-                }
-                opts.scale = Some(args[arg_idx].parse::<f64>().map_err(|_| {
-                    grim_rs::Error::Io(std::io::Error::new(
-                        std::io::ErrorKind::InvalidInput,
-                        "Invalid scale factor",
-                    ))
-                })?);
-            }
-            "-g" => {
-                arg_idx += 1;
-                if arg_idx >= args.len() {
-                    eprintln!("Error: -g requires an argument");
-                    std::process::exit(1);
-                }
-                if args[arg_idx] == "-" {
-                    opts.geometry = Some(Grim::read_region_from_stdin()?);
-                } else {
-                    opts.geometry = Some(args[arg_idx].parse()?);
-                }
-            }
-            "-t" => {
-                arg_idx += 1;
-                if arg_idx >= args.len() {
-                    eprintln!("Error: -t requires an argument");
-                    std::process::exit(1);
-                }
-                match args[arg_idx].as_str() {
-                    "png" => {
-                        opts.filetype = FileType::Png;
-                    }
-                    "ppm" => {
-                        opts.filetype = FileType::Ppm;
-                    }
-                    "jpeg" => {
-                        opts.filetype = FileType::Jpeg;
-                    }
-                    _ => {
-                        eprintln!("Error: invalid filetype: {}", args[arg_idx]);
-                        std::process::exit(1);
-                    }
-                }
-            }
-            "-q" => {
-                arg_idx += 1;
-                if arg_idx >= args.len() {
-                    eprintln!("Error: -q requires an argument");
-                    std::process::exit(1);
-                }
-                let quality: i32 = args[arg_idx].parse().map_err(|_| {
-                    grim_rs::Error::Io(std::io::Error::new(
-                        std::io::ErrorKind::InvalidInput,
-                        "Invalid quality value",
-                    ))
-                })?;
-                if !(0..=100).contains(&quality) {
-                    eprintln!("Error: JPEG quality must be between 0 and 100");
-                    std::process::exit(1);
-                }
-                opts.jpeg_quality = quality as u8;
-            }
-            "-l" => {
-                arg_idx += 1;
-                if arg_idx >= args.len() {
-                    eprintln!("Error: -l requires an argument");
-                    std::process::exit(1);
-                }
-                let level: i32 = args[arg_idx].parse().map_err(|_| {
-                    grim_rs::Error::Io(std::io::Error::new(
-                        std::io::ErrorKind::InvalidInput,
-                        "Invalid compression level",
-                    ))
-                })?;
-                if level > 9 {
-                    eprintln!("Error: PNG compression level must be between 0 and 9");
-                    std::process::exit(1);
-                }
-                opts.png_level = level as u8;
-            }
-            "-o" => {
-                arg_idx += 1;
-                if arg_idx >= args.len() {
-                    eprintln!("Error: -o requires an argument");
-                    std::process::exit(1);
-                }
-                opts.output_name = Some(args[arg_idx].clone());
-            }
-            "-c" => {
-                opts.with_cursor = true;
-            }
-            _ => {
-                if output_file.is_none() {
-                    output_file = Some(args[arg_idx].clone());
-                } else {
-                    eprintln!("Error: too many arguments");
-                    std::process::exit(1);
-                }
-            }
+    let args: Vec<String> = env::args().skip(1).collect();
+    let (mut opts, output_file) = match cli::parse_args(&args) {
+        Ok(parsed) => parsed,
+        Err(e) => exit_with_usage_error(e),
+    };
+    if opts.help {
+        print_help();
+        return Ok(());
+    }
+
+    if opts.interactive {
+        let mut selection = SelectionOptions::new();
+        if let Some((width, height)) = opts.aspect_ratio {
+            selection = selection.aspect_ratio(width, height);
         }
-        arg_idx += 1;
+        opts.geometry = match grim_rs::select_region_with_options(&selection) {
+            Ok(region) => Some(region),
+            // Distinct from failures (status 1) so scripts can tell them apart.
+            Err(grim_rs::Error::SelectionCancelled) => {
+                eprintln!("Selection cancelled");
+                std::process::exit(2);
+            }
+            Err(e) => return Err(e),
+        };
     }
 
     let output_file = if let Some(file) = output_file {
         file
+    } else if opts.clipboard {
+        String::new()
     } else {
         generate_default_filename(opts.filetype)?
     };
 
     let mut grim = Grim::new()?;
 
-    let result = if let Some(ref output_name) = opts.output_name {
-        if opts.with_cursor {
-            let mut params =
-                CaptureParameters::new(output_name.clone()).overlay_cursor(opts.with_cursor);
-            if let Some(region) = opts.geometry {
-                params = params.region(region);
-            }
-            if let Some(scale) = opts.scale {
-                params = params.scale(scale);
-            }
-            let multi_result =
-                grim.capture_outputs_with_scale(vec![params], opts.scale.unwrap_or(1.0))?;
-            if let Some(capture_result) = multi_result.get(output_name) {
-                capture_result.clone()
-            } else {
-                return Err(grim_rs::Error::OutputNotFound(output_name.clone()));
-            }
+    if cli::uses_png_fast_path(&opts) {
+        let png_data = if let Some(ref output_name) = opts.output_name {
+            grim.capture_output_to_png(output_name, opts.png_level)?
+        } else if let Some(geometry) = opts.geometry {
+            grim.capture_region_to_png(geometry, opts.png_level)?
         } else {
-            grim.capture_output_with_scale(output_name, opts.scale.unwrap_or(1.0))?
-        }
-    } else if let Some(ref geometry) = opts.geometry {
-        grim.capture_region_with_scale(*geometry, opts.scale.unwrap_or(1.0))?
-    } else {
-        grim.capture_all_with_scale(opts.scale.unwrap_or(1.0))?
-    };
+            grim.capture_all_to_png(opts.png_level)?
+        };
+        return write_png_data(&png_data, &output_file);
+    }
+
+    let result = grim.capture(&cli::capture_options(&opts))?;
 
+    if opts.clipboard {
+        return copy_to_clipboard(&grim, &result, &opts);
+    }
     save_or_write_result(&grim, &result, &output_file, &opts)?;
 
     Ok(())
 }
 
-#[derive(Debug)]
-struct Options {
-    scale: Option<f64>,
-    geometry: Option<GrimBox>,
-    filetype: FileType,
-    jpeg_quality: u8,
-    png_level: u8,
-    output_name: Option<String>,
-    with_cursor: bool,
-}
-
-impl Default for Options {
-    fn default() -> Self {
-        Self {
-            scale: None,
-            geometry: None,
-            filetype: FileType::Png,
-            jpeg_quality: 80,
-            png_level: 6,
-            output_name: None,
-            with_cursor: false,
-        }
-    }
-}
-
-#[derive(Debug, Clone, Copy)]
-enum FileType {
-    Png,
-    Ppm,
-    Jpeg,
+fn exit_with_usage_error(error: CliError) -> ! {
+    eprintln!("Error: {}", error);
+    std::process::exit(1);
 }
 
 fn save_or_write_result(
@@ -206,6 +84,65 @@ fn save_or_write_result(
     }
 }
 
+/// Copy the capture to the clipboard and serve it until another client
+/// takes the selection, like `wl-copy --foreground`.
+#[cfg(feature = "clipboard")]
+fn copy_to_clipboard(
+    grim: &grim_rs::Grim,
+    result: &grim_rs::CaptureResult,
+    opts: &Options,
+) -> grim_rs::Result<()> {
+    let selection = match opts.filetype {
+        FileType::Png => grim.copy_to_clipboard(
+            result.data(),
+            result.width(),
+            result.height(),
+            image::ImageFormat::Png,
+        )?,
+        FileType::Jpeg => grim.copy_encoded_to_clipboard(
+            encode_jpeg(grim, result, opts)?,
+            image::ImageFormat::Jpeg,
+        )?,
+        _ => {
+            eprintln!("Error: only png and jpeg can be copied to the clipboard");
+            std::process::exit(1);
+        }
+    };
+    selection.wait()
+}
+
+#[cfg(not(feature = "clipboard"))]
+fn copy_to_clipboard(
+    _grim: &grim_rs::Grim,
+    _result: &grim_rs::CaptureResult,
+    _opts: &Options,
+) -> grim_rs::Result<()> {
+    eprintln!("Error: grim-rs was built without the clipboard feature");
+    std::process::exit(1);
+}
+
+fn write_png_data(encoded: &[u8], output_file: &str) -> grim_rs::Result<()> {
+    write_encoded(encoded, output_file, "PNG")
+}
+
+/// Write an encoded image to `output_file`, or to stdout for `-`.
+fn write_encoded(encoded: &[u8], output_file: &str, format_name: &str) -> grim_rs::Result<()> {
+    use std::io::Write;
+
+    if output_file == "-" {
+        let stdout = io::stdout();
+        let mut handle = stdout.lock();
+        handle.write_all(encoded)?;
+        handle.flush()?;
+    } else {
+        fs::write(output_file, encoded).map_err(|e| grim_rs::Error::IoWithContext {
+            operation: format!("writing {} data to file '{}'", format_name, output_file),
+            source: e,
+        })?;
+    }
+    Ok(())
+}
+
 fn write_to_stdout(
     grim: &grim_rs::Grim,
     result: &grim_rs::CaptureResult,
@@ -214,7 +151,14 @@ fn write_to_stdout(
     match opts.filetype {
         FileType::Png => write_png_to_stdout(grim, result, opts.png_level),
         FileType::Ppm => grim.write_ppm_to_stdout(result.data(), result.width(), result.height()),
-        FileType::Jpeg => write_jpeg_to_stdout(grim, result, opts.jpeg_quality),
+        FileType::Bmp => grim.write_bmp_to_stdout(result.data(), result.width(), result.height()),
+        FileType::Jpeg => write_encoded(&encode_jpeg(grim, result, opts)?, "-", "JPEG"),
+        FileType::Webp => grim.write_webp_to_stdout_with_quality(
+            result.data(),
+            result.width(),
+            result.height(),
+            opts.jpeg_quality,
+        ),
     }
 }
 
@@ -228,7 +172,15 @@ fn save_to_file(
     match opts.filetype {
         FileType::Png => save_png_to_file(grim, result, path, opts.png_level),
         FileType::Ppm => grim.save_ppm(result.data(), result.width(), result.height(), path),
-        FileType::Jpeg => save_jpeg_to_file(grim, result, path, opts.jpeg_quality),
+        FileType::Bmp => grim.save_bmp(result.data(), result.width(), result.height(), path),
+        FileType::Jpeg => write_encoded(&encode_jpeg(grim, result, opts)?, output_file, "JPEG"),
+        FileType::Webp => grim.save_webp_with_quality(
+            result.data(),
+            result.width(),
+            result.height(),
+            path,
+            opts.jpeg_quality,
+        ),
     }
 }
 
@@ -268,66 +220,21 @@ fn save_png_to_file(
     }
 }
 
-fn write_jpeg_to_stdout(
-    grim: &grim_rs::Grim,
-    result: &grim_rs::CaptureResult,
-    quality: u8,
-) -> grim_rs::Result<()> {
-    #[cfg(feature = "jpeg")]
-    {
-        if quality == 80 {
-            grim.write_jpeg_to_stdout(result.data(), result.width(), result.height())
-        } else {
-            grim.write_jpeg_to_stdout_with_quality(
-                result.data(),
-                result.width(),
-                result.height(),
-                quality,
-            )
-        }
-    }
-    #[cfg(not(feature = "jpeg"))]
-    {
-        let _ = (grim, result, quality);
-        Err(create_jpeg_not_supported_error())
-    }
-}
-
-fn save_jpeg_to_file(
+/// Encode the capture as JPEG with the `-q` quality, flattened onto the `-b`
+/// background. Every JPEG the CLI writes, to a file, stdout or the
+/// clipboard, is encoded here.
+fn encode_jpeg(
     grim: &grim_rs::Grim,
     result: &grim_rs::CaptureResult,
-    path: &Path,
-    quality: u8,
-) -> grim_rs::Result<()> {
-    #[cfg(feature = "jpeg")]
-    {
-        if quality == 80 {
-            grim.save_jpeg(result.data(), result.width(), result.height(), path)
-        } else {
-            grim.save_jpeg_with_quality(
-                result.data(),
-                result.width(),
-                result.height(),
-                path,
-                quality,
-            )
-        }
-    }
-    #[cfg(not(feature = "jpeg"))]
-    {
-        let _ = (grim, result, path, quality);
-        Err(create_jpeg_not_supported_error())
-    }
-}
-
-#[cfg(not(feature = "jpeg"))]
-fn create_jpeg_not_supported_error() -> grim_rs::Error {
-    grim_rs::Error::ImageProcessing(image::ImageError::Unsupported(
-        image::error::UnsupportedError::from_format_and_kind(
-            image::error::ImageFormatHint::Name("JPEG".to_string()),
-            image::error::UnsupportedErrorKind::Format(image::ImageFormat::Jpeg.into()),
-        ),
-    ))
+    opts: &Options,
+) -> grim_rs::Result<Vec<u8>> {
+    grim.to_jpeg_with_options(
+        result.data(),
+        result.width(),
+        result.height(),
+        opts.jpeg_quality,
+        opts.background,
+    )
 }
 
 fn print_help() {
@@ -338,13 +245,23 @@ fn print_help() {
          -h              Show help message and quit.\n\
          -s <factor>     Set the output image's scale factor.\n\
          -g <geometry>   Set the region to capture.\n\
-         -t png|ppm|jpeg Set the output filetype.\n\
-         -q <quality>    Set the JPEG filetype compression rate (0-100).\n\
+         -i              Select the region to capture interactively.\n\
+         -a <W:H>        Lock the interactive selection to an aspect ratio.\n\
+         -t png|ppm|jpeg|webp|bmp\n\
+                         Set the output filetype.\n\
+         -q <quality>    Set the JPEG/WebP filetype quality (0-100,\n\
+                         WebP is lossless at 100).\n\
          -l <level>      Set the PNG filetype compression level (0-9).\n\
+         -b <RRGGBB>     Set the JPEG background that transparent areas\n\
+                         are blended onto (default 000000).\n\
          -o <output>     Set the output name to capture.\n\
          -c              Include cursors in the screenshot.\n\
+         -y, --clipboard Copy the screenshot (png or jpeg) to the clipboard\n\
+                         instead of saving it. Keeps running until another\n\
+                         client takes the selection.\n\
          \n\
          If output-file is '-', output to standard output.\n\
+         If the interactive selection is cancelled, exit with status 2.\n\
          If no output-file is specified, use a default timestamped filename."
     );
 }
@@ -359,7 +276,9 @@ fn generate_default_filename(filetype: FileType) -> grim_rs::Result<String> {
     let ext = match filetype {
         FileType::Png => "png",
         FileType::Ppm => "ppm",
+        FileType::Bmp => "bmp",
         FileType::Jpeg => "jpeg",
+        FileType::Webp => "webp",
     };
 
     let output_dir = get_output_dir();
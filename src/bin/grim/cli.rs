@@ -0,0 +1,426 @@
+//! Command-line parsing for the `grim-rs` binary.
+//!
+//! [`parse_args`] only parses and validates; it never prints or exits, so
+//! `main` decides how to report a [`CliError`].
+
+use grim_rs::{Box as GrimBox, CaptureOptions, Grim};
+use std::fmt;
+
+#[derive(Debug)]
+pub(crate) struct Options {
+    pub(crate) help: bool,
+    pub(crate) scale: Option<f64>,
+    pub(crate) geometry: Option<GrimBox>,
+    pub(crate) interactive: bool,
+    pub(crate) aspect_ratio: Option<(u32, u32)>,
+    pub(crate) filetype: FileType,
+    pub(crate) jpeg_quality: u8,
+    pub(crate) png_level: u8,
+    pub(crate) background: [u8; 3],
+    pub(crate) output_name: Option<String>,
+    pub(crate) with_cursor: bool,
+    pub(crate) clipboard: bool,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            help: false,
+            scale: None,
+            geometry: None,
+            interactive: false,
+            aspect_ratio: None,
+            filetype: FileType::Png,
+            jpeg_quality: 80,
+            png_level: 6,
+            background: [0, 0, 0],
+            output_name: None,
+            with_cursor: false,
+            clipboard: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum FileType {
+    Png,
+    Ppm,
+    Jpeg,
+    Webp,
+    Bmp,
+}
+
+/// Why the command line was rejected.
+#[derive(Debug)]
+pub(crate) enum CliError {
+    /// A flag that takes a value was the last argument.
+    MissingArgument(String),
+    /// A flag's value could not be parsed or is out of range.
+    InvalidValue {
+        flag: String,
+        value: String,
+        expected: &'static str,
+    },
+    /// An argument starting with `-` that is not a known flag.
+    UnknownFlag(String),
+    /// More than one output file was given.
+    TooManyArguments,
+    /// Two options that cannot be used together.
+    Conflict(&'static str),
+    /// Reading the region from standard input (`-g -`) failed.
+    Stdin(grim_rs::Error),
+}
+
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CliError::MissingArgument(flag) => write!(f, "{} requires an argument", flag),
+            CliError::InvalidValue {
+                flag,
+                value,
+                expected,
+            } => write!(
+                f,
+                "invalid value for {}: {} (expected {})",
+                flag, value, expected
+            ),
+            CliError::UnknownFlag(flag) => write!(f, "unknown option: {}", flag),
+            CliError::TooManyArguments => write!(f, "too many arguments"),
+            CliError::Conflict(message) => write!(f, "{}", message),
+            CliError::Stdin(e) => write!(f, "reading region from stdin: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for CliError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            CliError::Stdin(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+/// Parse the command line, without the program name.
+///
+/// Returns the options and the output file, if one was given (`-` means
+/// standard output). `-i` is only recorded here; the selection itself
+/// happens in `main`.
+pub(crate) fn parse_args(args: &[String]) -> Result<(Options, Option<String>), CliError> {
+    let mut opts = Options::default();
+    let mut output_file = None;
+    let mut args = args.iter();
+
+    while let Some(arg) = args.next() {
+        let flag = arg.as_str();
+        let mut value = || {
+            args.next()
+                .map(String::as_str)
+                .ok_or_else(|| CliError::MissingArgument(flag.to_string()))
+        };
+        let invalid = |value: &str, expected| CliError::InvalidValue {
+            flag: flag.to_string(),
+            value: value.to_string(),
+            expected,
+        };
+
+        match flag {
+            "-h" | "--help" => {
+                opts.help = true;
+                return Ok((opts, output_file));
+            }
+            "-s" => {
+                let value = value()?;
+                let scale = value
+                    .parse::<f64>()
+                    .ok()
+                    .filter(|scale| scale.is_finite() && *scale > 0.0)
+                    .ok_or_else(|| invalid(value, "a positive number"))?;
+                opts.scale = Some(scale);
+            }
+            "-g" => {
+                let value = value()?;
+                opts.geometry = Some(if value == "-" {
+                    Grim::read_region_from_stdin().map_err(CliError::Stdin)?
+                } else {
+                    value
+                        .parse()
+                        .map_err(|_| invalid(value, "\"x,y widthxheight\""))?
+                });
+            }
+            "-i" => {
+                opts.interactive = true;
+            }
+            "-a" => {
+                let value = value()?;
+                opts.aspect_ratio =
+                    Some(parse_aspect_ratio(value).ok_or_else(|| invalid(value, "W:H"))?);
+            }
+            "-t" => {
+                let value = value()?;
+                opts.filetype = match value {
+                    "png" => FileType::Png,
+                    "ppm" => FileType::Ppm,
+                    "jpeg" => FileType::Jpeg,
+                    "webp" => FileType::Webp,
+                    "bmp" => FileType::Bmp,
+                    _ => return Err(invalid(value, "png, ppm, jpeg, webp or bmp")),
+                };
+            }
+            "-q" => {
+                let value = value()?;
+                opts.jpeg_quality = value
+                    .parse::<u8>()
+                    .ok()
+                    .filter(|quality| *quality <= 100)
+                    .ok_or_else(|| invalid(value, "a quality between 0 and 100"))?;
+            }
+            "-l" => {
+                let value = value()?;
+                opts.png_level = value
+                    .parse::<u8>()
+                    .ok()
+                    .filter(|level| *level <= 9)
+                    .ok_or_else(|| invalid(value, "a compression level between 0 and 9"))?;
+            }
+            "-b" => {
+                let value = value()?;
+                opts.background = parse_hex_color(value).ok_or_else(|| invalid(value, "RRGGBB"))?;
+            }
+            "-o" => {
+                opts.output_name = Some(value()?.to_string());
+            }
+            "-c" => {
+                opts.with_cursor = true;
+            }
+            "-y" | "--clipboard" => {
+                opts.clipboard = true;
+            }
+            // A lone "-" is the stdout output file, not a flag.
+            _ if flag.starts_with('-') && flag != "-" => {
+                return Err(CliError::UnknownFlag(flag.to_string()));
+            }
+            _ => {
+                if output_file.is_some() {
+                    return Err(CliError::TooManyArguments);
+                }
+                output_file = Some(flag.to_string());
+            }
+        }
+    }
+
+    if opts.aspect_ratio.is_some() && !opts.interactive {
+        return Err(CliError::Conflict("-a requires -i"));
+    }
+    if opts.clipboard && output_file.is_some() {
+        return Err(CliError::Conflict(
+            "-y cannot be combined with an output file",
+        ));
+    }
+    Ok((opts, output_file))
+}
+
+/// Whether the capture can be encoded straight to PNG without going
+/// through [`CaptureOptions`].
+pub(crate) fn uses_png_fast_path(opts: &Options) -> bool {
+    matches!(opts.filetype, FileType::Png)
+        && opts.scale.is_none()
+        && !opts.with_cursor
+        && !opts.clipboard
+        // -o with -g captures part of the output, which the fast path can't.
+        && !(opts.output_name.is_some() && opts.geometry.is_some())
+}
+
+/// Capture options for everything but the PNG fast path. With both `-o`
+/// and `-g`, the geometry is clipped to that output's logical geometry.
+pub(crate) fn capture_options(opts: &Options) -> CaptureOptions {
+    let mut options = CaptureOptions::new().overlay_cursor(opts.with_cursor);
+    if let Some(scale) = opts.scale {
+        options = options.scale(scale);
+    }
+    if let Some(ref output_name) = opts.output_name {
+        options = options.output(output_name.clone());
+    }
+    if let Some(geometry) = opts.geometry {
+        options = options.region(geometry);
+    }
+    options
+}
+
+/// Parse a `W:H` aspect ratio with both parts non-zero.
+fn parse_aspect_ratio(value: &str) -> Option<(u32, u32)> {
+    let (width, height) = value.split_once(':')?;
+    let (width, height) = (width.parse().ok()?, height.parse().ok()?);
+    (width > 0 && height > 0).then_some((width, height))
+}
+
+/// Parse a `RRGGBB` hex color, with or without a leading `#`.
+fn parse_hex_color(value: &str) -> Option<[u8; 3]> {
+    let hex = value.strip_prefix('#').unwrap_or(value);
+    if hex.len() != 6 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+    Some([channel(0)?, channel(2)?, channel(4)?])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn try_parse(args: &[&str]) -> Result<(Options, Option<String>), CliError> {
+        let args: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+        parse_args(&args)
+    }
+
+    fn parse(args: &[&str]) -> (Options, Option<String>) {
+        try_parse(args).unwrap()
+    }
+
+    fn invalid_flag(args: &[&str]) -> String {
+        match try_parse(args) {
+            Err(CliError::InvalidValue { flag, .. }) => flag,
+            other => panic!("expected an invalid value, got {:?}", other.map(|_| ())),
+        }
+    }
+
+    #[test]
+    fn test_defaults() {
+        let (opts, output_file) = parse(&[]);
+        assert!(!opts.help);
+        assert_eq!(opts.filetype, FileType::Png);
+        assert_eq!((opts.jpeg_quality, opts.png_level), (80, 6));
+        assert!(output_file.is_none());
+    }
+
+    #[test]
+    fn test_help_stops_parsing() {
+        let (opts, _) = parse(&["-h", "--bogus"]);
+        assert!(opts.help);
+        assert!(parse(&["--help"]).0.help);
+    }
+
+    #[test]
+    fn test_flags() {
+        let (opts, _) = parse(&[
+            "-s", "0.5", "-t", "jpeg", "-q", "90", "-l", "9", "-b", "#ff8000", "-c", "-y",
+        ]);
+        assert_eq!(opts.scale, Some(0.5));
+        assert_eq!(opts.filetype, FileType::Jpeg);
+        assert_eq!(opts.jpeg_quality, 90);
+        assert_eq!(opts.png_level, 9);
+        assert_eq!(opts.background, [255, 128, 0]);
+        assert!(opts.with_cursor);
+        assert!(opts.clipboard);
+
+        let (opts, _) = parse(&["-i", "-a", "16:9", "-t", "webp"]);
+        assert!(opts.interactive);
+        assert_eq!(opts.aspect_ratio, Some((16, 9)));
+        assert_eq!(opts.filetype, FileType::Webp);
+        assert!(parse(&["--clipboard"]).0.clipboard);
+    }
+
+    #[test]
+    fn test_stdout_sentinel_is_an_output_file() {
+        let (_, output_file) = parse(&["-t", "ppm", "-"]);
+        assert_eq!(output_file.as_deref(), Some("-"));
+    }
+
+    #[test]
+    fn test_too_many_arguments() {
+        assert!(matches!(
+            try_parse(&["a.png", "b.png"]),
+            Err(CliError::TooManyArguments)
+        ));
+    }
+
+    #[test]
+    fn test_unknown_flag() {
+        assert!(matches!(
+            try_parse(&["-x"]),
+            Err(CliError::UnknownFlag(flag)) if flag == "-x"
+        ));
+    }
+
+    #[test]
+    fn test_missing_argument() {
+        for flag in ["-s", "-g", "-a", "-t", "-q", "-l", "-b", "-o"] {
+            assert!(matches!(
+                try_parse(&[flag]),
+                Err(CliError::MissingArgument(missing)) if missing == flag
+            ));
+        }
+    }
+
+    #[test]
+    fn test_invalid_values() {
+        assert_eq!(invalid_flag(&["-s", "fast"]), "-s");
+        assert_eq!(invalid_flag(&["-s", "0"]), "-s");
+        assert_eq!(invalid_flag(&["-q", "101"]), "-q");
+        assert_eq!(invalid_flag(&["-q", "-5"]), "-q");
+        assert_eq!(invalid_flag(&["-l", "10"]), "-l");
+        assert_eq!(invalid_flag(&["-l", "max"]), "-l");
+        assert_eq!(invalid_flag(&["-t", "gif"]), "-t");
+        assert_eq!(invalid_flag(&["-g", "10,10"]), "-g");
+        assert_eq!(invalid_flag(&["-b", "red"]), "-b");
+        assert_eq!(invalid_flag(&["-i", "-a", "16:0"]), "-a");
+    }
+
+    #[test]
+    fn test_conflicts() {
+        assert!(matches!(
+            try_parse(&["-a", "4:3"]),
+            Err(CliError::Conflict(_))
+        ));
+        assert!(matches!(
+            try_parse(&["-y", "shot.png"]),
+            Err(CliError::Conflict(_))
+        ));
+    }
+
+    #[test]
+    fn test_output_only() {
+        let (opts, output_file) = parse(&["-o", "DP-1"]);
+        assert_eq!(opts.output_name.as_deref(), Some("DP-1"));
+        assert!(opts.geometry.is_none());
+        assert!(output_file.is_none());
+        assert!(uses_png_fast_path(&opts));
+    }
+
+    #[test]
+    fn test_geometry_only() {
+        let (opts, output_file) = parse(&["-g", "10,20 300x200", "shot.png"]);
+        assert_eq!(opts.geometry, Some(GrimBox::new(10, 20, 300, 200)));
+        assert_eq!(output_file.as_deref(), Some("shot.png"));
+        assert!(uses_png_fast_path(&opts));
+    }
+
+    #[test]
+    fn test_output_with_geometry_clips_to_output() {
+        let (opts, _) = parse(&["-o", "DP-1", "-g", "10,20 300x200"]);
+        assert!(!uses_png_fast_path(&opts));
+
+        let options = capture_options(&opts);
+        assert_eq!(options.target_output(), Some("DP-1"));
+        assert_eq!(
+            options.target_region(),
+            Some(GrimBox::new(10, 20, 300, 200))
+        );
+        assert!(!options.overlay_cursor_enabled());
+    }
+
+    #[test]
+    fn test_output_with_geometry_and_cursor() {
+        let (opts, _) = parse(&["-g", "10,20 300x200", "-c", "-o", "DP-1", "-s", "2"]);
+        assert!(!uses_png_fast_path(&opts));
+
+        let options = capture_options(&opts);
+        assert_eq!(options.target_output(), Some("DP-1"));
+        assert_eq!(
+            options.target_region(),
+            Some(GrimBox::new(10, 20, 300, 200))
+        );
+        assert!(options.overlay_cursor_enabled());
+        assert_eq!(options.scale_factor(), Some(2.0));
+    }
+}
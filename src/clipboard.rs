@@ -0,0 +1,266 @@
+//! Copying encoded screenshots to the Wayland clipboard.
+//!
+//! Uses `zwlr_data_control_v1`, which lets a client set the selection without
+//! owning a focused surface. On Wayland the clipboard contents stay with the
+//! client that set them: every paste asks the data source to write the bytes
+//! into a pipe. The source therefore gets its own connection and is served
+//! from a background thread until another client takes the selection, which
+//! means the process has to stay alive that long (see
+//! [`ClipboardSelection::wait`]).
+
+use crate::{Error, Result};
+use std::io::Write;
+use std::thread::JoinHandle;
+use wayland_client::{
+    delegate_noop, event_created_child,
+    protocol::{
+        wl_registry::{self, WlRegistry},
+        wl_seat::WlSeat,
+    },
+    Connection, Dispatch, EventQueue, QueueHandle,
+};
+use wayland_protocols_wlr::data_control::v1::client::{
+    zwlr_data_control_device_v1::{self, ZwlrDataControlDeviceV1},
+    zwlr_data_control_manager_v1::ZwlrDataControlManagerV1,
+    zwlr_data_control_offer_v1::ZwlrDataControlOfferV1,
+    zwlr_data_control_source_v1::{self, ZwlrDataControlSourceV1},
+};
+
+/// MIME type offered for `format`, or `None` if it can't be copied.
+pub(crate) fn mime_type(format: image::ImageFormat) -> Option<&'static str> {
+    match format {
+        image::ImageFormat::Png => Some("image/png"),
+        image::ImageFormat::Jpeg => Some("image/jpeg"),
+        _ => None,
+    }
+}
+
+/// A clipboard selection served from a background thread.
+///
+/// Dropping the handle does not withdraw the selection: the thread keeps
+/// answering paste requests until another client sets the clipboard or the
+/// process exits.
+#[derive(Debug)]
+pub struct ClipboardSelection {
+    thread: JoinHandle<Result<()>>,
+}
+
+impl ClipboardSelection {
+    /// Returns `true` while this process still owns the selection.
+    pub fn is_active(&self) -> bool {
+        !self.thread.is_finished()
+    }
+
+    /// Block until another client takes the selection.
+    ///
+    /// Short-lived programs should call this before exiting, otherwise the
+    /// clipboard is emptied as soon as the process ends.
+    pub fn wait(self) -> Result<()> {
+        self.thread
+            .join()
+            .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+    }
+}
+
+/// Set the clipboard to `data`, offered as `mime_type`.
+pub(crate) fn copy(data: Vec<u8>, mime_type: &'static str) -> Result<ClipboardSelection> {
+    let connection = Connection::connect_to_env()
+        .map_err(|e| Error::wayland_connection("Failed to connect to Wayland").with_source(e))?;
+    let mut event_queue = connection.new_event_queue();
+    let qh = event_queue.handle();
+    let _registry = connection.display().get_registry(&qh, ());
+
+    let mut state = ClipboardState::new(data, mime_type);
+    roundtrip(&mut event_queue, &mut state)?;
+
+    let manager = state.manager.clone().ok_or_else(|| {
+        Error::UnsupportedProtocol("zwlr_data_control_manager_v1 not available".to_string())
+    })?;
+    let seat = state
+        .seat
+        .clone()
+        .ok_or_else(|| Error::UnsupportedProtocol("wl_seat not available".to_string()))?;
+
+    let source = manager.create_data_source(&qh, ());
+    source.offer(mime_type.to_string());
+    let device = manager.get_data_device(&seat, &qh, ());
+    device.set_selection(Some(&source));
+    roundtrip(&mut event_queue, &mut state)?;
+    if state.finished {
+        source.destroy();
+        return Err(Error::wayland_connection(
+            "The compositor did not accept the clipboard selection".to_string(),
+        ));
+    }
+
+    let thread = std::thread::Builder::new()
+        .name("grim-clipboard".to_string())
+        .spawn(move || {
+            let _connection = connection;
+            let result = loop {
+                if state.finished {
+                    break Ok(());
+                }
+                if let Err(e) = event_queue.blocking_dispatch(&mut state) {
+                    break Err(
+                        Error::wayland_connection("Failed to serve clipboard").with_source(e)
+                    );
+                }
+            };
+            source.destroy();
+            device.destroy();
+            let _ = event_queue.flush();
+            result
+        })?;
+    Ok(ClipboardSelection { thread })
+}
+
+fn roundtrip(
+    event_queue: &mut EventQueue<ClipboardState>,
+    state: &mut ClipboardState,
+) -> Result<()> {
+    event_queue
+        .roundtrip(state)
+        .map(|_| ())
+        .map_err(|e| Error::wayland_connection("Wayland roundtrip failed").with_source(e))
+}
+
+struct ClipboardState {
+    seat: Option<WlSeat>,
+    manager: Option<ZwlrDataControlManagerV1>,
+    data: Vec<u8>,
+    mime_type: &'static str,
+    /// Set once the selection was replaced or the device went away.
+    finished: bool,
+}
+
+impl ClipboardState {
+    fn new(data: Vec<u8>, mime_type: &'static str) -> Self {
+        Self {
+            seat: None,
+            manager: None,
+            data,
+            mime_type,
+            finished: false,
+        }
+    }
+
+    /// Answer a paste request for `requested` by writing the data to `pipe`.
+    ///
+    /// Requests for a MIME type that wasn't offered get an empty reply.
+    fn send(&self, requested: &str, mut pipe: impl Write) {
+        if requested != self.mime_type {
+            log::debug!("Ignoring clipboard request for {}", requested);
+            return;
+        }
+        if let Err(e) = pipe.write_all(&self.data) {
+            log::debug!("Failed to write clipboard data: {}", e);
+        }
+    }
+}
+
+impl Dispatch<WlRegistry, ()> for ClipboardState {
+    fn event(
+        state: &mut Self,
+        registry: &WlRegistry,
+        event: wl_registry::Event,
+        _: &(),
+        _conn: &Connection,
+        qh: &QueueHandle<Self>,
+    ) {
+        if let wl_registry::Event::Global {
+            name,
+            interface,
+            version,
+        } = event
+        {
+            match interface.as_str() {
+                "wl_seat" if state.seat.is_none() => {
+                    state.seat = Some(registry.bind(name, version.min(2), qh, ()));
+                }
+                "zwlr_data_control_manager_v1" => {
+                    state.manager = Some(registry.bind(name, version.min(2), qh, ()));
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+impl Dispatch<ZwlrDataControlSourceV1, ()> for ClipboardState {
+    fn event(
+        state: &mut Self,
+        _: &ZwlrDataControlSourceV1,
+        event: zwlr_data_control_source_v1::Event,
+        _: &(),
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+    ) {
+        match event {
+            zwlr_data_control_source_v1::Event::Send { mime_type, fd } => {
+                state.send(&mime_type, std::fs::File::from(fd));
+            }
+            zwlr_data_control_source_v1::Event::Cancelled => {
+                state.finished = true;
+            }
+            _ => {}
+        }
+    }
+}
+
+impl Dispatch<ZwlrDataControlDeviceV1, ()> for ClipboardState {
+    fn event(
+        state: &mut Self,
+        _: &ZwlrDataControlDeviceV1,
+        event: zwlr_data_control_device_v1::Event,
+        _: &(),
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+    ) {
+        match event {
+            // Offers for the current selection, including our own, are not
+            // read; release them right away.
+            zwlr_data_control_device_v1::Event::Selection { id: Some(offer) }
+            | zwlr_data_control_device_v1::Event::PrimarySelection { id: Some(offer) } => {
+                offer.destroy();
+            }
+            zwlr_data_control_device_v1::Event::Finished => {
+                state.finished = true;
+            }
+            _ => {}
+        }
+    }
+
+    event_created_child!(ClipboardState, ZwlrDataControlDeviceV1, [
+        zwlr_data_control_device_v1::EVT_DATA_OFFER_OPCODE => (ZwlrDataControlOfferV1, ()),
+    ]);
+}
+
+delegate_noop!(ClipboardState: ignore WlSeat);
+delegate_noop!(ClipboardState: ZwlrDataControlManagerV1);
+delegate_noop!(ClipboardState: ignore ZwlrDataControlOfferV1);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mime_types_for_formats() {
+        assert_eq!(mime_type(image::ImageFormat::Png), Some("image/png"));
+        assert_eq!(mime_type(image::ImageFormat::Jpeg), Some("image/jpeg"));
+        assert_eq!(mime_type(image::ImageFormat::Bmp), None);
+    }
+
+    #[test]
+    fn test_send_serves_only_offered_mime_type() {
+        let state = ClipboardState::new(vec![1, 2, 3], "image/png");
+
+        let mut pipe = Vec::new();
+        state.send("image/png", &mut pipe);
+        assert_eq!(pipe, vec![1, 2, 3]);
+
+        let mut pipe = Vec::new();
+        state.send("text/plain", &mut pipe);
+        assert!(pipe.is_empty());
+    }
+}
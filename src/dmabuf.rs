@@ -0,0 +1,186 @@
+//! GPU buffers for the `linux_dmabuf` screencopy path.
+//!
+//! When the compositor offers a dmabuf for a screencopy frame, the frame can
+//! be copied into a GBM buffer object on the GPU instead of a wl_shm pool,
+//! which saves the compositor a GPU-to-CPU readback. The buffer object is
+//! mapped afterwards to read the pixels back as RGBA.
+
+use crate::{Error, Result};
+use gbm::{BufferObject, BufferObjectFlags, Device, DeviceDestroyedError, Format};
+use std::fs::{File, OpenOptions};
+use std::os::fd::OwnedFd;
+
+/// DRM fourcc codes for the formats the dmabuf path can convert.
+const DRM_FORMAT_XRGB8888: u32 = u32::from_le_bytes(*b"XR24");
+const DRM_FORMAT_ARGB8888: u32 = u32::from_le_bytes(*b"AR24");
+const DRM_FORMAT_XBGR8888: u32 = u32::from_le_bytes(*b"XB24");
+const DRM_FORMAT_ABGR8888: u32 = u32::from_le_bytes(*b"AB24");
+
+/// Render nodes probed when opening a GBM device.
+const RENDER_NODES: std::ops::RangeInclusive<u32> = 128..=135;
+
+/// Returns whether [`DmabufBuffer::read_rgba`] can convert `fourcc`.
+pub(crate) fn is_supported_format(fourcc: u32) -> bool {
+    matches!(
+        fourcc,
+        DRM_FORMAT_XRGB8888 | DRM_FORMAT_ARGB8888 | DRM_FORMAT_XBGR8888 | DRM_FORMAT_ABGR8888
+    )
+}
+
+/// Allocates buffer objects on the first usable DRM render node.
+pub(crate) struct DmabufAllocator {
+    device: Device<File>,
+}
+
+impl DmabufAllocator {
+    /// Open a GBM device, or return `None` if no render node is usable.
+    pub(crate) fn open() -> Option<Self> {
+        for minor in RENDER_NODES {
+            let path = format!("/dev/dri/renderD{}", minor);
+            let file = match OpenOptions::new().read(true).write(true).open(&path) {
+                Ok(file) => file,
+                Err(_) => continue,
+            };
+            match Device::new(file) {
+                Ok(device) => {
+                    log::debug!("Using {} for dmabuf capture", path);
+                    return Some(Self { device });
+                }
+                Err(e) => log::debug!("Failed to create GBM device on {}: {}", path, e),
+            }
+        }
+        None
+    }
+
+    /// Allocate a linear buffer object matching a `linux_dmabuf` frame offer.
+    pub(crate) fn allocate(&self, width: u32, height: u32, fourcc: u32) -> Result<DmabufBuffer> {
+        let format = Format::try_from(fourcc).map_err(|_| {
+            Error::buffer_creation(format!("unknown dmabuf format 0x{:08x}", fourcc))
+        })?;
+        let bo = self
+            .device
+            .create_buffer_object::<()>(
+                width,
+                height,
+                format,
+                BufferObjectFlags::RENDERING | BufferObjectFlags::LINEAR,
+            )
+            .map_err(|e| {
+                Error::buffer_creation(format!(
+                    "failed to allocate {}x{} GBM buffer",
+                    width, height
+                ))
+                .with_source(e)
+            })?;
+        Ok(DmabufBuffer {
+            bo,
+            width,
+            height,
+            fourcc,
+        })
+    }
+}
+
+/// A GBM buffer object that a screencopy frame is copied into.
+pub(crate) struct DmabufBuffer {
+    bo: BufferObject<()>,
+    width: u32,
+    height: u32,
+    fourcc: u32,
+}
+
+impl DmabufBuffer {
+    /// Export the buffer as a dmabuf file descriptor.
+    pub(crate) fn fd(&self) -> Result<OwnedFd> {
+        self.bo
+            .fd()
+            .map_err(|e| Error::buffer_creation("failed to export dmabuf fd").with_source(e))
+    }
+
+    pub(crate) fn stride(&self) -> Result<u32> {
+        self.bo.stride().map_err(device_destroyed)
+    }
+
+    pub(crate) fn offset(&self) -> Result<u32> {
+        self.bo.offset(0).map_err(device_destroyed)
+    }
+
+    pub(crate) fn modifier(&self) -> Result<u64> {
+        self.bo.modifier().map(u64::from).map_err(device_destroyed)
+    }
+
+    pub(crate) fn fourcc(&self) -> u32 {
+        self.fourcc
+    }
+
+    /// Map the buffer and convert its contents to tightly packed RGBA.
+    ///
+    /// `allocator` must be the one that allocated this buffer; GBM only maps
+    /// buffer objects through the device that created them.
+    pub(crate) fn read_rgba(&self, allocator: &DmabufAllocator) -> Result<Vec<u8>> {
+        let (width, height, fourcc) = (self.width, self.height, self.fourcc);
+        self.bo
+            .map(&allocator.device, 0, 0, width, height, |mapped| {
+                convert_to_rgba(
+                    mapped.buffer(),
+                    mapped.stride() as usize,
+                    width,
+                    height,
+                    fourcc,
+                )
+            })
+            .map_err(|e| Error::buffer_creation("failed to map dmabuf").with_source(e))?
+            .map_err(|e| Error::buffer_creation("failed to map dmabuf").with_source(e))
+    }
+}
+
+fn device_destroyed(e: DeviceDestroyedError) -> Error {
+    Error::buffer_creation("GBM device was destroyed").with_source(e)
+}
+
+/// Repack a mapped dmabuf into RGBA, dropping row padding.
+fn convert_to_rgba(src: &[u8], stride: usize, width: u32, height: u32, fourcc: u32) -> Vec<u8> {
+    let row_len = width as usize * 4;
+    let mut data = Vec::with_capacity(row_len * height as usize);
+    for row in src.chunks(stride).take(height as usize) {
+        data.extend_from_slice(&row[..row_len]);
+    }
+
+    let swap_red_blue = matches!(fourcc, DRM_FORMAT_XRGB8888 | DRM_FORMAT_ARGB8888);
+    let opaque = matches!(fourcc, DRM_FORMAT_XRGB8888 | DRM_FORMAT_XBGR8888);
+    for pixel in data.chunks_exact_mut(4) {
+        if swap_red_blue {
+            pixel.swap(0, 2);
+        }
+        if opaque {
+            pixel[3] = 255;
+        }
+    }
+    data
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_convert_xrgb_drops_padding_and_swaps_channels() {
+        // 1x2 image, 8-byte stride: BGRX pixel followed by 4 bytes of padding.
+        let src = [1, 2, 3, 0, 9, 9, 9, 9, 4, 5, 6, 0, 9, 9, 9, 9];
+        let rgba = convert_to_rgba(&src, 8, 1, 2, DRM_FORMAT_XRGB8888);
+        assert_eq!(rgba, vec![3, 2, 1, 255, 6, 5, 4, 255]);
+    }
+
+    #[test]
+    fn test_convert_abgr_keeps_alpha() {
+        let src = [10, 20, 30, 40];
+        let rgba = convert_to_rgba(&src, 4, 1, 1, DRM_FORMAT_ABGR8888);
+        assert_eq!(rgba, vec![10, 20, 30, 40]);
+    }
+
+    #[test]
+    fn test_supported_formats() {
+        assert!(is_supported_format(DRM_FORMAT_XRGB8888));
+        assert!(!is_supported_format(u32::from_le_bytes(*b"NV12")));
+    }
+}
@@ -1,5 +1,8 @@
 use thiserror::Error;
 
+/// Underlying cause attached to Wayland and buffer errors.
+pub type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
 #[derive(Error, Debug)]
 pub enum Error {
     #[error("Invalid geometry format: {0}")]
@@ -14,11 +17,18 @@ pub enum Error {
     #[error("Invalid capture region: {0}")]
     InvalidRegion(String),
 
+    #[error("Invalid argument: {0}")]
+    InvalidArgument(String),
+
     #[error("Screenshot capture failed")]
     CaptureFailed,
 
-    #[error("Buffer creation failed: {0}")]
-    BufferCreation(String),
+    #[error("Buffer creation failed: {message}")]
+    BufferCreation {
+        message: String,
+        #[source]
+        source: Option<BoxError>,
+    },
 
     #[error("Image processing error: {0}")]
     ImageProcessing(#[from] image::ImageError),
@@ -29,17 +39,38 @@ pub enum Error {
     #[error("IO error during {operation}: {source}")]
     IoWithContext {
         operation: String,
+        #[source]
         source: std::io::Error,
     },
 
     #[error("Compositor doesn't support required protocol: {0}")]
     UnsupportedProtocol(String),
 
-    #[error("Wayland connection error: {0}")]
-    WaylandConnection(String),
+    #[error("Unsupported buffer format: {0}")]
+    UnsupportedBufferFormat(String),
+
+    #[error("Wayland connection error: {message}")]
+    WaylandConnection {
+        message: String,
+        #[source]
+        source: Option<BoxError>,
+    },
+
+    #[error("Frame capture failed: {message}")]
+    FrameCapture {
+        message: String,
+        #[source]
+        source: Option<BoxError>,
+    },
 
-    #[error("Frame capture failed: {0}")]
-    FrameCapture(String),
+    #[error("Timed out after {waited:?} waiting for the compositor")]
+    Timeout { waited: std::time::Duration },
+
+    #[error("Operation was cancelled")]
+    Cancelled,
+
+    #[error("Region selection was cancelled")]
+    SelectionCancelled,
 
     #[error("Output transform not supported: {0}")]
     TransformNotSupported(String),
@@ -51,4 +82,93 @@ pub enum Error {
     ScalingFailed(String),
 }
 
+/// Broad category of an [`Error`], for callers that decide how to react
+/// (e.g. whether to retry) without matching every variant.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// Connecting to or talking with the Wayland compositor failed.
+    Connection,
+    /// The compositor lacks a protocol or buffer format grim-rs needs.
+    Protocol,
+    /// Capturing or post-processing a frame failed.
+    Capture,
+    /// Encoding or decoding an image failed.
+    Encode,
+    /// Reading or writing a file or stream failed.
+    Io,
+    /// An argument such as a geometry, region or output name was invalid.
+    InvalidInput,
+    /// The compositor didn't answer in time.
+    Timeout,
+    /// The operation or region selection was cancelled.
+    Cancelled,
+}
+
+impl Error {
+    /// Creates a [`Error::WaylandConnection`] without an underlying cause.
+    pub fn wayland_connection(message: impl Into<String>) -> Self {
+        Error::WaylandConnection {
+            message: message.into(),
+            source: None,
+        }
+    }
+
+    /// Creates a [`Error::FrameCapture`] without an underlying cause.
+    pub fn frame_capture(message: impl Into<String>) -> Self {
+        Error::FrameCapture {
+            message: message.into(),
+            source: None,
+        }
+    }
+
+    /// Creates a [`Error::BufferCreation`] without an underlying cause.
+    pub fn buffer_creation(message: impl Into<String>) -> Self {
+        Error::BufferCreation {
+            message: message.into(),
+            source: None,
+        }
+    }
+
+    /// Attaches the error that caused this one, returned by
+    /// [`source`](std::error::Error::source).
+    ///
+    /// Only `WaylandConnection`, `FrameCapture` and `BufferCreation` carry a
+    /// cause; other errors are returned unchanged.
+    pub fn with_source(mut self, cause: impl Into<BoxError>) -> Self {
+        if let Error::WaylandConnection { source, .. }
+        | Error::FrameCapture { source, .. }
+        | Error::BufferCreation { source, .. } = &mut self
+        {
+            *source = Some(cause.into());
+        }
+        self
+    }
+
+    /// Returns the broad category of this error.
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            Error::WaylandConnection { .. } => ErrorKind::Connection,
+            Error::UnsupportedProtocol(_) | Error::UnsupportedBufferFormat(_) => {
+                ErrorKind::Protocol
+            }
+            Error::NoOutputs
+            | Error::CaptureFailed
+            | Error::BufferCreation { .. }
+            | Error::FrameCapture { .. }
+            | Error::TransformNotSupported(_)
+            | Error::InvertFailed(_)
+            | Error::ScalingFailed(_) => ErrorKind::Capture,
+            Error::ImageProcessing(_) => ErrorKind::Encode,
+            Error::Io(_) | Error::IoWithContext { .. } => ErrorKind::Io,
+            Error::InvalidGeometry(_)
+            | Error::OutputNotFound(_)
+            | Error::InvalidRegion(_)
+            | Error::InvalidArgument(_) => ErrorKind::InvalidInput,
+            Error::Timeout { .. } => ErrorKind::Timeout,
+            Error::Cancelled | Error::SelectionCancelled => ErrorKind::Cancelled,
+        }
+    }
+}
+
 pub type Result<T> = std::result::Result<T, Error>;
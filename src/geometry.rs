@@ -1,6 +1,6 @@
 use std::fmt;
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct Box {
     x: i32,
     y: i32,
@@ -63,6 +63,83 @@ impl Box {
 
         Some(Box::new(x1, y1, x2 - x1, y2 - y1))
     }
+
+    /// Returns this box with every coordinate multiplied by `factor`, e.g. to
+    /// turn a logical region into buffer pixels.
+    ///
+    /// The edges are scaled and rounded independently, so boxes that touch
+    /// before scaling still touch afterwards.
+    pub fn scale(&self, factor: f64) -> Box {
+        Self::from_edges(
+            self.x as f64 * factor,
+            self.y as f64 * factor,
+            (self.x + self.width) as f64 * factor,
+            (self.y + self.height) as f64 * factor,
+        )
+    }
+
+    /// Returns this box moved by `dx` horizontally and `dy` vertically.
+    pub fn translate(&self, dx: i32, dy: i32) -> Box {
+        Box::new(self.x + dx, self.y + dy, self.width, self.height)
+    }
+
+    /// Returns this box with its size multiplied by `factor` while keeping
+    /// its center in place. A factor above 1 grows the box on every side.
+    pub fn scale_around_center(&self, factor: f64) -> Box {
+        let center_x = self.x as f64 + self.width as f64 / 2.0;
+        let center_y = self.y as f64 + self.height as f64 / 2.0;
+        let half_width = self.width as f64 * factor / 2.0;
+        let half_height = self.height as f64 * factor / 2.0;
+        Self::from_edges(
+            center_x - half_width,
+            center_y - half_height,
+            center_x + half_width,
+            center_y + half_height,
+        )
+    }
+
+    fn from_edges(left: f64, top: f64, right: f64, bottom: f64) -> Box {
+        let x1 = left.round() as i32;
+        let y1 = top.round() as i32;
+        Box::new(
+            x1,
+            y1,
+            right.round() as i32 - x1,
+            bottom.round() as i32 - y1,
+        )
+    }
+
+    /// Returns the smallest box containing both `self` and `other`.
+    ///
+    /// Empty boxes contain nothing, so they are ignored: the union of a box
+    /// with an empty box is the non-empty one. If both are empty, `self` is
+    /// returned unchanged.
+    pub fn union(&self, other: &Box) -> Box {
+        if other.is_empty() {
+            return *self;
+        }
+        if self.is_empty() {
+            return *other;
+        }
+
+        let x1 = self.x.min(other.x);
+        let y1 = self.y.min(other.y);
+        let x2 = (self.x + self.width).max(other.x + other.width);
+        let y2 = (self.y + self.height).max(other.y + other.height);
+
+        Box::new(x1, y1, x2 - x1, y2 - y1)
+    }
+
+    /// Returns the smallest box containing every non-empty box in `boxes`.
+    ///
+    /// Returns `None` if `boxes` contains no non-empty box.
+    pub fn union_all(boxes: &[Box]) -> Option<Box> {
+        boxes
+            .iter()
+            .filter(|b| !b.is_empty())
+            .copied()
+            .reduce(|acc, b| acc.union(&b))
+    }
 }
 
 impl fmt::Display for Box {
@@ -74,33 +151,80 @@ impl fmt::Display for Box {
 impl std::str::FromStr for Box {
     type Err = crate::Error;
 
+    /// Parses `"<x>,<y> <width>x<height>"`, the format printed by `slurp`.
+    ///
+    /// Components may be fractional; the box is grown to whole pixels by
+    /// flooring the origin and rounding the far edges up, so the parsed box
+    /// covers the whole requested area. Any run of spaces or tabs may
+    /// separate the position from the size.
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let parts: Vec<&str> = s.split(' ').collect();
-        if parts.len() != 2 {
-            return Err(crate::Error::InvalidGeometry(s.to_string()));
-        }
+        let invalid = |reason: String| {
+            crate::Error::InvalidGeometry(format!(
+                "{:?}: {} (expected \"<x>,<y> <width>x<height>\")",
+                s, reason
+            ))
+        };
 
-        let xy: Vec<&str> = parts[0].split(',').collect();
-        let wh: Vec<&str> = parts[1].split('x').collect();
+        let mut halves = s.split_whitespace();
+        let (position, size) = match (halves.next(), halves.next(), halves.next()) {
+            (Some(position), Some(size), None) => (position, size),
+            _ => {
+                return Err(invalid(
+                    "position and size must be separated by whitespace".to_string(),
+                ))
+            }
+        };
 
-        if xy.len() != 2 || wh.len() != 2 {
-            return Err(crate::Error::InvalidGeometry(s.to_string()));
-        }
+        let (x, y) = position
+            .split_once(',')
+            .ok_or_else(|| invalid(format!("position {:?} is missing a ','", position)))?;
+        let (width, height) = size
+            .split_once('x')
+            .ok_or_else(|| invalid(format!("size {:?} is missing an 'x'", size)))?;
+
+        let component = |name: &str, value: &str| -> Result<f64, Self::Err> {
+            value
+                .parse::<f64>()
+                .ok()
+                .filter(|v| v.is_finite() && v.abs() <= i32::MAX as f64)
+                .ok_or_else(|| invalid(format!("invalid {} {:?}", name, value)))
+        };
+        let x = component("x", x)?;
+        let y = component("y", y)?;
+        let width = component("width", width)?;
+        let height = component("height", height)?;
 
-        let x = xy[0]
-            .parse()
-            .map_err(|_| crate::Error::InvalidGeometry(s.to_string()))?;
-        let y = xy[1]
-            .parse()
-            .map_err(|_| crate::Error::InvalidGeometry(s.to_string()))?;
-        let width = wh[0]
-            .parse()
-            .map_err(|_| crate::Error::InvalidGeometry(s.to_string()))?;
-        let height = wh[1]
-            .parse()
-            .map_err(|_| crate::Error::InvalidGeometry(s.to_string()))?;
-
-        Ok(Box::new(x, y, width, height))
+        let x1 = x.floor();
+        let y1 = y.floor();
+        let x2 = (x + width).ceil();
+        let y2 = (y + height).ceil();
+        Ok(Box::new(
+            x1 as i32,
+            y1 as i32,
+            (x2 - x1) as i32,
+            (y2 - y1) as i32,
+        ))
+    }
+}
+
+/// Serialized as the `"<x>,<y> <width>x<height>"` string used by `-g`.
+#[cfg(feature = "serde")]
+impl serde::Serialize for Box {
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Box {
+    fn deserialize<D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> std::result::Result<Self, D::Error> {
+        let s = std::borrow::Cow::<str>::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
     }
 }
 
@@ -118,6 +242,30 @@ mod tests {
         assert_eq!(parsed.height(), 400);
     }
 
+    #[test]
+    fn test_box_parsing_slurp_output() {
+        let parsed: Box = "103.5,56.2 640.0x480.0".parse().unwrap();
+        assert_eq!(parsed, Box::new(103, 56, 641, 481));
+
+        let parsed: Box = "-1920,-200 1920x1080".parse().unwrap();
+        assert_eq!(parsed, Box::new(-1920, -200, 1920, 1080));
+
+        let parsed: Box = "-10.5,4 \t  20x10.25".parse().unwrap();
+        assert_eq!(parsed, Box::new(-11, 4, 21, 11));
+    }
+
+    #[test]
+    fn test_box_parsing_reports_failing_component() {
+        let message = |s: &str| s.parse::<Box>().unwrap_err().to_string();
+        assert!(message("10,abc 300x400").contains("invalid y \"abc\""));
+        assert!(message("10,20 300xfoo").contains("invalid height \"foo\""));
+        assert!(message("10;20 300x400").contains("missing a ','"));
+        assert!(message("10,20 300*400").contains("missing an 'x'"));
+        assert!(message("10,20").contains("separated by whitespace"));
+        assert!(message("10,20 300x400 extra").contains("separated by whitespace"));
+        assert!(message("10,20 infx400").contains("invalid width"));
+    }
+
     #[test]
     fn test_box_intersection() {
         let box1 = Box::new(0, 0, 100, 100);
@@ -131,4 +279,90 @@ mod tests {
         assert_eq!(intersection.width(), 50);
         assert_eq!(intersection.height(), 50);
     }
+
+    #[test]
+    fn test_box_scale() {
+        assert_eq!(
+            Box::new(10, -20, 300, 400).scale(2.0),
+            Box::new(20, -40, 600, 800)
+        );
+        assert_eq!(Box::new(3, 3, 3, 3).scale(1.5), Box::new(5, 5, 4, 4));
+
+        // Neighbours stay adjacent after fractional scaling.
+        let left = Box::new(0, 0, 3, 3).scale(1.5);
+        let right = Box::new(3, 0, 3, 3).scale(1.5);
+        assert_eq!(left.x() + left.width(), right.x());
+    }
+
+    #[test]
+    fn test_box_translate() {
+        let region = Box::new(10, 20, 30, 40);
+        assert_eq!(region.translate(-15, 5), Box::new(-5, 25, 30, 40));
+        assert_eq!(region.translate(0, 0), region);
+    }
+
+    #[test]
+    fn test_box_scale_around_center() {
+        let region = Box::new(10, 10, 20, 40);
+        assert_eq!(region.scale_around_center(2.0), Box::new(0, -10, 40, 80));
+        assert_eq!(region.scale_around_center(0.5), Box::new(15, 20, 10, 20));
+        assert_eq!(region.scale_around_center(1.0), region);
+    }
+
+    #[test]
+    fn test_box_hash() {
+        use std::collections::hash_map::DefaultHasher;
+        use std::collections::HashSet;
+        use std::hash::{Hash, Hasher};
+
+        let hash = |region: &Box| {
+            let mut hasher = DefaultHasher::new();
+            region.hash(&mut hasher);
+            hasher.finish()
+        };
+        assert_eq!(hash(&Box::new(1, 2, 3, 4)), hash(&Box::new(1, 2, 3, 4)));
+
+        let regions: HashSet<Box> = [
+            Box::new(1, 2, 3, 4),
+            Box::new(1, 2, 3, 4),
+            Box::new(2, 1, 3, 4),
+        ]
+        .into_iter()
+        .collect();
+        assert_eq!(regions.len(), 2);
+    }
+
+    #[test]
+    fn test_box_union() {
+        let box1 = Box::new(0, 0, 100, 100);
+        let box2 = Box::new(150, -50, 50, 50);
+        assert_eq!(box1.union(&box2), Box::new(0, -50, 200, 150));
+
+        let empty = Box::new(500, 500, 0, 10);
+        assert_eq!(box1.union(&empty), box1);
+        assert_eq!(empty.union(&box1), box1);
+        assert_eq!(empty.union(&empty), empty);
+    }
+
+    #[test]
+    fn test_box_union_all() {
+        let boxes = [
+            Box::new(0, 0, 10, 10),
+            Box::new(0, 0, 0, 0),
+            Box::new(20, 30, 5, 5),
+        ];
+        assert_eq!(Box::union_all(&boxes), Some(Box::new(0, 0, 25, 35)));
+        assert_eq!(Box::union_all(&[]), None);
+        assert_eq!(Box::union_all(&[Box::new(1, 1, -5, 5)]), None);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_uses_geometry_string() {
+        let region = Box::new(-10, 20, 300, 400);
+        let json = serde_json::to_string(&region).unwrap();
+        assert_eq!(json, "\"-10,20 300x400\"");
+        assert_eq!(serde_json::from_str::<Box>(&json).unwrap(), region);
+        assert!(serde_json::from_str::<Box>("\"10,20\"").is_err());
+    }
 }
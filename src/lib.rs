@@ -3,16 +3,22 @@
 //! A pure Rust implementation of the grim screenshot utility for Wayland.
 //!
 //! This library provides a simple interface for taking screenshots on Wayland
-//! compositors that support the `wlr-screencopy` protocol.
+//! compositors that support the `wlr-screencopy` or `ext-image-copy-capture`
+//! protocol.
 //!
 //! ## Features
 //!
 //! - Capture entire screen (all outputs)
 //! - Capture specific output by name
 //! - Capture specific region
+//! - Select a region interactively with [`select_region`]
 //! - Capture multiple outputs with different parameters
-//! - Save screenshots as PNG or JPEG
-//! - Get screenshot data as PNG or JPEG bytes
+//! - Save screenshots as PNG, JPEG or WebP
+//! - Get screenshot data as PNG, JPEG or WebP bytes
+//! - Share a single connection between threads with [`SharedGrim`]
+//! - Copy screenshots to the clipboard (requires the `clipboard` feature)
+//! - Capture from async code with `AsyncGrim` (requires the `tokio` feature)
+//! - Zero-copy capture through GPU buffers (requires the `dmabuf` feature)
 //!
 //! ## Example
 //!
@@ -34,17 +40,46 @@
 pub mod error;
 pub mod geometry;
 
+#[cfg(feature = "tokio")]
+mod async_grim;
+#[cfg(feature = "clipboard")]
+mod clipboard;
+#[cfg(feature = "dmabuf")]
+mod dmabuf;
+#[cfg(feature = "parallel")]
+mod parallel_png;
+mod redact;
+mod region_select;
+mod shared;
 mod wayland_capture;
 
-pub use error::{Error, Result};
+#[cfg(feature = "tokio")]
+pub use async_grim::AsyncGrim;
+#[cfg(feature = "clipboard")]
+pub use clipboard::ClipboardSelection;
+pub use error::{BoxError, Error, ErrorKind, Result};
 pub use geometry::Box;
+pub use region_select::{select_region, select_region_with_options, SelectionOptions};
+pub use shared::{CaptureStreamHandle, SharedGrim};
 
 use wayland_capture::WaylandCapture as PlatformCapture;
 
 /// Result of a screenshot capture operation.
 ///
 /// Contains the raw image data and dimensions of the captured area.
+///
+/// With the `serde` feature the pixels are serialized PNG-encoded rather than
+/// as a raw RGBA array, and deserializing checks that the image matches the
+/// stated dimensions.
+///
+/// Equality and hashing compare the image only: two captures of the same
+/// pixels are equal even if they were presented at different times.
 #[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Deserialize),
+    serde(try_from = "CaptureResultRepr")
+)]
 pub struct CaptureResult {
     /// Raw RGBA image data.
     ///
@@ -54,6 +89,24 @@ pub struct CaptureResult {
     width: u32,
     /// Height of the captured image in pixels.
     height: u32,
+    /// When the compositor presented the captured frame, if known.
+    timestamp: Option<std::time::Duration>,
+}
+
+impl PartialEq for CaptureResult {
+    fn eq(&self, other: &Self) -> bool {
+        self.width == other.width && self.height == other.height && self.data == other.data
+    }
+}
+
+impl Eq for CaptureResult {}
+
+impl std::hash::Hash for CaptureResult {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.data.hash(state);
+        self.width.hash(state);
+        self.height.hash(state);
+    }
 }
 
 impl CaptureResult {
@@ -62,6 +115,7 @@ impl CaptureResult {
             data,
             width,
             height,
+            timestamp: None,
         }
     }
 
@@ -80,809 +134,5226 @@ impl CaptureResult {
     pub fn into_data(self) -> Vec<u8> {
         self.data
     }
-}
-
-/// Information about a display output.
-#[derive(Debug, Clone)]
-pub struct Output {
-    /// Name of the output (e.g., "eDP-1", "HDMI-A-1").
-    name: String,
-    /// Geometry of the output (position and size).
-    geometry: Box,
-    /// Scale factor of the output (e.g., 1 for normal DPI, 2 for HiDPI).
-    scale: i32,
-    /// Description of the output (e.g., monitor model, manufacturer info).
-    description: Option<String>,
-}
-
-impl Output {
-    pub fn name(&self) -> &str {
-        &self.name
-    }
 
-    pub fn geometry(&self) -> &Box {
-        &self.geometry
+    /// Returns when the compositor presented the captured frame.
+    ///
+    /// Taken from the capture protocol's `ready`/`presentation_time` event and
+    /// measured on the compositor's `CLOCK_MONOTONIC` clock, so it can be
+    /// compared with input event times but not with wall-clock time. Results
+    /// derived from a single frame (cropped, scaled, oriented) keep its
+    /// timestamp. `None` for results built with [`CaptureResult::new`],
+    /// composites of several outputs, and compositors that send no time.
+    pub fn timestamp(&self) -> Option<std::time::Duration> {
+        self.timestamp
     }
 
-    pub fn scale(&self) -> i32 {
-        self.scale
-    }
+    /// Returns a hash of the image dimensions and pixel data.
+    ///
+    /// Two captures with the same hash almost certainly contain the same
+    /// image, which makes this a cheap way to detect screen changes.
+    /// The value is only meaningful within a single process: it is not
+    /// stable across Rust versions and must not be persisted.
+    pub fn content_hash(&self) -> u64 {
+        use std::hash::{Hash, Hasher};
 
-    pub fn description(&self) -> Option<&str> {
-        self.description.as_deref()
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        self.width.hash(&mut hasher);
+        self.height.hash(&mut hasher);
+        self.data.hash(&mut hasher);
+        hasher.finish()
     }
-}
 
-/// Parameters for capturing a specific output.
-///
-/// Allows specifying different capture parameters for each output when
-///
-/// capturing multiple outputs simultaneously.
-#[derive(Debug, Clone)]
-pub struct CaptureParameters {
-    /// Name of the output to capture.
-    ///
-    /// Must match one of the names returned by [`Grim::get_outputs`].
-    output_name: String,
-    /// Optional region within the output to capture.
+    /// Returns a histogram of pixel luma (BT.601 weights, alpha ignored).
     ///
-    /// If `None`, the entire output will be captured.
-    ///
-    /// If `Some(region)`, only the specified region will be captured.
-    ///
-    /// The region must be within the bounds of the output.
-    region: Option<Box>,
-    /// Whether to include the cursor in the capture.
-    ///
-    /// If `true`, the cursor will be included in the screenshot.
-    ///
-    /// If `false`, the cursor will be excluded from the screenshot.
-    overlay_cursor: bool,
-    /// Scale factor for the output image.
+    /// Entry `i` counts the pixels whose brightness rounds to `i`, which
+    /// makes it easy to tell an all-black capture from a real one.
     ///
-    /// If `None`, uses the default scale (typically the highest output scale).
+    /// # Example
     ///
-    /// If `Some(scale)`, the output image will be scaled accordingly.
-    scale: Option<f64>,
-}
-
-impl CaptureParameters {
-    /// Creates a new CaptureParameters with the specified output name.
+    /// ```rust
+    /// use grim_rs::CaptureResult;
     ///
-    /// By default, captures the entire output without cursor and with default scale.
-    pub fn new(output_name: impl Into<String>) -> Self {
-        Self {
-            output_name: output_name.into(),
-            region: None,
-            overlay_cursor: false,
-            scale: None,
+    /// let capture = CaptureResult::new(vec![0, 0, 0, 255, 255, 255, 255, 255], 2, 1);
+    /// let histogram = capture.histogram();
+    /// assert_eq!((histogram[0], histogram[255]), (1, 1));
+    /// ```
+    pub fn histogram(&self) -> [u32; 256] {
+        let mut luma = [0u32; 256];
+        for pixel in self.data.chunks_exact(4) {
+            let weighted =
+                77 * u32::from(pixel[0]) + 150 * u32::from(pixel[1]) + 29 * u32::from(pixel[2]);
+            luma[((weighted + 128) >> 8) as usize] += 1;
         }
+        luma
     }
 
-    /// Sets the region to capture within the output.
-    pub fn region(mut self, region: Box) -> Self {
-        self.region = Some(region);
-        self
-    }
-
-    /// Sets whether to include the cursor in the capture.
-    pub fn overlay_cursor(mut self, overlay_cursor: bool) -> Self {
-        self.overlay_cursor = overlay_cursor;
-        self
-    }
-
-    /// Sets the scale factor for the output image.
-    pub fn scale(mut self, scale: f64) -> Self {
-        self.scale = Some(scale);
-        self
-    }
-
-    /// Returns the output name.
-    pub fn output_name(&self) -> &str {
-        &self.output_name
-    }
-
-    /// Returns the region, if set.
-    pub fn region_ref(&self) -> Option<&Box> {
-        self.region.as_ref()
-    }
-
-    /// Returns whether cursor overlay is enabled.
-    pub fn overlay_cursor_enabled(&self) -> bool {
-        self.overlay_cursor
-    }
-
-    /// Returns the scale factor, if set.
-    pub fn scale_factor(&self) -> Option<f64> {
-        self.scale
+    /// Returns separate histograms of the red, green, blue and alpha
+    /// channels, in that order.
+    pub fn channel_histograms(&self) -> ([u32; 256], [u32; 256], [u32; 256], [u32; 256]) {
+        let mut red = [0u32; 256];
+        let mut green = [0u32; 256];
+        let mut blue = [0u32; 256];
+        let mut alpha = [0u32; 256];
+        for pixel in self.data.chunks_exact(4) {
+            red[pixel[0] as usize] += 1;
+            green[pixel[1] as usize] += 1;
+            blue[pixel[2] as usize] += 1;
+            alpha[pixel[3] as usize] += 1;
+        }
+        (red, green, blue, alpha)
     }
-}
 
-/// Result of capturing multiple outputs.
-///
-/// Contains a map of output names to their respective capture results.
-#[derive(Debug, Clone)]
-pub struct MultiOutputCaptureResult {
-    /// Map of output names to their capture results.
+    /// Returns `true` if every pixel is black, ignoring alpha.
     ///
-    /// The keys are output names, and the values are the corresponding
-    /// capture results for each output.
-    outputs: std::collections::HashMap<String, CaptureResult>,
-}
-
-impl MultiOutputCaptureResult {
-    /// Creates a new MultiOutputCaptureResult with the given outputs map.
-    pub fn new(outputs: std::collections::HashMap<String, CaptureResult>) -> Self {
-        Self { outputs }
-    }
-
-    /// Gets the capture result for the specified output name.
-    pub fn get(&self, output_name: &str) -> Option<&CaptureResult> {
-        self.outputs.get(output_name)
-    }
-
-    /// Returns a reference to the outputs map.
-    pub fn outputs(&self) -> &std::collections::HashMap<String, CaptureResult> {
-        &self.outputs
+    /// Some compositors report a successful capture but leave the buffer
+    /// zeroed; this detects that case. Stops at the first non-black pixel.
+    pub fn is_blank(&self) -> bool {
+        self.data
+            .chunks_exact(4)
+            .all(|pixel| pixel[..3] == [0, 0, 0])
     }
 
-    /// Consumes self and returns the outputs map.
-    pub fn into_outputs(self) -> std::collections::HashMap<String, CaptureResult> {
-        self.outputs
+    /// Returns `true` if all pixels are identical, including alpha.
+    ///
+    /// Stops at the first pixel that differs from the first one. An empty
+    /// image is uniform.
+    pub fn is_uniform(&self) -> bool {
+        let mut pixels = self.data.chunks_exact(4);
+        match pixels.next() {
+            Some(first) => pixels.all(|pixel| pixel == first),
+            None => true,
+        }
     }
-}
-
-/// Main interface for taking screenshots.
-///
-/// Provides methods for capturing screenshots of the entire screen,
-/// specific outputs, regions, or multiple outputs with different parameters.
-pub struct Grim {
-    platform_capture: PlatformCapture,
-}
 
-impl Grim {
-    /// Create a new Grim instance.
+    /// Returns the per-channel absolute difference between two captures.
     ///
-    /// Establishes a connection to the Wayland compositor and initializes
-    /// the necessary protocols for screen capture.
+    /// Each byte of the result is `|self - other|` for the same channel of
+    /// the same pixel, alpha included, so identical captures give an
+    /// all-zero image. Useful for visualizing what changed on screen.
     ///
     /// # Errors
     ///
-    /// Returns an error if:
-    /// - Cannot connect to the Wayland compositor
-    /// - Required Wayland protocols are not available
-    /// - Other initialization errors occur
+    /// Returns `Error::InvalidRegion` if the captures have different
+    /// dimensions, or `Error::ImageProcessing` if either data length
+    /// doesn't match its dimensions.
     ///
     /// # Example
     ///
     /// ```rust
-    /// use grim_rs::Grim;
+    /// use grim_rs::CaptureResult;
     ///
-    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
-    /// let grim = Grim::new()?;
-    /// # Ok(())
-    /// # }
+    /// let before = CaptureResult::new(vec![10, 20, 30, 255], 1, 1);
+    /// let after = CaptureResult::new(vec![15, 20, 0, 255], 1, 1);
+    /// assert_eq!(before.diff(&after)?.data(), &[5, 0, 30, 0]);
+    /// # Ok::<(), grim_rs::Error>(())
     /// ```
-    pub fn new() -> Result<Self> {
-        let platform_capture = PlatformCapture::new()?;
-        Ok(Self { platform_capture })
+    pub fn diff(&self, other: &CaptureResult) -> Result<CaptureResult> {
+        self.check_same_size(other)?;
+        let data = self
+            .data
+            .iter()
+            .zip(&other.data)
+            .map(|(&a, &b)| a.abs_diff(b))
+            .collect();
+        Ok(CaptureResult::new(data, self.width, self.height))
     }
 
-    /// Get information about available display outputs.
+    /// Summarizes how much two captures differ.
     ///
-    /// Returns a list of all connected display outputs with their names,
-    /// geometries, and scale factors.
+    /// Cheaper than [`diff`](Self::diff) when only a yes/no or a threshold
+    /// is needed, e.g. to assert in a UI test that a region did not change.
     ///
     /// # Errors
     ///
-    /// Returns an error if:
-    /// - No outputs are available
-    /// - Failed to retrieve output information
+    /// Same as [`diff`](Self::diff).
     ///
     /// # Example
     ///
     /// ```rust
-    /// use grim_rs::Grim;
-    ///
-    /// let mut grim = Grim::new()?;
-    /// let outputs = grim.get_outputs()?;
+    /// use grim_rs::CaptureResult;
     ///
-    /// for output in outputs {
-    ///     println!("Output: {} ({}x{})", output.name(), output.geometry().width(), output.geometry().height());
-    /// }
+    /// let before = CaptureResult::new(vec![0; 2 * 4], 2, 1);
+    /// let after = CaptureResult::new(vec![0, 0, 0, 0, 40, 0, 0, 0], 2, 1);
+    /// let stats = before.diff_stats(&after)?;
+    /// assert_eq!(stats.changed_pixels(), 1);
+    /// assert_eq!(stats.max_delta(), 40);
     /// # Ok::<(), grim_rs::Error>(())
     /// ```
-    pub fn get_outputs(&mut self) -> Result<Vec<Output>> {
-        self.platform_capture.get_outputs()
+    pub fn diff_stats(&self, other: &CaptureResult) -> Result<DiffStats> {
+        self.check_same_size(other)?;
+        let mut stats = DiffStats::default();
+        let mut total = 0u64;
+        for (a, b) in self.data.chunks_exact(4).zip(other.data.chunks_exact(4)) {
+            let mut changed = false;
+            for (&a, &b) in a.iter().zip(b) {
+                let delta = a.abs_diff(b);
+                total += delta as u64;
+                stats.max_delta = stats.max_delta.max(delta);
+                changed |= delta != 0;
+            }
+            stats.changed_pixels += changed as u64;
+        }
+        if !self.data.is_empty() {
+            stats.mean_delta = total as f64 / self.data.len() as f64;
+        }
+        Ok(stats)
     }
 
-    /// Capture the entire screen (all outputs).
+    fn check_same_size(&self, other: &CaptureResult) -> Result<()> {
+        if (self.width, self.height) != (other.width, other.height) {
+            return Err(Error::InvalidRegion(format!(
+                "cannot compare a {}x{} capture with a {}x{} one",
+                self.width, self.height, other.width, other.height
+            )));
+        }
+        check_rgba_len(&self.data, self.width, self.height)?;
+        check_rgba_len(&other.data, other.width, other.height)
+    }
+
+    /// Returns a copy of the image scaled by `factor`.
     ///
-    /// Captures a screenshot that includes all connected display outputs,
-    /// arranged according to their physical positions.
+    /// Uses the same resampling as [`CaptureOptions::scale`] with
+    /// [`ScaleFilter::Auto`], including the fast pixel-duplicating path for
+    /// 2x, 3x and 4x upscaling. No [`Grim`] instance is needed.
     ///
     /// # Errors
     ///
-    /// Returns an error if:
-    /// - No outputs are available
-    /// - Failed to capture the screen
-    /// - Buffer creation failed
+    /// Returns `Error::InvalidRegion` if the scaled image would be empty, or
+    /// `Error::ImageProcessing` if the data length doesn't match the
+    /// dimensions.
     ///
     /// # Example
     ///
     /// ```rust
-    /// use grim_rs::Grim;
+    /// use grim_rs::CaptureResult;
     ///
-    /// let mut grim = Grim::new()?;
-    /// let result = grim.capture_all()?;
-    /// println!("Captured screen: {}x{}", result.width(), result.height());
+    /// let capture = CaptureResult::new(vec![255; 4 * 4 * 4], 4, 4);
+    /// let half = capture.scale(0.5)?;
+    /// assert_eq!((half.width(), half.height()), (2, 2));
     /// # Ok::<(), grim_rs::Error>(())
     /// ```
-    pub fn capture_all(&mut self) -> Result<CaptureResult> {
-        self.platform_capture.capture_all()
+    pub fn scale(&self, factor: f64) -> Result<CaptureResult> {
+        check_rgba_len(&self.data, self.width, self.height)?;
+        wayland_capture::scale_capture(self.clone(), factor, ScaleFilter::Auto)
     }
 
-    /// Capture the entire screen (all outputs) with specified scale factor.
+    /// Returns a copy of the image resampled to exactly `width`x`height`.
     ///
-    /// Captures a screenshot that includes all connected display outputs,
-    /// arranged according to their physical positions, with a specified scale factor.
+    /// The filter is chosen like [`ScaleFilter::Auto`] from the smaller of
+    /// the horizontal and vertical scale factors.
     ///
-    /// # Arguments
+    /// # Errors
     ///
-    /// * `scale` - Scale factor for the output image
+    /// Returns `Error::InvalidRegion` if `width` or `height` is zero, or
+    /// `Error::ImageProcessing` if the data length doesn't match the
+    /// dimensions.
+    pub fn scale_to(&self, width: u32, height: u32) -> Result<CaptureResult> {
+        self.resized(width, height, ScaleFilter::Auto)
+    }
+
+    /// Returns a copy of the image resampled to `new_width`x`new_height`
+    /// with `filter`.
+    ///
+    /// With [`ScaleFilter::Auto`] or [`ScaleFilter::Nearest`], exact 2x, 3x
+    /// and 4x upscaling duplicates pixels without resampling. Otherwise the
+    /// [`ScaleFilter::Auto`] filter is picked from the smaller of the
+    /// horizontal and vertical scale factors.
     ///
     /// # Errors
     ///
-    /// Returns an error if:
-    /// - No outputs are available
-    /// - Failed to capture the screen
-    /// - Buffer creation failed
+    /// Returns `Error::InvalidRegion` if `new_width` or `new_height` is
+    /// zero, or `Error::ImageProcessing` if the data length doesn't match
+    /// the dimensions.
     ///
     /// # Example
     ///
     /// ```rust
-    /// use grim_rs::Grim;
+    /// use grim_rs::{CaptureResult, ScaleFilter};
     ///
-    /// let mut grim = Grim::new()?;
-    /// let result = grim.capture_all_with_scale(1.0)?;
-    /// println!("Captured screen: {}x{}", result.width(), result.height());
+    /// let capture = CaptureResult::new(vec![255; 4 * 4 * 4], 4, 4);
+    /// let thumb = capture.resized(3, 2, ScaleFilter::Lanczos3)?;
+    /// assert_eq!((thumb.width(), thumb.height()), (3, 2));
     /// # Ok::<(), grim_rs::Error>(())
     /// ```
-    pub fn capture_all_with_scale(&mut self, scale: f64) -> Result<CaptureResult> {
-        self.platform_capture.capture_all_with_scale(scale)
+    pub fn resized(
+        &self,
+        new_width: u32,
+        new_height: u32,
+        filter: ScaleFilter,
+    ) -> Result<CaptureResult> {
+        check_rgba_len(&self.data, self.width, self.height)?;
+        if new_width == 0 || new_height == 0 {
+            return Err(Error::InvalidRegion(
+                "Scaled dimensions must be positive".to_string(),
+            ));
+        }
+        if new_width == self.width && new_height == self.height {
+            return Ok(self.clone());
+        }
+        if matches!(filter, ScaleFilter::Auto | ScaleFilter::Nearest) {
+            if let Some(factor) =
+                (2..=4).find(|&f| new_width == self.width * f && new_height == self.height * f)
+            {
+                return Ok(wayland_capture::scale_integer_fast(self.clone(), factor));
+            }
+        }
+        let factor = (new_width as f64 / self.width.max(1) as f64)
+            .min(new_height as f64 / self.height.max(1) as f64);
+        wayland_capture::resize_capture(
+            self.clone(),
+            new_width,
+            new_height,
+            wayland_capture::filter_type(filter, factor),
+        )
     }
 
-    /// Capture a specific output by name.
-    ///
-    /// Captures a screenshot of the specified display output.
+    /// Extract a sub-region of the captured image without re-capturing.
     ///
-    /// # Arguments
-    ///
-    /// * `output_name` - Name of the output to capture (e.g., "eDP-1", "HDMI-A-1")
+    /// `region` is given in image pixel coordinates, with `(0, 0)` being the
+    /// top-left corner of this capture.
     ///
     /// # Errors
     ///
-    /// Returns an error if:
-    /// - The specified output is not found
-    /// - Failed to capture the output
-    /// - Buffer creation failed
+    /// Returns `Error::InvalidRegion` if `region` is empty or does not lie
+    /// entirely within the image bounds.
     ///
     /// # Example
     ///
     /// ```rust
-    /// use grim_rs::Grim;
+    /// use grim_rs::{Box, Grim};
     ///
     /// let mut grim = Grim::new()?;
-    /// // Get available outputs first
-    /// let outputs = grim.get_outputs()?;
-    /// if let Some(output) = outputs.first() {
-    ///     let result = grim.capture_output(output.name())?;
-    ///     println!("Captured output: {}x{}", result.width(), result.height());
-    /// }
+    /// let result = grim.capture_all()?;
+    /// let corner = result.crop(Box::new(0, 0, 200, 200))?;
+    /// assert_eq!(corner.width(), 200);
     /// # Ok::<(), grim_rs::Error>(())
     /// ```
-    pub fn capture_output(&mut self, output_name: &str) -> Result<CaptureResult> {
-        self.platform_capture.capture_output(output_name)
+    pub fn crop(&self, region: Box) -> Result<CaptureResult> {
+        let within_bounds = region.x() >= 0
+            && region.y() >= 0
+            && region.x() as i64 + region.width() as i64 <= self.width as i64
+            && region.y() as i64 + region.height() as i64 <= self.height as i64;
+        if region.is_empty() || !within_bounds {
+            return Err(Error::InvalidRegion(format!(
+                "crop region {} is outside of the {}x{} image",
+                region, self.width, self.height
+            )));
+        }
+
+        let stride = self.width as usize * 4;
+        let row_len = region.width() as usize * 4;
+        let x_offset = region.x() as usize * 4;
+        let mut data = Vec::with_capacity(row_len * region.height() as usize);
+        for row in self
+            .data
+            .chunks_exact(stride)
+            .skip(region.y() as usize)
+            .take(region.height() as usize)
+        {
+            data.extend_from_slice(&row[x_offset..x_offset + row_len]);
+        }
+
+        Ok(CaptureResult {
+            data,
+            width: region.width() as u32,
+            height: region.height() as u32,
+            timestamp: self.timestamp,
+        })
     }
 
-    /// Capture a specific output by name with specified scale factor.
+    /// Pixelate `region` in place, replacing each `block_size`x`block_size`
+    /// cell with its average color.
     ///
-    /// Captures a screenshot of the specified display output with a specified scale factor.
-    ///
-    /// # Arguments
-    ///
-    /// * `output_name` - Name of the output to capture (e.g., "eDP-1", "HDMI-A-1")
-    /// * `scale` - Scale factor for the output image
-    ///
-    /// # Errors
-    ///
-    /// Returns an error if:
-    /// - The specified output is not found
-    /// - Failed to capture the output
-    /// - Buffer creation failed
+    /// `region` is given in image pixel coordinates and is clamped to the
+    /// image bounds. Nothing happens if it lies entirely outside the image or
+    /// `block_size` is 0.
     ///
     /// # Example
     ///
     /// ```rust
-    /// use grim_rs::Grim;
+    /// use grim_rs::{Box, CaptureResult};
     ///
-    /// let mut grim = Grim::new()?;
-    /// // Get available outputs first
-    /// let outputs = grim.get_outputs()?;
-    /// if let Some(output) = outputs.first() {
-    ///     let result = grim.capture_output_with_scale(output.name(), 0.5)?;
-    ///     println!("Captured output at 50% scale: {}x{}", result.width(), result.height());
-    /// }
-    /// # Ok::<(), grim_rs::Error>(())
+    /// let mut capture = CaptureResult::new(vec![255; 8 * 8 * 4], 8, 8);
+    /// capture.pixelate(Box::new(2, 2, 4, 4), 2);
     /// ```
-    pub fn capture_output_with_scale(
-        &mut self,
-        output_name: &str,
-        scale: f64,
-    ) -> Result<CaptureResult> {
-        self.platform_capture
-            .capture_output_with_scale(output_name, scale)
+    pub fn pixelate(&mut self, region: Box, block_size: u32) {
+        if block_size == 0 {
+            return;
+        }
+        if let Some(region) = self.clamp_region(region) {
+            redact::pixelate(
+                &mut self.data,
+                self.width as usize,
+                region,
+                block_size as usize,
+            );
+        }
     }
 
-    /// Capture a specific region.
+    /// Blur `region` in place with an approximate gaussian of standard
+    /// deviation `sigma` pixels.
     ///
-    /// Captures a screenshot of the specified rectangular region.
-    ///
-    /// # Arguments
-    ///
-    /// * `region` - The region to capture, specified as a [`Box`]
-    ///
-    /// # Errors
-    ///
-    /// Returns an error if:
-    /// - No outputs are available
-    /// - Failed to capture the region
-    /// - Buffer creation failed
+    /// `region` is given in image pixel coordinates and is clamped to the
+    /// image bounds. Only pixels inside the region are sampled, so nothing
+    /// outside it bleeds in. Nothing happens if the region lies entirely
+    /// outside the image or `sigma` is not positive.
     ///
     /// # Example
     ///
     /// ```rust
-    /// use grim_rs::{Grim, Box};
+    /// use grim_rs::{Box, CaptureResult};
     ///
-    /// let mut grim = Grim::new()?;
-    /// // x=100, y=100, width=800, height=600
-    /// let region = Box::new(100, 100, 800, 600);
-    /// let result = grim.capture_region(region)?;
-    /// println!("Captured region: {}x{}", result.width(), result.height());
-    /// # Ok::<(), grim_rs::Error>(())
+    /// let mut capture = CaptureResult::new(vec![255; 8 * 8 * 4], 8, 8);
+    /// capture.blur(Box::new(0, 0, 8, 4), 2.0);
     /// ```
-    pub fn capture_region(&mut self, region: Box) -> Result<CaptureResult> {
-        self.platform_capture.capture_region(region)
+    pub fn blur(&mut self, region: Box, sigma: f32) {
+        if sigma.is_nan() || sigma <= 0.0 {
+            return;
+        }
+        if let Some(region) = self.clamp_region(region) {
+            redact::blur(&mut self.data, self.width as usize, region, sigma);
+        }
     }
 
-    /// Capture a specific region with specified scale factor.
-    ///
-    /// Captures a screenshot of the specified rectangular region with a specified scale factor.
-    ///
-    /// # Arguments
-    ///
-    /// * `region` - The region to capture, specified as a [`Box`]
-    /// * `scale` - Scale factor for the output image
-    ///
-    /// # Errors
+    /// Fill `region` with `rgba`, blending it over the existing pixels
+    /// according to its alpha.
     ///
-    /// Returns an error if:
-    /// - No outputs are available
-    /// - Failed to capture the region
-    /// - Buffer creation failed
+    /// `region` is given in image pixel coordinates and is clamped to the
+    /// image bounds. An alpha of 255 overwrites the pixels; an alpha of 0
+    /// leaves them unchanged.
     ///
     /// # Example
     ///
     /// ```rust
-    /// use grim_rs::{Grim, Box};
+    /// use grim_rs::{Box, CaptureResult};
     ///
-    /// let mut grim = Grim::new()?;
-    /// // x=100, y=100, width=800, height=600
-    /// let region = Box::new(100, 100, 800, 600);
-    /// let result = grim.capture_region_with_scale(region, 1.0)?;
-    /// println!("Captured region: {}x{}", result.width(), result.height());
-    /// # Ok::<(), grim_rs::Error>(())
+    /// let mut capture = CaptureResult::new(vec![255; 8 * 8 * 4], 8, 8);
+    /// capture.fill_rect(Box::new(2, 2, 4, 4), [255, 0, 0, 128]);
     /// ```
-    pub fn capture_region_with_scale(&mut self, region: Box, scale: f64) -> Result<CaptureResult> {
-        self.platform_capture
-            .capture_region_with_scale(region, scale)
+    pub fn fill_rect(&mut self, region: Box, rgba: [u8; 4]) {
+        let Some(region) = self.clamp_region(region) else {
+            return;
+        };
+        let stride = self.width as usize * 4;
+        let x_offset = region.x() as usize * 4;
+        let row_len = region.width() as usize * 4;
+        for row in self
+            .data
+            .chunks_exact_mut(stride)
+            .skip(region.y() as usize)
+            .take(region.height() as usize)
+        {
+            for pixel in row[x_offset..x_offset + row_len].chunks_exact_mut(4) {
+                wayland_capture::blend_pixel(pixel, &rgba);
+            }
+        }
     }
 
-    /// Capture multiple outputs with different parameters.
-    ///
-    /// Captures screenshots of multiple outputs simultaneously, each with
-    /// potentially different parameters (region, cursor inclusion, etc.).
-    ///
-    /// # Arguments
+    /// Draw a `thickness` pixel border just inside the edges of `region`,
+    /// blended like [`fill_rect`](Self::fill_rect).
+    ///
+    /// The border is clamped to the image bounds, so only the visible sides
+    /// of a partly off-screen region are drawn. A border thick enough to
+    /// cover the whole region fills it.
+    pub fn draw_rect_border(&mut self, region: Box, thickness: u32, rgba: [u8; 4]) {
+        if thickness == 0 || region.is_empty() {
+            return;
+        }
+        let thickness = thickness.min(i32::MAX as u32) as i32;
+        if thickness.saturating_mul(2) >= region.width().min(region.height()) {
+            self.fill_rect(region, rgba);
+            return;
+        }
+        let (x, y, width, height) = (region.x(), region.y(), region.width(), region.height());
+        let inner_height = height - 2 * thickness;
+        for side in [
+            Box::new(x, y, width, thickness),
+            Box::new(x, y + height - thickness, width, thickness),
+            Box::new(x, y + thickness, thickness, inner_height),
+            Box::new(
+                x + width - thickness,
+                y + thickness,
+                thickness,
+                inner_height,
+            ),
+        ] {
+            self.fill_rect(side, rgba);
+        }
+    }
+
+    /// Clamp `region` to the image, or `None` if nothing is left to touch.
+    fn clamp_region(&self, region: Box) -> Option<Box> {
+        check_rgba_len(&self.data, self.width, self.height).ok()?;
+        let bounds = Box::new(0, 0, self.width as i32, self.height as i32);
+        region.intersection(&bounds)
+    }
+
+    /// Encode this capture as PNG, consuming it.
     ///
-    /// * `parameters` - Vector of [`CaptureParameters`] specifying what to capture
-    ///   from each output
+    /// Shorthand for [`Grim::into_png`] with the default compression level (6).
     ///
     /// # Errors
     ///
-    /// Returns an error if:
-    /// - Any specified output is not found
-    /// - Any specified region is outside the bounds of its output
-    /// - Failed to capture any of the outputs
-    /// - Buffer creation failed
-    ///
-    /// # Example
-    ///
-    /// ```rust
-    /// use grim_rs::{Grim, CaptureParameters, Box};
-    ///
-    /// let mut grim = Grim::new()?;
-    ///
-    /// // Get available outputs
-    /// let outputs = grim.get_outputs()?;
-    ///
-    /// // Prepare capture parameters for multiple outputs
-    /// let mut parameters = vec![
-    ///     CaptureParameters::new(outputs[0].name())
-    ///         .overlay_cursor(true)
-    /// ];
-    ///
-    /// // If we have a second output, capture a region of it
-    /// if outputs.len() > 1 {
-    ///     let region = Box::new(0, 0, 400, 300);
-    ///     parameters.push(
-    ///         CaptureParameters::new(outputs[1].name())
-    ///             .region(region)
-    ///     );
-    /// }
-    ///
-    /// // Capture all specified outputs
-    /// let results = grim.capture_outputs(parameters)?;
-    /// println!("Captured {} outputs", results.outputs().len());
-    /// # Ok::<(), grim_rs::Error>(())
-    /// ```
-    pub fn capture_outputs(
-        &mut self,
-        parameters: Vec<CaptureParameters>,
-    ) -> Result<MultiOutputCaptureResult> {
-        self.platform_capture.capture_outputs(parameters)
+    /// Returns an error if image processing failed.
+    pub fn into_png(self, grim: &Grim) -> Result<Vec<u8>> {
+        grim.into_png(self, 6)
     }
 
-    /// Capture outputs with scale factor.
-    ///
-    /// Captures screenshots of multiple outputs simultaneously with a specific scale factor.
-    ///
-    /// # Arguments
+    /// Encode this capture as JPEG, consuming it.
     ///
-    /// * `parameters` - Vector of CaptureParameters with scale factors
-    /// * `default_scale` - Default scale factor to use when not specified in parameters
+    /// Shorthand for [`Grim::to_jpeg_owned`].
     ///
     /// # Errors
     ///
-    /// Returns an error if any of the outputs can't be captured
-    pub fn capture_outputs_with_scale(
-        &mut self,
-        parameters: Vec<CaptureParameters>,
-        default_scale: f64,
-    ) -> Result<MultiOutputCaptureResult> {
-        self.platform_capture
-            .capture_outputs_with_scale(parameters, default_scale)
+    /// Returns an error if image processing failed or JPEG support is not enabled.
+    pub fn into_jpeg(self, grim: &Grim, quality: u8) -> Result<Vec<u8>> {
+        let (width, height) = (self.width, self.height);
+        grim.to_jpeg_owned(self.data, width, height, quality)
     }
 
-    /// Save captured data as PNG.
-    ///
-    /// Saves the captured image data to a PNG file.
-    ///
-    /// # Arguments
+    /// Encode this capture as PPM, consuming it.
     ///
-    /// * `data` - Raw RGBA image data from a capture result
-    /// * `width` - Width of the image in pixels
-    /// * `height` - Height of the image in pixels
-    /// * `path` - Path where to save the PNG file
+    /// Shorthand for [`Grim::to_ppm_owned`].
     ///
     /// # Errors
     ///
-    /// Returns an error if:
-    /// - Failed to create or write to the file
-    /// - Image processing failed
-    ///
-    /// # Example
-    ///
-    /// ```rust,no_run
-    /// use grim_rs::Grim;
-    /// use chrono::Local;
-    ///
-    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
-    /// let mut grim = Grim::new()?;
-    /// let result = grim.capture_all()?;
-    ///
-    /// // Generate timestamped filename
-    /// let filename = format!("{}_grim.png", Local::now().format("%Y%m%d_%Hh%Mm%Ss"));
-    /// grim.save_png(result.data(), result.width(), result.height(), &filename)?;
-    /// # Ok(())
-    /// # }
-    /// ```
-    pub fn save_png<P: AsRef<std::path::Path>>(
-        &self,
-        data: &[u8],
-        width: u32,
-        height: u32,
-        path: P,
-    ) -> Result<()> {
-        self.save_png_with_compression(data, width, height, path, 6) // Default compression level of 6
+    /// Returns an error if image processing failed.
+    pub fn into_ppm(self, grim: &Grim) -> Result<Vec<u8>> {
+        let (width, height) = (self.width, self.height);
+        grim.to_ppm_owned(self.data, width, height)
     }
 
-    /// Save captured data as PNG with compression level control.
+    /// Convert this capture into an [`image::DynamicImage`].
     ///
-    /// Saves the captured image data to a PNG file with specified compression level.
-    ///
-    /// # Arguments
-    ///
-    /// * `data` - Raw RGBA image data from a capture result
-    /// * `width` - Width of the image in pixels
-    /// * `height` - Height of the image in pixels
-    /// * `path` - Path where to save the PNG file
-    /// * `compression` - PNG compression level (0-9, where 9 is highest compression)
+    /// The pixel data is copied into an `ImageBuffer<Rgba<u8>, _>` and
+    /// lifted into `DynamicImage::ImageRgba8`, ready for the `image` crate's
+    /// filters, drawing helpers and encoders.
     ///
     /// # Errors
     ///
-    /// Returns an error if:
-    /// - Failed to create or write to the file
-    /// - Image processing failed
+    /// Returns an error if the buffer length does not match `width * height * 4`.
     ///
     /// # Example
     ///
-    /// ```rust,no_run
+    /// ```rust
     /// use grim_rs::Grim;
-    /// use chrono::Local;
     ///
-    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
     /// let mut grim = Grim::new()?;
     /// let result = grim.capture_all()?;
-    ///
-    /// // Generate timestamped filename
-    /// let filename = format!("{}_grim.png", Local::now().format("%Y%m%d_%Hh%Mm%Ss"));
-    /// grim.save_png_with_compression(result.data(), result.width(), result.height(), &filename, 9)?;
-    /// # Ok(())
-    /// # }
+    /// let gray = result.to_image()?.grayscale();
+    /// println!("{}x{}", gray.width(), gray.height());
+    /// # Ok::<(), grim_rs::Error>(())
     /// ```
-    pub fn save_png_with_compression<P: AsRef<std::path::Path>>(
-        &self,
-        data: &[u8],
-        width: u32,
-        height: u32,
-        path: P,
-        compression: u8,
-    ) -> Result<()> {
-        use image::{ImageBuffer, Rgba};
-        use std::io::BufWriter;
-
-        let _img = ImageBuffer::<Rgba<u8>, _>::from_raw(width, height, data.to_vec()).ok_or(
+    pub fn to_image(&self) -> Result<image::DynamicImage> {
+        let buffer = image::RgbaImage::from_raw(self.width, self.height, self.data.clone()).ok_or(
             Error::ImageProcessing(image::ImageError::Parameter(
                 image::error::ParameterError::from_kind(
                     image::error::ParameterErrorKind::DimensionMismatch,
                 ),
             )),
         )?;
+        Ok(image::DynamicImage::ImageRgba8(buffer))
+    }
 
-        let file = std::fs::File::create(&path).map_err(|e| Error::IoWithContext {
-            operation: format!("creating output file '{}'", path.as_ref().display()),
-            source: e,
-        })?;
-        let writer = BufWriter::new(file);
-        let mut encoder = png::Encoder::new(writer, width, height);
-
-        let compression_level = match compression {
-            0 => png::Compression::Fast,
-            1..=3 => png::Compression::Best,
-            4..=6 => png::Compression::Default,
-            7..=9 => png::Compression::Best,
-            _ => png::Compression::Default,
-        };
-        encoder.set_compression(compression_level);
+    /// Create a capture result from an [`image::DynamicImage`].
+    ///
+    /// Images that are not already 8-bit RGBA are converted first.
+    pub fn from_image(img: image::DynamicImage) -> Self {
+        let rgba = img.into_rgba8();
+        let (width, height) = rgba.dimensions();
+        Self::new(rgba.into_raw(), width, height)
+    }
+}
 
-        encoder.set_color(png::ColorType::Rgba);
-        encoder.set_filter(png::FilterType::NoFilter);
+/// Serialized form of [`CaptureResult`], with the pixels as a PNG stream.
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename = "CaptureResult")]
+struct CaptureResultRepr {
+    width: u32,
+    height: u32,
+    #[serde(default)]
+    timestamp: Option<std::time::Duration>,
+    png: Vec<u8>,
+}
 
-        let mut writer = encoder.write_header().map_err(|e| {
-            Error::Io(std::io::Error::new(
-                std::io::ErrorKind::Other,
-                format!("PNG encoding error: {}", e),
-            ))
-        })?;
+#[cfg(feature = "serde")]
+impl serde::Serialize for CaptureResult {
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        use image::ImageEncoder;
 
-        writer.write_image_data(data).map_err(|e| {
-            Error::Io(std::io::Error::new(
-                std::io::ErrorKind::Other,
-                format!("PNG encoding error: {}", e),
-            ))
-        })?;
-        writer.finish().map_err(|e| {
-            Error::Io(std::io::Error::new(
-                std::io::ErrorKind::Other,
-                format!("PNG encoding error: {}", e),
-            ))
-        })?;
+        let mut png = Vec::new();
+        image::codecs::png::PngEncoder::new(&mut png)
+            .write_image(
+                &self.data,
+                self.width,
+                self.height,
+                image::ExtendedColorType::Rgba8,
+            )
+            .map_err(serde::ser::Error::custom)?;
+        CaptureResultRepr {
+            width: self.width,
+            height: self.height,
+            timestamp: self.timestamp,
+            png,
+        }
+        .serialize(serializer)
+    }
+}
 
-        Ok(())
+#[cfg(feature = "serde")]
+impl TryFrom<CaptureResultRepr> for CaptureResult {
+    type Error = Error;
+
+    fn try_from(repr: CaptureResultRepr) -> Result<Self> {
+        let rgba =
+            image::load_from_memory_with_format(&repr.png, image::ImageFormat::Png)?.into_rgba8();
+        if rgba.dimensions() != (repr.width, repr.height) {
+            return Err(Error::InvalidGeometry(format!(
+                "PNG is {}x{} but the capture is {}x{}",
+                rgba.width(),
+                rgba.height(),
+                repr.width,
+                repr.height
+            )));
+        }
+        Ok(Self {
+            data: rgba.into_raw(),
+            width: repr.width,
+            height: repr.height,
+            timestamp: repr.timestamp,
+        })
     }
+}
 
-    /// Save captured data as JPEG.
-    ///
-    /// Saves the captured image data to a JPEG file.
-    ///
-    /// This function is only available when the `jpeg` feature is enabled.
+/// Repeatedly call `capture` until two consecutive frames have the same
+/// [`CaptureResult::content_hash`] or `max_wait` elapses.
+///
+/// Returns the stable frame, or the last captured frame on timeout.
+fn wait_for_stable_frame<F>(
+    mut capture: F,
+    max_wait: std::time::Duration,
+    poll_interval: std::time::Duration,
+) -> Result<CaptureResult>
+where
+    F: FnMut() -> Result<CaptureResult>,
+{
+    use std::time::Instant;
+
+    let deadline = Instant::now() + max_wait;
+    let mut previous = capture()?;
+    let mut previous_hash = previous.content_hash();
+
+    loop {
+        let now = Instant::now();
+        if now >= deadline {
+            return Ok(previous);
+        }
+        std::thread::sleep(poll_interval.min(deadline - now));
+
+        let current = capture()?;
+        let current_hash = current.content_hash();
+        if current_hash == previous_hash {
+            return Ok(current);
+        }
+        previous = current;
+        previous_hash = current_hash;
+    }
+}
+
+/// Apply compression level, color type and row filter to a PNG encoder.
+fn configure_png_encoder<W: std::io::Write>(encoder: &mut png::Encoder<W>, options: &PngOptions) {
+    let compression_level = match options.compression {
+        0 => png::Compression::Fast,
+        1..=3 => png::Compression::Best,
+        4..=6 => png::Compression::Default,
+        7..=9 => png::Compression::Best,
+        _ => png::Compression::Default,
+    };
+    encoder.set_compression(compression_level);
+    encoder.set_color(png::ColorType::Rgba);
+
+    let filter = match options.filter {
+        PngFilter::NoFilter => png::FilterType::NoFilter,
+        PngFilter::Sub => png::FilterType::Sub,
+        PngFilter::Up => png::FilterType::Up,
+        PngFilter::Avg => png::FilterType::Avg,
+        PngFilter::Paeth => png::FilterType::Paeth,
+        PngFilter::Adaptive => {
+            encoder.set_adaptive_filter(png::AdaptiveFilterType::Adaptive);
+            return;
+        }
+    };
+    encoder.set_filter(filter);
+}
+
+/// Encode RGBA data as PNG straight into `writer`.
+fn write_png<W: std::io::Write>(
+    writer: W,
+    data: &[u8],
+    width: u32,
+    height: u32,
+    options: &PngOptions,
+    metadata: Option<&PngMetadata>,
+) -> Result<()> {
+    let png_error = |e: png::EncodingError| {
+        Error::Io(std::io::Error::other(format!("PNG encoding error: {}", e)))
+    };
+
+    check_rgba_len(data, width, height)?;
+    let mut encoder = png::Encoder::new(writer, width, height);
+    configure_png_encoder(&mut encoder, options);
+    if let Some(metadata) = metadata {
+        add_png_metadata(&mut encoder, metadata).map_err(png_error)?;
+    }
+    if options.embed_dpi {
+        let dpi = metadata
+            .and_then(|metadata| metadata.dpi)
+            .unwrap_or(DEFAULT_PNG_DPI);
+        let pixels_per_meter = png_pixels_per_meter(dpi);
+        encoder.set_pixel_dims(Some(png::PixelDimensions {
+            xppu: pixels_per_meter,
+            yppu: pixels_per_meter,
+            unit: png::Unit::Meter,
+        }));
+    }
+    let mut writer = encoder.write_header().map_err(png_error)?;
+    writer.write_image_data(data).map_err(png_error)?;
+    writer.finish().map_err(png_error)
+}
+
+/// Encode RGBA data as PNG into a new file at `path`.
+fn save_png_file(
+    path: &std::path::Path,
+    data: &[u8],
+    width: u32,
+    height: u32,
+    options: &PngOptions,
+    metadata: Option<&PngMetadata>,
+) -> Result<()> {
+    use std::io::{BufWriter, Write};
+
+    check_rgba_len(data, width, height)?;
+    let file = std::fs::File::create(path).map_err(|e| Error::IoWithContext {
+        operation: format!("creating output file '{}'", path.display()),
+        source: e,
+    })?;
+    let mut writer = BufWriter::new(file);
+    write_png(&mut writer, data, width, height, options, metadata)?;
+    writer.flush().map_err(|e| Error::IoWithContext {
+        operation: format!("writing PNG data to file '{}'", path.display()),
+        source: e,
+    })?;
+    Ok(())
+}
+
+/// Resolution written to the pHYs chunk when the source scale is unknown.
+const DEFAULT_PNG_DPI: f64 = 96.0;
+
+/// Convert `dpi` to the pixels-per-meter unit of the PNG pHYs chunk.
+fn png_pixels_per_meter(dpi: f64) -> u32 {
+    (dpi / 0.0254).round() as u32
+}
+
+/// Queue the tEXt chunks for `metadata` on `encoder`.
+///
+/// `metadata.dpi` is written by [`write_png`] as part of the pHYs chunk.
+fn add_png_metadata<W: std::io::Write>(
+    encoder: &mut png::Encoder<W>,
+    metadata: &PngMetadata,
+) -> std::result::Result<(), png::EncodingError> {
+    if let Some(software) = &metadata.software {
+        encoder.add_text_chunk("Software".to_string(), software.clone())?;
+    }
+    if let Some(time) = metadata.creation_time {
+        // The PNG spec recommends the RFC 1123 date format for this keyword.
+        let time = chrono::DateTime::<chrono::Utc>::from(time).to_rfc2822();
+        encoder.add_text_chunk("Creation Time".to_string(), time)?;
+    }
+    Ok(())
+}
+
+/// PNG metadata for a capture of `logical_width` logical pixels that is
+/// `pixel_width` pixels wide, assuming 96 DPI at scale 1.
+fn capture_png_metadata(pixel_width: u32, logical_width: i32) -> PngMetadata {
+    let scale = if logical_width > 0 {
+        pixel_width as f64 / logical_width as f64
+    } else {
+        1.0
+    };
+    PngMetadata::screenshot(96.0 * scale)
+}
+
+/// The output whose logical geometry contains the point `(x, y)`.
+fn output_at(outputs: &[Output], x: f64, y: f64) -> Option<&Output> {
+    outputs.iter().find(|output| {
+        let geometry = output.geometry;
+        x >= geometry.x() as f64
+            && y >= geometry.y() as f64
+            && x < (geometry.x() + geometry.width()) as f64
+            && y < (geometry.y() + geometry.height()) as f64
+    })
+}
+
+/// A cursor bitmap for software cursor compositing.
+///
+/// Used with [`CaptureOptions::software_cursor`] and [`blend_cursor`]. The
+/// hotspot is the pixel of the image that sits exactly at the pointer
+/// position, e.g. the tip of an arrow.
+///
+/// # Example
+///
+/// ```rust
+/// use grim_rs::{CaptureResult, CursorImage};
+///
+/// let arrow = CaptureResult::new(vec![255; 16 * 16 * 4], 16, 16);
+/// let cursor = CursorImage::new(arrow, 1, 1);
+/// assert_eq!(cursor.hotspot(), (1, 1));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CursorImage {
+    /// Straight-alpha RGBA cursor pixels.
+    image: CaptureResult,
+    hotspot_x: i32,
+    hotspot_y: i32,
+}
+
+impl CursorImage {
+    /// Creates a cursor from an RGBA image and its hotspot in image pixels.
+    pub fn new(image: CaptureResult, hotspot_x: i32, hotspot_y: i32) -> Self {
+        Self {
+            image,
+            hotspot_x,
+            hotspot_y,
+        }
+    }
+
+    /// Returns the cursor image.
+    pub fn image(&self) -> &CaptureResult {
+        &self.image
+    }
+
+    /// Returns the hotspot as `(x, y)` in image pixels.
+    pub fn hotspot(&self) -> (i32, i32) {
+        (self.hotspot_x, self.hotspot_y)
+    }
+}
+
+/// Alpha-blend `cursor` onto an RGBA image with its hotspot at `(x, y)`.
+///
+/// `x` and `y` are in image pixels and may lie anywhere: parts of the
+/// cursor outside the image are clipped. The cursor is drawn at its own
+/// pixel size, so supply a bitmap matching the image's scale.
+///
+/// # Errors
+///
+/// Returns `Error::ImageProcessing` if `data` or the cursor image doesn't
+/// match its dimensions.
+///
+/// # Example
+///
+/// ```rust
+/// use grim_rs::{blend_cursor, CaptureResult, CursorImage};
+///
+/// let mut data = vec![0u8; 8 * 8 * 4];
+/// let cursor = CursorImage::new(CaptureResult::new(vec![255; 4 * 4 * 4], 4, 4), 0, 0);
+/// blend_cursor(&mut data, 8, 8, &cursor, 6, 6)?;
+/// assert_eq!(&data[(7 * 8 + 7) * 4..], &[255, 255, 255, 255]);
+/// # Ok::<(), grim_rs::Error>(())
+/// ```
+pub fn blend_cursor(
+    data: &mut [u8],
+    width: u32,
+    height: u32,
+    cursor: &CursorImage,
+    x: i32,
+    y: i32,
+) -> Result<()> {
+    check_rgba_len(data, width, height)?;
+    let sprite = &cursor.image;
+    check_rgba_len(&sprite.data, sprite.width, sprite.height)?;
+
+    let origin_x = x as i64 - cursor.hotspot_x as i64;
+    let origin_y = y as i64 - cursor.hotspot_y as i64;
+    let left = origin_x.max(0);
+    let top = origin_y.max(0);
+    let right = (origin_x + sprite.width as i64).min(width as i64);
+    let bottom = (origin_y + sprite.height as i64).min(height as i64);
+    if left >= right || top >= bottom {
+        return Ok(());
+    }
+
+    let columns = (right - left) as usize;
+    for row in top..bottom {
+        let dest_start = (row as usize * width as usize + left as usize) * 4;
+        let src_row = (row - origin_y) as usize;
+        let src_start = (src_row * sprite.width as usize + (left - origin_x) as usize) * 4;
+        for (dest, src) in data[dest_start..dest_start + columns * 4]
+            .chunks_exact_mut(4)
+            .zip(sprite.data[src_start..src_start + columns * 4].chunks_exact(4))
+        {
+            wayland_capture::blend_pixel(dest, src);
+        }
+    }
+    Ok(())
+}
+
+/// Extract the pixels of `with` that differ from `without` as a sprite
+/// cropped to their bounding box, along with the box's top-left corner.
+///
+/// Returns `None` if the images have different sizes or are identical.
+fn cursor_sprite(
+    without: &CaptureResult,
+    with: &CaptureResult,
+) -> Option<(CaptureResult, i32, i32)> {
+    if without.width != with.width
+        || without.height != with.height
+        || without.data.len() != with.data.len()
+    {
+        return None;
+    }
+
+    let width = with.width as usize;
+    let mut bounds: Option<(usize, usize, usize, usize)> = None;
+    for (index, (a, b)) in without
+        .data
+        .chunks_exact(4)
+        .zip(with.data.chunks_exact(4))
+        .enumerate()
+    {
+        if a != b {
+            let (x, y) = (index % width, index / width);
+            bounds = Some(match bounds {
+                Some((x1, y1, x2, y2)) => (x1.min(x), y1.min(y), x2.max(x), y2.max(y)),
+                None => (x, y, x, y),
+            });
+        }
+    }
+    let (x1, y1, x2, y2) = bounds?;
+
+    let (sprite_width, sprite_height) = (x2 - x1 + 1, y2 - y1 + 1);
+    let mut data = Vec::with_capacity(sprite_width * sprite_height * 4);
+    for y in y1..=y2 {
+        let row = (y * width + x1) * 4..(y * width + x2 + 1) * 4;
+        for (a, b) in without.data[row.clone()]
+            .chunks_exact(4)
+            .zip(with.data[row].chunks_exact(4))
+        {
+            if a == b {
+                data.extend_from_slice(&[0, 0, 0, 0]);
+            } else {
+                data.extend_from_slice(b);
+            }
+        }
+    }
+    Some((
+        CaptureResult::new(data, sprite_width as u32, sprite_height as u32),
+        x1 as i32,
+        y1 as i32,
+    ))
+}
+
+/// Check that `data` holds exactly `width * height` RGBA pixels.
+fn check_rgba_len(data: &[u8], width: u32, height: u32) -> Result<()> {
+    if data.len() != (width as usize) * (height as usize) * 4 {
+        return Err(Error::ImageProcessing(image::ImageError::Parameter(
+            image::error::ParameterError::from_kind(
+                image::error::ParameterErrorKind::DimensionMismatch,
+            ),
+        )));
+    }
+    Ok(())
+}
+
+/// Drop the alpha channel of an RGBA buffer without reallocating.
+fn rgba_to_rgb_in_place(data: &mut Vec<u8>) {
+    let pixels = data.len() / 4;
+    for i in 0..pixels {
+        data.copy_within(i * 4..i * 4 + 3, i * 3);
+    }
+    data.truncate(pixels * 3);
+}
+
+/// Background that transparent pixels are composited onto when encoding to a
+/// format without alpha.
+#[cfg(feature = "jpeg")]
+const DEFAULT_FLATTEN_BACKGROUND: [u8; 3] = [0, 0, 0];
+
+/// Composite straight-alpha RGBA pixels onto an opaque `background` and drop
+/// the alpha channel, without reallocating.
+#[cfg(feature = "jpeg")]
+fn flatten_rgba_to_rgb_in_place(data: &mut Vec<u8>, background: [u8; 3]) {
+    let pixels = data.len() / 4;
+    for i in 0..pixels {
+        let pixel = [data[i * 4], data[i * 4 + 1], data[i * 4 + 2]];
+        let alpha = data[i * 4 + 3] as u32;
+        for (channel, (&value, &bg)) in pixel.iter().zip(&background).enumerate() {
+            let blended = value as u32 * alpha + bg as u32 * (255 - alpha);
+            data[i * 3 + channel] = ((blended + 127) / 255) as u8;
+        }
+    }
+    data.truncate(pixels * 3);
+}
+
+/// Error for an image format that can't be put on the clipboard.
+#[cfg(feature = "clipboard")]
+fn clipboard_unsupported(format: image::ImageFormat) -> Error {
+    Error::ImageProcessing(image::ImageError::Unsupported(
+        image::error::UnsupportedError::from_format_and_kind(
+            format.into(),
+            image::error::UnsupportedErrorKind::Format(format.into()),
+        ),
+    ))
+}
+
+/// Encode an RGBA buffer as JPEG after flattening it onto `background`.
+#[cfg(feature = "jpeg")]
+fn encode_jpeg_flattened(
+    mut data: Vec<u8>,
+    width: u32,
+    height: u32,
+    quality: u8,
+    background: [u8; 3],
+) -> Result<Vec<u8>> {
+    check_rgba_len(&data, width, height)?;
+    flatten_rgba_to_rgb_in_place(&mut data, background);
+
+    let mut jpeg_data = Vec::new();
+    let encoder = jpeg_encoder::Encoder::new(&mut jpeg_data, quality);
+    encoder
+        .encode(
+            &data,
+            width as u16,
+            height as u16,
+            jpeg_encoder::ColorType::Rgb,
+        )
+        .map_err(|e| Error::Io(std::io::Error::other(format!("JPEG encoding error: {}", e))))?;
+
+    Ok(jpeg_data)
+}
+
+/// How much two captures differ, from [`CaptureResult::diff_stats`].
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct DiffStats {
+    max_delta: u8,
+    mean_delta: f64,
+    changed_pixels: u64,
+}
+
+impl DiffStats {
+    /// Returns the largest difference of any single channel.
+    pub fn max_delta(&self) -> u8 {
+        self.max_delta
+    }
+
+    /// Returns the mean absolute difference over all RGBA channels, from 0
+    /// (identical) to 255.
+    pub fn mean_delta(&self) -> f64 {
+        self.mean_delta
+    }
+
+    /// Returns the number of pixels with at least one differing channel.
+    pub fn changed_pixels(&self) -> u64 {
+        self.changed_pixels
+    }
+}
+
+/// Rotation and flip applied to an output's contents.
+///
+/// Mirrors the `wl_output.transform` values: rotations are counter-clockwise,
+/// and the `Flipped*` variants flip around the vertical axis before rotating.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum OutputTransform {
+    /// No transform.
+    #[default]
+    Normal,
+    /// Rotated by 90 degrees.
+    Rotated90,
+    /// Rotated by 180 degrees.
+    Rotated180,
+    /// Rotated by 270 degrees.
+    Rotated270,
+    /// Flipped.
+    Flipped,
+    /// Flipped and rotated by 90 degrees.
+    Flipped90,
+    /// Flipped and rotated by 180 degrees.
+    Flipped180,
+    /// Flipped and rotated by 270 degrees.
+    Flipped270,
+}
+
+impl OutputTransform {
+    /// Returns whether the transform swaps the output's width and height.
+    pub fn swaps_dimensions(&self) -> bool {
+        matches!(
+            self,
+            Self::Rotated90 | Self::Rotated270 | Self::Flipped90 | Self::Flipped270
+        )
+    }
+}
+
+/// Information about a display output.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Output {
+    /// Name of the output (e.g., "eDP-1", "HDMI-A-1").
+    name: String,
+    /// Geometry of the output (position and size).
+    geometry: Box,
+    /// Scale factor of the output (e.g., 1 for normal DPI, 2 for HiDPI).
+    scale: i32,
+    /// Description of the output (e.g., monitor model, manufacturer info).
+    description: Option<String>,
+    /// Refresh rate of the current mode in millihertz, if known.
+    refresh_rate_mhz: Option<u32>,
+    /// Transform applied to the output's contents.
+    transform: OutputTransform,
+    /// Physical width and height in millimeters, if known.
+    physical_size_mm: Option<(u32, u32)>,
+    /// Manufacturer of the monitor (e.g., "Dell Inc.").
+    make: String,
+    /// Model of the monitor (e.g., "DELL U2720Q").
+    model: String,
+}
+
+impl Output {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn geometry(&self) -> &Box {
+        &self.geometry
+    }
+
+    pub fn scale(&self) -> i32 {
+        self.scale
+    }
+
+    pub fn description(&self) -> Option<&str> {
+        self.description.as_deref()
+    }
+
+    /// Refresh rate of the output's current mode in millihertz.
+    ///
+    /// Uses the same unit as the Wayland `wl_output.mode` event, so a 60 Hz
+    /// display reports `60000`. Returns `None` if the compositor did not
+    /// report a refresh rate.
+    pub fn refresh_rate_mhz(&self) -> Option<u32> {
+        self.refresh_rate_mhz
+    }
+
+    /// Transform (rotation and flip) applied to the output's contents.
+    pub fn transform(&self) -> OutputTransform {
+        self.transform
+    }
+
+    /// Physical width and height of the output in millimeters.
+    ///
+    /// Returns `None` if the compositor does not know the physical size,
+    /// e.g. for projectors or virtual outputs.
+    pub fn physical_size_mm(&self) -> Option<(u32, u32)> {
+        self.physical_size_mm
+    }
+
+    /// Manufacturer of the monitor as reported by `wl_output.geometry`.
+    ///
+    /// Empty if the compositor did not report one; some compositors send
+    /// placeholders such as `"Unknown"`.
+    pub fn make(&self) -> &str {
+        &self.make
+    }
+
+    /// Model of the monitor as reported by `wl_output.geometry`.
+    ///
+    /// Empty if the compositor did not report one.
+    pub fn model(&self) -> &str {
+        &self.model
+    }
+
+    /// Returns whether the logical pixel at `(x, y)` lies on this output.
+    pub fn contains_point(&self, x: i32, y: i32) -> bool {
+        // Widened so outputs at the edge of the coordinate space don't overflow.
+        let within = |point: i32, start: i32, len: i32| {
+            let (point, start) = (i64::from(point), i64::from(start));
+            point >= start && point < start + i64::from(len)
+        };
+        let geometry = &self.geometry;
+        within(x, geometry.x(), geometry.width()) && within(y, geometry.y(), geometry.height())
+    }
+
+    /// Returns whether `region` lies entirely on this output.
+    ///
+    /// Unlike [`Box::intersects`], a region that only partly overlaps the
+    /// output doesn't count. An empty region is never contained.
+    pub fn contains_box(&self, region: &Box) -> bool {
+        self.geometry.intersection(region) == Some(*region)
+    }
+}
+
+/// Formats the output like `wlr-randr`'s summary line, e.g.
+/// `eDP-1: 1920x1080+0+0 @2x (Built-in display)`. The description is left
+/// out if the compositor did not send one.
+impl std::fmt::Display for Output {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}: {}x{}{:+}{:+} @{}x",
+            self.name,
+            self.geometry.width(),
+            self.geometry.height(),
+            self.geometry.x(),
+            self.geometry.y(),
+            self.scale
+        )?;
+        if let Some(description) = &self.description {
+            write!(f, " ({})", description)?;
+        }
+        Ok(())
+    }
+}
+
+/// Parameters for capturing a specific output.
+///
+/// Allows specifying different capture parameters for each output when
+///
+/// capturing multiple outputs simultaneously.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CaptureParameters {
+    /// Name of the output to capture.
+    ///
+    /// Must match one of the names returned by [`Grim::get_outputs`].
+    output_name: String,
+    /// Optional region within the output to capture.
+    ///
+    /// If `None`, the entire output will be captured.
+    ///
+    /// If `Some(region)`, only the specified region will be captured.
+    ///
+    /// The region must be within the bounds of the output.
+    region: Option<Box>,
+    /// Whether to include the cursor in the capture.
+    ///
+    /// If `true`, the cursor will be included in the screenshot.
+    ///
+    /// If `false`, the cursor will be excluded from the screenshot.
+    overlay_cursor: bool,
+    /// Scale factor for the output image.
+    ///
+    /// If `None`, uses the default scale (typically the highest output scale).
+    ///
+    /// If `Some(scale)`, the output image will be scaled accordingly.
+    scale: Option<f64>,
+}
+
+impl CaptureParameters {
+    /// Creates a new CaptureParameters with the specified output name.
+    ///
+    /// By default, captures the entire output without cursor and with default scale.
+    pub fn new(output_name: impl Into<String>) -> Self {
+        Self {
+            output_name: output_name.into(),
+            region: None,
+            overlay_cursor: false,
+            scale: None,
+        }
+    }
+
+    /// Creates parameters for the whole output with the cursor included.
+    ///
+    /// Shorthand for `CaptureParameters::new(output_name).overlay_cursor(true)`.
+    pub fn with_cursor_overlay(output_name: impl Into<String>) -> Self {
+        Self::new(output_name).overlay_cursor(true)
+    }
+
+    /// Creates parameters for `region` of the output.
+    ///
+    /// Shorthand for `CaptureParameters::new(output_name).region(region)`.
+    pub fn for_region(output_name: impl Into<String>, region: Box) -> Self {
+        Self::new(output_name).region(region)
+    }
+
+    /// Sets the region to capture within the output.
+    pub fn region(mut self, region: Box) -> Self {
+        self.region = Some(region);
+        self
+    }
+
+    /// Sets whether to include the cursor in the capture.
+    ///
+    /// As with [`CaptureOptions::overlay_cursor`], the compositor decides
+    /// whether the cursor actually appears.
+    pub fn overlay_cursor(mut self, overlay_cursor: bool) -> Self {
+        self.overlay_cursor = overlay_cursor;
+        self
+    }
+
+    /// Sets the scale factor for the output image.
+    pub fn scale(mut self, scale: f64) -> Self {
+        self.scale = Some(scale);
+        self
+    }
+
+    /// Returns the output name.
+    pub fn output_name(&self) -> &str {
+        &self.output_name
+    }
+
+    /// Returns the region, if set.
+    pub fn region_ref(&self) -> Option<&Box> {
+        self.region.as_ref()
+    }
+
+    /// Returns whether cursor overlay is enabled.
+    pub fn overlay_cursor_enabled(&self) -> bool {
+        self.overlay_cursor
+    }
+
+    /// Returns the scale factor, if set.
+    pub fn scale_factor(&self) -> Option<f64> {
+        self.scale
+    }
+}
+
+/// Resampling filter used when a capture is scaled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ScaleFilter {
+    /// Pick a filter from the scale factor: nearest neighbour for
+    /// upscaling (with a fast path for 2x-4x), progressively smoother
+    /// filters for stronger downscaling.
+    #[default]
+    Auto,
+    /// Nearest neighbour.
+    Nearest,
+    /// Linear (triangle) filter.
+    Triangle,
+    /// Cubic (Catmull-Rom) filter.
+    CatmullRom,
+    /// Lanczos filter with window 3.
+    Lanczos3,
+}
+
+/// Options for a single capture.
+///
+/// Passed to [`Grim::capture`], which picks what to capture from the
+/// `output` and `region` settings:
+///
+/// | `output` | `region` | Captured area                              |
+/// |----------|----------|--------------------------------------------|
+/// | `None`   | `None`   | All outputs                                |
+/// | `Some`   | `None`   | The whole named output                     |
+/// | `None`   | `Some`   | The region, composited from all outputs    |
+/// | `Some`   | `Some`   | The part of the region lying on the output |
+///
+/// The cursor, scale and exclusion settings are also accepted by
+/// [`Grim::capture_all_with_options`], [`Grim::capture_output_with_options`]
+/// and [`Grim::capture_region_with_options`], which ignore `output` and
+/// `region` in favour of their own target. [`Grim::capture_stream`] only
+/// uses the `overlay_cursor` and scale settings.
+///
+/// # Example
+///
+/// ```rust
+/// use grim_rs::{Box, CaptureOptions, ScaleFilter};
+///
+/// let options = CaptureOptions::new()
+///     .region(Box::new(0, 0, 800, 600))
+///     .overlay_cursor(true)
+///     .scale(0.5)
+///     .scale_filter(ScaleFilter::Lanczos3);
+/// assert!(options.overlay_cursor_enabled());
+/// assert_eq!(options.scale_factor(), Some(0.5));
+/// assert_eq!(options.target_region(), Some(Box::new(0, 0, 800, 600)));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct CaptureOptions {
+    /// Whether to include the cursor in the capture.
+    overlay_cursor: bool,
+    /// Scale factor for the output image.
+    ///
+    /// If `None`, the image is returned at its captured size.
+    scale: Option<f64>,
+    /// Filter used when `scale` resizes the image.
+    scale_filter: ScaleFilter,
+    /// Region to capture, in layout coordinates.
+    region: Option<Box>,
+    /// Name of the output to capture.
+    output: Option<String>,
+    /// Regions to black out, in layout coordinates.
+    exclusions: Vec<Box>,
+    /// Fill color for `exclusions`; opaque black if `None`.
+    exclusion_color: Option<[u8; 4]>,
+    /// Fill color for areas no output covers; transparent if `None`.
+    background: Option<[u8; 4]>,
+    /// Cursor drawn by grim-rs instead of the compositor.
+    software_cursor: Option<CursorImage>,
+}
+
+impl CaptureOptions {
+    /// Creates options that capture without cursor and without scaling.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets whether to include the cursor in the capture.
+    ///
+    /// Whether the cursor actually appears is up to the compositor: some
+    /// draw it into the screencopy frame only when asked, others never do
+    /// (e.g. with hardware cursors) and return the frame without it.
+    pub fn overlay_cursor(mut self, overlay_cursor: bool) -> Self {
+        self.overlay_cursor = overlay_cursor;
+        self
+    }
+
+    /// Sets the scale factor for the output image.
+    pub fn scale(mut self, scale: f64) -> Self {
+        self.scale = Some(scale);
+        self
+    }
+
+    /// Sets the filter used when scaling.
+    pub fn scale_filter(mut self, filter: ScaleFilter) -> Self {
+        self.scale_filter = filter;
+        self
+    }
+
+    /// Restricts the capture to a region in layout coordinates.
+    pub fn region(mut self, region: Box) -> Self {
+        self.region = Some(region);
+        self
+    }
+
+    /// Restricts the capture to the output with the given name.
+    pub fn output(mut self, output_name: impl Into<String>) -> Self {
+        self.output = Some(output_name.into());
+        self
+    }
+
+    /// Hides a region, in layout coordinates, from the capture.
+    ///
+    /// After capturing, the region is filled with the
+    /// [exclusion color](Self::exclusion_color), e.g. to keep a password
+    /// manager out of a full-screen screenshot. On HiDPI outputs the region
+    /// is scaled to buffer pixels, and parts outside the captured area are
+    /// ignored. May be called repeatedly to exclude several regions.
+    pub fn exclude(mut self, region: Box) -> Self {
+        self.exclusions.push(region);
+        self
+    }
+
+    /// Draws `cursor` at the pointer position after capturing.
+    ///
+    /// For compositors that ignore [`overlay_cursor`](Self::overlay_cursor).
+    /// The compositor is then not asked for its own cursor, to avoid drawing
+    /// it twice. The pointer position is looked up the same way as for
+    /// [`Grim::capture_focused_output`]; if the pointer can't be found the
+    /// capture is returned without a cursor. Wayland doesn't give clients
+    /// the cursor image, so it has to be supplied here.
+    pub fn software_cursor(mut self, cursor: CursorImage) -> Self {
+        self.software_cursor = Some(cursor);
+        self
+    }
+
+    /// Sets the RGBA color excluded regions are filled with.
+    ///
+    /// Defaults to opaque black. A color with an alpha below 255 is blended
+    /// over the captured pixels instead of replacing them.
+    pub fn exclusion_color(mut self, rgba: [u8; 4]) -> Self {
+        self.exclusion_color = Some(rgba);
+        self
+    }
+
+    /// Sets the RGBA color for parts of the capture no output covers.
+    ///
+    /// When a region or the whole layout spans a gap between outputs, e.g.
+    /// the empty corner of an L-shaped monitor arrangement, that area is
+    /// filled with this color. Defaults to transparent black.
+    pub fn background(mut self, rgba: [u8; 4]) -> Self {
+        self.background = Some(rgba);
+        self
+    }
+
+    /// Returns whether cursor overlay is enabled.
+    pub fn overlay_cursor_enabled(&self) -> bool {
+        self.overlay_cursor
+    }
+
+    /// Returns the scale factor, if set.
+    pub fn scale_factor(&self) -> Option<f64> {
+        self.scale
+    }
+
+    /// Returns the filter used when scaling.
+    pub fn filter_type(&self) -> ScaleFilter {
+        self.scale_filter
+    }
+
+    /// Returns the region to capture, if set.
+    pub fn target_region(&self) -> Option<Box> {
+        self.region
+    }
+
+    /// Returns the name of the output to capture, if set.
+    pub fn target_output(&self) -> Option<&str> {
+        self.output.as_deref()
+    }
+
+    /// Returns the regions hidden from the capture.
+    pub fn exclusions(&self) -> &[Box] {
+        &self.exclusions
+    }
+
+    /// Returns the color excluded regions are filled with.
+    pub fn exclusion_fill(&self) -> [u8; 4] {
+        self.exclusion_color.unwrap_or([0, 0, 0, 255])
+    }
+
+    /// Returns the color for parts of the capture no output covers.
+    pub fn background_fill(&self) -> [u8; 4] {
+        self.background.unwrap_or([0, 0, 0, 0])
+    }
+
+    /// Returns the software cursor, if set.
+    pub fn cursor_image(&self) -> Option<&CursorImage> {
+        self.software_cursor.as_ref()
+    }
+}
+
+/// Row filter applied before PNG compression.
+///
+/// Screenshots with large flat areas usually compress noticeably better with
+/// [`PngFilter::Sub`] or [`PngFilter::Up`] than without filtering, at the
+/// cost of some encoding time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PngFilter {
+    /// No filtering (fastest).
+    #[default]
+    NoFilter,
+    /// Difference to the pixel on the left.
+    Sub,
+    /// Difference to the pixel above.
+    Up,
+    /// Difference to the average of the left and upper pixels.
+    Avg,
+    /// Paeth predictor.
+    Paeth,
+    /// Pick the best filter for every row (slowest, usually smallest).
+    Adaptive,
+}
+
+/// Options for PNG encoding.
+///
+/// Used with [`Grim::save_png_with_options`] and [`Grim::to_png_with_options`].
+///
+/// By default a pHYs chunk is written so viewers show HiDPI screenshots at
+/// their intended size. Its resolution comes from [`PngMetadata::dpi`] when
+/// set (the `capture_*_to_png` methods derive it from the output scale) and
+/// is 96 DPI otherwise.
+///
+/// # Example
+///
+/// ```rust
+/// use grim_rs::{PngFilter, PngOptions};
+///
+/// let options = PngOptions::new().compression(9).filter(PngFilter::Up);
+/// assert_eq!(options.compression_level(), 9);
+/// assert_eq!(options.filter_type(), PngFilter::Up);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PngOptions {
+    /// Compression level (0-9, where 9 is highest compression).
+    compression: u8,
+    /// Row filter.
+    filter: PngFilter,
+    /// Whether to write a pHYs chunk.
+    embed_dpi: bool,
+}
+
+impl Default for PngOptions {
+    fn default() -> Self {
+        Self {
+            compression: 6,
+            filter: PngFilter::NoFilter,
+            embed_dpi: true,
+        }
+    }
+}
+
+impl PngOptions {
+    /// Creates options with compression level 6, no filtering and a pHYs chunk.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the compression level (0-9, where 9 is highest compression).
+    pub fn compression(mut self, compression: u8) -> Self {
+        self.compression = compression;
+        self
+    }
+
+    /// Sets the row filter.
+    pub fn filter(mut self, filter: PngFilter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    /// Returns the compression level.
+    pub fn compression_level(&self) -> u8 {
+        self.compression
+    }
+
+    /// Sets whether to write a pHYs (physical resolution) chunk.
+    pub fn embed_dpi(mut self, embed_dpi: bool) -> Self {
+        self.embed_dpi = embed_dpi;
+        self
+    }
+
+    /// Returns the row filter.
+    pub fn filter_type(&self) -> PngFilter {
+        self.filter
+    }
+
+    /// Returns whether a pHYs chunk is written.
+    pub fn dpi_embedded(&self) -> bool {
+        self.embed_dpi
+    }
+}
+
+/// Compression used for TIFF output.
+///
+/// Used with [`Grim::save_tiff_with_options`] and [`Grim::to_tiff_with_options`].
+/// All variants are lossless.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TiffCompression {
+    /// Uncompressed strips (fastest, largest, most widely readable).
+    #[default]
+    None,
+    /// LZW compression.
+    Lzw,
+    /// Deflate (zlib) compression, usually the smallest.
+    Deflate,
+}
+
+/// Ancillary chunks to embed in a PNG.
+///
+/// `software` and `creation_time` are written as tEXt chunks with the
+/// standard `Software` and `Creation Time` keywords; `dpi` becomes a pHYs
+/// chunk, so image viewers can show HiDPI screenshots at their physical size.
+/// Unset fields are left out.
+///
+/// # Example
+///
+/// ```rust
+/// use grim_rs::PngMetadata;
+///
+/// let metadata = PngMetadata::new().software("my-tool 1.0").dpi(192.0);
+/// assert_eq!(metadata.software_name(), Some("my-tool 1.0"));
+/// assert_eq!(metadata.dpi_value(), Some(192.0));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PngMetadata {
+    /// Name of the program that created the image.
+    software: Option<String>,
+    /// Time the screenshot was taken.
+    creation_time: Option<std::time::SystemTime>,
+    /// Pixel density in dots per inch.
+    dpi: Option<f64>,
+}
+
+impl PngMetadata {
+    /// Creates metadata without any chunks.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Metadata for a screenshot taken now: `grim-rs <version>` as the
+    /// software, the current time, and the given pixel density.
+    pub fn screenshot(dpi: f64) -> Self {
+        Self::new()
+            .software(concat!("grim-rs ", env!("CARGO_PKG_VERSION")))
+            .creation_time(std::time::SystemTime::now())
+            .dpi(dpi)
+    }
+
+    /// Sets the `Software` text.
+    pub fn software(mut self, software: impl Into<String>) -> Self {
+        self.software = Some(software.into());
+        self
+    }
+
+    /// Sets the `Creation Time` text.
+    pub fn creation_time(mut self, time: std::time::SystemTime) -> Self {
+        self.creation_time = Some(time);
+        self
+    }
+
+    /// Sets the pixel density in dots per inch.
+    pub fn dpi(mut self, dpi: f64) -> Self {
+        self.dpi = Some(dpi);
+        self
+    }
+
+    /// Returns the `Software` text, if set.
+    pub fn software_name(&self) -> Option<&str> {
+        self.software.as_deref()
+    }
+
+    /// Returns the creation time, if set.
+    pub fn creation_time_value(&self) -> Option<std::time::SystemTime> {
+        self.creation_time
+    }
+
+    /// Returns the pixel density, if set.
+    pub fn dpi_value(&self) -> Option<f64> {
+        self.dpi
+    }
+}
+
+/// How overlapping captures are combined by [`MultiOutputCaptureResult::stitch_with_options`]
+/// and [`MultiOutputCaptureResult::composite_with_options`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CompositeBlend {
+    /// Later captures overwrite earlier ones, alpha included.
+    #[default]
+    Replace,
+    /// Later captures are alpha-blended over what is already there.
+    AlphaOver,
+}
+
+/// Options for compositing several captures into one image.
+///
+/// # Example
+///
+/// ```rust
+/// use grim_rs::{CompositeBlend, CompositeOptions};
+///
+/// let options = CompositeOptions::new()
+///     .background([0, 0, 0, 255])
+///     .blend(CompositeBlend::AlphaOver);
+/// assert_eq!(options.background_color(), [0, 0, 0, 255]);
+/// assert_eq!(options.blend_mode(), CompositeBlend::AlphaOver);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct CompositeOptions {
+    /// RGBA color of areas not covered by any capture.
+    background: [u8; 4],
+    /// How overlapping captures are combined.
+    blend: CompositeBlend,
+}
+
+impl CompositeOptions {
+    /// Creates options with a transparent background and [`CompositeBlend::Replace`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the RGBA color of areas not covered by any capture.
+    pub fn background(mut self, background: [u8; 4]) -> Self {
+        self.background = background;
+        self
+    }
+
+    /// Sets how overlapping captures are combined.
+    pub fn blend(mut self, blend: CompositeBlend) -> Self {
+        self.blend = blend;
+        self
+    }
+
+    /// Returns the background color.
+    pub fn background_color(&self) -> [u8; 4] {
+        self.background
+    }
+
+    /// Returns the blend mode.
+    pub fn blend_mode(&self) -> CompositeBlend {
+        self.blend
+    }
+}
+
+/// Where a per-output capture of a [`MultiOutputCaptureResult`] sits in
+/// the compositor layout.
+///
+/// Recorded at capture time, so callers can composite the results without
+/// querying [`Grim::get_outputs`] again.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OutputPlacement {
+    /// Captured area in logical layout coordinates.
+    geometry: Box,
+    /// Scale factor of the output.
+    scale: i32,
+}
+
+impl OutputPlacement {
+    /// Creates a placement for a capture of `geometry` on an output with `scale`.
+    pub fn new(geometry: Box, scale: i32) -> Self {
+        Self { geometry, scale }
+    }
+
+    /// Returns the captured area in logical layout coordinates.
+    ///
+    /// This is the requested region, or the output's
+    /// [`geometry`](Output::geometry) when the whole output was captured.
+    pub fn geometry(&self) -> &Box {
+        &self.geometry
+    }
+
+    /// Returns the scale factor of the output.
+    ///
+    /// The capture itself may have been resampled, e.g. by
+    /// [`Grim::capture_outputs_with_scale`]; compare its size with
+    /// [`geometry`](Self::geometry) for the effective scale.
+    pub fn scale(&self) -> i32 {
+        self.scale
+    }
+}
+
+/// Result of capturing multiple outputs.
+///
+/// Contains a map of output names to their respective capture results and,
+/// for captures made by [`Grim::capture_outputs`], where each one sits in
+/// the layout.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MultiOutputCaptureResult {
+    /// Map of output names to their capture results.
+    ///
+    /// The keys are output names, and the values are the corresponding
+    /// capture results for each output.
+    outputs: std::collections::HashMap<String, CaptureResult>,
+    /// Layout position and scale of each captured output.
+    placements: std::collections::HashMap<String, OutputPlacement>,
+}
+
+impl MultiOutputCaptureResult {
+    /// Creates a new MultiOutputCaptureResult with the given outputs map.
+    ///
+    /// The result carries no placements; add them with
+    /// [`with_placements`](Self::with_placements).
+    pub fn new(outputs: std::collections::HashMap<String, CaptureResult>) -> Self {
+        Self {
+            outputs,
+            placements: std::collections::HashMap::new(),
+        }
+    }
+
+    /// Sets the layout placements, keyed by output name.
+    pub fn with_placements(
+        mut self,
+        placements: std::collections::HashMap<String, OutputPlacement>,
+    ) -> Self {
+        self.placements = placements;
+        self
+    }
+
+    /// Gets the capture result for the specified output name.
+    pub fn get(&self, output_name: &str) -> Option<&CaptureResult> {
+        self.outputs.get(output_name)
+    }
+
+    /// Gets the layout placement of the specified output's capture.
+    pub fn placement(&self, output_name: &str) -> Option<&OutputPlacement> {
+        self.placements.get(output_name)
+    }
+
+    /// Returns a reference to the placements map.
+    pub fn placements(&self) -> &std::collections::HashMap<String, OutputPlacement> {
+        &self.placements
+    }
+
+    /// Returns a reference to the outputs map.
+    pub fn outputs(&self) -> &std::collections::HashMap<String, CaptureResult> {
+        &self.outputs
+    }
+
+    /// Consumes self and returns the outputs map.
+    pub fn into_outputs(self) -> std::collections::HashMap<String, CaptureResult> {
+        self.outputs
+    }
+
+    /// Composite the captured outputs into a single image.
+    ///
+    /// Each capture is placed at its output's [`Output::geometry`] on a canvas
+    /// spanning all captured outputs, and resampled to the output's logical
+    /// size, just like [`Grim::capture_all`] does. Areas not covered by any
+    /// output are transparent. Per-output results can be post-processed
+    /// (e.g. redacted) through [`outputs`](Self::outputs) before stitching.
+    ///
+    /// `outputs` is usually the list returned by [`Grim::get_outputs`];
+    /// outputs without a capture are ignored.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::OutputNotFound` if a captured output is missing from
+    /// `outputs`, `Error::NoOutputs` if nothing was captured, or
+    /// `Error::ScalingFailed` if a capture can't be resampled.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use grim_rs::{CaptureParameters, Grim};
+    ///
+    /// let mut grim = Grim::new()?;
+    /// let outputs = grim.get_outputs()?;
+    /// let parameters = outputs
+    ///     .iter()
+    ///     .map(|output| CaptureParameters::new(output.name()))
+    ///     .collect();
+    /// let result = grim.capture_outputs(parameters)?;
+    /// let stitched = result.stitch(&outputs)?;
+    /// println!("Stitched {}x{}", stitched.width(), stitched.height());
+    /// # Ok::<(), grim_rs::Error>(())
+    /// ```
+    pub fn stitch(&self, outputs: &[Output]) -> Result<CaptureResult> {
+        self.stitch_with_options(outputs, &CompositeOptions::default())
+    }
+
+    /// [`stitch`](Self::stitch) with a background color and overlap handling.
+    ///
+    /// Outputs may have different scales and negative positions: every
+    /// capture is resampled to its logical size and the canvas starts at the
+    /// top-left corner of the layout.
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`stitch`](Self::stitch).
+    pub fn stitch_with_options(
+        &self,
+        outputs: &[Output],
+        options: &CompositeOptions,
+    ) -> Result<CaptureResult> {
+        wayland_capture::stitch_captures(&self.outputs, outputs, options)
+    }
+
+    /// Composite the named captures into a single image at explicit positions.
+    ///
+    /// Like [`stitch`](Self::stitch), but the layout is given as
+    /// `(output name, logical box)` pairs instead of [`Output`]s, e.g. to
+    /// arrange captures differently from the compositor layout. Each capture
+    /// is resampled to its box and placed on a canvas spanning all boxes;
+    /// later entries are drawn over earlier ones and uncovered areas are
+    /// transparent. Captures missing from `layout` are left out.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::OutputNotFound` if `layout` names an output that was
+    /// not captured, `Error::NoOutputs` if `layout` has no non-empty box, or
+    /// `Error::ScalingFailed` if a capture can't be resampled.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use grim_rs::{Box, CaptureResult, MultiOutputCaptureResult};
+    /// use std::collections::HashMap;
+    ///
+    /// let mut captures = HashMap::new();
+    /// captures.insert("DP-1".to_string(), CaptureResult::new(vec![0; 4 * 4 * 4], 4, 4));
+    /// captures.insert("DP-2".to_string(), CaptureResult::new(vec![0; 4 * 4 * 4], 4, 4));
+    /// let result = MultiOutputCaptureResult::new(captures);
+    ///
+    /// let layout = [
+    ///     ("DP-1".to_string(), Box::new(0, 0, 4, 4)),
+    ///     ("DP-2".to_string(), Box::new(4, 0, 4, 4)),
+    /// ];
+    /// let combined = result.composite(&layout)?;
+    /// assert_eq!((combined.width(), combined.height()), (8, 4));
+    /// # Ok::<(), grim_rs::Error>(())
+    /// ```
+    pub fn composite(&self, layout: &[(String, Box)]) -> Result<CaptureResult> {
+        self.composite_with_options(layout, &CompositeOptions::default())
+    }
+
+    /// [`composite`](Self::composite) with a background color and overlap
+    /// handling.
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`composite`](Self::composite).
+    pub fn composite_with_options(
+        &self,
+        layout: &[(String, Box)],
+        options: &CompositeOptions,
+    ) -> Result<CaptureResult> {
+        let placed = layout
+            .iter()
+            .map(|(name, geometry)| {
+                self.outputs
+                    .get(name)
+                    .map(|capture| (*geometry, capture))
+                    .ok_or_else(|| Error::OutputNotFound(name.clone()))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        wayland_capture::composite_captures(&placed, options)
+    }
+}
+
+/// A frame captured by a [`CaptureStream`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StreamFrame {
+    capture: CaptureResult,
+}
+
+impl StreamFrame {
+    /// Returns the captured image.
+    pub fn capture(&self) -> &CaptureResult {
+        &self.capture
+    }
+
+    /// Consumes the frame and returns the captured image.
+    pub fn into_capture(self) -> CaptureResult {
+        self.capture
+    }
+
+    /// Returns when the compositor presented the frame.
+    ///
+    /// Measured on the `CLOCK_MONOTONIC` clock, so only differences between
+    /// frames are meaningful. Zero if the compositor did not send a time;
+    /// otherwise the same as the capture's [`CaptureResult::timestamp`].
+    pub fn timestamp(&self) -> std::time::Duration {
+        self.capture.timestamp.unwrap_or_default()
+    }
+}
+
+/// Repeated captures of one output, created by [`Grim::capture_stream`].
+///
+/// The shared-memory buffer is kept between frames, and frames after the
+/// first are requested with screencopy damage tracking where the compositor
+/// supports it: [`next_frame`](Self::next_frame) then waits until the output
+/// actually changes instead of returning identical frames. If nothing
+/// changes within the capture timeout it fails with `Error::Timeout`, which
+/// a recorder can treat as "repeat the previous frame".
+pub struct CaptureStream<'a> {
+    grim: &'a mut Grim,
+    stream: wayland_capture::ShmStream,
+    options: CaptureOptions,
+}
+
+impl CaptureStream<'_> {
+    /// Capture the next frame.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::Timeout` if the compositor sends no new frame within
+    /// the capture timeout, or another error if the capture fails.
+    pub fn next_frame(&mut self) -> Result<StreamFrame> {
+        let capture = self
+            .grim
+            .platform_capture
+            .next_stream_frame(&mut self.stream)?;
+        let capture = match self.options.scale_factor() {
+            Some(scale) => {
+                wayland_capture::scale_capture(capture, scale, self.options.filter_type())?
+            }
+            None => capture,
+        };
+        Ok(StreamFrame { capture })
+    }
+}
+
+/// Main interface for taking screenshots.
+///
+/// Provides methods for capturing screenshots of the entire screen,
+/// specific outputs, regions, or multiple outputs with different parameters.
+///
+/// `Grim` is `Send`, so it can be moved to another thread, but capture methods
+/// take `&mut self`. To share one connection between threads, wrap it in a
+/// [`SharedGrim`].
+pub struct Grim {
+    platform_capture: PlatformCapture,
+}
+
+impl Grim {
+    /// Create a new Grim instance.
+    ///
+    /// Establishes a connection to the Wayland compositor and initializes
+    /// the necessary protocols for screen capture.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - Cannot connect to the Wayland compositor
+    /// - Required Wayland protocols are not available
+    /// - Other initialization errors occur
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use grim_rs::Grim;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let grim = Grim::new()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn new() -> Result<Self> {
+        let platform_capture = PlatformCapture::new()?;
+        Ok(Self { platform_capture })
+    }
+
+    /// Convert this instance into an [`AsyncGrim`] for use from async code.
+    ///
+    /// The Wayland connection is moved into the new handle; its capture
+    /// methods await the compositor's events on the runtime's reactor.
+    /// Must be used from within a tokio runtime.
+    ///
+    /// Requires the `tokio` feature.
+    #[cfg(feature = "tokio")]
+    pub fn into_async(self) -> AsyncGrim {
+        AsyncGrim::from_grim(self)
+    }
+
+    /// Enable or disable the dmabuf capture path.
+    ///
+    /// When enabled (the default), single-output captures copy the frame
+    /// into a GPU buffer if the compositor offers a dmabuf and a DRM render
+    /// node is available, and fall back to wl_shm otherwise. A log line at
+    /// `info` level reports each dmabuf capture.
+    ///
+    /// Requires the `dmabuf` feature.
+    #[cfg(feature = "dmabuf")]
+    pub fn set_dmabuf_enabled(&mut self, enabled: bool) {
+        self.platform_capture.set_dmabuf_enabled(enabled);
+    }
+
+    /// Set how long a capture waits for the compositor before giving up.
+    ///
+    /// The deadline covers each wait for frame events (buffer negotiation and
+    /// the copy itself) and is measured in wall-clock time, so unrelated
+    /// events arriving in the meantime don't extend it. On expiry the capture
+    /// fails with `Error::Timeout`. Defaults to 5 seconds.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use grim_rs::Grim;
+    /// use std::time::Duration;
+    ///
+    /// let mut grim = Grim::new()?;
+    /// grim.set_capture_timeout(Duration::from_secs(1));
+    /// let result = grim.capture_all()?;
+    /// # Ok::<(), grim_rs::Error>(())
+    /// ```
+    pub fn set_capture_timeout(&mut self, timeout: std::time::Duration) {
+        self.platform_capture.set_capture_timeout(timeout);
+    }
+
+    /// Re-read the output list before every capture instead of caching it.
+    ///
+    /// By default output state is read on the first capture and reused
+    /// until the compositor reports an output being added, removed or
+    /// changed, which saves several Wayland round-trips per capture. The
+    /// tradeoff is that changes the compositor doesn't announce (or that
+    /// arrive between the check and the capture) are only seen after
+    /// [`refresh_outputs`](Self::refresh_outputs) or
+    /// [`invalidate_output_cache`](Self::invalidate_output_cache). Enable
+    /// this to always capture against a fresh snapshot at the cost of that
+    /// extra work. Disabled by default.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use grim_rs::Grim;
+    ///
+    /// let mut grim = Grim::new()?;
+    /// grim.set_always_refresh_outputs(true);
+    /// let result = grim.capture_all()?;
+    /// # Ok::<(), grim_rs::Error>(())
+    /// ```
+    pub fn set_always_refresh_outputs(&mut self, always: bool) {
+        self.platform_capture.set_always_refresh_outputs(always);
+    }
+
+    /// Get information about available display outputs.
+    ///
+    /// Returns a list of all connected display outputs with their names,
+    /// geometries, and scale factors.
+    ///
+    /// Monitors that are unplugged are dropped from the list as soon as the
+    /// compositor removes their `wl_output` global, and newly connected ones
+    /// are picked up on the next call, so the result reflects hotplug without
+    /// creating a new `Grim` instance.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - No outputs are available
+    /// - Failed to retrieve output information
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use grim_rs::Grim;
+    ///
+    /// let mut grim = Grim::new()?;
+    /// let outputs = grim.get_outputs()?;
+    ///
+    /// for output in outputs {
+    ///     println!("Output: {} ({}x{})", output.name(), output.geometry().width(), output.geometry().height());
+    /// }
+    /// # Ok::<(), grim_rs::Error>(())
+    /// ```
+    pub fn get_outputs(&mut self) -> Result<Vec<Output>> {
+        self.platform_capture.get_outputs()
+    }
+
+    /// Pass the output list to `f` without cloning it.
+    ///
+    /// The list is re-read first if the compositor reported output changes.
+    /// Like [`get_outputs`](Self::get_outputs), but `f` borrows the outputs
+    /// held by this instance instead of receiving owned copies, which is
+    /// cheaper when only a few fields are needed. Clone an [`Output`] to keep
+    /// it beyond the call.
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`get_outputs`](Self::get_outputs).
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use grim_rs::Grim;
+    ///
+    /// let mut grim = Grim::new()?;
+    /// let has_hdmi = grim.with_outputs(|outputs| {
+    ///     outputs.iter().any(|output| output.name().starts_with("HDMI"))
+    /// })?;
+    /// println!("HDMI output connected: {}", has_hdmi);
+    /// # Ok::<(), grim_rs::Error>(())
+    /// ```
+    pub fn with_outputs<F, T>(&mut self, f: F) -> Result<T>
+    where
+        F: FnOnce(&[Output]) -> T,
+    {
+        self.platform_capture.with_outputs(f)
+    }
+
+    /// Re-read the output list from the compositor.
+    ///
+    /// Output state is cached between captures on the same `Grim` instance
+    /// and refreshed automatically when the compositor reports an output
+    /// being connected, disconnected or changed. Call this to re-read it
+    /// right away; [`invalidate_output_cache`](Self::invalidate_output_cache)
+    /// defers the work to the next capture instead.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - No outputs are available
+    /// - Failed to process Wayland events
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use grim_rs::Grim;
+    ///
+    /// let mut grim = Grim::new()?;
+    /// // ... the user changes the monitor layout ...
+    /// grim.refresh_outputs()?;
+    /// let result = grim.capture_all()?;
+    /// # Ok::<(), grim_rs::Error>(())
+    /// ```
+    pub fn refresh_outputs(&mut self) -> Result<()> {
+        self.platform_capture.refresh_outputs()
+    }
+
+    /// Mark the cached output state as stale.
+    ///
+    /// The next capture or output query re-reads every output from the
+    /// compositor before using it. Changes the compositor announces are
+    /// picked up without this; use it when the cache must not be trusted,
+    /// e.g. after the compositor was reconfigured behind its back.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use grim_rs::Grim;
+    ///
+    /// let mut grim = Grim::new()?;
+    /// grim.invalidate_output_cache();
+    /// let result = grim.capture_all()?;
+    /// # Ok::<(), grim_rs::Error>(())
+    /// ```
+    pub fn invalidate_output_cache(&mut self) {
+        self.platform_capture.invalidate_outputs();
+    }
+
+    /// Get the output named `name`.
+    ///
+    /// Uses the same output list as [`get_outputs`](Self::get_outputs), but
+    /// clones only the matching entry.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::OutputNotFound` if no output has that name, or the same
+    /// errors as [`get_outputs`](Self::get_outputs).
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use grim_rs::Grim;
+    ///
+    /// let mut grim = Grim::new()?;
+    /// let output = grim.get_output("eDP-1")?;
+    /// println!("eDP-1 is {}x{}", output.geometry().width(), output.geometry().height());
+    /// # Ok::<(), grim_rs::Error>(())
+    /// ```
+    pub fn get_output(&mut self, name: &str) -> Result<Output> {
+        self.with_outputs(|outputs| outputs.iter().find(|output| output.name() == name).cloned())?
+            .ok_or_else(|| Error::OutputNotFound(name.to_string()))
+    }
+
+    /// Check whether an output named `name` is connected.
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`get_outputs`](Self::get_outputs).
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use grim_rs::Grim;
+    ///
+    /// let mut grim = Grim::new()?;
+    /// if grim.has_output("HDMI-A-1")? {
+    ///     let result = grim.capture_output("HDMI-A-1")?;
+    /// }
+    /// # Ok::<(), grim_rs::Error>(())
+    /// ```
+    pub fn has_output(&mut self, name: &str) -> Result<bool> {
+        self.with_outputs(|outputs| outputs.iter().any(|output| output.name() == name))
+    }
+
+    /// Capture a screenshot as described by `options`.
+    ///
+    /// This is the general entry point behind all single-capture methods:
+    /// the target (all outputs, one output, a region, or a region clipped to
+    /// one output), cursor overlay, scale and scale filter are all taken
+    /// from [`CaptureOptions`].
+    ///
+    /// # Arguments
+    ///
+    /// * `options` - What to capture and how
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - No outputs are available
+    /// - The requested output is not found
+    /// - The region is empty or does not intersect the requested outputs
+    /// - Failed to capture or scale the image
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use grim_rs::{Box, CaptureOptions, Grim};
+    ///
+    /// let mut grim = Grim::new()?;
+    /// let options = CaptureOptions::new()
+    ///     .region(Box::new(0, 0, 640, 480))
+    ///     .overlay_cursor(true)
+    ///     .scale(0.5);
+    /// let result = grim.capture(&options)?;
+    /// println!("Captured: {}x{}", result.width(), result.height());
+    /// # Ok::<(), grim_rs::Error>(())
+    /// ```
+    pub fn capture(&mut self, options: &CaptureOptions) -> Result<CaptureResult> {
+        self.platform_capture.capture(options)
+    }
+
+    /// Capture the entire screen (all outputs).
+    ///
+    /// Captures a screenshot that includes all connected display outputs,
+    /// arranged according to their physical positions.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - No outputs are available
+    /// - Failed to capture the screen
+    /// - Buffer creation failed
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use grim_rs::Grim;
+    ///
+    /// let mut grim = Grim::new()?;
+    /// let result = grim.capture_all()?;
+    /// println!("Captured screen: {}x{}", result.width(), result.height());
+    /// # Ok::<(), grim_rs::Error>(())
+    /// ```
+    pub fn capture_all(&mut self) -> Result<CaptureResult> {
+        self.platform_capture.capture_all()
+    }
+
+    /// Capture the entire screen (all outputs) with specified scale factor.
+    ///
+    /// Captures a screenshot that includes all connected display outputs,
+    /// arranged according to their physical positions, with a specified scale factor.
+    ///
+    /// # Arguments
+    ///
+    /// * `scale` - Scale factor for the output image
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - No outputs are available
+    /// - Failed to capture the screen
+    /// - Buffer creation failed
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use grim_rs::Grim;
+    ///
+    /// let mut grim = Grim::new()?;
+    /// let result = grim.capture_all_with_scale(1.0)?;
+    /// println!("Captured screen: {}x{}", result.width(), result.height());
+    /// # Ok::<(), grim_rs::Error>(())
+    /// ```
+    pub fn capture_all_with_scale(&mut self, scale: f64) -> Result<CaptureResult> {
+        self.platform_capture.capture_all_with_scale(scale)
+    }
+
+    /// Capture the entire screen with the given options.
+    ///
+    /// # Arguments
+    ///
+    /// * `options` - Cursor and scale settings for the capture
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - No outputs are available
+    /// - Failed to capture the screen
+    /// - Buffer creation failed
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use grim_rs::{CaptureOptions, Grim};
+    ///
+    /// let mut grim = Grim::new()?;
+    /// let options = CaptureOptions::new().overlay_cursor(true);
+    /// let result = grim.capture_all_with_options(&options)?;
+    /// # Ok::<(), grim_rs::Error>(())
+    /// ```
+    pub fn capture_all_with_options(&mut self, options: &CaptureOptions) -> Result<CaptureResult> {
+        self.platform_capture.capture_all_with_options(options)
+    }
+
+    /// Capture the entire screen with some regions blacked out.
+    ///
+    /// Shorthand for [`Grim::capture_all_with_options`] with every region in
+    /// `exclusions` passed to [`CaptureOptions::exclude`]. Regions are in
+    /// layout coordinates, like [`Grim::capture_region`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - No outputs are available
+    /// - Failed to capture the screen
+    /// - Buffer creation failed
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use grim_rs::{Box, Grim};
+    ///
+    /// let mut grim = Grim::new()?;
+    /// let result = grim.capture_all_with_exclusions(&[Box::new(0, 0, 400, 300)])?;
+    /// println!("Captured screen: {}x{}", result.width(), result.height());
+    /// # Ok::<(), grim_rs::Error>(())
+    /// ```
+    pub fn capture_all_with_exclusions(&mut self, exclusions: &[Box]) -> Result<CaptureResult> {
+        let options = exclusions
+            .iter()
+            .fold(CaptureOptions::new(), |options, region| {
+                options.exclude(*region)
+            });
+        self.capture_all_with_options(&options)
+    }
+
+    /// Capture the entire screen (all outputs) including the cursor.
+    ///
+    /// Shorthand for [`Grim::capture`] with
+    /// `CaptureOptions::new().overlay_cursor(true)`.
+    /// See [`CaptureOptions::overlay_cursor`] for compositor support.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - No outputs are available
+    /// - Failed to capture the screen
+    /// - Buffer creation failed
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use grim_rs::Grim;
+    ///
+    /// let mut grim = Grim::new()?;
+    /// let result = grim.capture_all_with_cursor()?;
+    /// println!("Captured screen: {}x{}", result.width(), result.height());
+    /// # Ok::<(), grim_rs::Error>(())
+    /// ```
+    pub fn capture_all_with_cursor(&mut self) -> Result<CaptureResult> {
+        self.capture(&CaptureOptions::new().overlay_cursor(true))
+    }
+
+    /// Capture the entire screen (all outputs) including the cursor, with
+    /// specified scale factor.
+    ///
+    /// # Arguments
+    ///
+    /// * `scale` - Scale factor for the output image
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - No outputs are available
+    /// - Failed to capture the screen
+    /// - Buffer creation failed
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use grim_rs::Grim;
+    ///
+    /// let mut grim = Grim::new()?;
+    /// let result = grim.capture_all_with_cursor_and_scale(0.5)?;
+    /// println!("Captured screen: {}x{}", result.width(), result.height());
+    /// # Ok::<(), grim_rs::Error>(())
+    /// ```
+    pub fn capture_all_with_cursor_and_scale(&mut self, scale: f64) -> Result<CaptureResult> {
+        self.capture(&CaptureOptions::new().overlay_cursor(true).scale(scale))
+    }
+
+    /// Capture a specific output by name.
+    ///
+    /// Captures a screenshot of the specified display output.
+    ///
+    /// # Arguments
+    ///
+    /// * `output_name` - Name of the output to capture (e.g., "eDP-1", "HDMI-A-1")
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The specified output is not found
+    /// - Failed to capture the output
+    /// - Buffer creation failed
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use grim_rs::Grim;
+    ///
+    /// let mut grim = Grim::new()?;
+    /// // Get available outputs first
+    /// let outputs = grim.get_outputs()?;
+    /// if let Some(output) = outputs.first() {
+    ///     let result = grim.capture_output(output.name())?;
+    ///     println!("Captured output: {}x{}", result.width(), result.height());
+    /// }
+    /// # Ok::<(), grim_rs::Error>(())
+    /// ```
+    pub fn capture_output(&mut self, output_name: &str) -> Result<CaptureResult> {
+        self.platform_capture.capture_output(output_name)
+    }
+
+    /// Capture the output at `index` in the list returned by [`Grim::get_outputs`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::OutputNotFound`] if `index` is out of range, and
+    /// otherwise the same errors as [`Grim::capture_output`].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use grim_rs::Grim;
+    ///
+    /// let mut grim = Grim::new()?;
+    /// let result = grim.capture_output_by_index(0)?;
+    /// println!("Captured first output: {}x{}", result.width(), result.height());
+    /// # Ok::<(), grim_rs::Error>(())
+    /// ```
+    pub fn capture_output_by_index(&mut self, index: usize) -> Result<CaptureResult> {
+        let name = self.with_outputs(|outputs| {
+            outputs
+                .get(index)
+                .map(|output| output.name().to_string())
+                .ok_or_else(|| {
+                    Error::OutputNotFound(format!(
+                        "no output at index {} ({} output{} available)",
+                        index,
+                        outputs.len(),
+                        if outputs.len() == 1 { "" } else { "s" }
+                    ))
+                })
+        })??;
+        self.capture_output(&name)
+    }
+
+    /// Capture the output the pointer is currently on.
+    ///
+    /// Wayland only reports the pointer position to the client whose surface
+    /// is under it, so this briefly maps a transparent `zwlr_layer_shell_v1`
+    /// overlay on every output to locate the pointer, removes it again, and
+    /// then captures the output whose [`geometry`](Output::geometry)
+    /// contains the pointer.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The compositor lacks `zwlr_layer_shell_v1`
+    /// - The pointer is not over any output (`Error::OutputNotFound`)
+    /// - Failed to capture the output
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use grim_rs::Grim;
+    ///
+    /// let mut grim = Grim::new()?;
+    /// let result = grim.capture_focused_output()?;
+    /// println!("Captured current output: {}x{}", result.width(), result.height());
+    /// # Ok::<(), grim_rs::Error>(())
+    /// ```
+    pub fn capture_focused_output(&mut self) -> Result<CaptureResult> {
+        let (x, y) = region_select::pointer_position()?;
+        let name = self
+            .with_outputs(|outputs| output_at(outputs, x, y).map(|output| output.name.clone()))?
+            .ok_or_else(|| {
+                Error::OutputNotFound(format!("no output contains the pointer at {},{}", x, y))
+            })?;
+        self.capture_output(&name)
+    }
+
+    /// Start capturing frames of an output repeatedly, e.g. for recording.
+    ///
+    /// Cheaper per frame than calling [`Grim::capture_output`] in a loop: the
+    /// output is looked up once and the shared-memory buffer is reused. The
+    /// cursor, scale and filter settings of `options` apply to every frame;
+    /// its output and region are ignored, as the whole output is captured.
+    /// Needs `zwlr_screencopy_manager_v1`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::OutputNotFound` if the output does not exist, or
+    /// `Error::UnsupportedProtocol` if the compositor lacks
+    /// `zwlr_screencopy_manager_v1`.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use grim_rs::{CaptureOptions, Grim};
+    ///
+    /// let mut grim = Grim::new()?;
+    /// let name = grim.get_outputs()?[0].name().to_string();
+    /// let mut stream = grim.capture_stream(&name, &CaptureOptions::new())?;
+    /// for _ in 0..10 {
+    ///     let frame = stream.next_frame()?;
+    ///     println!("{:?}: {}x{}", frame.timestamp(), frame.capture().width(), frame.capture().height());
+    /// }
+    /// # Ok::<(), grim_rs::Error>(())
+    /// ```
+    pub fn capture_stream(
+        &mut self,
+        output_name: &str,
+        options: &CaptureOptions,
+    ) -> Result<CaptureStream<'_>> {
+        let stream = self
+            .platform_capture
+            .start_stream(output_name, options.overlay_cursor_enabled())?;
+        Ok(CaptureStream {
+            grim: self,
+            stream,
+            options: options.clone(),
+        })
+    }
+
+    /// Capture a specific output by name, including the cursor.
+    ///
+    /// Shorthand for [`Grim::capture`] with
+    /// `CaptureOptions::new().output(output_name).overlay_cursor(true)`.
+    /// See [`CaptureOptions::overlay_cursor`] for compositor support.
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`Grim::capture_output`].
+    pub fn capture_output_with_cursor(&mut self, output_name: &str) -> Result<CaptureResult> {
+        self.capture(
+            &CaptureOptions::new()
+                .output(output_name)
+                .overlay_cursor(true),
+        )
+    }
+
+    /// Capture the cursor image on its own.
+    ///
+    /// Captures the whole screen twice, without and with the cursor, and
+    /// keeps only the pixels that differ. Returns the cursor sprite, with
+    /// unchanged pixels made transparent, and the position of its top-left
+    /// corner within a [`capture_all`](Self::capture_all) image.
+    ///
+    /// This only works if the compositor composites the cursor into
+    /// screencopy frames when asked (see [`CaptureOptions::overlay_cursor`]).
+    /// Anything else that changes between the two captures, including the
+    /// cursor moving, ends up in the sprite as well, and cursor pixels that
+    /// match the screen underneath come out transparent.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::FrameCapture` if both captures are identical, e.g.
+    /// because the compositor left the cursor out, and otherwise the same
+    /// errors as [`capture_all`](Self::capture_all).
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use grim_rs::Grim;
+    ///
+    /// let mut grim = Grim::new()?;
+    /// let (sprite, x, y) = grim.capture_cursor()?;
+    /// println!("{}x{} cursor at {},{}", sprite.width(), sprite.height(), x, y);
+    /// # Ok::<(), grim_rs::Error>(())
+    /// ```
+    pub fn capture_cursor(&mut self) -> Result<(CaptureResult, i32, i32)> {
+        let without = self.capture_all()?;
+        let with = self.capture(&CaptureOptions::new().overlay_cursor(true))?;
+        cursor_sprite(&without, &with).ok_or_else(|| {
+            Error::frame_capture("no cursor pixels found in the overlay capture".to_string())
+        })
+    }
+
+    /// Capture a specific output by name with specified scale factor.
+    ///
+    /// Captures a screenshot of the specified display output with a specified scale factor.
+    ///
+    /// # Arguments
+    ///
+    /// * `output_name` - Name of the output to capture (e.g., "eDP-1", "HDMI-A-1")
+    /// * `scale` - Scale factor for the output image
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The specified output is not found
+    /// - Failed to capture the output
+    /// - Buffer creation failed
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use grim_rs::Grim;
+    ///
+    /// let mut grim = Grim::new()?;
+    /// // Get available outputs first
+    /// let outputs = grim.get_outputs()?;
+    /// if let Some(output) = outputs.first() {
+    ///     let result = grim.capture_output_with_scale(output.name(), 0.5)?;
+    ///     println!("Captured output at 50% scale: {}x{}", result.width(), result.height());
+    /// }
+    /// # Ok::<(), grim_rs::Error>(())
+    /// ```
+    pub fn capture_output_with_scale(
+        &mut self,
+        output_name: &str,
+        scale: f64,
+    ) -> Result<CaptureResult> {
+        self.platform_capture
+            .capture_output_with_scale(output_name, scale)
+    }
+
+    /// Capture a downscaled thumbnail of a specific output.
+    ///
+    /// The result keeps the output's aspect ratio and fits within
+    /// `max_width`x`max_height`; it is never larger than the output itself.
+    /// Unlike [`capture_output_with_scale`](Self::capture_output_with_scale),
+    /// the frame is averaged down while it is copied out of the shared
+    /// buffer, so no full-resolution RGBA image is allocated. Frames that
+    /// arrive via dmabuf or `ext-image-copy-capture-v1` are captured in full
+    /// and resized afterwards.
+    ///
+    /// # Arguments
+    ///
+    /// * `output_name` - Name of the output to capture
+    /// * `max_width` - Maximum thumbnail width in pixels
+    /// * `max_height` - Maximum thumbnail height in pixels
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - `max_width` or `max_height` is zero
+    /// - The specified output is not found
+    /// - Failed to capture the output
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use grim_rs::Grim;
+    ///
+    /// let mut grim = Grim::new()?;
+    /// let outputs = grim.get_outputs()?;
+    /// if let Some(output) = outputs.first() {
+    ///     let thumbnail = grim.capture_output_thumbnail(output.name(), 256, 256)?;
+    ///     assert!(thumbnail.width() <= 256 && thumbnail.height() <= 256);
+    /// }
+    /// # Ok::<(), grim_rs::Error>(())
+    /// ```
+    pub fn capture_output_thumbnail(
+        &mut self,
+        output_name: &str,
+        max_width: u32,
+        max_height: u32,
+    ) -> Result<CaptureResult> {
+        self.platform_capture
+            .capture_output_thumbnail(output_name, max_width, max_height)
+    }
+
+    /// Capture a specific output by name with the given options.
+    ///
+    /// # Arguments
+    ///
+    /// * `output_name` - Name of the output to capture
+    /// * `options` - Cursor and scale settings for the capture
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The specified output is not found
+    /// - Failed to capture the output
+    /// - Buffer creation failed
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use grim_rs::{CaptureOptions, Grim};
+    ///
+    /// let mut grim = Grim::new()?;
+    /// let outputs = grim.get_outputs()?;
+    /// if let Some(output) = outputs.first() {
+    ///     let options = CaptureOptions::new().overlay_cursor(true).scale(0.5);
+    ///     let result = grim.capture_output_with_options(output.name(), &options)?;
+    /// }
+    /// # Ok::<(), grim_rs::Error>(())
+    /// ```
+    pub fn capture_output_with_options(
+        &mut self,
+        output_name: &str,
+        options: &CaptureOptions,
+    ) -> Result<CaptureResult> {
+        self.platform_capture
+            .capture_output_with_options(output_name, options)
+    }
+
+    /// Capture a specific region.
+    ///
+    /// Captures a screenshot of the specified rectangular region.
+    ///
+    /// # Arguments
+    ///
+    /// * `region` - The region to capture, specified as a [`Box`]
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - No outputs are available
+    /// - Failed to capture the region
+    /// - Buffer creation failed
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use grim_rs::{Grim, Box};
+    ///
+    /// let mut grim = Grim::new()?;
+    /// // x=100, y=100, width=800, height=600
+    /// let region = Box::new(100, 100, 800, 600);
+    /// let result = grim.capture_region(region)?;
+    /// println!("Captured region: {}x{}", result.width(), result.height());
+    /// # Ok::<(), grim_rs::Error>(())
+    /// ```
+    pub fn capture_region(&mut self, region: Box) -> Result<CaptureResult> {
+        self.platform_capture.capture_region(region)
+    }
+
+    /// Capture a specific region including the cursor.
+    ///
+    /// Shorthand for [`Grim::capture`] with
+    /// `CaptureOptions::new().region(region).overlay_cursor(true)`.
+    /// See [`CaptureOptions::overlay_cursor`] for compositor support.
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`Grim::capture_region`].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use grim_rs::{Grim, Box};
+    ///
+    /// let mut grim = Grim::new()?;
+    /// let result = grim.capture_region_with_cursor(Box::new(100, 100, 800, 600))?;
+    /// println!("Captured region: {}x{}", result.width(), result.height());
+    /// # Ok::<(), grim_rs::Error>(())
+    /// ```
+    pub fn capture_region_with_cursor(&mut self, region: Box) -> Result<CaptureResult> {
+        self.capture(&CaptureOptions::new().region(region).overlay_cursor(true))
+    }
+
+    /// Capture a specific region with specified scale factor.
+    ///
+    /// Captures a screenshot of the specified rectangular region with a specified scale factor.
+    ///
+    /// # Arguments
+    ///
+    /// * `region` - The region to capture, specified as a [`Box`]
+    /// * `scale` - Scale factor for the output image
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - No outputs are available
+    /// - Failed to capture the region
+    /// - Buffer creation failed
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use grim_rs::{Grim, Box};
+    ///
+    /// let mut grim = Grim::new()?;
+    /// // x=100, y=100, width=800, height=600
+    /// let region = Box::new(100, 100, 800, 600);
+    /// let result = grim.capture_region_with_scale(region, 1.0)?;
+    /// println!("Captured region: {}x{}", result.width(), result.height());
+    /// # Ok::<(), grim_rs::Error>(())
+    /// ```
+    pub fn capture_region_with_scale(&mut self, region: Box, scale: f64) -> Result<CaptureResult> {
+        self.platform_capture
+            .capture_region_with_scale(region, scale)
+    }
+
+    /// Capture a specific region with the given options.
+    ///
+    /// # Arguments
+    ///
+    /// * `region` - A `Box` defining the region to capture
+    /// * `options` - Cursor and scale settings for the capture
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The specified region is invalid
+    /// - Failed to capture the region
+    /// - Buffer creation failed
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use grim_rs::{Box, CaptureOptions, Grim};
+    ///
+    /// let mut grim = Grim::new()?;
+    /// let region = Box::new(0, 0, 800, 600);
+    /// let options = CaptureOptions::new().overlay_cursor(true);
+    /// let result = grim.capture_region_with_options(region, &options)?;
+    /// # Ok::<(), grim_rs::Error>(())
+    /// ```
+    pub fn capture_region_with_options(
+        &mut self,
+        region: Box,
+        options: &CaptureOptions,
+    ) -> Result<CaptureResult> {
+        self.platform_capture
+            .capture_region_with_options(region, options)
+    }
+
+    /// Capture a specific region once its content stops changing.
+    ///
+    /// Repeatedly captures `region`, waiting `poll_interval` between captures,
+    /// until two consecutive frames are identical (compared with
+    /// [`CaptureResult::content_hash`]) or `max_wait` elapses. This avoids
+    /// grabbing a frame in the middle of an animation, e.g. a menu sliding in.
+    ///
+    /// Every poll is a full capture plus a hash of the pixel data, so a short
+    /// `poll_interval` costs CPU and compositor time, while a long one adds
+    /// latency before the stable frame is returned. The minimum latency is
+    /// two captures plus one `poll_interval`.
+    ///
+    /// # Arguments
+    ///
+    /// * `region` - The region to capture, specified as a [`Box`]
+    /// * `max_wait` - Maximum time to wait for the content to settle
+    /// * `poll_interval` - Delay between consecutive captures
+    ///
+    /// # Returns
+    ///
+    /// Returns the first frame that matches its predecessor. If the content
+    /// never stabilizes within `max_wait`, the last captured frame is returned.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any of the captures fails (see [`Grim::capture_region`]).
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use grim_rs::{Grim, Box};
+    /// use std::time::Duration;
+    ///
+    /// let mut grim = Grim::new()?;
+    /// let region = Box::new(0, 0, 400, 300);
+    /// let result = grim.capture_region_when_stable(
+    ///     region,
+    ///     Duration::from_secs(2),
+    ///     Duration::from_millis(50),
+    /// )?;
+    /// println!("Captured stable region: {}x{}", result.width(), result.height());
+    /// # Ok::<(), grim_rs::Error>(())
+    /// ```
+    pub fn capture_region_when_stable(
+        &mut self,
+        region: Box,
+        max_wait: std::time::Duration,
+        poll_interval: std::time::Duration,
+    ) -> Result<CaptureResult> {
+        wait_for_stable_frame(|| self.capture_region(region), max_wait, poll_interval)
+    }
+
+    /// Capture multiple outputs with different parameters.
+    ///
+    /// Captures screenshots of multiple outputs simultaneously, each with
+    /// potentially different parameters (region, cursor inclusion, etc.).
+    ///
+    /// # Arguments
+    ///
+    /// * `parameters` - Vector of [`CaptureParameters`] specifying what to capture
+    ///   from each output
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - Any specified output is not found
+    /// - Any specified region is outside the bounds of its output
+    /// - Failed to capture any of the outputs
+    /// - Buffer creation failed
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use grim_rs::{Grim, CaptureParameters, Box};
+    ///
+    /// let mut grim = Grim::new()?;
+    ///
+    /// // Get available outputs
+    /// let outputs = grim.get_outputs()?;
+    ///
+    /// // Prepare capture parameters for multiple outputs
+    /// let mut parameters = vec![
+    ///     CaptureParameters::new(outputs[0].name())
+    ///         .overlay_cursor(true)
+    /// ];
+    ///
+    /// // If we have a second output, capture a region of it
+    /// if outputs.len() > 1 {
+    ///     let region = Box::new(0, 0, 400, 300);
+    ///     parameters.push(
+    ///         CaptureParameters::new(outputs[1].name())
+    ///             .region(region)
+    ///     );
+    /// }
+    ///
+    /// // Capture all specified outputs
+    /// let results = grim.capture_outputs(parameters)?;
+    /// println!("Captured {} outputs", results.outputs().len());
+    /// # Ok::<(), grim_rs::Error>(())
+    /// ```
+    pub fn capture_outputs(
+        &mut self,
+        parameters: Vec<CaptureParameters>,
+    ) -> Result<MultiOutputCaptureResult> {
+        self.platform_capture.capture_outputs(parameters)
+    }
+
+    /// Capture outputs with scale factor.
+    ///
+    /// Captures screenshots of multiple outputs simultaneously. Each output is
+    /// scaled by the factor set with [`CaptureParameters::scale`], or by
+    /// `default_scale` if its parameters don't set one.
+    ///
+    /// # Arguments
+    ///
+    /// * `parameters` - Vector of CaptureParameters with scale factors
+    /// * `default_scale` - Default scale factor to use when not specified in parameters
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any of the outputs can't be captured
+    pub fn capture_outputs_with_scale(
+        &mut self,
+        parameters: Vec<CaptureParameters>,
+        default_scale: f64,
+    ) -> Result<MultiOutputCaptureResult> {
+        self.platform_capture
+            .capture_outputs_with_scale(parameters, default_scale)
+    }
+
+    /// Capture the entire screen and return it as PNG bytes.
+    ///
+    /// The returned bytes are a complete PNG stream (signature, header, data
+    /// and end chunk), ready to be written to a file, a socket or stdout as is.
+    /// The captured pixel buffer is encoded in place, so no extra copy of it
+    /// is made. The PNG carries `Software` and `Creation Time` text chunks
+    /// and a pHYs chunk of 96 DPI per unit of output scale (see
+    /// [`PngMetadata::screenshot`]).
+    ///
+    /// # Arguments
+    ///
+    /// * `compression` - PNG compression level (0-9, where 9 is highest compression)
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - No outputs are available
+    /// - The capture or the PNG encoding fails
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use grim_rs::Grim;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut grim = Grim::new()?;
+    /// std::fs::write("screenshot.png", grim.capture_all_to_png(6)?)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn capture_all_to_png(&mut self, compression: u8) -> Result<Vec<u8>> {
+        let result = self.capture_all()?;
+        let layout = self.with_outputs(|outputs| {
+            let geometries: Vec<Box> = outputs.iter().map(|output| *output.geometry()).collect();
+            Box::union_all(&geometries)
+        })?;
+        let logical_width = layout.map_or(0, |layout| layout.width());
+        self.encode_capture_png(&result, compression, logical_width)
+    }
+
+    /// Capture a specific output and return it as PNG bytes.
+    ///
+    /// The returned bytes are a complete PNG stream, ready to write.
+    /// See [`capture_all_to_png`](Self::capture_all_to_png).
+    ///
+    /// # Arguments
+    ///
+    /// * `output_name` - Name of the output to capture
+    /// * `compression` - PNG compression level (0-9, where 9 is highest compression)
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The specified output is not found
+    /// - The capture or the PNG encoding fails
+    pub fn capture_output_to_png(&mut self, output_name: &str, compression: u8) -> Result<Vec<u8>> {
+        let result = self.capture_output(output_name)?;
+        let logical_width = self.get_output(output_name)?.geometry().width();
+        self.encode_capture_png(&result, compression, logical_width)
+    }
+
+    /// Capture a specific region and return it as PNG bytes.
+    ///
+    /// The returned bytes are a complete PNG stream, ready to write.
+    /// See [`capture_all_to_png`](Self::capture_all_to_png).
+    ///
+    /// # Arguments
+    ///
+    /// * `region` - A `Box` defining the region to capture
+    /// * `compression` - PNG compression level (0-9, where 9 is highest compression)
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The specified region is invalid
+    /// - The capture or the PNG encoding fails
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use grim_rs::{Box, Grim};
+    ///
+    /// let mut grim = Grim::new()?;
+    /// let png_bytes = grim.capture_region_to_png(Box::new(0, 0, 200, 150), 6)?;
+    /// assert!(png_bytes.starts_with(b"\x89PNG"));
+    /// # Ok::<(), grim_rs::Error>(())
+    /// ```
+    pub fn capture_region_to_png(&mut self, region: Box, compression: u8) -> Result<Vec<u8>> {
+        let result = self.capture_region(region)?;
+        self.encode_capture_png(&result, compression, region.width())
+    }
+
+    /// Encode `result` as PNG with metadata whose DPI reflects how many
+    /// pixels the capture has per logical pixel.
+    fn encode_capture_png(
+        &self,
+        result: &CaptureResult,
+        compression: u8,
+        logical_width: i32,
+    ) -> Result<Vec<u8>> {
+        self.to_png_with_metadata(
+            result.data(),
+            result.width(),
+            result.height(),
+            &PngOptions::new().compression(compression),
+            &capture_png_metadata(result.width(), logical_width),
+        )
+    }
+
+    /// Save captured data as PNG.
+    ///
+    /// Saves the captured image data to a PNG file.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - Raw RGBA image data from a capture result
+    /// * `width` - Width of the image in pixels
+    /// * `height` - Height of the image in pixels
+    /// * `path` - Path where to save the PNG file
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - Failed to create or write to the file
+    /// - Image processing failed
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use grim_rs::Grim;
+    /// use chrono::Local;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut grim = Grim::new()?;
+    /// let result = grim.capture_all()?;
+    ///
+    /// // Generate timestamped filename
+    /// let filename = format!("{}_grim.png", Local::now().format("%Y%m%d_%Hh%Mm%Ss"));
+    /// grim.save_png(result.data(), result.width(), result.height(), &filename)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn save_png<P: AsRef<std::path::Path>>(
+        &self,
+        data: &[u8],
+        width: u32,
+        height: u32,
+        path: P,
+    ) -> Result<()> {
+        self.save_png_with_compression(data, width, height, path, 6) // Default compression level of 6
+    }
+
+    /// Save captured data as PNG with compression level control.
+    ///
+    /// Saves the captured image data to a PNG file with specified compression level.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - Raw RGBA image data from a capture result
+    /// * `width` - Width of the image in pixels
+    /// * `height` - Height of the image in pixels
+    /// * `path` - Path where to save the PNG file
+    /// * `compression` - PNG compression level (0-9, where 9 is highest compression)
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - Failed to create or write to the file
+    /// - Image processing failed
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use grim_rs::Grim;
+    /// use chrono::Local;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut grim = Grim::new()?;
+    /// let result = grim.capture_all()?;
+    ///
+    /// // Generate timestamped filename
+    /// let filename = format!("{}_grim.png", Local::now().format("%Y%m%d_%Hh%Mm%Ss"));
+    /// grim.save_png_with_compression(result.data(), result.width(), result.height(), &filename, 9)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn save_png_with_compression<P: AsRef<std::path::Path>>(
+        &self,
+        data: &[u8],
+        width: u32,
+        height: u32,
+        path: P,
+        compression: u8,
+    ) -> Result<()> {
+        self.save_png_with_options(
+            data,
+            width,
+            height,
+            path,
+            &PngOptions::new().compression(compression),
+        )
+    }
+
+    /// Save captured data as PNG with explicit encoder options.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - Raw RGBA image data from a capture result
+    /// * `width` - Width of the image in pixels
+    /// * `height` - Height of the image in pixels
+    /// * `path` - Path where to save the PNG file
+    /// * `options` - Compression level and row filter
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - Failed to create or write to the file
+    /// - Image processing failed
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use grim_rs::{Grim, PngFilter, PngOptions};
+    ///
+    /// let mut grim = Grim::new()?;
+    /// let result = grim.capture_all()?;
+    /// let options = PngOptions::new().compression(9).filter(PngFilter::Up);
+    /// grim.save_png_with_options(result.data(), result.width(), result.height(), "screenshot.png", &options)?;
+    /// # Ok::<(), grim_rs::Error>(())
+    /// ```
+    pub fn save_png_with_options<P: AsRef<std::path::Path>>(
+        &self,
+        data: &[u8],
+        width: u32,
+        height: u32,
+        path: P,
+        options: &PngOptions,
+    ) -> Result<()> {
+        save_png_file(path.as_ref(), data, width, height, options, None)
+    }
+
+    /// Save captured data as PNG with ancillary metadata chunks.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - Raw RGBA image data from a capture result
+    /// * `width` - Width of the image in pixels
+    /// * `height` - Height of the image in pixels
+    /// * `path` - Path where to save the PNG file
+    /// * `options` - Compression level and row filter
+    /// * `metadata` - Software, creation time and DPI to embed
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - Failed to create or write to the file
+    /// - Image processing failed
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use grim_rs::{Grim, PngMetadata, PngOptions};
+    ///
+    /// let mut grim = Grim::new()?;
+    /// let result = grim.capture_all()?;
+    /// let metadata = PngMetadata::screenshot(192.0);
+    /// grim.save_png_with_metadata(result.data(), result.width(), result.height(), "screenshot.png", &PngOptions::new(), &metadata)?;
+    /// # Ok::<(), grim_rs::Error>(())
+    /// ```
+    pub fn save_png_with_metadata<P: AsRef<std::path::Path>>(
+        &self,
+        data: &[u8],
+        width: u32,
+        height: u32,
+        path: P,
+        options: &PngOptions,
+        metadata: &PngMetadata,
+    ) -> Result<()> {
+        save_png_file(path.as_ref(), data, width, height, options, Some(metadata))
+    }
+
+    /// Save captured data as JPEG.
+    ///
+    /// Saves the captured image data to a JPEG file.
+    ///
+    /// This function is only available when the `jpeg` feature is enabled.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - Raw RGBA image data from a capture result
+    /// * `width` - Width of the image in pixels
+    /// * `height` - Height of the image in pixels
+    /// * `path` - Path where to save the JPEG file
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - Failed to create or write to the file
+    /// - Image processing failed
+    /// - JPEG support is not enabled (when feature is disabled)
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use grim_rs::Grim;
+    /// use chrono::Local;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut grim = Grim::new()?;
+    /// let result = grim.capture_all()?;
+    ///
+    /// // Generate timestamped filename
+    /// let filename = format!("{}_grim.jpg", Local::now().format("%Y%m%d_%Hh%Mm%Ss"));
+    /// grim.save_jpeg(result.data(), result.width(), result.height(), &filename)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "jpeg")]
+    pub fn save_jpeg<P: AsRef<std::path::Path>>(
+        &self,
+        data: &[u8],
+        width: u32,
+        height: u32,
+        path: P,
+    ) -> Result<()> {
+        self.save_jpeg_with_quality(data, width, height, path, 80)
+    }
+
+    /// Save captured data as JPEG with quality control.
+    ///
+    /// Saves the captured image data to a JPEG file with specified quality.
+    ///
+    /// This function is only available when the `jpeg` feature is enabled.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - Raw RGBA image data from a capture result
+    /// * `width` - Width of the image in pixels
+    /// * `height` - Height of the image in pixels
+    /// * `path` - Path where to save the JPEG file
+    /// * `quality` - JPEG quality level (0-100, where 100 is highest quality)
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - Failed to create or write to the file
+    /// - Image processing failed
+    /// - JPEG support is not enabled (when feature is disabled)
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use grim_rs::Grim;
+    /// use chrono::Local;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut grim = Grim::new()?;
+    /// let result = grim.capture_all()?;
+    ///
+    /// // Generate timestamped filename
+    /// let filename = format!("{}_grim.jpg", Local::now().format("%Y%m%d_%Hh%Mm%Ss"));
+    /// grim.save_jpeg_with_quality(result.data(), result.width(), result.height(), &filename, 90)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "jpeg")]
+    pub fn save_jpeg_with_quality<P: AsRef<std::path::Path>>(
+        &self,
+        data: &[u8],
+        width: u32,
+        height: u32,
+        path: P,
+        quality: u8,
+    ) -> Result<()> {
+        use std::io::{BufWriter, Write};
+
+        check_rgba_len(data, width, height)?;
+        let file = std::fs::File::create(&path).map_err(|e| Error::IoWithContext {
+            operation: format!("creating output file '{}'", path.as_ref().display()),
+            source: e,
+        })?;
+        let mut writer = BufWriter::new(file);
+        self.encode_jpeg_into(data, width, height, quality, &mut writer)?;
+        writer.flush().map_err(|e| Error::IoWithContext {
+            operation: format!("writing JPEG data to file '{}'", path.as_ref().display()),
+            source: e,
+        })?;
+        Ok(())
+    }
+
+    /// Save captured data as JPEG (stub when feature is disabled).
+    ///
+    /// This stub is used when the `jpeg` feature is disabled.
+    ///
+    /// # Errors
+    ///
+    /// Always returns an error indicating that JPEG support is not enabled.
+    #[cfg(not(feature = "jpeg"))]
+    pub fn save_jpeg<P: AsRef<std::path::Path>>(
+        &self,
+        _data: &[u8],
+        _width: u32,
+        _height: u32,
+        _path: P,
+    ) -> Result<()> {
+        Err(Error::ImageProcessing(image::ImageError::Unsupported(
+            image::error::UnsupportedError::from_format_and_kind(
+                image::error::ImageFormatHint::Name("JPEG".to_string()),
+                image::error::UnsupportedErrorKind::Format(image::ImageFormat::Jpeg.into()),
+            ),
+        )))
+    }
+
+    /// Save captured data as JPEG with quality control (stub when feature is disabled).
+    ///
+    /// This stub is used when the `jpeg` feature is disabled.
+    ///
+    /// # Errors
+    ///
+    /// Always returns an error indicating that JPEG support is not enabled.
+    #[cfg(not(feature = "jpeg"))]
+    pub fn save_jpeg_with_quality<P: AsRef<std::path::Path>>(
+        &self,
+        _data: &[u8],
+        _width: u32,
+        _height: u32,
+        _path: P,
+        _quality: u8,
+    ) -> Result<()> {
+        Err(Error::ImageProcessing(image::ImageError::Unsupported(
+            image::error::UnsupportedError::from_format_and_kind(
+                image::error::ImageFormatHint::Name("JPEG".to_string()),
+                image::error::UnsupportedErrorKind::Format(image::ImageFormat::Jpeg.into()),
+            ),
+        )))
+    }
+
+    /// Get image data as JPEG bytes.
+    ///
+    /// Converts the captured image data to JPEG format and returns the bytes.
+    ///
+    /// This function is only available when the `jpeg` feature is enabled.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - Raw RGBA image data from a capture result
+    /// * `width` - Width of the image in pixels
+    /// * `height` - Height of the image in pixels
+    ///
+    /// # Returns
+    ///
+    /// Returns the JPEG-encoded image data as a vector of bytes.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - Image processing failed
+    /// - JPEG support is not enabled (when feature is disabled)
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use grim_rs::Grim;
+    ///
+    /// let mut grim = Grim::new()?;
+    /// let result = grim.capture_all()?;
+    /// let jpeg_bytes = grim.to_jpeg(result.data(), result.width(), result.height())?;
+    /// println!("JPEG data size: {} bytes", jpeg_bytes.len());
+    /// # Ok::<(), grim_rs::Error>(())
+    /// ```
+    #[cfg(feature = "jpeg")]
+    pub fn to_jpeg(&self, data: &[u8], width: u32, height: u32) -> Result<Vec<u8>> {
+        self.to_jpeg_with_quality(data, width, height, 80)
+    }
+
+    /// Get image data as JPEG bytes with quality control.
+    ///
+    /// Converts the captured image data to JPEG format with specified quality and returns the bytes.
+    ///
+    /// This function is only available when the `jpeg` feature is enabled.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - Raw RGBA image data from a capture result
+    /// * `width` - Width of the image in pixels
+    /// * `height` - Height of the image in pixels
+    /// * `quality` - JPEG quality level (0-100, where 100 is highest quality)
+    ///
+    /// # Returns
+    ///
+    /// Returns the JPEG-encoded image data as a vector of bytes.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - Image processing failed
+    /// - JPEG support is not enabled (when feature is disabled)
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use grim_rs::Grim;
+    ///
+    /// let mut grim = Grim::new()?;
+    /// let result = grim.capture_all()?;
+    /// let jpeg_bytes = grim.to_jpeg_with_quality(result.data(), result.width(), result.height(), 90)?;
+    /// println!("JPEG data size: {} bytes", jpeg_bytes.len());
+    /// # Ok::<(), grim_rs::Error>(())
+    /// ```
+    #[cfg(feature = "jpeg")]
+    pub fn to_jpeg_with_quality(
+        &self,
+        data: &[u8],
+        width: u32,
+        height: u32,
+        quality: u8,
+    ) -> Result<Vec<u8>> {
+        self.to_jpeg_owned(data.to_vec(), width, height, quality)
+    }
+
+    /// Get image data as JPEG bytes (stub when feature is disabled).
+    ///
+    /// This stub is used when the `jpeg` feature is disabled.
+    ///
+    /// # Errors
+    ///
+    /// Always returns an error indicating that JPEG support is not enabled.
+    #[cfg(not(feature = "jpeg"))]
+    pub fn to_jpeg(&self, _data: &[u8], _width: u32, _height: u32) -> Result<Vec<u8>> {
+        Err(Error::ImageProcessing(image::ImageError::Unsupported(
+            image::error::UnsupportedError::from_format_and_kind(
+                image::error::ImageFormatHint::Name("JPEG".to_string()),
+                image::error::UnsupportedErrorKind::Format(image::ImageFormat::Jpeg.into()),
+            ),
+        )))
+    }
+
+    /// Get image data as JPEG bytes with quality control (stub when feature is disabled).
+    ///
+    /// This stub is used when the `jpeg` feature is disabled.
+    ///
+    /// # Errors
+    ///
+    /// Always returns an error indicating that JPEG support is not enabled.
+    #[cfg(not(feature = "jpeg"))]
+    pub fn to_jpeg_with_quality(
+        &self,
+        _data: &[u8],
+        _width: u32,
+        _height: u32,
+        _quality: u8,
+    ) -> Result<Vec<u8>> {
+        Err(Error::ImageProcessing(image::ImageError::Unsupported(
+            image::error::UnsupportedError::from_format_and_kind(
+                image::error::ImageFormatHint::Name("JPEG".to_string()),
+                image::error::UnsupportedErrorKind::Format(image::ImageFormat::Jpeg.into()),
+            ),
+        )))
+    }
+
+    /// Get image data as JPEG bytes, flattening transparency onto a color.
+    ///
+    /// JPEG has no alpha channel, so every pixel is alpha-blended onto
+    /// `background` before encoding. The other JPEG methods use opaque black.
+    ///
+    /// This function is only available when the `jpeg` feature is enabled.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - Raw RGBA image data from a capture result
+    /// * `width` - Width of the image in pixels
+    /// * `height` - Height of the image in pixels
+    /// * `quality` - JPEG quality level (0-100, where 100 is highest quality)
+    /// * `background` - RGB color that transparent pixels are blended onto
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - Image processing failed
+    /// - JPEG support is not enabled (when feature is disabled)
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use grim_rs::Grim;
+    ///
+    /// let mut grim = Grim::new()?;
+    /// let result = grim.capture_all()?;
+    /// let jpeg_bytes = grim.to_jpeg_with_options(
+    ///     result.data(),
+    ///     result.width(),
+    ///     result.height(),
+    ///     90,
+    ///     [255, 255, 255],
+    /// )?;
+    /// println!("JPEG data size: {} bytes", jpeg_bytes.len());
+    /// # Ok::<(), grim_rs::Error>(())
+    /// ```
+    #[cfg(feature = "jpeg")]
+    pub fn to_jpeg_with_options(
+        &self,
+        data: &[u8],
+        width: u32,
+        height: u32,
+        quality: u8,
+        background: [u8; 3],
+    ) -> Result<Vec<u8>> {
+        encode_jpeg_flattened(data.to_vec(), width, height, quality, background)
+    }
+
+    /// Get image data as JPEG bytes with a background color (stub when feature is disabled).
+    ///
+    /// This stub is used when the `jpeg` feature is disabled.
+    ///
+    /// # Errors
+    ///
+    /// Always returns an error indicating that JPEG support is not enabled.
+    #[cfg(not(feature = "jpeg"))]
+    pub fn to_jpeg_with_options(
+        &self,
+        _data: &[u8],
+        _width: u32,
+        _height: u32,
+        _quality: u8,
+        _background: [u8; 3],
+    ) -> Result<Vec<u8>> {
+        Err(Error::ImageProcessing(image::ImageError::Unsupported(
+            image::error::UnsupportedError::from_format_and_kind(
+                image::error::ImageFormatHint::Name("JPEG".to_string()),
+                image::error::UnsupportedErrorKind::Format(image::ImageFormat::Jpeg.into()),
+            ),
+        )))
+    }
+
+    /// Get image data as JPEG bytes, consuming the RGBA buffer.
+    ///
+    /// Same as [`to_jpeg_with_quality`](Self::to_jpeg_with_quality), but the
+    /// RGBA buffer is converted to RGB in place instead of being copied, which
+    /// avoids a second full-size allocation for large captures.
+    ///
+    /// This function is only available when the `jpeg` feature is enabled.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - Raw RGBA image data, e.g. from [`CaptureResult::into_data`]
+    /// * `width` - Width of the image in pixels
+    /// * `height` - Height of the image in pixels
+    /// * `quality` - JPEG quality level (0-100, where 100 is highest quality)
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - Image processing failed
+    /// - JPEG support is not enabled (when feature is disabled)
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use grim_rs::Grim;
+    ///
+    /// let mut grim = Grim::new()?;
+    /// let result = grim.capture_all()?;
+    /// let (width, height) = (result.width(), result.height());
+    /// let jpeg_bytes = grim.to_jpeg_owned(result.into_data(), width, height, 90)?;
+    /// println!("JPEG data size: {} bytes", jpeg_bytes.len());
+    /// # Ok::<(), grim_rs::Error>(())
+    /// ```
+    #[cfg(feature = "jpeg")]
+    pub fn to_jpeg_owned(
+        &self,
+        data: Vec<u8>,
+        width: u32,
+        height: u32,
+        quality: u8,
+    ) -> Result<Vec<u8>> {
+        encode_jpeg_flattened(data, width, height, quality, DEFAULT_FLATTEN_BACKGROUND)
+    }
+
+    /// Get image data as JPEG bytes, consuming the RGBA buffer (stub when feature is disabled).
+    ///
+    /// This stub is used when the `jpeg` feature is disabled.
+    ///
+    /// # Errors
+    ///
+    /// Always returns an error indicating that JPEG support is not enabled.
+    #[cfg(not(feature = "jpeg"))]
+    pub fn to_jpeg_owned(
+        &self,
+        _data: Vec<u8>,
+        _width: u32,
+        _height: u32,
+        _quality: u8,
+    ) -> Result<Vec<u8>> {
+        Err(Error::ImageProcessing(image::ImageError::Unsupported(
+            image::error::UnsupportedError::from_format_and_kind(
+                image::error::ImageFormatHint::Name("JPEG".to_string()),
+                image::error::UnsupportedErrorKind::Format(image::ImageFormat::Jpeg.into()),
+            ),
+        )))
+    }
+
+    /// Encode image data as JPEG directly into `writer`.
+    ///
+    /// Fully opaque RGBA data is passed to the encoder as is, so neither an
+    /// RGB copy nor an intermediate `Vec<u8>` of encoded bytes is made. Images
+    /// with transparent pixels are first flattened onto opaque black. Wrap
+    /// unbuffered targets in a [`std::io::BufWriter`].
+    ///
+    /// This function is only available when the `jpeg` feature is enabled.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - Raw RGBA image data from a capture result
+    /// * `width` - Width of the image in pixels
+    /// * `height` - Height of the image in pixels
+    /// * `quality` - JPEG quality level (0-100, where 100 is highest quality)
+    /// * `writer` - Destination for the encoded bytes
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - Writing to `writer` failed
+    /// - Image processing failed
+    /// - JPEG support is not enabled (when feature is disabled)
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use grim_rs::Grim;
+    /// use std::io::BufWriter;
+    ///
+    /// let mut grim = Grim::new()?;
+    /// let result = grim.capture_all()?;
+    /// let file = std::fs::File::create("screenshot.jpg")?;
+    /// grim.encode_jpeg_into(result.data(), result.width(), result.height(), 90, BufWriter::new(file))?;
+    /// # Ok::<(), grim_rs::Error>(())
+    /// ```
+    #[cfg(feature = "jpeg")]
+    pub fn encode_jpeg_into<W: std::io::Write>(
+        &self,
+        data: &[u8],
+        width: u32,
+        height: u32,
+        quality: u8,
+        writer: W,
+    ) -> Result<()> {
+        check_rgba_len(data, width, height)?;
+        let flattened;
+        let (pixels, color_type) = if data.chunks_exact(4).all(|px| px[3] == 255) {
+            (data, jpeg_encoder::ColorType::Rgba)
+        } else {
+            let mut rgb = data.to_vec();
+            flatten_rgba_to_rgb_in_place(&mut rgb, DEFAULT_FLATTEN_BACKGROUND);
+            flattened = rgb;
+            (flattened.as_slice(), jpeg_encoder::ColorType::Rgb)
+        };
+        jpeg_encoder::Encoder::new(writer, quality)
+            .encode(pixels, width as u16, height as u16, color_type)
+            .map_err(|e| Error::Io(std::io::Error::other(format!("JPEG encoding error: {}", e))))
+    }
+
+    /// Encode image data as JPEG into a writer (stub when feature is disabled).
+    ///
+    /// This stub is used when the `jpeg` feature is disabled.
+    ///
+    /// # Errors
+    ///
+    /// Always returns an error indicating that JPEG support is not enabled.
+    #[cfg(not(feature = "jpeg"))]
+    pub fn encode_jpeg_into<W: std::io::Write>(
+        &self,
+        _data: &[u8],
+        _width: u32,
+        _height: u32,
+        _quality: u8,
+        _writer: W,
+    ) -> Result<()> {
+        Err(Error::ImageProcessing(image::ImageError::Unsupported(
+            image::error::UnsupportedError::from_format_and_kind(
+                image::error::ImageFormatHint::Name("JPEG".to_string()),
+                image::error::UnsupportedErrorKind::Format(image::ImageFormat::Jpeg.into()),
+            ),
+        )))
+    }
+
+    /// Save captured data as WebP.
+    ///
+    /// Saves the captured image data to a WebP file with default quality (80).
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - Raw RGBA image data from a capture result
+    /// * `width` - Width of the image in pixels
+    /// * `height` - Height of the image in pixels
+    /// * `path` - Path where to save the WebP file
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - Failed to create or write to the file
+    /// - Image processing failed
+    /// - WebP support is not enabled (when feature is disabled)
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use grim_rs::Grim;
+    /// use chrono::Local;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut grim = Grim::new()?;
+    /// let result = grim.capture_all()?;
+    ///
+    /// // Generate timestamped filename
+    /// let filename = format!("{}_grim.webp", Local::now().format("%Y%m%d_%Hh%Mm%Ss"));
+    /// grim.save_webp(result.data(), result.width(), result.height(), &filename)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn save_webp<P: AsRef<std::path::Path>>(
+        &self,
+        data: &[u8],
+        width: u32,
+        height: u32,
+        path: P,
+    ) -> Result<()> {
+        self.save_webp_with_quality(data, width, height, path, 80)
+    }
+
+    /// Save captured data as WebP with quality control.
+    ///
+    /// Saves the captured image data to a WebP file with specified quality.
+    /// A quality of 100 selects lossless encoding.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - Raw RGBA image data from a capture result
+    /// * `width` - Width of the image in pixels
+    /// * `height` - Height of the image in pixels
+    /// * `path` - Path where to save the WebP file
+    /// * `quality` - WebP quality level (0-99 lossy, 100 lossless)
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - Failed to create or write to the file
+    /// - Image processing failed
+    /// - WebP support is not enabled (when feature is disabled)
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use grim_rs::Grim;
+    /// use chrono::Local;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut grim = Grim::new()?;
+    /// let result = grim.capture_all()?;
+    ///
+    /// // Generate timestamped filename
+    /// let filename = format!("{}_grim.webp", Local::now().format("%Y%m%d_%Hh%Mm%Ss"));
+    /// grim.save_webp_with_quality(result.data(), result.width(), result.height(), &filename, 100)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn save_webp_with_quality<P: AsRef<std::path::Path>>(
+        &self,
+        data: &[u8],
+        width: u32,
+        height: u32,
+        path: P,
+        quality: u8,
+    ) -> Result<()> {
+        let webp_data = self.to_webp_with_quality(data, width, height, quality)?;
+        std::fs::write(&path, webp_data).map_err(|e| Error::IoWithContext {
+            operation: format!("writing WebP data to file '{}'", path.as_ref().display()),
+            source: e,
+        })?;
+        Ok(())
+    }
+
+    /// Get image data as WebP bytes.
+    ///
+    /// Converts the captured image data to WebP format with default quality (80)
+    /// and returns the bytes.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - Raw RGBA image data from a capture result
+    /// * `width` - Width of the image in pixels
+    /// * `height` - Height of the image in pixels
+    ///
+    /// # Returns
+    ///
+    /// Returns the WebP-encoded image data as a vector of bytes.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - Image processing failed
+    /// - WebP support is not enabled (when feature is disabled)
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use grim_rs::Grim;
+    ///
+    /// let mut grim = Grim::new()?;
+    /// let result = grim.capture_all()?;
+    /// let webp_bytes = grim.to_webp(result.data(), result.width(), result.height())?;
+    /// println!("WebP data size: {} bytes", webp_bytes.len());
+    /// # Ok::<(), grim_rs::Error>(())
+    /// ```
+    pub fn to_webp(&self, data: &[u8], width: u32, height: u32) -> Result<Vec<u8>> {
+        self.to_webp_with_quality(data, width, height, 80)
+    }
+
+    /// Get image data as WebP bytes with quality control.
+    ///
+    /// Converts the captured image data to WebP format with specified quality and
+    /// returns the bytes. The alpha channel is preserved. A quality of 100 selects
+    /// lossless encoding; lower values use lossy encoding.
+    ///
+    /// This function is only available when the `webp` feature is enabled.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - Raw RGBA image data from a capture result
+    /// * `width` - Width of the image in pixels
+    /// * `height` - Height of the image in pixels
+    /// * `quality` - WebP quality level (0-99 lossy, 100 lossless)
+    ///
+    /// # Returns
+    ///
+    /// Returns the WebP-encoded image data as a vector of bytes.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - Image processing failed
+    /// - WebP support is not enabled (when feature is disabled)
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use grim_rs::Grim;
+    ///
+    /// let mut grim = Grim::new()?;
+    /// let result = grim.capture_all()?;
+    /// let webp_bytes = grim.to_webp_with_quality(result.data(), result.width(), result.height(), 90)?;
+    /// println!("WebP data size: {} bytes", webp_bytes.len());
+    /// # Ok::<(), grim_rs::Error>(())
+    /// ```
+    #[cfg(feature = "webp")]
+    pub fn to_webp_with_quality(
+        &self,
+        data: &[u8],
+        width: u32,
+        height: u32,
+        quality: u8,
+    ) -> Result<Vec<u8>> {
+        check_rgba_len(data, width, height)?;
+
+        let lossless = quality >= 100;
+        let encoder = webp::Encoder::from_rgba(data, width, height);
+        let webp_data = encoder
+            .encode_simple(lossless, quality.min(100) as f32)
+            .map_err(|e| {
+                Error::Io(std::io::Error::other(format!(
+                    "WebP encoding error: {:?}",
+                    e
+                )))
+            })?;
+
+        Ok(webp_data.to_vec())
+    }
+
+    /// Get image data as WebP bytes with quality control (stub when feature is disabled).
+    ///
+    /// This stub is used when the `webp` feature is disabled.
+    ///
+    /// # Errors
+    ///
+    /// Always returns an error indicating that WebP support is not enabled.
+    #[cfg(not(feature = "webp"))]
+    pub fn to_webp_with_quality(
+        &self,
+        _data: &[u8],
+        _width: u32,
+        _height: u32,
+        _quality: u8,
+    ) -> Result<Vec<u8>> {
+        Err(Error::ImageProcessing(image::ImageError::Unsupported(
+            image::error::UnsupportedError::from_format_and_kind(
+                image::error::ImageFormatHint::Name("WebP".to_string()),
+                image::error::UnsupportedErrorKind::Format(image::ImageFormat::WebP.into()),
+            ),
+        )))
+    }
+
+    /// Get image data as PNG bytes.
+    ///
+    /// Converts the captured image data to PNG format and returns the bytes.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - Raw RGBA image data from a capture result
+    /// * `width` - Width of the image in pixels
+    /// * `height` - Height of the image in pixels
+    ///
+    /// # Returns
+    ///
+    /// Returns the PNG-encoded image data as a vector of bytes.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - Image processing failed
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use grim_rs::Grim;
+    ///
+    /// let mut grim = Grim::new()?;
+    /// let result = grim.capture_all()?;
+    /// let png_bytes = grim.to_png(result.data(), result.width(), result.height())?;
+    /// println!("PNG data size: {} bytes", png_bytes.len());
+    /// # Ok::<(), grim_rs::Error>(())
+    /// ```
+    pub fn to_png(&self, data: &[u8], width: u32, height: u32) -> Result<Vec<u8>> {
+        self.to_png_with_compression(data, width, height, 6)
+    }
+
+    /// Get image data as PNG bytes with compression level control.
+    ///
+    /// Converts the captured image data to PNG format with specified compression level and returns the bytes.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - Raw RGBA image data from a capture result
+    /// * `width` - Width of the image in pixels
+    /// * `height` - Height of the image in pixels
+    /// * `compression` - PNG compression level (0-9, where 9 is highest compression)
+    ///
+    /// # Returns
+    ///
+    /// Returns the PNG-encoded image data as a vector of bytes.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - Image processing failed
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use grim_rs::Grim;
+    ///
+    /// let mut grim = Grim::new()?;
+    /// let result = grim.capture_all()?;
+    /// let png_bytes = grim.to_png_with_compression(result.data(), result.width(), result.height(), 9)?;
+    /// println!("PNG data size: {} bytes", png_bytes.len());
+    /// # Ok::<(), grim_rs::Error>(())
+    /// ```
+    pub fn to_png_with_compression(
+        &self,
+        data: &[u8],
+        width: u32,
+        height: u32,
+        compression: u8,
+    ) -> Result<Vec<u8>> {
+        self.to_png_with_options(
+            data,
+            width,
+            height,
+            &PngOptions::new().compression(compression),
+        )
+    }
+
+    /// Get image data as PNG bytes with explicit encoder options.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - Raw RGBA image data from a capture result
+    /// * `width` - Width of the image in pixels
+    /// * `height` - Height of the image in pixels
+    /// * `options` - Compression level and row filter
+    ///
+    /// # Returns
+    ///
+    /// Returns the PNG-encoded image data as a vector of bytes.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - Image processing failed
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use grim_rs::{Grim, PngFilter, PngOptions};
+    ///
+    /// let mut grim = Grim::new()?;
+    /// let result = grim.capture_all()?;
+    /// let options = PngOptions::new().filter(PngFilter::Sub);
+    /// let png_bytes = grim.to_png_with_options(result.data(), result.width(), result.height(), &options)?;
+    /// println!("PNG data size: {} bytes", png_bytes.len());
+    /// # Ok::<(), grim_rs::Error>(())
+    /// ```
+    pub fn to_png_with_options(
+        &self,
+        data: &[u8],
+        width: u32,
+        height: u32,
+        options: &PngOptions,
+    ) -> Result<Vec<u8>> {
+        let mut output = Vec::new();
+        write_png(&mut output, data, width, height, options, None)?;
+        Ok(output)
+    }
+
+    /// Get image data as PNG bytes with ancillary metadata chunks.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - Raw RGBA image data from a capture result
+    /// * `width` - Width of the image in pixels
+    /// * `height` - Height of the image in pixels
+    /// * `options` - Compression level and row filter
+    /// * `metadata` - Software, creation time and DPI to embed
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - Image processing failed
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use grim_rs::{Grim, PngMetadata, PngOptions};
+    ///
+    /// let mut grim = Grim::new()?;
+    /// let result = grim.capture_all()?;
+    /// let metadata = PngMetadata::new().software("my-tool").dpi(144.0);
+    /// let png_bytes = grim.to_png_with_metadata(result.data(), result.width(), result.height(), &PngOptions::new(), &metadata)?;
+    /// # Ok::<(), grim_rs::Error>(())
+    /// ```
+    pub fn to_png_with_metadata(
+        &self,
+        data: &[u8],
+        width: u32,
+        height: u32,
+        options: &PngOptions,
+        metadata: &PngMetadata,
+    ) -> Result<Vec<u8>> {
+        let mut output = Vec::new();
+        write_png(&mut output, data, width, height, options, Some(metadata))?;
+        Ok(output)
+    }
+
+    /// Get image data as PNG bytes, compressing row bands on several threads.
+    ///
+    /// The image is split into one band of rows per thread; each band is
+    /// deflate-compressed independently and the pieces are joined into a
+    /// single valid PNG stream. Rows are stored unfiltered, so files are
+    /// slightly larger than with [`to_png_with_compression`](Self::to_png_with_compression),
+    /// but encoding a 3840x2160 frame scales almost linearly with the
+    /// number of cores.
+    ///
+    /// Without the `parallel` feature this falls back to
+    /// [`to_png_with_compression`](Self::to_png_with_compression).
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - Raw RGBA image data from a capture result
+    /// * `width` - Width of the image in pixels
+    /// * `height` - Height of the image in pixels
+    /// * `compression` - PNG compression level (0-9, where 9 is highest compression)
+    /// * `threads` - Number of worker threads, or 0 for one per available CPU
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - Image processing failed
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use grim_rs::Grim;
+    ///
+    /// let mut grim = Grim::new()?;
+    /// let result = grim.capture_all()?;
+    /// let png_bytes = grim.to_png_parallel(result.data(), result.width(), result.height(), 6, 0)?;
+    /// println!("PNG data size: {} bytes", png_bytes.len());
+    /// # Ok::<(), grim_rs::Error>(())
+    /// ```
+    pub fn to_png_parallel(
+        &self,
+        data: &[u8],
+        width: u32,
+        height: u32,
+        compression: u8,
+        threads: usize,
+    ) -> Result<Vec<u8>> {
+        check_rgba_len(data, width, height)?;
+
+        #[cfg(feature = "parallel")]
+        if width > 0 && height > 0 {
+            return parallel_png::encode(data, width, height, compression, threads);
+        }
+
+        #[cfg(not(feature = "parallel"))]
+        let _ = threads;
+        self.to_png_with_compression(data, width, height, compression)
+    }
+
+    /// Get image data as PNG bytes, consuming the RGBA buffer.
+    ///
+    /// Takes ownership of the buffer so it can be dropped as soon as encoding
+    /// finishes, e.g. straight from [`CaptureResult::into_data`]. The buffer
+    /// is wrapped in an `image::RgbaImage` in place rather than copied.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - Raw RGBA image data
+    /// * `width` - Width of the image in pixels
+    /// * `height` - Height of the image in pixels
+    /// * `compression` - PNG compression level (0-9, where 9 is highest compression)
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - Image processing failed
+    pub fn to_png_owned(
+        &self,
+        data: Vec<u8>,
+        width: u32,
+        height: u32,
+        compression: u8,
+    ) -> Result<Vec<u8>> {
+        let image =
+            image::RgbaImage::from_raw(width, height, data).ok_or(Error::ImageProcessing(
+                image::ImageError::Parameter(image::error::ParameterError::from_kind(
+                    image::error::ParameterErrorKind::DimensionMismatch,
+                )),
+            ))?;
+        self.to_png_with_compression(image.as_raw(), width, height, compression)
+    }
+
+    /// Encode a capture result as PNG, consuming it.
+    ///
+    /// The pixel buffer is moved out of `result` with
+    /// [`CaptureResult::into_data`] and handed to the PNG encoder directly,
+    /// so no intermediate copy of the RGBA data is made and the buffer is
+    /// freed as soon as encoding finishes.
+    ///
+    /// For a 3840x2160 capture this keeps peak memory at one ~33 MB RGBA
+    /// buffer plus the compressed output, instead of two RGBA buffers when
+    /// the capture is kept alive and encoded through [`to_png`](Self::to_png).
+    ///
+    /// # Arguments
+    ///
+    /// * `result` - Capture result to encode
+    /// * `compression` - PNG compression level (0-9, where 9 is highest compression)
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - Image processing failed
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use grim_rs::Grim;
+    ///
+    /// let mut grim = Grim::new()?;
+    /// let result = grim.capture_all()?;
+    /// let png_bytes = grim.into_png(result, 6)?;
+    /// println!("PNG data size: {} bytes", png_bytes.len());
+    /// # Ok::<(), grim_rs::Error>(())
+    /// ```
+    pub fn into_png(&self, result: CaptureResult, compression: u8) -> Result<Vec<u8>> {
+        let (width, height) = (result.width, result.height);
+        self.to_png_owned(result.into_data(), width, height, compression)
+    }
+
+    /// Encode image data as PNG directly into `writer`.
+    ///
+    /// Unlike [`to_png_with_compression`](Self::to_png_with_compression), the
+    /// encoded bytes are streamed into `writer` as they are produced instead
+    /// of being collected in a `Vec<u8>` first. Wrap unbuffered targets such
+    /// as files or sockets in a [`std::io::BufWriter`].
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - Raw RGBA image data from a capture result
+    /// * `width` - Width of the image in pixels
+    /// * `height` - Height of the image in pixels
+    /// * `compression` - PNG compression level (0-9, where 9 is highest compression)
+    /// * `writer` - Destination for the encoded bytes
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - Writing to `writer` failed
+    /// - Image processing failed
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use grim_rs::Grim;
+    /// use std::io::BufWriter;
+    ///
+    /// let mut grim = Grim::new()?;
+    /// let result = grim.capture_all()?;
+    /// let file = std::fs::File::create("screenshot.png")?;
+    /// grim.encode_png_into(result.data(), result.width(), result.height(), 6, BufWriter::new(file))?;
+    /// # Ok::<(), grim_rs::Error>(())
+    /// ```
+    pub fn encode_png_into<W: std::io::Write>(
+        &self,
+        data: &[u8],
+        width: u32,
+        height: u32,
+        compression: u8,
+        writer: W,
+    ) -> Result<()> {
+        write_png(
+            writer,
+            data,
+            width,
+            height,
+            &PngOptions::new().compression(compression),
+            None,
+        )
+    }
+
+    /// Save captured data as PPM.
+    ///
+    /// Saves the captured image data to a PPM file.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - Raw RGBA image data from a capture result
+    /// * `width` - Width of the image in pixels
+    /// * `height` - Height of the image in pixels
+    /// * `path` - Path where to save the PPM file
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - Failed to create or write to the file
+    /// - Image processing failed
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use grim_rs::Grim;
+    /// use chrono::Local;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut grim = Grim::new()?;
+    /// let result = grim.capture_all()?;
+    ///
+    /// // Generate timestamped filename
+    /// let filename = format!("{}_grim.ppm", Local::now().format("%Y%m%d_%Hh%Mm%Ss"));
+    /// grim.save_ppm(result.data(), result.width(), result.height(), &filename)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn save_ppm<P: AsRef<std::path::Path>>(
+        &self,
+        data: &[u8],
+        width: u32,
+        height: u32,
+        path: P,
+    ) -> Result<()> {
+        use std::io::{BufWriter, Write};
+
+        check_rgba_len(data, width, height)?;
+        let file = std::fs::File::create(&path).map_err(|e| Error::IoWithContext {
+            operation: format!("creating output file '{}'", path.as_ref().display()),
+            source: e,
+        })?;
+        let mut writer = BufWriter::new(file);
+        self.encode_ppm_into(data, width, height, &mut writer)?;
+        writer.flush().map_err(|e| Error::IoWithContext {
+            operation: format!("writing PPM data to file '{}'", path.as_ref().display()),
+            source: e,
+        })?;
+        Ok(())
+    }
+
+    /// Get image data as PPM bytes.
+    ///
+    /// Converts the captured image data to PPM format and returns the bytes.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - Raw RGBA image data from a capture result
+    /// * `width` - Width of the image in pixels
+    /// * `height` - Height of the image in pixels
+    ///
+    /// # Returns
+    ///
+    /// Returns the PPM-encoded image data as a vector of bytes.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - Image processing failed
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use grim_rs::Grim;
+    ///
+    /// let mut grim = Grim::new()?;
+    /// let result = grim.capture_all()?;
+    /// let ppm_bytes = grim.to_ppm(result.data(), result.width(), result.height())?;
+    /// println!("PPM data size: {} bytes", ppm_bytes.len());
+    /// # Ok::<(), grim_rs::Error>(())
+    /// ```
+    pub fn to_ppm(&self, data: &[u8], width: u32, height: u32) -> Result<Vec<u8>> {
+        let mut ppm_data = Vec::with_capacity(data.len() / 4 * 3 + 32);
+        self.encode_ppm_into(data, width, height, &mut ppm_data)?;
+        Ok(ppm_data)
+    }
+
+    /// Get image data as PPM bytes, consuming the RGBA buffer.
+    ///
+    /// The RGB payload is written into the RGBA buffer in place, so no
+    /// second full-size allocation is made.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - Raw RGBA image data, e.g. from [`CaptureResult::into_data`]
+    /// * `width` - Width of the image in pixels
+    /// * `height` - Height of the image in pixels
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - Image processing failed
+    pub fn to_ppm_owned(&self, mut data: Vec<u8>, width: u32, height: u32) -> Result<Vec<u8>> {
+        check_rgba_len(&data, width, height)?;
+        rgba_to_rgb_in_place(&mut data);
+
+        let header = format!("P6\n{} {}\n255\n", width, height);
+        data.splice(0..0, header.into_bytes());
+        Ok(data)
+    }
+
+    /// Encode image data as binary PPM directly into `writer`.
+    ///
+    /// Pixels are converted to RGB one row at a time, so only a single row
+    /// is buffered regardless of the image size.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - Raw RGBA image data from a capture result
+    /// * `width` - Width of the image in pixels
+    /// * `height` - Height of the image in pixels
+    /// * `writer` - Destination for the encoded bytes
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - Writing to `writer` failed
+    /// - Image processing failed
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use grim_rs::Grim;
+    ///
+    /// let mut grim = Grim::new()?;
+    /// let result = grim.capture_all()?;
+    /// let stdout = std::io::stdout();
+    /// grim.encode_ppm_into(result.data(), result.width(), result.height(), stdout.lock())?;
+    /// # Ok::<(), grim_rs::Error>(())
+    /// ```
+    pub fn encode_ppm_into<W: std::io::Write>(
+        &self,
+        data: &[u8],
+        width: u32,
+        height: u32,
+        mut writer: W,
+    ) -> Result<()> {
+        check_rgba_len(data, width, height)?;
+        write!(writer, "P6\n{} {}\n255\n", width, height)?;
+
+        let mut row = Vec::with_capacity(width as usize * 3);
+        for rgba_row in data.chunks_exact((width as usize * 4).max(1)) {
+            row.clear();
+            for pixel in rgba_row.chunks_exact(4) {
+                row.extend_from_slice(&pixel[..3]);
+            }
+            writer.write_all(&row)?;
+        }
+        Ok(())
+    }
+
+    /// Save captured data as QOI.
+    ///
+    /// Saves the captured image data to a QOI ("Quite OK Image") file. QOI is
+    /// lossless like PNG but encodes and decodes much faster, which makes it a
+    /// good fit for throwaway screenshots in automation loops.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - Raw RGBA image data from a capture result
+    /// * `width` - Width of the image in pixels
+    /// * `height` - Height of the image in pixels
+    /// * `path` - Path where to save the QOI file
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - Failed to create or write to the file
+    /// - Image processing failed
+    /// - QOI support is not enabled (when feature is disabled)
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use grim_rs::Grim;
+    /// use chrono::Local;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut grim = Grim::new()?;
+    /// let result = grim.capture_all()?;
+    ///
+    /// // Generate timestamped filename
+    /// let filename = format!("{}_grim.qoi", Local::now().format("%Y%m%d_%Hh%Mm%Ss"));
+    /// grim.save_qoi(result.data(), result.width(), result.height(), &filename)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn save_qoi<P: AsRef<std::path::Path>>(
+        &self,
+        data: &[u8],
+        width: u32,
+        height: u32,
+        path: P,
+    ) -> Result<()> {
+        let qoi_data = self.to_qoi(data, width, height)?;
+        std::fs::write(&path, qoi_data).map_err(|e| Error::IoWithContext {
+            operation: format!("writing QOI data to file '{}'", path.as_ref().display()),
+            source: e,
+        })?;
+        Ok(())
+    }
+
+    /// Get image data as QOI bytes.
+    ///
+    /// Converts the captured image data to QOI format and returns the bytes.
+    /// The alpha channel is preserved.
+    ///
+    /// This function is only available when the `qoi` feature is enabled.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - Raw RGBA image data from a capture result
+    /// * `width` - Width of the image in pixels
+    /// * `height` - Height of the image in pixels
+    ///
+    /// # Returns
+    ///
+    /// Returns the QOI-encoded image data as a vector of bytes.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - Image processing failed
+    /// - QOI support is not enabled (when feature is disabled)
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use grim_rs::Grim;
+    ///
+    /// let mut grim = Grim::new()?;
+    /// let result = grim.capture_all()?;
+    /// let qoi_bytes = grim.to_qoi(result.data(), result.width(), result.height())?;
+    /// println!("QOI data size: {} bytes", qoi_bytes.len());
+    /// # Ok::<(), grim_rs::Error>(())
+    /// ```
+    #[cfg(feature = "qoi")]
+    pub fn to_qoi(&self, data: &[u8], width: u32, height: u32) -> Result<Vec<u8>> {
+        check_rgba_len(data, width, height)?;
+
+        qoi::encode_to_vec(data, width, height)
+            .map_err(|e| Error::Io(std::io::Error::other(format!("QOI encoding error: {}", e))))
+    }
+
+    /// Get image data as QOI bytes (stub when feature is disabled).
+    ///
+    /// This stub is used when the `qoi` feature is disabled.
+    ///
+    /// # Errors
+    ///
+    /// Always returns an error indicating that QOI support is not enabled.
+    #[cfg(not(feature = "qoi"))]
+    pub fn to_qoi(&self, _data: &[u8], _width: u32, _height: u32) -> Result<Vec<u8>> {
+        Err(Error::ImageProcessing(image::ImageError::Unsupported(
+            image::error::UnsupportedError::from_format_and_kind(
+                image::error::ImageFormatHint::Name("QOI".to_string()),
+                image::error::UnsupportedErrorKind::Format(image::ImageFormat::Qoi.into()),
+            ),
+        )))
+    }
+
+    /// Save captured data as uncompressed TIFF.
+    ///
+    /// Saves the captured image data to a TIFF file with 8-bit RGBA samples.
+    /// Use [`Grim::save_tiff_with_options`] to compress it.
+    ///
+    /// This function is only available when the `tiff` feature is enabled.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - Raw RGBA image data from a capture result
+    /// * `width` - Width of the image in pixels
+    /// * `height` - Height of the image in pixels
+    /// * `path` - Path where to save the TIFF file
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - Failed to create or write to the file
+    /// - Image processing failed
+    /// - TIFF support is not enabled (when feature is disabled)
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use grim_rs::Grim;
+    ///
+    /// let mut grim = Grim::new()?;
+    /// let result = grim.capture_all()?;
+    /// grim.save_tiff(result.data(), result.width(), result.height(), "screenshot.tiff")?;
+    /// # Ok::<(), grim_rs::Error>(())
+    /// ```
+    pub fn save_tiff<P: AsRef<std::path::Path>>(
+        &self,
+        data: &[u8],
+        width: u32,
+        height: u32,
+        path: P,
+    ) -> Result<()> {
+        self.save_tiff_with_options(data, width, height, path, TiffCompression::None)
+    }
+
+    /// Save captured data as TIFF with the given compression.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - Failed to create or write to the file
+    /// - Image processing failed
+    /// - TIFF support is not enabled (when feature is disabled)
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use grim_rs::{Grim, TiffCompression};
+    ///
+    /// let mut grim = Grim::new()?;
+    /// let result = grim.capture_all()?;
+    /// grim.save_tiff_with_options(
+    ///     result.data(),
+    ///     result.width(),
+    ///     result.height(),
+    ///     "screenshot.tiff",
+    ///     TiffCompression::Deflate,
+    /// )?;
+    /// # Ok::<(), grim_rs::Error>(())
+    /// ```
+    pub fn save_tiff_with_options<P: AsRef<std::path::Path>>(
+        &self,
+        data: &[u8],
+        width: u32,
+        height: u32,
+        path: P,
+        compression: TiffCompression,
+    ) -> Result<()> {
+        let tiff_data = self.to_tiff_with_options(data, width, height, compression)?;
+        std::fs::write(&path, tiff_data).map_err(|e| Error::IoWithContext {
+            operation: format!("writing TIFF data to file '{}'", path.as_ref().display()),
+            source: e,
+        })?;
+        Ok(())
+    }
+
+    /// Get image data as uncompressed TIFF bytes.
+    ///
+    /// This function is only available when the `tiff` feature is enabled.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - Image processing failed
+    /// - TIFF support is not enabled (when feature is disabled)
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use grim_rs::Grim;
+    ///
+    /// let mut grim = Grim::new()?;
+    /// let result = grim.capture_all()?;
+    /// let tiff_bytes = grim.to_tiff(result.data(), result.width(), result.height())?;
+    /// println!("TIFF data size: {} bytes", tiff_bytes.len());
+    /// # Ok::<(), grim_rs::Error>(())
+    /// ```
+    pub fn to_tiff(&self, data: &[u8], width: u32, height: u32) -> Result<Vec<u8>> {
+        self.to_tiff_with_options(data, width, height, TiffCompression::None)
+    }
+
+    /// Get image data as TIFF bytes with the given compression.
+    ///
+    /// The image is written as a single 8-bit RGBA page; the alpha channel
+    /// is preserved.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - Image processing failed
+    /// - TIFF support is not enabled (when feature is disabled)
+    #[cfg(feature = "tiff")]
+    pub fn to_tiff_with_options(
+        &self,
+        data: &[u8],
+        width: u32,
+        height: u32,
+        compression: TiffCompression,
+    ) -> Result<Vec<u8>> {
+        use tiff::encoder::{colortype::RGBA8, compression as tiff_compression, TiffEncoder};
+
+        check_rgba_len(data, width, height)?;
+
+        let mut cursor = std::io::Cursor::new(Vec::new());
+        let encoded = TiffEncoder::new(&mut cursor).and_then(|mut encoder| match compression {
+            TiffCompression::None => encoder.write_image_with_compression::<RGBA8, _>(
+                width,
+                height,
+                tiff_compression::Uncompressed,
+                data,
+            ),
+            TiffCompression::Lzw => encoder.write_image_with_compression::<RGBA8, _>(
+                width,
+                height,
+                tiff_compression::Lzw,
+                data,
+            ),
+            TiffCompression::Deflate => encoder.write_image_with_compression::<RGBA8, _>(
+                width,
+                height,
+                tiff_compression::Deflate::default(),
+                data,
+            ),
+        });
+        encoded
+            .map_err(|e| Error::Io(std::io::Error::other(format!("TIFF encoding error: {}", e))))?;
+        Ok(cursor.into_inner())
+    }
+
+    /// Get image data as TIFF bytes (stub when feature is disabled).
+    ///
+    /// This stub is used when the `tiff` feature is disabled.
+    ///
+    /// # Errors
+    ///
+    /// Always returns an error indicating that TIFF support is not enabled.
+    #[cfg(not(feature = "tiff"))]
+    pub fn to_tiff_with_options(
+        &self,
+        _data: &[u8],
+        _width: u32,
+        _height: u32,
+        _compression: TiffCompression,
+    ) -> Result<Vec<u8>> {
+        Err(Error::ImageProcessing(image::ImageError::Unsupported(
+            image::error::UnsupportedError::from_format_and_kind(
+                image::error::ImageFormatHint::Name("TIFF".to_string()),
+                image::error::UnsupportedErrorKind::Format(image::ImageFormat::Tiff.into()),
+            ),
+        )))
+    }
+
+    /// Save captured data as BMP.
+    ///
+    /// Saves the captured image data to an uncompressed 24-bit BMP file.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - Raw RGBA image data from a capture result
+    /// * `width` - Width of the image in pixels
+    /// * `height` - Height of the image in pixels
+    /// * `path` - Path where to save the BMP file
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - Failed to create or write to the file
+    /// - Image processing failed
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use grim_rs::Grim;
+    /// use chrono::Local;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut grim = Grim::new()?;
+    /// let result = grim.capture_all()?;
+    ///
+    /// // Generate timestamped filename
+    /// let filename = format!("{}_grim.bmp", Local::now().format("%Y%m%d_%Hh%Mm%Ss"));
+    /// grim.save_bmp(result.data(), result.width(), result.height(), &filename)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn save_bmp<P: AsRef<std::path::Path>>(
+        &self,
+        data: &[u8],
+        width: u32,
+        height: u32,
+        path: P,
+    ) -> Result<()> {
+        let bmp_data = self.to_bmp(data, width, height)?;
+        std::fs::write(&path, bmp_data).map_err(|e| Error::IoWithContext {
+            operation: format!("writing BMP data to file '{}'", path.as_ref().display()),
+            source: e,
+        })?;
+        Ok(())
+    }
+
+    /// Get image data as BMP bytes.
+    ///
+    /// Converts the captured image data to an uncompressed 24-bit BMP
+    /// (`BITMAPINFOHEADER`) and returns the bytes. Rows are stored bottom-up
+    /// in BGR order and padded to a multiple of 4 bytes. The alpha channel is
+    /// dropped.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - Raw RGBA image data from a capture result
+    /// * `width` - Width of the image in pixels
+    /// * `height` - Height of the image in pixels
+    ///
+    /// # Returns
+    ///
+    /// Returns the BMP-encoded image data as a vector of bytes.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - Image processing failed
+    /// - The image is too large to be described by a BMP header
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use grim_rs::Grim;
+    ///
+    /// let mut grim = Grim::new()?;
+    /// let result = grim.capture_all()?;
+    /// let bmp_bytes = grim.to_bmp(result.data(), result.width(), result.height())?;
+    /// println!("BMP data size: {} bytes", bmp_bytes.len());
+    /// # Ok::<(), grim_rs::Error>(())
+    /// ```
+    pub fn to_bmp(&self, data: &[u8], width: u32, height: u32) -> Result<Vec<u8>> {
+        const FILE_HEADER_SIZE: u32 = 14;
+        const INFO_HEADER_SIZE: u32 = 40;
+        const PIXELS_PER_METER: i32 = 2835; // 72 DPI
+
+        check_rgba_len(data, width, height)?;
+
+        let too_large = || {
+            Error::ImageProcessing(image::ImageError::Limits(
+                image::error::LimitError::from_kind(image::error::LimitErrorKind::DimensionError),
+            ))
+        };
+        let row_size = (width as u64 * 3 + 3) & !3;
+        let image_size = u32::try_from(row_size * height as u64).map_err(|_| too_large())?;
+        let pixel_offset = FILE_HEADER_SIZE + INFO_HEADER_SIZE;
+        let file_size = image_size.checked_add(pixel_offset).ok_or_else(too_large)?;
+        let signed_width = i32::try_from(width).map_err(|_| too_large())?;
+        let signed_height = i32::try_from(height).map_err(|_| too_large())?;
+
+        let mut bmp_data = Vec::with_capacity(file_size as usize);
+
+        // BITMAPFILEHEADER
+        bmp_data.extend_from_slice(b"BM");
+        bmp_data.extend_from_slice(&file_size.to_le_bytes());
+        bmp_data.extend_from_slice(&[0; 4]); // reserved
+        bmp_data.extend_from_slice(&pixel_offset.to_le_bytes());
+
+        // BITMAPINFOHEADER; a positive height means bottom-up row order
+        bmp_data.extend_from_slice(&INFO_HEADER_SIZE.to_le_bytes());
+        bmp_data.extend_from_slice(&signed_width.to_le_bytes());
+        bmp_data.extend_from_slice(&signed_height.to_le_bytes());
+        bmp_data.extend_from_slice(&1u16.to_le_bytes()); // planes
+        bmp_data.extend_from_slice(&24u16.to_le_bytes()); // bits per pixel
+        bmp_data.extend_from_slice(&0u32.to_le_bytes()); // BI_RGB, no compression
+        bmp_data.extend_from_slice(&image_size.to_le_bytes());
+        bmp_data.extend_from_slice(&PIXELS_PER_METER.to_le_bytes());
+        bmp_data.extend_from_slice(&PIXELS_PER_METER.to_le_bytes());
+        bmp_data.extend_from_slice(&0u32.to_le_bytes()); // colors used
+        bmp_data.extend_from_slice(&0u32.to_le_bytes()); // important colors
+
+        let padding = row_size as usize - width as usize * 3;
+        if width > 0 {
+            for row in data.chunks_exact(width as usize * 4).rev() {
+                for pixel in row.chunks_exact(4) {
+                    bmp_data.extend_from_slice(&[pixel[2], pixel[1], pixel[0]]);
+                }
+                bmp_data.resize(bmp_data.len() + padding, 0);
+            }
+        }
+
+        Ok(bmp_data)
+    }
+
+    /// Read region from stdin.
+    ///
+    /// Reads a region specification from standard input in the format "x,y widthxheight".
+    ///
+    /// # Returns
+    ///
+    /// Returns a `Box` representing the region read from stdin.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - Failed to read from stdin
+    /// - The input format is invalid
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use grim_rs::{Grim, Box};
+    ///
+    /// // Parse region from string (same format as stdin would provide)
+    /// let region = "100,100 800x600".parse::<Box>()?;
+    /// println!("Region: {}", region);
+    /// # Ok::<(), grim_rs::Error>(())
+    /// ```
+    pub fn read_region_from_stdin() -> Result<Box> {
+        use std::io::{self, BufRead};
+
+        let stdin = io::stdin();
+        let mut handle = stdin.lock();
+        let mut line = String::new();
+
+        handle.read_line(&mut line)?;
+
+        // Remove newline characters
+        line = line.trim_end().to_string();
+
+        line.parse()
+    }
+
+    /// Write image data to stdout as PNG.
+    ///
+    /// Writes captured image data directly to standard output in PNG format.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - Raw RGBA image data from a capture result
+    /// * `width` - Width of the image in pixels
+    /// * `height` - Height of the image in pixels
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - Failed to write to stdout
+    /// - Image processing failed
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use grim_rs::Grim;
+    ///
+    /// let mut grim = Grim::new()?;
+    /// let result = grim.capture_all()?;
+    /// grim.write_png_to_stdout(result.data(), result.width(), result.height())?;
+    /// # Ok::<(), grim_rs::Error>(())
+    /// ```
+    pub fn write_png_to_stdout(&self, data: &[u8], width: u32, height: u32) -> Result<()> {
+        self.write_png_to_stdout_with_compression(data, width, height, 6)
+    }
+
+    /// Write image data to stdout as PNG with compression level.
+    ///
+    /// Writes captured image data directly to standard output in PNG format with specified compression.
     ///
     /// # Arguments
     ///
     /// * `data` - Raw RGBA image data from a capture result
     /// * `width` - Width of the image in pixels
     /// * `height` - Height of the image in pixels
-    /// * `path` - Path where to save the JPEG file
+    /// * `compression` - PNG compression level (0-9, where 9 is highest compression)
     ///
     /// # Errors
     ///
     /// Returns an error if:
-    /// - Failed to create or write to the file
+    /// - Failed to write to stdout
     /// - Image processing failed
-    /// - JPEG support is not enabled (when feature is disabled)
     ///
     /// # Example
     ///
-    /// ```rust,no_run
+    /// ```rust
     /// use grim_rs::Grim;
-    /// use chrono::Local;
     ///
-    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
     /// let mut grim = Grim::new()?;
     /// let result = grim.capture_all()?;
-    ///
-    /// // Generate timestamped filename
-    /// let filename = format!("{}_grim.jpg", Local::now().format("%Y%m%d_%Hh%Mm%Ss"));
-    /// grim.save_jpeg(result.data(), result.width(), result.height(), &filename)?;
-    /// # Ok(())
-    /// # }
+    /// grim.write_png_to_stdout_with_compression(result.data(), result.width(), result.height(), 6)?;
+    /// # Ok::<(), grim_rs::Error>(())
     /// ```
-    #[cfg(feature = "jpeg")]
-    pub fn save_jpeg<P: AsRef<std::path::Path>>(
+    pub fn write_png_to_stdout_with_compression(
         &self,
         data: &[u8],
         width: u32,
         height: u32,
-        path: P,
+        compression: u8,
     ) -> Result<()> {
-        self.save_jpeg_with_quality(data, width, height, path, 80)
+        use std::io::{BufWriter, Write};
+        let mut writer = BufWriter::new(std::io::stdout().lock());
+        self.encode_png_into(data, width, height, compression, &mut writer)?;
+        writer.flush()?;
+        Ok(())
     }
 
-    /// Save captured data as JPEG with quality control.
+    /// Write image data to stdout as JPEG.
     ///
-    /// Saves the captured image data to a JPEG file with specified quality.
+    /// Writes captured image data directly to standard output in JPEG format.
     ///
-    /// This function is only available when the `jpeg` feature is enabled.
+    /// # Arguments
+    ///
+    /// * `data` - Raw RGBA image data from a capture result
+    /// * `width` - Width of the image in pixels
+    /// * `height` - Height of the image in pixels
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - Failed to write to stdout
+    /// - Image processing failed
+    /// - JPEG support is not enabled
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use grim_rs::Grim;
+    ///
+    /// let mut grim = Grim::new()?;
+    /// let result = grim.capture_all()?;
+    /// grim.write_jpeg_to_stdout(result.data(), result.width(), result.height())?;
+    /// # Ok::<(), grim_rs::Error>(())
+    /// ```
+    #[cfg(feature = "jpeg")]
+    pub fn write_jpeg_to_stdout(&self, data: &[u8], width: u32, height: u32) -> Result<()> {
+        self.write_jpeg_to_stdout_with_quality(data, width, height, 80)
+    }
+
+    /// Write image data to stdout as JPEG with quality control.
+    ///
+    /// Writes captured image data directly to standard output in JPEG format with specified quality.
     ///
     /// # Arguments
     ///
     /// * `data` - Raw RGBA image data from a capture result
     /// * `width` - Width of the image in pixels
     /// * `height` - Height of the image in pixels
-    /// * `path` - Path where to save the JPEG file
     /// * `quality` - JPEG quality level (0-100, where 100 is highest quality)
     ///
     /// # Errors
     ///
     /// Returns an error if:
-    /// - Failed to create or write to the file
+    /// - Failed to write to stdout
     /// - Image processing failed
-    /// - JPEG support is not enabled (when feature is disabled)
+    /// - JPEG support is not enabled
     ///
     /// # Example
     ///
-    /// ```rust,no_run
+    /// ```rust
     /// use grim_rs::Grim;
-    /// use chrono::Local;
     ///
-    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
     /// let mut grim = Grim::new()?;
     /// let result = grim.capture_all()?;
-    ///
-    /// // Generate timestamped filename
-    /// let filename = format!("{}_grim.jpg", Local::now().format("%Y%m%d_%Hh%Mm%Ss"));
-    /// grim.save_jpeg_with_quality(result.data(), result.width(), result.height(), &filename, 90)?;
-    /// # Ok(())
-    /// # }
+    /// grim.write_jpeg_to_stdout_with_quality(result.data(), result.width(), result.height(), 90)?;
+    /// # Ok::<(), grim_rs::Error>(())
     /// ```
     #[cfg(feature = "jpeg")]
-    pub fn save_jpeg_with_quality<P: AsRef<std::path::Path>>(
+    pub fn write_jpeg_to_stdout_with_quality(
         &self,
         data: &[u8],
         width: u32,
         height: u32,
-        path: P,
         quality: u8,
     ) -> Result<()> {
-        use image::{buffer::ConvertBuffer, ImageBuffer, Rgb, Rgba};
-
-        let rgba_img = ImageBuffer::<Rgba<u8>, _>::from_raw(width, height, data.to_vec()).ok_or(
-            Error::ImageProcessing(image::ImageError::Parameter(
-                image::error::ParameterError::from_kind(
-                    image::error::ParameterErrorKind::DimensionMismatch,
-                ),
-            )),
-        )?;
-
-        let rgb_img: ImageBuffer<Rgb<u8>, Vec<u8>> = rgba_img.convert();
-
-        let mut output_file = std::fs::File::create(&path).map_err(|e| Error::IoWithContext {
-            operation: format!("creating output file '{}'", path.as_ref().display()),
-            source: e,
-        })?;
-        let mut _encoder = jpeg_encoder::Encoder::new(&mut output_file, quality);
-        let rgb_data = rgb_img.as_raw();
-
-        _encoder
-            .encode(
-                rgb_data,
-                width as u16,
-                height as u16,
-                jpeg_encoder::ColorType::Rgb,
-            )
-            .map_err(|e| {
-                Error::Io(std::io::Error::new(
-                    std::io::ErrorKind::Other,
-                    format!("JPEG encoding error: {}", e),
-                ))
-            })?;
-
+        use std::io::{BufWriter, Write};
+        let mut writer = BufWriter::new(std::io::stdout().lock());
+        self.encode_jpeg_into(data, width, height, quality, &mut writer)?;
+        writer.flush()?;
         Ok(())
     }
 
-    /// Save captured data as JPEG (stub when feature is disabled).
+    /// Write image data to stdout as PPM.
     ///
-    /// This stub is used when the `jpeg` feature is disabled.
+    /// Writes captured image data directly to standard output in PPM format.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - Raw RGBA image data from a capture result
+    /// * `width` - Width of the image in pixels
+    /// * `height` - Height of the image in pixels
     ///
     /// # Errors
     ///
-    /// Always returns an error indicating that JPEG support is not enabled.
-    #[cfg(not(feature = "jpeg"))]
-    pub fn save_jpeg<P: AsRef<std::path::Path>>(
-        &self,
-        _data: &[u8],
-        _width: u32,
-        _height: u32,
-        _path: P,
-    ) -> Result<()> {
-        Err(Error::ImageProcessing(image::ImageError::Unsupported(
-            image::error::UnsupportedError::from_format_and_kind(
-                image::error::ImageFormatHint::Name("JPEG".to_string()),
-                image::error::UnsupportedErrorKind::Format(image::ImageFormat::Jpeg.into()),
-            ),
-        )))
-    }
-
-    /// Save captured data as JPEG with quality control (stub when feature is disabled).
+    /// Returns an error if:
+    /// - Failed to write to stdout
+    /// - Image processing failed
     ///
-    /// This stub is used when the `jpeg` feature is disabled.
+    /// # Example
     ///
-    /// # Errors
+    /// ```rust
+    /// use grim_rs::Grim;
     ///
-    /// Always returns an error indicating that JPEG support is not enabled.
-    #[cfg(not(feature = "jpeg"))]
-    pub fn save_jpeg_with_quality<P: AsRef<std::path::Path>>(
-        &self,
-        _data: &[u8],
-        _width: u32,
-        _height: u32,
-        _path: P,
-        _quality: u8,
-    ) -> Result<()> {
-        Err(Error::ImageProcessing(image::ImageError::Unsupported(
-            image::error::UnsupportedError::from_format_and_kind(
-                image::error::ImageFormatHint::Name("JPEG".to_string()),
-                image::error::UnsupportedErrorKind::Format(image::ImageFormat::Jpeg.into()),
-            ),
-        )))
+    /// let mut grim = Grim::new()?;
+    /// let result = grim.capture_all()?;
+    /// grim.write_ppm_to_stdout(result.data(), result.width(), result.height())?;
+    /// # Ok::<(), grim_rs::Error>(())
+    /// ```
+    pub fn write_ppm_to_stdout(&self, data: &[u8], width: u32, height: u32) -> Result<()> {
+        use std::io::{BufWriter, Write};
+        let mut writer = BufWriter::new(std::io::stdout().lock());
+        self.encode_ppm_into(data, width, height, &mut writer)?;
+        writer.flush()?;
+        Ok(())
     }
 
-    /// Get image data as JPEG bytes.
-    ///
-    /// Converts the captured image data to JPEG format and returns the bytes.
+    /// Write image data to stdout as BMP.
     ///
-    /// This function is only available when the `jpeg` feature is enabled.
+    /// Writes captured image data directly to standard output in BMP format.
     ///
     /// # Arguments
     ///
@@ -890,15 +5361,11 @@ impl Grim {
     /// * `width` - Width of the image in pixels
     /// * `height` - Height of the image in pixels
     ///
-    /// # Returns
-    ///
-    /// Returns the JPEG-encoded image data as a vector of bytes.
-    ///
     /// # Errors
     ///
     /// Returns an error if:
+    /// - Failed to write to stdout
     /// - Image processing failed
-    /// - JPEG support is not enabled (when feature is disabled)
     ///
     /// # Example
     ///
@@ -907,37 +5374,35 @@ impl Grim {
     ///
     /// let mut grim = Grim::new()?;
     /// let result = grim.capture_all()?;
-    /// let jpeg_bytes = grim.to_jpeg(result.data(), result.width(), result.height())?;
-    /// println!("JPEG data size: {} bytes", jpeg_bytes.len());
+    /// grim.write_bmp_to_stdout(result.data(), result.width(), result.height())?;
     /// # Ok::<(), grim_rs::Error>(())
     /// ```
-    #[cfg(feature = "jpeg")]
-    pub fn to_jpeg(&self, data: &[u8], width: u32, height: u32) -> Result<Vec<u8>> {
-        self.to_jpeg_with_quality(data, width, height, 80)
+    pub fn write_bmp_to_stdout(&self, data: &[u8], width: u32, height: u32) -> Result<()> {
+        let bmp_data = self.to_bmp(data, width, height)?;
+        use std::io::Write;
+        let stdout = std::io::stdout();
+        let mut handle = stdout.lock();
+        handle.write_all(&bmp_data)?;
+        handle.flush()?;
+        Ok(())
     }
 
-    /// Get image data as JPEG bytes with quality control.
-    ///
-    /// Converts the captured image data to JPEG format with specified quality and returns the bytes.
+    /// Write image data to stdout as WebP.
     ///
-    /// This function is only available when the `jpeg` feature is enabled.
+    /// Writes captured image data directly to standard output in WebP format.
     ///
     /// # Arguments
     ///
     /// * `data` - Raw RGBA image data from a capture result
     /// * `width` - Width of the image in pixels
     /// * `height` - Height of the image in pixels
-    /// * `quality` - JPEG quality level (0-100, where 100 is highest quality)
-    ///
-    /// # Returns
-    ///
-    /// Returns the JPEG-encoded image data as a vector of bytes.
     ///
     /// # Errors
     ///
     /// Returns an error if:
+    /// - Failed to write to stdout
     /// - Image processing failed
-    /// - JPEG support is not enabled (when feature is disabled)
+    /// - WebP support is not enabled
     ///
     /// # Example
     ///
@@ -946,623 +5411,998 @@ impl Grim {
     ///
     /// let mut grim = Grim::new()?;
     /// let result = grim.capture_all()?;
-    /// let jpeg_bytes = grim.to_jpeg_with_quality(result.data(), result.width(), result.height(), 90)?;
-    /// println!("JPEG data size: {} bytes", jpeg_bytes.len());
+    /// grim.write_webp_to_stdout(result.data(), result.width(), result.height())?;
     /// # Ok::<(), grim_rs::Error>(())
     /// ```
-    #[cfg(feature = "jpeg")]
-    pub fn to_jpeg_with_quality(
-        &self,
-        data: &[u8],
-        width: u32,
-        height: u32,
-        quality: u8,
-    ) -> Result<Vec<u8>> {
-        use image::{buffer::ConvertBuffer, ImageBuffer, Rgb, Rgba};
-
-        let rgba_img = ImageBuffer::<Rgba<u8>, _>::from_raw(width, height, data.to_vec()).ok_or(
-            Error::ImageProcessing(image::ImageError::Parameter(
-                image::error::ParameterError::from_kind(
-                    image::error::ParameterErrorKind::DimensionMismatch,
-                ),
-            )),
-        )?;
-
-        let rgb_img: ImageBuffer<Rgb<u8>, Vec<u8>> = rgba_img.convert();
-
-        let mut jpeg_data = Vec::new();
-        let mut _encoder = jpeg_encoder::Encoder::new(&mut jpeg_data, quality);
-        let rgb_data = rgb_img.as_raw();
-
-        _encoder
-            .encode(
-                rgb_data,
-                width as u16,
-                height as u16,
-                jpeg_encoder::ColorType::Rgb,
-            )
-            .map_err(|e| {
-                Error::Io(std::io::Error::new(
-                    std::io::ErrorKind::Other,
-                    format!("JPEG encoding error: {}", e),
-                ))
-            })?;
-
-        Ok(jpeg_data)
+    pub fn write_webp_to_stdout(&self, data: &[u8], width: u32, height: u32) -> Result<()> {
+        self.write_webp_to_stdout_with_quality(data, width, height, 80)
     }
 
-    /// Get image data as JPEG bytes (stub when feature is disabled).
+    /// Write image data to stdout as WebP with quality control.
     ///
-    /// This stub is used when the `jpeg` feature is disabled.
+    /// Writes captured image data directly to standard output in WebP format with specified quality.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - Raw RGBA image data from a capture result
+    /// * `width` - Width of the image in pixels
+    /// * `height` - Height of the image in pixels
+    /// * `quality` - WebP quality level (0-99 lossy, 100 lossless)
     ///
     /// # Errors
     ///
-    /// Always returns an error indicating that JPEG support is not enabled.
-    #[cfg(not(feature = "jpeg"))]
-    pub fn to_jpeg(&self, _data: &[u8], _width: u32, _height: u32) -> Result<Vec<u8>> {
-        Err(Error::ImageProcessing(image::ImageError::Unsupported(
-            image::error::UnsupportedError::from_format_and_kind(
-                image::error::ImageFormatHint::Name("JPEG".to_string()),
-                image::error::UnsupportedErrorKind::Format(image::ImageFormat::Jpeg.into()),
-            ),
-        )))
-    }
-
-    /// Get image data as JPEG bytes with quality control (stub when feature is disabled).
+    /// Returns an error if:
+    /// - Failed to write to stdout
+    /// - Image processing failed
+    /// - WebP support is not enabled
     ///
-    /// This stub is used when the `jpeg` feature is disabled.
+    /// # Example
     ///
-    /// # Errors
+    /// ```rust
+    /// use grim_rs::Grim;
     ///
-    /// Always returns an error indicating that JPEG support is not enabled.
-    #[cfg(not(feature = "jpeg"))]
-    pub fn to_jpeg_with_quality(
+    /// let mut grim = Grim::new()?;
+    /// let result = grim.capture_all()?;
+    /// grim.write_webp_to_stdout_with_quality(result.data(), result.width(), result.height(), 90)?;
+    /// # Ok::<(), grim_rs::Error>(())
+    /// ```
+    pub fn write_webp_to_stdout_with_quality(
         &self,
-        _data: &[u8],
-        _width: u32,
-        _height: u32,
-        _quality: u8,
-    ) -> Result<Vec<u8>> {
-        Err(Error::ImageProcessing(image::ImageError::Unsupported(
-            image::error::UnsupportedError::from_format_and_kind(
-                image::error::ImageFormatHint::Name("JPEG".to_string()),
-                image::error::UnsupportedErrorKind::Format(image::ImageFormat::Jpeg.into()),
-            ),
-        )))
+        data: &[u8],
+        width: u32,
+        height: u32,
+        quality: u8,
+    ) -> Result<()> {
+        let webp_data = self.to_webp_with_quality(data, width, height, quality)?;
+        use std::io::Write;
+        let stdout = std::io::stdout();
+        let mut handle = stdout.lock();
+        handle.write_all(&webp_data)?;
+        handle.flush()?;
+        Ok(())
     }
 
-    /// Get image data as PNG bytes.
+    /// Copy image data to the Wayland clipboard.
     ///
-    /// Converts the captured image data to PNG format and returns the bytes.
+    /// The image is encoded as `format` (PNG or JPEG) and offered with the
+    /// matching `image/png` or `image/jpeg` MIME type through
+    /// `zwlr_data_control_v1`. Pastes are answered from a background thread
+    /// with its own Wayland connection until another client sets the
+    /// clipboard. The process must stay alive for the clipboard to stay
+    /// filled: call [`ClipboardSelection::wait`] before exiting.
+    ///
+    /// Requires the `clipboard` feature.
     ///
     /// # Arguments
     ///
     /// * `data` - Raw RGBA image data from a capture result
     /// * `width` - Width of the image in pixels
     /// * `height` - Height of the image in pixels
-    ///
-    /// # Returns
-    ///
-    /// Returns the PNG-encoded image data as a vector of bytes.
+    /// * `format` - `ImageFormat::Png` or `ImageFormat::Jpeg`
     ///
     /// # Errors
     ///
     /// Returns an error if:
+    /// - `format` is not PNG or JPEG, or its encoder is not enabled
     /// - Image processing failed
+    /// - The compositor doesn't support `zwlr_data_control_manager_v1`
     ///
     /// # Example
     ///
-    /// ```rust
+    /// ```rust,no_run
     /// use grim_rs::Grim;
+    /// use image::ImageFormat;
     ///
     /// let mut grim = Grim::new()?;
     /// let result = grim.capture_all()?;
-    /// let png_bytes = grim.to_png(result.data(), result.width(), result.height())?;
-    /// println!("PNG data size: {} bytes", png_bytes.len());
+    /// let selection = grim.copy_to_clipboard(result.data(), result.width(), result.height(), ImageFormat::Png)?;
+    /// selection.wait()?;
     /// # Ok::<(), grim_rs::Error>(())
     /// ```
-    pub fn to_png(&self, data: &[u8], width: u32, height: u32) -> Result<Vec<u8>> {
-        self.to_png_with_compression(data, width, height, 6)
+    #[cfg(feature = "clipboard")]
+    pub fn copy_to_clipboard(
+        &self,
+        data: &[u8],
+        width: u32,
+        height: u32,
+        format: image::ImageFormat,
+    ) -> Result<ClipboardSelection> {
+        let encoded = match format {
+            image::ImageFormat::Jpeg => self.to_jpeg(data, width, height)?,
+            image::ImageFormat::Png => self.to_png(data, width, height)?,
+            _ => return Err(clipboard_unsupported(format)),
+        };
+        self.copy_encoded_to_clipboard(encoded, format)
+    }
+
+    /// Copy an already encoded image to the Wayland clipboard.
+    ///
+    /// Like [`copy_to_clipboard`](Self::copy_to_clipboard), for images
+    /// encoded with other settings, e.g. a JPEG from
+    /// [`to_jpeg_with_options`](Self::to_jpeg_with_options). `encoded` must
+    /// be a complete PNG or JPEG stream matching `format`.
+    ///
+    /// Requires the `clipboard` feature.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `format` is not PNG or JPEG, or the compositor
+    /// doesn't support `zwlr_data_control_manager_v1`.
+    #[cfg(feature = "clipboard")]
+    pub fn copy_encoded_to_clipboard(
+        &self,
+        encoded: Vec<u8>,
+        format: image::ImageFormat,
+    ) -> Result<ClipboardSelection> {
+        let mime_type =
+            clipboard::mime_type(format).ok_or_else(|| clipboard_unsupported(format))?;
+        clipboard::copy(encoded, mime_type)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_geometry_parsing() {
+        let geometry: Box = "100,200 800x600".parse().unwrap();
+        assert_eq!(geometry.x(), 100);
+        assert_eq!(geometry.y(), 200);
+        assert_eq!(geometry.width(), 800);
+        assert_eq!(geometry.height(), 600);
+    }
+
+    #[test]
+    fn test_mock_capture() {
+        let result = std::panic::catch_unwind(|| {
+            let mut grim = Grim::new().unwrap();
+            grim.capture_all()
+        });
+
+        match result {
+            Ok(capture_result) => {
+                if let Ok(capture) = capture_result {
+                    assert_eq!(
+                        capture.data.len(),
+                        (capture.width * capture.height * 4) as usize
+                    );
+                } else {
+                    assert!(matches!(capture_result, Err(Error::NoOutputs)));
+                }
+            }
+            Err(_) => {
+                panic!("Test panicked unexpectedly");
+            }
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "png")]
+    fn test_to_png() {
+        let grim = Grim::new().unwrap();
+        let test_data = vec![255u8; 64];
+        let png_data = grim.to_png(&test_data, 4, 4).unwrap();
+        assert!(!png_data.is_empty());
+    }
+
+    #[test]
+    #[cfg(feature = "jpeg")]
+    fn test_to_jpeg() {
+        let grim = Grim::new().unwrap();
+        let test_data = vec![255u8; 64];
+        let jpeg_data = grim.to_jpeg(&test_data, 4, 4).unwrap();
+        assert!(!jpeg_data.is_empty());
+    }
+
+    #[test]
+    #[cfg(not(feature = "jpeg"))]
+    fn test_jpeg_disabled() {
+        let grim = Grim::new().unwrap();
+        let test_data = vec![255u8; 16];
+        let jpeg_result = grim.to_jpeg(&test_data, 4, 4);
+        assert!(jpeg_result.is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "webp")]
+    fn test_to_webp() {
+        let grim = Grim::new().unwrap();
+        let test_data = vec![255u8; 64];
+        let webp_data = grim.to_webp(&test_data, 4, 4).unwrap();
+        assert_eq!(&webp_data[0..4], b"RIFF");
+        assert_eq!(&webp_data[8..12], b"WEBP");
+    }
+
+    #[test]
+    #[cfg(feature = "webp")]
+    fn test_to_webp_lossless() {
+        let grim = Grim::new().unwrap();
+        let test_data: Vec<u8> = (0..64).map(|i| (i * 4) as u8).collect();
+        let webp_data = grim.to_webp_with_quality(&test_data, 4, 4, 100).unwrap();
+        let decoded = webp::Decoder::new(&webp_data).decode().unwrap();
+        assert_eq!(&*decoded, &test_data[..]);
+    }
+
+    #[test]
+    #[cfg(not(feature = "webp"))]
+    fn test_webp_disabled() {
+        let grim = Grim::new().unwrap();
+        let test_data = vec![255u8; 64];
+        let webp_result = grim.to_webp(&test_data, 4, 4);
+        assert!(matches!(webp_result, Err(Error::ImageProcessing(_))));
+        assert!(grim.save_webp(&test_data, 4, 4, "unused.webp").is_err());
+        assert!(!std::path::Path::new("unused.webp").exists());
+    }
+
+    #[test]
+    #[cfg(feature = "qoi")]
+    fn test_qoi_round_trip() {
+        let grim = Grim::new().unwrap();
+        let test_data: Vec<u8> = (0..8 * 8 * 4).map(|i| (i * 7) as u8).collect();
+        let qoi_data = grim.to_qoi(&test_data, 8, 8).unwrap();
+        let (header, decoded) = qoi::decode_to_vec(&qoi_data).unwrap();
+        assert_eq!((header.width, header.height), (8, 8));
+        assert_eq!(decoded, test_data);
+    }
+
+    #[test]
+    #[cfg(not(feature = "qoi"))]
+    fn test_qoi_disabled() {
+        let grim = Grim::new().unwrap();
+        let test_data = vec![255u8; 64];
+        assert!(grim.to_qoi(&test_data, 4, 4).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "tiff")]
+    fn test_tiff_round_trip() {
+        use tiff::decoder::{Decoder, DecodingResult};
+
+        let grim = Grim::new().unwrap();
+        let test_data: Vec<u8> = (0..8 * 6 * 4).map(|i| (i * 7) as u8).collect();
+        for compression in [
+            TiffCompression::None,
+            TiffCompression::Lzw,
+            TiffCompression::Deflate,
+        ] {
+            let tiff_data = grim
+                .to_tiff_with_options(&test_data, 8, 6, compression)
+                .unwrap();
+            let mut decoder = Decoder::new(std::io::Cursor::new(tiff_data)).unwrap();
+            assert_eq!(decoder.dimensions().unwrap(), (8, 6));
+            match decoder.read_image().unwrap() {
+                DecodingResult::U8(decoded) => assert_eq!(decoded, test_data, "{:?}", compression),
+                _ => panic!("expected 8-bit samples for {:?}", compression),
+            }
+        }
+        assert_eq!(
+            grim.to_tiff(&test_data, 8, 6).unwrap(),
+            grim.to_tiff_with_options(&test_data, 8, 6, TiffCompression::None)
+                .unwrap()
+        );
+    }
+
+    #[test]
+    #[cfg(not(feature = "tiff"))]
+    fn test_tiff_disabled() {
+        let grim = Grim::new().unwrap();
+        let test_data = vec![255u8; 64];
+        assert!(grim.to_tiff(&test_data, 4, 4).is_err());
+        assert!(grim.save_tiff(&test_data, 4, 4, "unused.tiff").is_err());
+        assert!(!std::path::Path::new("unused.tiff").exists());
+    }
+
+    #[test]
+    fn test_ppm_format() {
+        let grim = Grim::new().unwrap();
+        let test_data = vec![255u8; 16];
+        let ppm_result = grim.to_ppm(&test_data, 2, 2);
+        assert!(ppm_result.is_ok());
+        let ppm_data = ppm_result.unwrap();
+        assert!(ppm_data.starts_with(b"P6\n2 2\n255\n"));
+        assert!(ppm_data.len() >= 12);
+    }
+
+    #[test]
+    fn test_encode_into_matches_byte_encoders() {
+        let grim = Grim::new().unwrap();
+        let test_data: Vec<u8> = (0..64).collect();
+
+        let mut png = Vec::new();
+        grim.encode_png_into(&test_data, 4, 4, 6, &mut png).unwrap();
+        assert_eq!(png, grim.to_png(&test_data, 4, 4).unwrap());
+
+        let mut ppm = Vec::new();
+        grim.encode_ppm_into(&test_data, 4, 4, &mut ppm).unwrap();
+        assert_eq!(ppm, grim.to_ppm(&test_data, 4, 4).unwrap());
+        assert_eq!(&ppm[ppm.len() - 3..], &[60, 61, 62]);
+
+        #[cfg(feature = "jpeg")]
+        {
+            let mut jpeg = Vec::new();
+            grim.encode_jpeg_into(&test_data, 4, 4, 80, &mut jpeg)
+                .unwrap();
+            assert!(jpeg.starts_with(&[0xFF, 0xD8]));
+        }
+
+        let mut sink = Vec::new();
+        assert!(grim
+            .encode_ppm_into(&test_data[..60], 4, 4, &mut sink)
+            .is_err());
+    }
+
+    #[test]
+    fn test_png_metadata_chunks() {
+        let grim = Grim::new().unwrap();
+        let test_data = vec![128u8; 4 * 4 * 4];
+        let created = std::time::UNIX_EPOCH + std::time::Duration::from_secs(1_700_000_000);
+        let metadata = PngMetadata::new()
+            .software("grim-rs test")
+            .creation_time(created)
+            .dpi(192.0);
+        let png = grim
+            .to_png_with_metadata(&test_data, 4, 4, &PngOptions::new(), &metadata)
+            .unwrap();
+
+        let reader = png::Decoder::new(std::io::Cursor::new(png))
+            .read_info()
+            .unwrap();
+        let info = reader.info();
+        let text: Vec<(&str, &str)> = info
+            .uncompressed_latin1_text
+            .iter()
+            .map(|chunk| (chunk.keyword.as_str(), chunk.text.as_str()))
+            .collect();
+        assert!(text.contains(&("Software", "grim-rs test")));
+        assert!(text.contains(&("Creation Time", "Tue, 14 Nov 2023 22:13:20 +0000")));
+        let dims = info.pixel_dims.unwrap();
+        assert_eq!((dims.xppu, dims.yppu), (7559, 7559));
+        assert_eq!(dims.unit, png::Unit::Meter);
+    }
+
+    #[test]
+    fn test_png_embed_dpi() {
+        let grim = Grim::new().unwrap();
+        let test_data = vec![128u8; 4 * 4 * 4];
+        let pixel_dims = |png: Vec<u8>| {
+            png::Decoder::new(std::io::Cursor::new(png))
+                .read_info()
+                .unwrap()
+                .info()
+                .pixel_dims
+        };
+
+        let png = grim.to_png(&test_data, 4, 4).unwrap();
+        let dims = pixel_dims(png).unwrap();
+        assert_eq!((dims.xppu, dims.yppu), (3780, 3780));
+
+        let options = PngOptions::new().embed_dpi(false);
+        assert!(!options.dpi_embedded());
+        let metadata = PngMetadata::new().dpi(192.0);
+        let png = grim
+            .to_png_with_metadata(&test_data, 4, 4, &options, &metadata)
+            .unwrap();
+        assert!(pixel_dims(png).is_none());
+    }
+
+    #[test]
+    fn test_to_png_writes_default_dpi() {
+        let grim = Grim::new().unwrap();
+        let png = grim.to_png(&[0u8; 2 * 2 * 4], 2, 2).unwrap();
+
+        // Walk the chunks by hand so the test checks the bytes written, not
+        // the decoder's interpretation of them.
+        let mut pos = 8;
+        let mut phys = None;
+        while pos + 8 <= png.len() {
+            let len = u32::from_be_bytes(png[pos..pos + 4].try_into().unwrap()) as usize;
+            let kind = &png[pos + 4..pos + 8];
+            if kind == b"pHYs" {
+                phys = Some(png[pos + 8..pos + 8 + len].to_vec());
+            }
+            pos += 12 + len;
+        }
+        let phys = phys.expect("to_png should write a pHYs chunk");
+        assert_eq!(phys.len(), 9);
+        assert_eq!(u32::from_be_bytes(phys[0..4].try_into().unwrap()), 3780);
+        assert_eq!(u32::from_be_bytes(phys[4..8].try_into().unwrap()), 3780);
+        assert_eq!(phys[8], 1, "unit should be meters");
+    }
+
+    #[test]
+    fn test_capture_png_metadata_dpi_follows_scale() {
+        assert_eq!(capture_png_metadata(3840, 1920).dpi_value(), Some(192.0));
+        assert_eq!(capture_png_metadata(1920, 1920).dpi_value(), Some(96.0));
+        assert_eq!(capture_png_metadata(100, 0).dpi_value(), Some(96.0));
+    }
+
+    #[test]
+    fn test_encode_into_cursor_decodes() {
+        let grim = Grim::new().unwrap();
+        let test_data: Vec<u8> = (0..64).collect();
+
+        let mut png = std::io::Cursor::new(Vec::new());
+        grim.encode_png_into(&test_data, 4, 4, 9, &mut png).unwrap();
+        let png = png.into_inner();
+        assert!(png.starts_with(b"\x89PNG\r\n\x1a\n"));
+        let decoded = image::load_from_memory(&png).unwrap().to_rgba8();
+        assert_eq!(decoded.into_raw(), test_data);
+
+        let mut ppm = std::io::Cursor::new(Vec::new());
+        grim.encode_ppm_into(&test_data, 4, 4, &mut ppm).unwrap();
+        let ppm = ppm.into_inner();
+        assert!(ppm.starts_with(b"P6\n4 4\n255\n"));
+        assert_eq!(ppm.len(), 11 + 4 * 4 * 3);
+
+        #[cfg(feature = "jpeg")]
+        {
+            let mut jpeg = std::io::Cursor::new(Vec::new());
+            grim.encode_jpeg_into(&test_data, 4, 4, 90, &mut jpeg)
+                .unwrap();
+            let decoded = image::load_from_memory(jpeg.get_ref()).unwrap();
+            assert_eq!((decoded.width(), decoded.height()), (4, 4));
+        }
+    }
+
+    #[test]
+    fn test_bmp_format() {
+        let grim = Grim::new().unwrap();
+        #[rustfmt::skip]
+        let test_data = vec![
+            255, 0, 0, 255,   0, 255, 0, 255,
+            0, 0, 255, 255,   255, 255, 255, 255,
+        ];
+        let bmp_data = grim.to_bmp(&test_data, 2, 2).unwrap();
+
+        assert_eq!(&bmp_data[..2], b"BM");
+        let file_size = u32::from_le_bytes(bmp_data[2..6].try_into().unwrap());
+        assert_eq!(file_size as usize, bmp_data.len());
+        // 2 pixels * 3 bytes padded to 8 bytes per row
+        assert_eq!(bmp_data.len(), 54 + 2 * 8);
+
+        let decoded = image::load_from_memory_with_format(&bmp_data, image::ImageFormat::Bmp)
+            .unwrap()
+            .to_rgba8();
+        assert_eq!(decoded.dimensions(), (2, 2));
+        assert_eq!(decoded.as_raw(), &test_data);
+    }
+
+    #[test]
+    fn test_read_region_from_stdin() {
+        let region_str = "10,20 300x400";
+        let result: std::result::Result<Box, _> = region_str.parse();
+        assert!(result.is_ok());
+        let region = result.unwrap();
+        assert_eq!(region.x(), 10);
+        assert_eq!(region.y(), 20);
+        assert_eq!(region.width(), 300);
+        assert_eq!(region.height(), 400);
+    }
+
+    #[test]
+    fn test_content_hash_detects_changes() {
+        let a = CaptureResult::new(vec![10u8; 16], 2, 2);
+        let b = CaptureResult::new(vec![10u8; 16], 2, 2);
+        let c = CaptureResult::new(vec![11u8; 16], 2, 2);
+        let d = CaptureResult::new(vec![10u8; 16], 4, 1);
+
+        assert_eq!(a.content_hash(), b.content_hash());
+        assert_ne!(a.content_hash(), c.content_hash());
+        assert_ne!(a.content_hash(), d.content_hash());
+    }
+
+    #[test]
+    fn test_into_png_matches_to_png() {
+        let grim = Grim::new().unwrap();
+        let test_data: Vec<u8> = (0..64).map(|i| i as u8).collect();
+        let expected = grim.to_png_with_compression(&test_data, 4, 4, 9).unwrap();
+        let result = CaptureResult::new(test_data.clone(), 4, 4);
+        assert_eq!(grim.into_png(result, 9).unwrap(), expected);
+
+        let expected = grim.to_png(&test_data, 4, 4).unwrap();
+        let result = CaptureResult::new(test_data, 4, 4);
+        assert_eq!(result.into_png(&grim).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_png_options_default_matches_compression_method() {
+        let grim = Grim::new().unwrap();
+        let test_data: Vec<u8> = (0..64).map(|i| i as u8).collect();
+        let expected = grim.to_png_with_compression(&test_data, 4, 4, 6).unwrap();
+        let encoded = grim
+            .to_png_with_options(&test_data, 4, 4, &PngOptions::new())
+            .unwrap();
+        assert_eq!(encoded, expected);
+    }
+
+    #[test]
+    fn test_png_filters_round_trip() {
+        let grim = Grim::new().unwrap();
+        let test_data: Vec<u8> = (0..8 * 8 * 4).map(|i| (i * 3) as u8).collect();
+        for filter in [
+            PngFilter::NoFilter,
+            PngFilter::Sub,
+            PngFilter::Up,
+            PngFilter::Avg,
+            PngFilter::Paeth,
+            PngFilter::Adaptive,
+        ] {
+            let options = PngOptions::new().compression(9).filter(filter);
+            let png_data = grim
+                .to_png_with_options(&test_data, 8, 8, &options)
+                .unwrap();
+            let decoded = image::load_from_memory(&png_data).unwrap().to_rgba8();
+            assert_eq!(decoded.into_raw(), test_data, "filter {:?}", filter);
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "parallel")]
+    fn test_parallel_png_matches_serial() {
+        let grim = Grim::new().unwrap();
+        // 37 rows do not split evenly into any of the thread counts below.
+        let (width, height) = (53, 37);
+        let test_data: Vec<u8> = (0..width * height * 4)
+            .map(|i| (i * 7 % 251) as u8)
+            .collect();
+        let serial = grim
+            .to_png_with_compression(&test_data, width, height, 6)
+            .unwrap();
+        let serial = image::load_from_memory(&serial).unwrap().to_rgba8();
+
+        for threads in [1, 2, 3, 8, 64] {
+            let parallel = grim
+                .to_png_parallel(&test_data, width, height, 6, threads)
+                .unwrap();
+            let dims = png::Decoder::new(std::io::Cursor::new(&parallel))
+                .read_info()
+                .unwrap()
+                .info()
+                .pixel_dims
+                .unwrap();
+            assert_eq!((dims.xppu, dims.unit), (3780, png::Unit::Meter));
+            let decoded = image::load_from_memory(&parallel).unwrap().to_rgba8();
+            assert_eq!(decoded, serial, "threads = {}", threads);
+        }
+    }
+
+    #[test]
+    fn test_rgba_to_rgb_in_place() {
+        let mut data = vec![1, 2, 3, 255, 4, 5, 6, 255];
+        rgba_to_rgb_in_place(&mut data);
+        assert_eq!(data, vec![1, 2, 3, 4, 5, 6]);
+    }
+
+    #[test]
+    #[cfg(feature = "jpeg")]
+    fn test_flatten_rgba_blends_onto_background() {
+        let mut data = vec![200, 100, 0, 128, 10, 20, 30, 255, 9, 9, 9, 0];
+        flatten_rgba_to_rgb_in_place(&mut data, [0, 0, 255]);
+        assert_eq!(data, vec![100, 50, 127, 10, 20, 30, 0, 0, 255]);
+    }
+
+    #[test]
+    #[cfg(feature = "jpeg")]
+    fn test_jpeg_with_options_uses_background() {
+        let grim = Grim::new().unwrap();
+        let transparent = vec![0u8; 8 * 8 * 4];
+        let jpeg = grim
+            .to_jpeg_with_options(&transparent, 8, 8, 100, [255, 255, 255])
+            .unwrap();
+        let decoded = image::load_from_memory(&jpeg).unwrap().to_rgb8();
+        assert!(decoded.pixels().all(|px| px.0.iter().all(|&c| c >= 250)));
+    }
+
+    #[test]
+    fn test_owned_encoders_match_borrowed() {
+        let grim = Grim::new().unwrap();
+        let test_data: Vec<u8> = (0..64).map(|i| i as u8).collect();
+        assert_eq!(
+            grim.to_ppm_owned(test_data.clone(), 4, 4).unwrap(),
+            grim.to_ppm(&test_data, 4, 4).unwrap()
+        );
+        assert_eq!(
+            grim.to_png_owned(test_data.clone(), 4, 4, 6).unwrap(),
+            grim.to_png(&test_data, 4, 4).unwrap()
+        );
+        assert!(grim.to_ppm_owned(vec![0u8; 10], 4, 4).is_err());
     }
 
-    /// Get image data as PNG bytes with compression level control.
-    ///
-    /// Converts the captured image data to PNG format with specified compression level and returns the bytes.
-    ///
-    /// # Arguments
-    ///
-    /// * `data` - Raw RGBA image data from a capture result
-    /// * `width` - Width of the image in pixels
-    /// * `height` - Height of the image in pixels
-    /// * `compression` - PNG compression level (0-9, where 9 is highest compression)
-    ///
-    /// # Returns
-    ///
-    /// Returns the PNG-encoded image data as a vector of bytes.
-    ///
-    /// # Errors
-    ///
-    /// Returns an error if:
-    /// - Image processing failed
-    ///
-    /// # Example
-    ///
-    /// ```rust
-    /// use grim_rs::Grim;
-    ///
-    /// let mut grim = Grim::new()?;
-    /// let result = grim.capture_all()?;
-    /// let png_bytes = grim.to_png_with_compression(result.data(), result.width(), result.height(), 9)?;
-    /// println!("PNG data size: {} bytes", png_bytes.len());
-    /// # Ok::<(), grim_rs::Error>(())
-    /// ```
-    pub fn to_png_with_compression(
-        &self,
-        data: &[u8],
-        width: u32,
-        height: u32,
-        compression: u8,
-    ) -> Result<Vec<u8>> {
-        use image::{ImageBuffer, Rgba};
-        use std::io::Cursor;
+    #[test]
+    fn test_image_round_trip() {
+        let data: Vec<u8> = (0..3 * 2 * 4).map(|i| i as u8).collect();
+        let capture = CaptureResult::new(data.clone(), 3, 2);
 
-        let _img = ImageBuffer::<Rgba<u8>, _>::from_raw(width, height, data.to_vec()).ok_or(
-            Error::ImageProcessing(image::ImageError::Parameter(
-                image::error::ParameterError::from_kind(
-                    image::error::ParameterErrorKind::DimensionMismatch,
-                ),
-            )),
-        )?;
+        let img = capture.to_image().unwrap();
+        assert_eq!((img.width(), img.height()), (3, 2));
 
-        let mut output = Vec::new();
-        {
-            let writer = Cursor::new(&mut output);
-            let mut encoder = png::Encoder::new(writer, width, height);
-
-            let compression_level = match compression {
-                0 => png::Compression::Fast,
-                1..=3 => png::Compression::Best,
-                4..=6 => png::Compression::Default,
-                7..=9 => png::Compression::Best,
-                _ => png::Compression::Default,
-            };
-            encoder.set_compression(compression_level);
-
-            encoder.set_color(png::ColorType::Rgba);
-            encoder.set_filter(png::FilterType::NoFilter);
-
-            let mut writer = encoder.write_header().map_err(|e| {
-                Error::Io(std::io::Error::new(
-                    std::io::ErrorKind::Other,
-                    format!("PNG encoding error: {}", e),
-                ))
-            })?;
+        let back = CaptureResult::from_image(img);
+        assert_eq!(back.width(), 3);
+        assert_eq!(back.height(), 2);
+        assert_eq!(back.data(), &data[..]);
 
-            writer.write_image_data(data).map_err(|e| {
-                Error::Io(std::io::Error::new(
-                    std::io::ErrorKind::Other,
-                    format!("PNG encoding error: {}", e),
-                ))
-            })?;
-            writer.finish().map_err(|e| {
-                Error::Io(std::io::Error::new(
-                    std::io::ErrorKind::Other,
-                    format!("PNG encoding error: {}", e),
-                ))
-            })?;
-        }
+        assert!(CaptureResult::new(vec![0; 5], 3, 2).to_image().is_err());
+    }
 
-        Ok(output)
+    #[test]
+    fn test_capture_options_builder() {
+        let options = CaptureOptions::new();
+        assert!(!options.overlay_cursor_enabled());
+        assert_eq!(options.scale_factor(), None);
+
+        assert_eq!(options.filter_type(), ScaleFilter::Auto);
+        assert_eq!(options.target_region(), None);
+        assert_eq!(options.target_output(), None);
+
+        let options = options.overlay_cursor(true).scale(2.0);
+        assert!(options.overlay_cursor_enabled());
+        assert_eq!(options.scale_factor(), Some(2.0));
     }
 
-    /// Save captured data as PPM.
-    ///
-    /// Saves the captured image data to a PPM file.
-    ///
-    /// # Arguments
-    ///
-    /// * `data` - Raw RGBA image data from a capture result
-    /// * `width` - Width of the image in pixels
-    /// * `height` - Height of the image in pixels
-    /// * `path` - Path where to save the PPM file
-    ///
-    /// # Errors
-    ///
-    /// Returns an error if:
-    /// - Failed to create or write to the file
-    /// - Image processing failed
-    ///
-    /// # Example
-    ///
-    /// ```rust,no_run
-    /// use grim_rs::Grim;
-    /// use chrono::Local;
-    ///
-    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
-    /// let mut grim = Grim::new()?;
-    /// let result = grim.capture_all()?;
-    ///
-    /// // Generate timestamped filename
-    /// let filename = format!("{}_grim.ppm", Local::now().format("%Y%m%d_%Hh%Mm%Ss"));
-    /// grim.save_ppm(result.data(), result.width(), result.height(), &filename)?;
-    /// # Ok(())
-    /// # }
-    /// ```
-    pub fn save_ppm<P: AsRef<std::path::Path>>(
-        &self,
-        data: &[u8],
-        width: u32,
-        height: u32,
-        path: P,
-    ) -> Result<()> {
-        let ppm_data = self.to_ppm(data, width, height)?;
-        std::fs::write(&path, ppm_data).map_err(|e| Error::IoWithContext {
-            operation: format!("writing PPM data to file '{}'", path.as_ref().display()),
-            source: e,
-        })?;
-        Ok(())
+    #[test]
+    fn test_capture_options_combinations() {
+        let region = Box::new(10, 20, 300, 200);
+        let options = CaptureOptions::new()
+            .region(region)
+            .scale(0.5)
+            .overlay_cursor(true)
+            .scale_filter(ScaleFilter::CatmullRom);
+        assert_eq!(options.target_region(), Some(region));
+        assert_eq!(options.target_output(), None);
+        assert_eq!(options.scale_factor(), Some(0.5));
+        assert!(options.overlay_cursor_enabled());
+        assert_eq!(options.filter_type(), ScaleFilter::CatmullRom);
+
+        let options = options.output("DP-1");
+        assert_eq!(options.target_output(), Some("DP-1"));
+        assert_eq!(options.target_region(), Some(region));
     }
 
-    /// Get image data as PPM bytes.
-    ///
-    /// Converts the captured image data to PPM format and returns the bytes.
-    ///
-    /// # Arguments
-    ///
-    /// * `data` - Raw RGBA image data from a capture result
-    /// * `width` - Width of the image in pixels
-    /// * `height` - Height of the image in pixels
-    ///
-    /// # Returns
-    ///
-    /// Returns the PPM-encoded image data as a vector of bytes.
-    ///
-    /// # Errors
-    ///
-    /// Returns an error if:
-    /// - Image processing failed
-    ///
-    /// # Example
-    ///
-    /// ```rust
-    /// use grim_rs::Grim;
-    ///
-    /// let mut grim = Grim::new()?;
-    /// let result = grim.capture_all()?;
-    /// let ppm_bytes = grim.to_ppm(result.data(), result.width(), result.height())?;
-    /// println!("PPM data size: {} bytes", ppm_bytes.len());
-    /// # Ok::<(), grim_rs::Error>(())
-    /// ```
-    pub fn to_ppm(&self, data: &[u8], width: u32, height: u32) -> Result<Vec<u8>> {
-        let header = format!("P6\n{} {}\n255\n", width, height);
-        let mut ppm_data = header.into_bytes();
+    #[test]
+    fn test_capture_options_exclusions() {
+        let options = CaptureOptions::new();
+        assert!(options.exclusions().is_empty());
+        assert_eq!(options.exclusion_fill(), [0, 0, 0, 255]);
 
-        for chunk in data.chunks_exact(4) {
-            ppm_data.push(chunk[0]); // R
-            ppm_data.push(chunk[1]); // G
-            ppm_data.push(chunk[2]); // B
-        }
+        let options = options
+            .exclude(Box::new(0, 0, 10, 10))
+            .exclude(Box::new(-20, 5, 10, 10))
+            .exclusion_color([255, 255, 255, 128]);
+        assert_eq!(
+            options.exclusions(),
+            &[Box::new(0, 0, 10, 10), Box::new(-20, 5, 10, 10)]
+        );
+        assert_eq!(options.exclusion_fill(), [255, 255, 255, 128]);
+    }
 
-        Ok(ppm_data)
+    #[test]
+    fn test_capture_options_background() {
+        assert_eq!(CaptureOptions::new().background_fill(), [0, 0, 0, 0]);
+        let options = CaptureOptions::new().background([40, 40, 40, 255]);
+        assert_eq!(options.background_fill(), [40, 40, 40, 255]);
     }
 
-    /// Read region from stdin.
-    ///
-    /// Reads a region specification from standard input in the format "x,y widthxheight".
-    ///
-    /// # Returns
-    ///
-    /// Returns a `Box` representing the region read from stdin.
-    ///
-    /// # Errors
-    ///
-    /// Returns an error if:
-    /// - Failed to read from stdin
-    /// - The input format is invalid
-    ///
-    /// # Example
-    ///
-    /// ```rust
-    /// use grim_rs::{Grim, Box};
-    ///
-    /// // Parse region from string (same format as stdin would provide)
-    /// let region = "100,100 800x600".parse::<Box>()?;
-    /// println!("Region: {}", region);
-    /// # Ok::<(), grim_rs::Error>(())
-    /// ```
-    pub fn read_region_from_stdin() -> Result<Box> {
-        use std::io::{self, BufRead};
+    #[test]
+    fn test_crop() {
+        let data: Vec<u8> = (0..4 * 3 * 4).map(|i| i as u8).collect();
+        let capture = CaptureResult::new(data, 4, 3);
 
-        let stdin = io::stdin();
-        let mut handle = stdin.lock();
-        let mut line = String::new();
+        let cropped = capture.crop(Box::new(1, 1, 2, 2)).unwrap();
+        assert_eq!(cropped.width(), 2);
+        assert_eq!(cropped.height(), 2);
+        assert_eq!(&cropped.data()[..8], &capture.data()[20..28]);
+        assert_eq!(&cropped.data()[8..], &capture.data()[36..44]);
 
-        handle.read_line(&mut line)?;
+        assert!(matches!(
+            capture.crop(Box::new(3, 0, 2, 1)),
+            Err(Error::InvalidRegion(_))
+        ));
+        assert!(matches!(
+            capture.crop(Box::new(0, 0, 0, 1)),
+            Err(Error::InvalidRegion(_))
+        ));
+    }
 
-        // Remove newline characters
-        line = line.trim_end().to_string();
+    #[test]
+    fn test_resized() {
+        let data: Vec<u8> = (0..2 * 2 * 4).map(|i| (i * 16) as u8).collect();
+        let capture = CaptureResult::new(data, 2, 2);
 
-        line.parse()
+        let doubled = capture.resized(4, 4, ScaleFilter::Nearest).unwrap();
+        assert_eq!((doubled.width(), doubled.height()), (4, 4));
+        assert_eq!(&doubled.data()[..4], &capture.data()[..4]);
+        assert_eq!(&doubled.data()[4..8], &capture.data()[..4]);
+
+        let stretched = capture.resized(5, 1, ScaleFilter::Triangle).unwrap();
+        assert_eq!((stretched.width(), stretched.height()), (5, 1));
+        assert_eq!(stretched.data().len(), 5 * 4);
+
+        assert_eq!(
+            capture.resized(2, 2, ScaleFilter::Lanczos3).unwrap(),
+            capture
+        );
+        assert!(matches!(
+            capture.resized(0, 2, ScaleFilter::Auto),
+            Err(Error::InvalidRegion(_))
+        ));
     }
 
-    /// Write image data to stdout as PNG.
-    ///
-    /// Writes captured image data directly to standard output in PNG format.
-    ///
-    /// # Arguments
-    ///
-    /// * `data` - Raw RGBA image data from a capture result
-    /// * `width` - Width of the image in pixels
-    /// * `height` - Height of the image in pixels
-    ///
-    /// # Errors
-    ///
-    /// Returns an error if:
-    /// - Failed to write to stdout
-    /// - Image processing failed
-    ///
-    /// # Example
-    ///
-    /// ```rust
-    /// use grim_rs::Grim;
-    ///
-    /// let mut grim = Grim::new()?;
-    /// let result = grim.capture_all()?;
-    /// grim.write_png_to_stdout(result.data(), result.width(), result.height())?;
-    /// # Ok::<(), grim_rs::Error>(())
-    /// ```
-    pub fn write_png_to_stdout(&self, data: &[u8], width: u32, height: u32) -> Result<()> {
-        let png_data = self.to_png(data, width, height)?;
-        use std::io::Write;
-        let stdout = std::io::stdout();
-        let mut handle = stdout.lock();
-        handle.write_all(&png_data)?;
-        handle.flush()?;
-        Ok(())
+    #[test]
+    fn test_composite_side_by_side() {
+        let mut captures = std::collections::HashMap::new();
+        captures.insert(
+            "left".to_string(),
+            CaptureResult::new(vec![10; 100 * 100 * 4], 100, 100),
+        );
+        captures.insert(
+            "right".to_string(),
+            CaptureResult::new(vec![20; 100 * 100 * 4], 100, 100),
+        );
+        let result = MultiOutputCaptureResult::new(captures);
+
+        let layout = [
+            ("left".to_string(), Box::new(0, 0, 100, 100)),
+            ("right".to_string(), Box::new(100, 0, 100, 100)),
+        ];
+        let combined = result.composite(&layout).unwrap();
+        assert_eq!((combined.width(), combined.height()), (200, 100));
+        let pixel = |x: usize, y: usize| combined.data()[(y * 200 + x) * 4];
+        assert_eq!([pixel(0, 0), pixel(99, 99)], [10, 10]);
+        assert_eq!([pixel(100, 0), pixel(199, 99)], [20, 20]);
+
+        assert!(matches!(
+            result.composite(&[("missing".to_string(), Box::new(0, 0, 1, 1))]),
+            Err(Error::OutputNotFound(name)) if name == "missing"
+        ));
     }
 
-    /// Write image data to stdout as PNG with compression level.
-    ///
-    /// Writes captured image data directly to standard output in PNG format with specified compression.
-    ///
-    /// # Arguments
-    ///
-    /// * `data` - Raw RGBA image data from a capture result
-    /// * `width` - Width of the image in pixels
-    /// * `height` - Height of the image in pixels
-    /// * `compression` - PNG compression level (0-9, where 9 is highest compression)
-    ///
-    /// # Errors
-    ///
-    /// Returns an error if:
-    /// - Failed to write to stdout
-    /// - Image processing failed
-    ///
-    /// # Example
-    ///
-    /// ```rust
-    /// use grim_rs::Grim;
-    ///
-    /// let mut grim = Grim::new()?;
-    /// let result = grim.capture_all()?;
-    /// grim.write_png_to_stdout_with_compression(result.data(), result.width(), result.height(), 6)?;
-    /// # Ok::<(), grim_rs::Error>(())
-    /// ```
-    pub fn write_png_to_stdout_with_compression(
-        &self,
-        data: &[u8],
-        width: u32,
-        height: u32,
-        compression: u8,
-    ) -> Result<()> {
-        let png_data = self.to_png_with_compression(data, width, height, compression)?;
-        use std::io::Write;
-        let stdout = std::io::stdout();
-        let mut handle = stdout.lock();
-        handle.write_all(&png_data)?;
-        handle.flush()?;
-        Ok(())
+    #[test]
+    fn test_crop_corners() {
+        let data: Vec<u8> = (0..4 * 3 * 4).map(|i| i as u8).collect();
+        let capture = CaptureResult::new(data, 4, 3);
+
+        let top_left = capture.crop(Box::new(0, 0, 1, 1)).unwrap();
+        assert_eq!(top_left.data(), &capture.data()[..4]);
+
+        let bottom_right = capture.crop(Box::new(2, 1, 2, 2)).unwrap();
+        assert_eq!((bottom_right.width(), bottom_right.height()), (2, 2));
+        assert_eq!(&bottom_right.data()[..8], &capture.data()[24..32]);
+        assert_eq!(&bottom_right.data()[8..], &capture.data()[40..48]);
+
+        let whole = capture.crop(Box::new(0, 0, 4, 3)).unwrap();
+        assert_eq!(whole, capture);
     }
 
-    /// Write image data to stdout as JPEG.
-    ///
-    /// Writes captured image data directly to standard output in JPEG format.
-    ///
-    /// # Arguments
-    ///
-    /// * `data` - Raw RGBA image data from a capture result
-    /// * `width` - Width of the image in pixels
-    /// * `height` - Height of the image in pixels
-    ///
-    /// # Errors
-    ///
-    /// Returns an error if:
-    /// - Failed to write to stdout
-    /// - Image processing failed
-    /// - JPEG support is not enabled
-    ///
-    /// # Example
-    ///
-    /// ```rust
-    /// use grim_rs::Grim;
-    ///
-    /// let mut grim = Grim::new()?;
-    /// let result = grim.capture_all()?;
-    /// grim.write_jpeg_to_stdout(result.data(), result.width(), result.height())?;
-    /// # Ok::<(), grim_rs::Error>(())
-    /// ```
-    #[cfg(feature = "jpeg")]
-    pub fn write_jpeg_to_stdout(&self, data: &[u8], width: u32, height: u32) -> Result<()> {
-        self.write_jpeg_to_stdout_with_quality(data, width, height, 80)
+    #[test]
+    fn test_capture_timestamp() {
+        let mut capture = CaptureResult::new(vec![0; 4 * 2 * 4], 4, 2);
+        assert_eq!(capture.timestamp(), None);
+
+        capture.timestamp = Some(std::time::Duration::from_millis(1500));
+        let cropped = capture.crop(Box::new(1, 0, 2, 2)).unwrap();
+        assert_eq!(cropped.timestamp(), capture.timestamp());
+        assert_eq!(
+            cropped.content_hash(),
+            CaptureResult::new(vec![0; 16], 2, 2).content_hash()
+        );
+
+        // The timestamp doesn't take part in equality or hashing.
+        let untimed = CaptureResult::new(vec![0; 16], 2, 2);
+        assert_eq!(cropped, untimed);
+        let set: std::collections::HashSet<_> = [cropped, untimed].into_iter().collect();
+        assert_eq!(set.len(), 1);
     }
 
-    /// Write image data to stdout as JPEG with quality control.
-    ///
-    /// Writes captured image data directly to standard output in JPEG format with specified quality.
-    ///
-    /// # Arguments
-    ///
-    /// * `data` - Raw RGBA image data from a capture result
-    /// * `width` - Width of the image in pixels
-    /// * `height` - Height of the image in pixels
-    /// * `quality` - JPEG quality level (0-100, where 100 is highest quality)
-    ///
-    /// # Errors
-    ///
-    /// Returns an error if:
-    /// - Failed to write to stdout
-    /// - Image processing failed
-    /// - JPEG support is not enabled
-    ///
-    /// # Example
-    ///
-    /// ```rust
-    /// use grim_rs::Grim;
-    ///
-    /// let mut grim = Grim::new()?;
-    /// let result = grim.capture_all()?;
-    /// grim.write_jpeg_to_stdout_with_quality(result.data(), result.width(), result.height(), 90)?;
-    /// # Ok::<(), grim_rs::Error>(())
-    /// ```
-    #[cfg(feature = "jpeg")]
-    pub fn write_jpeg_to_stdout_with_quality(
-        &self,
-        data: &[u8],
-        width: u32,
-        height: u32,
-        quality: u8,
-    ) -> Result<()> {
-        let jpeg_data = self.to_jpeg_with_quality(data, width, height, quality)?;
-        use std::io::Write;
-        let stdout = std::io::stdout();
-        let mut handle = stdout.lock();
-        handle.write_all(&jpeg_data)?;
-        handle.flush()?;
-        Ok(())
+    #[test]
+    fn test_diff() {
+        let a = CaptureResult::new(vec![0, 100, 200, 255, 50, 50, 50, 255], 2, 1);
+        let b = CaptureResult::new(vec![10, 90, 200, 255, 50, 50, 50, 0], 2, 1);
+        let diff = a.diff(&b).unwrap();
+        assert_eq!((diff.width(), diff.height()), (2, 1));
+        assert_eq!(diff.data(), &[10, 10, 0, 0, 0, 0, 0, 255]);
+        assert!(a.diff(&a).unwrap().data().iter().all(|&v| v == 0));
+
+        let other_size = CaptureResult::new(vec![0; 8], 1, 2);
+        assert!(matches!(a.diff(&other_size), Err(Error::InvalidRegion(_))));
+        assert!(matches!(
+            a.diff_stats(&other_size),
+            Err(Error::InvalidRegion(_))
+        ));
     }
 
-    /// Write image data to stdout as PPM.
-    ///
-    /// Writes captured image data directly to standard output in PPM format.
-    ///
-    /// # Arguments
-    ///
-    /// * `data` - Raw RGBA image data from a capture result
-    /// * `width` - Width of the image in pixels
-    /// * `height` - Height of the image in pixels
-    ///
-    /// # Errors
-    ///
-    /// Returns an error if:
-    /// - Failed to write to stdout
-    /// - Image processing failed
-    ///
-    /// # Example
-    ///
-    /// ```rust
-    /// use grim_rs::Grim;
-    ///
-    /// let mut grim = Grim::new()?;
-    /// let result = grim.capture_all()?;
-    /// grim.write_ppm_to_stdout(result.data(), result.width(), result.height())?;
-    /// # Ok::<(), grim_rs::Error>(())
-    /// ```
-    pub fn write_ppm_to_stdout(&self, data: &[u8], width: u32, height: u32) -> Result<()> {
-        let ppm_data = self.to_ppm(data, width, height)?;
-        use std::io::Write;
-        let stdout = std::io::stdout();
-        let mut handle = stdout.lock();
-        handle.write_all(&ppm_data)?;
-        handle.flush()?;
-        Ok(())
+    #[test]
+    fn test_diff_stats() {
+        let a = CaptureResult::new(vec![0; 4 * 4], 2, 2);
+        let mut data = vec![0; 4 * 4];
+        data[0] = 8;
+        data[1] = 4;
+        data[15] = 20;
+        let b = CaptureResult::new(data, 2, 2);
+
+        let stats = a.diff_stats(&b).unwrap();
+        assert_eq!(stats.changed_pixels(), 2);
+        assert_eq!(stats.max_delta(), 20);
+        assert!((stats.mean_delta() - 32.0 / 16.0).abs() < f64::EPSILON);
+
+        let same = a.diff_stats(&a).unwrap();
+        assert_eq!(same, DiffStats::default());
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    #[test]
+    fn test_blend_cursor_clips_at_edges() {
+        // 3x3 cursor: opaque red with a half-transparent white center.
+        let mut sprite = [255, 0, 0, 255].repeat(9);
+        sprite[16..20].copy_from_slice(&[255, 255, 255, 128]);
+        let cursor = CursorImage::new(CaptureResult::new(sprite, 3, 3), 1, 1);
+        let (width, height) = (4u32, 4u32);
+        let pixel = |data: &[u8], x: u32, y: u32| {
+            let i = ((y * width + x) * 4) as usize;
+            data[i..i + 4].to_vec()
+        };
+
+        // Hotspot on the top-left pixel: only the bottom-right 2x2 is drawn.
+        let mut data = [0, 0, 0, 255].repeat(16);
+        blend_cursor(&mut data, width, height, &cursor, 0, 0).unwrap();
+        assert_eq!(pixel(&data, 0, 0), vec![128, 128, 128, 255]);
+        assert_eq!(pixel(&data, 1, 1), vec![255, 0, 0, 255]);
+        assert_eq!(pixel(&data, 2, 0), vec![0, 0, 0, 255]);
+        assert_eq!(pixel(&data, 0, 2), vec![0, 0, 0, 255]);
+
+        // Past the bottom-right corner: only the top-left pixel is drawn.
+        let mut data = [0, 0, 0, 255].repeat(16);
+        blend_cursor(&mut data, width, height, &cursor, 4, 4).unwrap();
+        assert_eq!(pixel(&data, 3, 3), vec![255, 0, 0, 255]);
+        assert_eq!(data.iter().filter(|&&v| v == 255).count(), 16 + 1);
+
+        // Entirely off-canvas leaves the image alone.
+        let mut data = [0, 0, 0, 255].repeat(16);
+        blend_cursor(&mut data, width, height, &cursor, -5, 1).unwrap();
+        assert_eq!(data, [0, 0, 0, 255].repeat(16));
+
+        assert!(blend_cursor(&mut data, 5, 4, &cursor, 0, 0).is_err());
+    }
 
     #[test]
-    fn test_geometry_parsing() {
-        let geometry: Box = "100,200 800x600".parse().unwrap();
-        assert_eq!(geometry.x(), 100);
-        assert_eq!(geometry.y(), 200);
-        assert_eq!(geometry.width(), 800);
-        assert_eq!(geometry.height(), 600);
+    fn test_capture_options_software_cursor() {
+        let cursor = CursorImage::new(CaptureResult::new(vec![0; 4], 1, 1), 0, 0);
+        assert!(CaptureOptions::new().cursor_image().is_none());
+        let options = CaptureOptions::new().software_cursor(cursor.clone());
+        assert_eq!(options.cursor_image(), Some(&cursor));
     }
 
     #[test]
-    fn test_mock_capture() {
-        let result = std::panic::catch_unwind(|| {
-            let mut grim = Grim::new().unwrap();
-            grim.capture_all()
-        });
+    fn test_crop_out_of_bounds() {
+        let capture = CaptureResult::new(vec![0; 4 * 3 * 4], 4, 3);
+        for region in [
+            Box::new(-1, 0, 2, 2),
+            Box::new(0, -1, 2, 2),
+            Box::new(0, 2, 4, 2),
+            Box::new(4, 0, 1, 1),
+        ] {
+            assert!(
+                matches!(capture.crop(region), Err(Error::InvalidRegion(_))),
+                "{} should be rejected",
+                region
+            );
+        }
+    }
 
-        match result {
-            Ok(capture_result) => {
-                if let Ok(capture) = capture_result {
-                    assert_eq!(
-                        capture.data.len(),
-                        (capture.width * capture.height * 4) as usize
-                    );
-                } else {
-                    assert!(matches!(capture_result, Err(Error::NoOutputs)));
-                }
-            }
-            Err(_) => {
-                panic!("Test panicked unexpectedly");
+    #[test]
+    fn test_pixelate_clamps_region() {
+        let mut capture = CaptureResult::new([40, 80, 120, 255].repeat(6 * 4), 6, 4);
+        let original = capture.data().to_vec();
+        capture.pixelate(Box::new(-3, 2, 20, 20), 4);
+        assert_eq!(capture.data(), original.as_slice());
+
+        capture.pixelate(Box::new(10, 10, 5, 5), 4);
+        capture.pixelate(Box::new(0, 0, 6, 4), 0);
+        assert_eq!(capture.data(), original.as_slice());
+    }
+
+    #[test]
+    fn test_blur_smooths_edge() {
+        // Left half black, right half white.
+        let mut data = Vec::new();
+        for _ in 0..8 {
+            for x in 0..8 {
+                let value = if x < 4 { 0 } else { 255 };
+                data.extend_from_slice(&[value, value, value, 255]);
             }
         }
+        let mut capture = CaptureResult::new(data, 8, 8);
+        capture.blur(Box::new(0, -2, 100, 6), 1.5);
+
+        let pixel = |x: usize, y: usize| capture.data()[(y * 8 + x) * 4];
+        assert!(pixel(3, 0) > 0 && pixel(4, 0) < 255);
+        assert!(pixel(3, 0) < pixel(4, 0));
+        assert_eq!(pixel(3, 5), 0);
+        assert_eq!(pixel(4, 5), 255);
     }
 
     #[test]
-    #[cfg(feature = "png")]
-    fn test_to_png() {
-        let grim = Grim::new().unwrap();
-        let test_data = vec![255u8; 64];
-        let png_data = grim.to_png(&test_data, 4, 4).unwrap();
-        assert!(!png_data.is_empty());
+    fn test_fill_rect_opaque_overwrites() {
+        let mut capture = CaptureResult::new([10, 20, 30, 255].repeat(4 * 4), 4, 4);
+        capture.fill_rect(Box::new(2, -1, 5, 2), [200, 100, 0, 255]);
+
+        let pixel = |x: usize, y: usize| capture.data()[(y * 4 + x) * 4..][..4].to_vec();
+        assert_eq!(pixel(2, 0), vec![200, 100, 0, 255]);
+        assert_eq!(pixel(3, 0), vec![200, 100, 0, 255]);
+        assert_eq!(pixel(1, 0), vec![10, 20, 30, 255]);
+        assert_eq!(pixel(2, 1), vec![10, 20, 30, 255]);
     }
 
     #[test]
-    #[cfg(feature = "jpeg")]
-    fn test_to_jpeg() {
-        let grim = Grim::new().unwrap();
-        let test_data = vec![255u8; 64];
-        let jpeg_data = grim.to_jpeg(&test_data, 4, 4).unwrap();
-        assert!(!jpeg_data.is_empty());
+    fn test_fill_rect_half_alpha_blends() {
+        let mut capture = CaptureResult::new([0, 0, 0, 255].repeat(2 * 2), 2, 2);
+        capture.fill_rect(Box::new(0, 0, 2, 2), [255, 255, 255, 128]);
+        assert_eq!(capture.data(), [128, 128, 128, 255].repeat(4).as_slice());
+
+        capture.fill_rect(Box::new(0, 0, 2, 2), [255, 0, 0, 0]);
+        assert_eq!(capture.data(), [128, 128, 128, 255].repeat(4).as_slice());
     }
 
     #[test]
-    #[cfg(not(feature = "jpeg"))]
-    fn test_jpeg_disabled() {
-        let grim = Grim::new().unwrap();
-        let test_data = vec![255u8; 16];
-        let jpeg_result = grim.to_jpeg(&test_data, 4, 4);
-        assert!(jpeg_result.is_err());
+    fn test_draw_rect_border() {
+        let mut capture = CaptureResult::new(vec![0; 6 * 6 * 4], 6, 6);
+        capture.draw_rect_border(Box::new(1, 1, 4, 4), 1, [255, 255, 255, 128]);
+
+        let alpha = |x: usize, y: usize| capture.data()[(y * 6 + x) * 4 + 3];
+        // Every border pixel is blended exactly once, corners included.
+        for (x, y) in [(1, 1), (4, 1), (1, 4), (4, 4), (2, 1), (1, 3)] {
+            assert_eq!(alpha(x, y), 128, "({}, {})", x, y);
+        }
+        for (x, y) in [(0, 0), (2, 2), (3, 3), (5, 5)] {
+            assert_eq!(alpha(x, y), 0, "({}, {})", x, y);
+        }
+
+        capture.draw_rect_border(Box::new(0, 0, 6, 6), 3, [9, 9, 9, 255]);
+        assert_eq!(capture.data(), [9, 9, 9, 255].repeat(36).as_slice());
     }
 
     #[test]
-    fn test_ppm_format() {
-        let grim = Grim::new().unwrap();
-        let test_data = vec![255u8; 16];
-        let ppm_result = grim.to_ppm(&test_data, 2, 2);
-        assert!(ppm_result.is_ok());
-        let ppm_data = ppm_result.unwrap();
-        assert!(ppm_data.starts_with(b"P6\n2 2\n255\n"));
-        assert!(ppm_data.len() >= 12);
+    fn test_wait_for_stable_frame_returns_first_repeat() {
+        let frames = [1u8, 2, 3, 3, 4];
+        let mut calls = 0;
+        let result = wait_for_stable_frame(
+            || {
+                let value = frames[calls];
+                calls += 1;
+                Ok(CaptureResult::new(vec![value; 4], 1, 1))
+            },
+            std::time::Duration::from_secs(5),
+            std::time::Duration::ZERO,
+        )
+        .unwrap();
+
+        assert_eq!(result.data(), &[3, 3, 3, 3]);
+        assert_eq!(calls, 4);
     }
 
     #[test]
-    fn test_read_region_from_stdin() {
-        let region_str = "10,20 300x400";
-        let result: std::result::Result<Box, _> = region_str.parse();
-        assert!(result.is_ok());
-        let region = result.unwrap();
-        assert_eq!(region.x(), 10);
-        assert_eq!(region.y(), 20);
-        assert_eq!(region.width(), 300);
-        assert_eq!(region.height(), 400);
+    fn test_wait_for_stable_frame_returns_last_frame_on_timeout() {
+        let mut counter = 0u8;
+        let result = wait_for_stable_frame(
+            || {
+                counter = counter.wrapping_add(1);
+                Ok(CaptureResult::new(vec![counter; 4], 1, 1))
+            },
+            std::time::Duration::from_millis(20),
+            std::time::Duration::from_millis(5),
+        )
+        .unwrap();
+
+        assert_eq!(result.data(), &[counter; 4]);
+    }
+
+    #[test]
+    fn test_wait_for_stable_frame_propagates_errors() {
+        let result = wait_for_stable_frame(
+            || Err(Error::CaptureFailed),
+            std::time::Duration::from_secs(1),
+            std::time::Duration::ZERO,
+        );
+        assert!(matches!(result, Err(Error::CaptureFailed)));
     }
 
     #[test]
@@ -1575,4 +6415,215 @@ mod tests {
             Err(e) => panic!("Unexpected error: {:?}", e),
         }
     }
+
+    #[test]
+    fn test_capture_result_scale() {
+        let data: Vec<u8> = (0..16).collect();
+        let capture = CaptureResult::new(data, 2, 2);
+
+        let doubled = capture.scale(2.0).unwrap();
+        assert_eq!((doubled.width(), doubled.height()), (4, 4));
+        assert_eq!(
+            &doubled.data()[..16],
+            &[0, 1, 2, 3, 0, 1, 2, 3, 4, 5, 6, 7, 4, 5, 6, 7]
+        );
+
+        let half = capture.scale(0.5).unwrap();
+        assert_eq!((half.width(), half.height()), (1, 1));
+        assert!(matches!(capture.scale(0.1), Err(Error::InvalidRegion(_))));
+    }
+
+    #[test]
+    fn test_capture_result_scale_to() {
+        let capture = CaptureResult::new(vec![200; 4 * 4 * 4], 4, 4);
+        let resized = capture.scale_to(3, 2).unwrap();
+        assert_eq!((resized.width(), resized.height()), (3, 2));
+        assert!(resized.data().iter().all(|&b| b == 200));
+
+        assert!(matches!(
+            capture.scale_to(0, 2),
+            Err(Error::InvalidRegion(_))
+        ));
+        let bad = CaptureResult::new(vec![0; 3], 4, 4);
+        assert!(matches!(bad.scale_to(2, 2), Err(Error::ImageProcessing(_))));
+    }
+
+    #[test]
+    fn test_capture_result_histograms() {
+        let capture = CaptureResult::new(
+            vec![255, 0, 0, 255, 0, 255, 0, 128, 255, 255, 255, 255],
+            3,
+            1,
+        );
+        let luma = capture.histogram();
+        assert_eq!(luma.iter().sum::<u32>(), 3);
+        assert_eq!(luma[77], 1);
+        assert_eq!(luma[149], 1);
+        assert_eq!(luma[255], 1);
+
+        let (red, green, blue, alpha) = capture.channel_histograms();
+        assert_eq!((red[255], red[0]), (2, 1));
+        assert_eq!((green[255], green[0]), (2, 1));
+        assert_eq!((blue[255], blue[0]), (1, 2));
+        assert_eq!((alpha[255], alpha[128]), (2, 1));
+    }
+
+    /// A scale-1 output called `name` covering `geometry`.
+    fn output(name: &str, geometry: Box) -> Output {
+        Output {
+            name: name.to_string(),
+            geometry,
+            scale: 1,
+            description: None,
+            refresh_rate_mhz: None,
+            transform: OutputTransform::Normal,
+            physical_size_mm: None,
+            make: String::new(),
+            model: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_output_at_point() {
+        let outputs = [
+            output("left", Box::new(-1920, 0, 1920, 1080)),
+            output("right", Box::new(0, 0, 2560, 1440)),
+        ];
+        let name_at = |x, y| output_at(&outputs, x, y).map(|output| output.name());
+
+        assert_eq!(name_at(-0.5, 10.0), Some("left"));
+        assert_eq!(name_at(0.0, 0.0), Some("right"));
+        assert_eq!(name_at(2559.9, 1439.9), Some("right"));
+        assert_eq!(name_at(-100.0, 1200.0), None);
+        assert_eq!(name_at(2560.0, 0.0), None);
+    }
+
+    #[test]
+    fn test_output_contains_point_and_box() {
+        let dp = output("DP-1", Box::new(1920, 0, 2560, 1440));
+
+        assert!(dp.contains_point(1920, 0));
+        assert!(dp.contains_point(4479, 1439));
+        assert!(!dp.contains_point(1919, 0));
+        assert!(!dp.contains_point(4480, 100));
+
+        // Points at the end of the coordinate space must not overflow.
+        let edge = output("edge", Box::new(i32::MAX - 10, i32::MAX - 10, 10, 10));
+        assert!(edge.contains_point(i32::MAX - 1, i32::MAX - 1));
+        assert!(!edge.contains_point(i32::MAX, i32::MAX));
+        assert!(!dp.contains_point(i32::MAX, i32::MAX));
+        assert!(!output("empty", Box::new(0, 0, 0, 0)).contains_point(0, 0));
+
+        assert!(dp.contains_box(&Box::new(1920, 0, 2560, 1440)));
+        assert!(dp.contains_box(&Box::new(2000, 100, 800, 600)));
+        // Straddles the left edge.
+        assert!(!dp.contains_box(&Box::new(1900, 100, 800, 600)));
+        assert!(!dp.contains_box(&Box::new(2000, 100, 0, 600)));
+    }
+
+    #[test]
+    fn test_cursor_sprite_isolates_changed_pixels() {
+        // 4x3 grey screen; the "cursor" changes (1,1) and (2,2).
+        let without = CaptureResult::new(vec![50; 4 * 3 * 4], 4, 3);
+        let mut with = without.clone();
+        with.data[(4 + 1) * 4..(4 + 1) * 4 + 4].copy_from_slice(&[255, 255, 255, 255]);
+        with.data[(2 * 4 + 2) * 4..(2 * 4 + 2) * 4 + 4].copy_from_slice(&[0, 0, 0, 255]);
+
+        let (sprite, x, y) = cursor_sprite(&without, &with).unwrap();
+        assert_eq!((x, y), (1, 1));
+        assert_eq!((sprite.width(), sprite.height()), (2, 2));
+        assert_eq!(
+            sprite.data(),
+            &[255, 255, 255, 255, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 255]
+        );
+
+        assert!(cursor_sprite(&without, &without).is_none());
+        assert!(cursor_sprite(&without, &CaptureResult::new(vec![50; 16], 2, 2)).is_none());
+    }
+
+    #[test]
+    fn test_capture_result_blank_and_uniform() {
+        let transparent_black = CaptureResult::new(vec![0, 0, 0, 0, 0, 0, 0, 255], 2, 1);
+        assert!(transparent_black.is_blank());
+        assert!(!transparent_black.is_uniform());
+
+        let grey = CaptureResult::new(vec![7; 16], 2, 2);
+        assert!(!grey.is_blank());
+        assert!(grey.is_uniform());
+
+        let empty = CaptureResult::new(Vec::new(), 0, 0);
+        assert!(empty.is_blank());
+        assert!(empty.is_uniform());
+    }
+
+    #[test]
+    fn test_capture_types_compare_by_value() {
+        let a = CaptureResult::new(vec![1, 2, 3, 4], 1, 1);
+        assert_eq!(a, a.clone());
+        assert_ne!(a, CaptureResult::new(vec![1, 2, 3, 5], 1, 1));
+        assert_ne!(a, CaptureResult::new(vec![1, 2, 3, 4], 1, 2));
+
+        let set: std::collections::HashSet<CaptureResult> =
+            [a.clone(), a.clone()].into_iter().collect();
+        assert_eq!(set.len(), 1);
+
+        let params = CaptureParameters::new("DP-1").scale(0.5);
+        assert_eq!(params, params.clone());
+        assert_ne!(params, CaptureParameters::new("DP-1"));
+
+        let mut outputs = std::collections::HashMap::new();
+        outputs.insert("DP-1".to_string(), a);
+        let multi = MultiOutputCaptureResult::new(outputs);
+        assert_eq!(multi, multi.clone());
+    }
+
+    #[test]
+    fn test_capture_parameters_presets() {
+        let params = CaptureParameters::with_cursor_overlay("DP-1");
+        assert_eq!(params.output_name(), "DP-1");
+        assert!(params.overlay_cursor_enabled());
+        assert!(params.region_ref().is_none());
+
+        let region = Box::new(10, 20, 300, 200);
+        let params = CaptureParameters::for_region("HDMI-A-1", region);
+        assert_eq!(params.output_name(), "HDMI-A-1");
+        assert_eq!(params.region_ref(), Some(&region));
+        assert!(!params.overlay_cursor_enabled());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_capture_parameters_serde_round_trip() {
+        let params = CaptureParameters::new("DP-1")
+            .region(Box::new(0, 0, 640, 480))
+            .overlay_cursor(true)
+            .scale(2.0);
+        let json = serde_json::to_value(&params).unwrap();
+        assert_eq!(json["region"], "0,0 640x480");
+        let parsed: CaptureParameters = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed, params);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_capture_result_serde_round_trip() {
+        let capture = CaptureResult {
+            data: vec![255, 0, 0, 255, 0, 0, 255, 128],
+            width: 2,
+            height: 1,
+            timestamp: Some(std::time::Duration::from_millis(5)),
+        };
+        let json = serde_json::to_value(&capture).unwrap();
+        assert!(json.get("data").is_none());
+        let png: Vec<u8> = serde_json::from_value(json["png"].clone()).unwrap();
+        assert_eq!(&png[..4], b"\x89PNG");
+        let parsed: CaptureResult = serde_json::from_value(json.clone()).unwrap();
+        assert_eq!(parsed.data(), capture.data());
+        assert_eq!(parsed.timestamp(), capture.timestamp());
+
+        // Dimensions that disagree with the image are rejected.
+        let mut wrong = json;
+        wrong["width"] = 3.into();
+        assert!(serde_json::from_value::<CaptureResult>(wrong).is_err());
+    }
 }
@@ -0,0 +1,201 @@
+//! Multi-threaded PNG encoder.
+//!
+//! The image is split into horizontal bands of scanlines. Each band is
+//! deflate-compressed on its own thread and ended with a sync flush, which
+//! leaves the compressed stream byte-aligned, so the bands can simply be
+//! concatenated into one zlib stream (the same trick pigz uses). Only the
+//! last band is finished with a final block. Scanlines are stored with
+//! filter type 0 (None), so no band depends on rows of another band.
+
+use crate::{Error, Result};
+use flate2::{Compress, Compression, Crc, FlushCompress, Status};
+
+const PNG_SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'];
+
+/// Encode tightly packed RGBA data as an 8-bit RGBA PNG using `threads`
+/// worker threads (`0` means one per available CPU).
+pub(crate) fn encode(
+    data: &[u8],
+    width: u32,
+    height: u32,
+    compression: u8,
+    threads: usize,
+) -> Result<Vec<u8>> {
+    let row_len = width as usize * 4;
+    let height = height as usize;
+    let threads = match threads {
+        0 => std::thread::available_parallelism().map_or(1, |n| n.get()),
+        n => n,
+    }
+    .clamp(1, height.max(1));
+    let level = Compression::new(u32::from(compression.min(9)));
+
+    let rows_per_band = height.div_ceil(threads).max(1);
+    let bands: Vec<&[u8]> = data.chunks(rows_per_band * row_len).collect();
+    let last = bands.len().saturating_sub(1);
+
+    let compressed = std::thread::scope(|scope| {
+        let handles: Vec<_> = bands
+            .iter()
+            .enumerate()
+            .map(|(index, band)| {
+                scope.spawn(move || compress_band(band, row_len, level, index == last))
+            })
+            .collect();
+        handles
+            .into_iter()
+            .map(|handle| {
+                handle
+                    .join()
+                    .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+            })
+            .collect::<Result<Vec<_>>>()
+    })?;
+
+    let mut adler = Adler32::new();
+    for row in data.chunks(row_len) {
+        adler.update(&[0]);
+        adler.update(row);
+    }
+
+    let mut idat = Vec::with_capacity(compressed.iter().map(Vec::len).sum::<usize>() + 6);
+    idat.extend_from_slice(&zlib_header(compression));
+    for band in &compressed {
+        idat.extend_from_slice(band);
+    }
+    idat.extend_from_slice(&adler.finish().to_be_bytes());
+
+    let mut ihdr = Vec::with_capacity(13);
+    ihdr.extend_from_slice(&width.to_be_bytes());
+    ihdr.extend_from_slice(&(height as u32).to_be_bytes());
+    // Bit depth 8, color type 6 (RGBA), deflate, adaptive filtering, no interlace.
+    ihdr.extend_from_slice(&[8, 6, 0, 0, 0]);
+
+    let mut png = Vec::with_capacity(idat.len() + 64);
+    png.extend_from_slice(&PNG_SIGNATURE);
+    write_chunk(&mut png, b"IHDR", &ihdr);
+    // pHYs at the default 96 DPI, matching `PngOptions::default()`.
+    let pixels_per_meter = crate::png_pixels_per_meter(crate::DEFAULT_PNG_DPI);
+    let mut phys = Vec::with_capacity(9);
+    phys.extend_from_slice(&pixels_per_meter.to_be_bytes());
+    phys.extend_from_slice(&pixels_per_meter.to_be_bytes());
+    phys.push(1);
+    write_chunk(&mut png, b"pHYs", &phys);
+    write_chunk(&mut png, b"IDAT", &idat);
+    write_chunk(&mut png, b"IEND", &[]);
+    Ok(png)
+}
+
+/// Deflate one band of scanlines (each prefixed with filter byte 0) into a
+/// raw deflate fragment. Every band but the last ends with a sync flush.
+fn compress_band(band: &[u8], row_len: usize, level: Compression, last: bool) -> Result<Vec<u8>> {
+    let mut filtered = Vec::with_capacity(band.len() + band.len() / row_len);
+    for row in band.chunks(row_len) {
+        filtered.push(0);
+        filtered.extend_from_slice(row);
+    }
+
+    let flush = if last {
+        FlushCompress::Finish
+    } else {
+        FlushCompress::Sync
+    };
+    let mut compressor = Compress::new(level, false);
+    let mut output = Vec::with_capacity(filtered.len() / 2 + 1024);
+    let mut input = &filtered[..];
+    loop {
+        output.reserve(input.len() / 2 + 1024);
+        let before = compressor.total_in();
+        let status = compressor
+            .compress_vec(input, &mut output, flush)
+            .map_err(|e| Error::Io(std::io::Error::other(format!("PNG encoding error: {}", e))))?;
+        input = &input[(compressor.total_in() - before) as usize..];
+
+        // A flush is complete once the compressor stops filling the buffer.
+        let done = match flush {
+            FlushCompress::Finish => status == Status::StreamEnd,
+            _ => input.is_empty() && output.len() < output.capacity(),
+        };
+        if done {
+            return Ok(output);
+        }
+    }
+}
+
+/// zlib stream header (CMF, FLG) with the compression level hint set.
+fn zlib_header(compression: u8) -> [u8; 2] {
+    let level_hint: u8 = match compression {
+        0 | 1 => 0,
+        2..=5 => 1,
+        6 => 2,
+        _ => 3,
+    };
+    let cmf: u8 = 0x78;
+    let mut flg = level_hint << 6;
+    let check = ((u16::from(cmf) << 8) | u16::from(flg)) % 31;
+    if check != 0 {
+        flg += (31 - check) as u8;
+    }
+    [cmf, flg]
+}
+
+fn write_chunk(png: &mut Vec<u8>, kind: &[u8; 4], payload: &[u8]) {
+    png.extend_from_slice(&(payload.len() as u32).to_be_bytes());
+    png.extend_from_slice(kind);
+    png.extend_from_slice(payload);
+    let mut crc = Crc::new();
+    crc.update(kind);
+    crc.update(payload);
+    png.extend_from_slice(&crc.sum().to_be_bytes());
+}
+
+/// Running Adler-32 checksum of the uncompressed zlib payload.
+struct Adler32 {
+    a: u32,
+    b: u32,
+}
+
+impl Adler32 {
+    const MOD: u32 = 65521;
+    /// Largest number of bytes that can be summed before `b` may overflow.
+    const NMAX: usize = 5552;
+
+    fn new() -> Self {
+        Self { a: 1, b: 0 }
+    }
+
+    fn update(&mut self, bytes: &[u8]) {
+        for chunk in bytes.chunks(Self::NMAX) {
+            for &byte in chunk {
+                self.a += u32::from(byte);
+                self.b += self.a;
+            }
+            self.a %= Self::MOD;
+            self.b %= Self::MOD;
+        }
+    }
+
+    fn finish(&self) -> u32 {
+        (self.b << 16) | self.a
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_adler32_known_value() {
+        let mut adler = Adler32::new();
+        adler.update(b"Wikipedia");
+        assert_eq!(adler.finish(), 0x11E6_0398);
+    }
+
+    #[test]
+    fn test_zlib_header_is_valid() {
+        for compression in 0..=9 {
+            let [cmf, flg] = zlib_header(compression);
+            assert_eq!(((u16::from(cmf) << 8) | u16::from(flg)) % 31, 0);
+        }
+    }
+}
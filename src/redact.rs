@@ -0,0 +1,190 @@
+//! In-place pixelation and blurring of RGBA images, for redacting parts of
+//! a screenshot.
+//!
+//! Both operate on a tightly packed RGBA buffer `width` pixels wide and only
+//! touch pixels inside `region`, which must already lie within the image.
+
+use crate::Box;
+
+/// Replace every `block_size`x`block_size` cell of `region` with its average
+/// color. Cells are aligned to the region's top-left corner; cells at the
+/// right and bottom edges may be smaller.
+pub(crate) fn pixelate(data: &mut [u8], width: usize, region: Box, block_size: usize) {
+    let (x0, y0) = (region.x() as usize, region.y() as usize);
+    let (x1, y1) = (x0 + region.width() as usize, y0 + region.height() as usize);
+
+    for cell_y in (y0..y1).step_by(block_size) {
+        let cell_bottom = (cell_y + block_size).min(y1);
+        for cell_x in (x0..x1).step_by(block_size) {
+            let cell_right = (cell_x + block_size).min(x1);
+            let rows = cell_y..cell_bottom;
+            let pixels = |row: usize| (row * width + cell_x) * 4..(row * width + cell_right) * 4;
+
+            let mut sum = [0u64; 4];
+            for row in rows.clone() {
+                for px in data[pixels(row)].chunks_exact(4) {
+                    for (total, &value) in sum.iter_mut().zip(px) {
+                        *total += value as u64;
+                    }
+                }
+            }
+            let count = ((cell_right - cell_x) * (cell_bottom - cell_y)) as u64;
+            let average = sum.map(|total| ((total + count / 2) / count) as u8);
+            for row in rows {
+                for px in data[pixels(row)].chunks_exact_mut(4) {
+                    px.copy_from_slice(&average);
+                }
+            }
+        }
+    }
+}
+
+/// Approximate a gaussian blur with standard deviation `sigma` inside
+/// `region` by three successive box blurs. Pixels outside the region are
+/// not sampled; the region's edge pixels are repeated instead.
+pub(crate) fn blur(data: &mut [u8], width: usize, region: Box, sigma: f32) {
+    let (x0, y0) = (region.x() as usize, region.y() as usize);
+    let (region_width, region_height) = (region.width() as usize, region.height() as usize);
+
+    let mut pixels = Vec::with_capacity(region_width * region_height * 4);
+    for row in y0..y0 + region_height {
+        let start = (row * width + x0) * 4;
+        pixels.extend_from_slice(&data[start..start + region_width * 4]);
+    }
+
+    let mut scratch = vec![0u8; pixels.len()];
+    for radius in box_radii(sigma) {
+        box_blur_rows(&pixels, &mut scratch, region_width, region_height, radius);
+        box_blur_columns(&scratch, &mut pixels, region_width, region_height, radius);
+    }
+
+    for (row, blurred) in pixels.chunks_exact(region_width * 4).enumerate() {
+        let start = ((y0 + row) * width + x0) * 4;
+        data[start..start + region_width * 4].copy_from_slice(blurred);
+    }
+}
+
+/// Radii of three box blurs whose combination approximates a gaussian with
+/// standard deviation `sigma` (Kovesi, "Fast almost-Gaussian filtering").
+fn box_radii(sigma: f32) -> [usize; 3] {
+    const PASSES: f32 = 3.0;
+    let sigma = sigma.max(0.0);
+    let ideal = (12.0 * sigma * sigma / PASSES + 1.0).sqrt();
+    let mut lower = ideal.floor() as i32;
+    if lower % 2 == 0 {
+        lower -= 1;
+    }
+    let lower = lower.max(1);
+    let upper = lower + 2;
+    let lower_f = lower as f32;
+    let lower_passes = ((12.0 * sigma * sigma
+        - PASSES * lower_f * lower_f
+        - 4.0 * PASSES * lower_f
+        - 3.0 * PASSES)
+        / (-4.0 * lower_f - 4.0))
+        .round() as i32;
+
+    let mut radii = [0; 3];
+    for (pass, radius) in radii.iter_mut().enumerate() {
+        let size = if (pass as i32) < lower_passes {
+            lower
+        } else {
+            upper
+        };
+        *radius = ((size - 1) / 2) as usize;
+    }
+    radii
+}
+
+/// Horizontal box blur of `radius` from `src` into `dst`, clamping at the
+/// left and right edges.
+fn box_blur_rows(src: &[u8], dst: &mut [u8], width: usize, height: usize, radius: usize) {
+    for row in 0..height {
+        let line = row * width;
+        box_blur_line(src, dst, width, radius, |i| (line + i) * 4);
+    }
+}
+
+/// Vertical box blur of `radius` from `src` into `dst`, clamping at the
+/// top and bottom edges.
+fn box_blur_columns(src: &[u8], dst: &mut [u8], width: usize, height: usize, radius: usize) {
+    for column in 0..width {
+        box_blur_line(src, dst, height, radius, |i| (i * width + column) * 4);
+    }
+}
+
+/// Box blur `len` pixels addressed by `offset` with a sliding window sum.
+fn box_blur_line(
+    src: &[u8],
+    dst: &mut [u8],
+    len: usize,
+    radius: usize,
+    offset: impl Fn(usize) -> usize,
+) {
+    if len == 0 {
+        return;
+    }
+    let window = (2 * radius + 1) as u32;
+    let clamped = |i: isize| offset(i.clamp(0, len as isize - 1) as usize);
+
+    let mut sum = [0u32; 4];
+    for i in -(radius as isize)..=radius as isize {
+        let at = clamped(i);
+        for (total, &value) in sum.iter_mut().zip(&src[at..at + 4]) {
+            *total += value as u32;
+        }
+    }
+    for i in 0..len {
+        let at = offset(i);
+        for (channel, total) in sum.iter().enumerate() {
+            dst[at + channel] = ((total + window / 2) / window) as u8;
+        }
+        let (leaving, entering) = (
+            clamped(i as isize - radius as isize),
+            clamped(i as isize + radius as isize + 1),
+        );
+        for (channel, total) in sum.iter_mut().enumerate() {
+            *total = *total + src[entering + channel] as u32 - src[leaving + channel] as u32;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pixelate_averages_cells() {
+        // 3x1 image: one 2-pixel cell and one 1-pixel cell at the edge.
+        let mut data = vec![0, 0, 0, 255, 100, 50, 10, 255, 7, 7, 7, 7];
+        pixelate(&mut data, 3, Box::new(0, 0, 3, 1), 2);
+        assert_eq!(data, vec![50, 25, 5, 255, 50, 25, 5, 255, 7, 7, 7, 7]);
+    }
+
+    #[test]
+    fn test_pixelate_leaves_outside_untouched() {
+        let mut data: Vec<u8> = (0..4 * 4 * 4).map(|i| i as u8).collect();
+        let original = data.clone();
+        pixelate(&mut data, 4, Box::new(1, 1, 2, 2), 2);
+        assert_eq!(&data[..16], &original[..16]);
+        assert_eq!(&data[48..], &original[48..]);
+        assert_eq!(&data[20..24], &data[24..28]);
+        assert_eq!(&data[20..24], &data[36..40]);
+    }
+
+    #[test]
+    fn test_box_radii_grow_with_sigma() {
+        assert_eq!(box_radii(0.0), [0, 0, 0]);
+        let small = box_radii(2.0);
+        let large = box_radii(8.0);
+        assert!(small.iter().sum::<usize>() < large.iter().sum::<usize>());
+    }
+
+    #[test]
+    fn test_blur_keeps_solid_color() {
+        let mut data = [30, 60, 90, 255].repeat(5 * 5);
+        let original = data.clone();
+        blur(&mut data, 5, Box::new(0, 0, 5, 5), 3.0);
+        assert_eq!(data, original);
+    }
+}
@@ -0,0 +1,1162 @@
+//! Interactive region selection, similar to `slurp`.
+//!
+//! A fullscreen `zwlr_layer_shell_v1` overlay is placed on every output.
+//! Dragging with the left mouse button selects a rectangle; Escape cancels.
+//! Pointer positions arrive in surface-local logical coordinates and are
+//! converted to the global logical layout (the space [`Grim::capture_region`]
+//! expects) using each output's `xdg_output` position.
+//!
+//! With [`SelectionOptions::freeze`] the outputs are captured before the
+//! overlay appears and the frozen frames are painted underneath the dimming,
+//! so the user selects from a still image instead of the live desktop.
+//!
+//! The same overlay, left transparent, is used by [`pointer_position`]:
+//! Wayland only tells a client where the pointer is while it is over one of
+//! the client's surfaces.
+//!
+//! [`Grim::capture_region`]: crate::Grim::capture_region
+
+use crate::{Box, CaptureParameters, CaptureResult, Error, Result, ScaleFilter};
+use std::os::fd::AsFd;
+use wayland_client::{
+    delegate_noop,
+    protocol::{
+        wl_buffer::WlBuffer,
+        wl_compositor::WlCompositor,
+        wl_keyboard::{self, WlKeyboard},
+        wl_output::{self, WlOutput},
+        wl_pointer::{self, WlPointer},
+        wl_registry::{self, WlRegistry},
+        wl_seat::{self, WlSeat},
+        wl_shm::{self, WlShm},
+        wl_shm_pool::WlShmPool,
+        wl_surface::WlSurface,
+    },
+    Connection, Dispatch, EventQueue, Proxy, QueueHandle, WEnum,
+};
+use wayland_protocols::wp::cursor_shape::v1::client::{
+    wp_cursor_shape_device_v1::{self, WpCursorShapeDeviceV1},
+    wp_cursor_shape_manager_v1::WpCursorShapeManagerV1,
+};
+use wayland_protocols::xdg::xdg_output::zv1::client::{
+    zxdg_output_manager_v1::ZxdgOutputManagerV1,
+    zxdg_output_v1::{self, ZxdgOutputV1},
+};
+use wayland_protocols_wlr::layer_shell::v1::client::{
+    zwlr_layer_shell_v1::{self, ZwlrLayerShellV1},
+    zwlr_layer_surface_v1::{self, ZwlrLayerSurfaceV1},
+};
+
+/// Linux input event code of the left mouse button.
+const BTN_LEFT: u32 = 0x110;
+/// Linux input event code of the Escape key.
+const KEY_ESC: u32 = 1;
+
+/// Overlay colour outside the selection (premultiplied ARGB, little endian).
+const DIM_PIXEL: [u8; 4] = [0, 0, 0, 0x60];
+/// Colour of the selection border.
+const BORDER_PIXEL: [u8; 4] = [0xff, 0xff, 0xff, 0xff];
+/// Width of the selection border in logical pixels.
+const BORDER_WIDTH: i32 = 2;
+/// Roundtrips [`pointer_position`] waits for `wl_pointer.enter` after
+/// mapping the probe surfaces.
+const PROBE_ROUNDTRIPS: usize = 3;
+
+/// What the overlay is shown for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+enum OverlayMode {
+    /// Dimmed overlay for dragging out a selection.
+    #[default]
+    Select,
+    /// Transparent overlay that only waits for the pointer to enter.
+    Probe,
+}
+
+/// Options for [`select_region_with_options`].
+///
+/// # Example
+///
+/// ```rust
+/// use grim_rs::SelectionOptions;
+///
+/// let options = SelectionOptions::new().freeze(true);
+/// assert!(options.freeze_enabled());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct SelectionOptions {
+    /// Whether to show a still capture of the outputs under the overlay.
+    freeze: bool,
+    /// Width to height ratio the selection is held to, if any.
+    aspect_ratio: Option<(u32, u32)>,
+    /// Distance in logical pixels within which the selection snaps to
+    /// output edges; 0 disables snapping.
+    snap_threshold: u32,
+}
+
+impl SelectionOptions {
+    /// Creates options for a selection over the live desktop.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets whether to freeze the screen while selecting.
+    ///
+    /// When enabled, every output is captured before the overlay is shown
+    /// and the capture is drawn as the overlay's background, so windows
+    /// updating underneath don't distract or flicker.
+    pub fn freeze(mut self, freeze: bool) -> Self {
+        self.freeze = freeze;
+        self
+    }
+
+    /// Returns whether the screen is frozen while selecting.
+    pub fn freeze_enabled(&self) -> bool {
+        self.freeze
+    }
+
+    /// Locks the selection to a `width`:`height` aspect ratio, e.g. `(16, 9)`.
+    ///
+    /// While dragging, the shorter side is grown to match the ratio, so the
+    /// selection still covers the pointer. A zero in either part removes
+    /// the lock.
+    pub fn aspect_ratio(mut self, width: u32, height: u32) -> Self {
+        self.aspect_ratio = (width > 0 && height > 0).then_some((width, height));
+        self
+    }
+
+    /// Returns the locked aspect ratio, if any.
+    pub fn locked_aspect_ratio(&self) -> Option<(u32, u32)> {
+        self.aspect_ratio
+    }
+
+    /// Snaps the selection to output edges and corners within `threshold`
+    /// logical pixels, e.g. 8.
+    ///
+    /// Each corner of the selection is snapped on its own, so dragging
+    /// close to a monitor boundary selects exactly up to it. 0 (the
+    /// default) disables snapping.
+    pub fn snap_threshold(mut self, threshold: u32) -> Self {
+        self.snap_threshold = threshold;
+        self
+    }
+
+    /// Returns the snapping distance, 0 if snapping is disabled.
+    pub fn snap_distance(&self) -> u32 {
+        self.snap_threshold
+    }
+}
+
+/// Let the user select a region with the mouse and return it.
+///
+/// Shows a dimmed overlay on every output; the user drags a rectangle with
+/// the left mouse button, and the selection may span several outputs. The
+/// returned box is in global logical coordinates and can be passed to
+/// [`Grim::capture_region`](crate::Grim::capture_region) directly. The
+/// overlay is removed before this function returns.
+///
+/// # Errors
+///
+/// Returns `Error::SelectionCancelled` if the user presses Escape or the
+/// compositor closes the overlay, `Error::InvalidRegion` if the selection
+/// is empty, and `Error::UnsupportedProtocol` if the compositor lacks
+/// `zwlr_layer_shell_v1`.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use grim_rs::Grim;
+///
+/// let region = grim_rs::select_region()?;
+/// let mut grim = Grim::new()?;
+/// let result = grim.capture_region(region)?;
+/// # Ok::<(), grim_rs::Error>(())
+/// ```
+pub fn select_region() -> Result<Box> {
+    select_region_with_options(&SelectionOptions::new())
+}
+
+/// Let the user select a region with the mouse, with the given options.
+///
+/// Behaves like [`select_region`]. With [`SelectionOptions::freeze`], the
+/// outputs are captured first and shown frozen under the overlay; the
+/// returned region can still be captured afresh or cropped from a capture
+/// taken with [`Grim::capture_all`](crate::Grim::capture_all).
+///
+/// # Errors
+///
+/// Same as [`select_region`]; freezing additionally fails if the outputs
+/// cannot be captured.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use grim_rs::{Grim, SelectionOptions};
+///
+/// let options = SelectionOptions::new().freeze(true);
+/// let region = grim_rs::select_region_with_options(&options)?;
+/// let mut grim = Grim::new()?;
+/// let result = grim.capture_region(region)?;
+/// # Ok::<(), grim_rs::Error>(())
+/// ```
+pub fn select_region_with_options(options: &SelectionOptions) -> Result<Box> {
+    // Capture before anything of the overlay can end up in the frame.
+    let frozen = if options.freeze_enabled() {
+        capture_frozen()?
+    } else {
+        Vec::new()
+    };
+    let (_connection, mut event_queue, mut state) = show_overlay(OverlayMode::Select)?;
+    state.frozen = frozen;
+    state.aspect_ratio = options.locked_aspect_ratio();
+    state.snap_threshold = options.snap_distance();
+    let qh = event_queue.handle();
+
+    let outcome = loop {
+        if let Some(outcome) = state.outcome.take() {
+            break outcome;
+        }
+        if let Err(e) = state.redraw(&qh) {
+            break Err(e);
+        }
+        if let Err(e) = event_queue.blocking_dispatch(&mut state) {
+            break Err(
+                Error::wayland_connection("Failed to dispatch selection events").with_source(e),
+            );
+        }
+    };
+
+    hide_overlay(&mut event_queue, &mut state)?;
+    outcome
+}
+
+/// Find the pointer in the global logical layout.
+///
+/// Briefly maps a transparent overlay on every output and waits for the
+/// compositor to report the pointer entering one of them.
+///
+/// # Errors
+///
+/// Returns `Error::OutputNotFound` if the compositor does not report the
+/// pointer over any output, and `Error::UnsupportedProtocol` if it lacks
+/// `zwlr_layer_shell_v1`.
+pub(crate) fn pointer_position() -> Result<(f64, f64)> {
+    let (_connection, mut event_queue, mut state) = show_overlay(OverlayMode::Probe)?;
+    let qh = event_queue.handle();
+
+    let mut outcome = Ok(());
+    for _ in 0..PROBE_ROUNDTRIPS {
+        if state.pointer_position.is_some() || state.outcome.is_some() {
+            break;
+        }
+        outcome = state
+            .redraw(&qh)
+            .and_then(|()| roundtrip(&mut event_queue, &mut state));
+        if outcome.is_err() {
+            break;
+        }
+    }
+
+    hide_overlay(&mut event_queue, &mut state)?;
+    outcome?;
+    state
+        .pointer_position
+        .ok_or_else(|| Error::OutputNotFound("the pointer is not over any output".to_string()))
+}
+
+/// Capture every output with its logical geometry, for a frozen overlay.
+fn capture_frozen() -> Result<Vec<(Box, CaptureResult)>> {
+    let mut grim = crate::Grim::new()?;
+    let parameters = grim
+        .get_outputs()?
+        .iter()
+        .map(|output| CaptureParameters::new(output.name()))
+        .collect();
+    let captures = grim.capture_outputs(parameters)?;
+    Ok(captures
+        .outputs()
+        .iter()
+        .filter_map(|(name, capture)| {
+            let placement = captures.placement(name)?;
+            Some((*placement.geometry(), capture.clone()))
+        })
+        .collect())
+}
+
+/// Connect and map an overlay layer surface on every output.
+fn show_overlay(
+    mode: OverlayMode,
+) -> Result<(Connection, EventQueue<SelectionState>, SelectionState)> {
+    let connection = Connection::connect_to_env()
+        .map_err(|e| Error::wayland_connection("Failed to connect to Wayland").with_source(e))?;
+    let mut event_queue = connection.new_event_queue();
+    let qh = event_queue.handle();
+    let _registry = connection.display().get_registry(&qh, ());
+
+    let mut state = SelectionState {
+        mode,
+        ..SelectionState::default()
+    };
+    roundtrip(&mut event_queue, &mut state)?;
+
+    let compositor = state
+        .compositor
+        .clone()
+        .ok_or_else(|| Error::UnsupportedProtocol("wl_compositor not available".to_string()))?;
+    let layer_shell = state.layer_shell.clone().ok_or_else(|| {
+        Error::UnsupportedProtocol("zwlr_layer_shell_v1 not available".to_string())
+    })?;
+    if state.shm.is_none() {
+        return Err(Error::UnsupportedProtocol(
+            "wl_shm not available".to_string(),
+        ));
+    }
+    if state.outputs.is_empty() {
+        return Err(Error::NoOutputs);
+    }
+
+    if let Some(manager) = &state.xdg_output_manager {
+        for (index, output) in state.outputs.iter().enumerate() {
+            manager.get_xdg_output(&output.output, &qh, index);
+        }
+    }
+    roundtrip(&mut event_queue, &mut state)?;
+
+    let (namespace, keyboard_interactivity) = match mode {
+        OverlayMode::Select => (
+            "selection",
+            zwlr_layer_surface_v1::KeyboardInteractivity::Exclusive,
+        ),
+        OverlayMode::Probe => (
+            "pointer-probe",
+            zwlr_layer_surface_v1::KeyboardInteractivity::None,
+        ),
+    };
+    for (index, output) in state.outputs.iter().enumerate() {
+        let surface = compositor.create_surface(&qh, ());
+        let layer_surface = layer_shell.get_layer_surface(
+            &surface,
+            Some(&output.output),
+            zwlr_layer_shell_v1::Layer::Overlay,
+            namespace.to_string(),
+            &qh,
+            index,
+        );
+        layer_surface.set_anchor(
+            zwlr_layer_surface_v1::Anchor::Top
+                | zwlr_layer_surface_v1::Anchor::Bottom
+                | zwlr_layer_surface_v1::Anchor::Left
+                | zwlr_layer_surface_v1::Anchor::Right,
+        );
+        layer_surface.set_exclusive_zone(-1);
+        layer_surface.set_keyboard_interactivity(keyboard_interactivity);
+        surface.commit();
+        state.surfaces.push(SelectionSurface {
+            surface,
+            layer_surface,
+            output: index,
+            size: (0, 0),
+            dirty: false,
+            background: None,
+        });
+    }
+    Ok((connection, event_queue, state))
+}
+
+/// Destroy the overlay surfaces.
+fn hide_overlay(
+    event_queue: &mut EventQueue<SelectionState>,
+    state: &mut SelectionState,
+) -> Result<()> {
+    for surface in state.surfaces.drain(..) {
+        surface.layer_surface.destroy();
+        surface.surface.destroy();
+    }
+    // Make sure the overlay is gone before the caller takes a screenshot.
+    roundtrip(event_queue, state)
+}
+
+fn roundtrip(
+    event_queue: &mut EventQueue<SelectionState>,
+    state: &mut SelectionState,
+) -> Result<()> {
+    event_queue
+        .roundtrip(state)
+        .map(|_| ())
+        .map_err(|e| Error::wayland_connection("Wayland roundtrip failed").with_source(e))
+}
+
+/// Convert a surface-local pointer position to the global logical layout.
+fn surface_to_global(origin: (i32, i32), x: f64, y: f64) -> (f64, f64) {
+    (origin.0 as f64 + x, origin.1 as f64 + y)
+}
+
+/// The whole-pixel box spanned by two corners of a drag.
+///
+/// The box is grown to cover both corners completely, so the result never
+/// misses a partially selected pixel.
+fn selection_box(start: (f64, f64), end: (f64, f64)) -> Box {
+    let x1 = start.0.min(end.0).floor() as i32;
+    let y1 = start.1.min(end.1).floor() as i32;
+    let x2 = start.0.max(end.0).ceil() as i32;
+    let y2 = start.1.max(end.1).ceil() as i32;
+    Box::new(x1, y1, x2 - x1, y2 - y1)
+}
+
+/// The whole-pixel box spanned by a drag, held to a `width`:`height` ratio.
+///
+/// The box is anchored at the start of the drag and its shorter side is
+/// grown to match the ratio, so it always covers the pointer.
+fn constrained_selection_box(start: (f64, f64), end: (f64, f64), ratio: (u32, u32)) -> Box {
+    let anchor = (start.0.round() as i32, start.1.round() as i32);
+    let width = (end.0 - anchor.0 as f64).abs().ceil() as i64;
+    let height = (end.1 - anchor.1 as f64).abs().ceil() as i64;
+    let (ratio_width, ratio_height) = (ratio.0 as i64, ratio.1 as i64);
+    let (width, height) = if width * ratio_height >= height * ratio_width {
+        let height = (width * ratio_height + ratio_width / 2) / ratio_width;
+        (width as i32, height as i32)
+    } else {
+        let width = (height * ratio_width + ratio_height / 2) / ratio_height;
+        (width as i32, height as i32)
+    };
+    let x = if end.0 < anchor.0 as f64 {
+        anchor.0 - width
+    } else {
+        anchor.0
+    };
+    let y = if end.1 < anchor.1 as f64 {
+        anchor.1 - height
+    } else {
+        anchor.1
+    };
+    Box::new(x, y, width, height)
+}
+
+/// Move `point` onto the nearest output edge within `threshold` on each axis.
+///
+/// An edge only attracts the point while the point is alongside it, i.e.
+/// within `threshold` of the output's extent on the other axis.
+fn snap_to_edges(point: (f64, f64), outputs: &[Box], threshold: f64) -> (f64, f64) {
+    let nearest = |value: f64, edges: &mut dyn Iterator<Item = i32>| {
+        edges
+            .map(|edge| edge as f64)
+            .filter(|edge| (edge - value).abs() <= threshold)
+            .min_by(|a, b| (a - value).abs().total_cmp(&(b - value).abs()))
+            .unwrap_or(value)
+    };
+    let alongside = |value: f64, start: i32, len: i32| {
+        value >= start as f64 - threshold && value <= (start + len) as f64 + threshold
+    };
+
+    let x = nearest(
+        point.0,
+        &mut outputs
+            .iter()
+            .filter(|output| alongside(point.1, output.y(), output.height()))
+            .flat_map(|output| [output.x(), output.x() + output.width()]),
+    );
+    let y = nearest(
+        point.1,
+        &mut outputs
+            .iter()
+            .filter(|output| alongside(point.0, output.x(), output.width()))
+            .flat_map(|output| [output.y(), output.y() + output.height()]),
+    );
+    (x, y)
+}
+
+/// Translate a global selection into the coordinates of a surface at `origin`.
+fn global_to_surface(selection: Box, origin: (i32, i32)) -> Box {
+    Box::new(
+        selection.x() - origin.0,
+        selection.y() - origin.1,
+        selection.width(),
+        selection.height(),
+    )
+}
+
+/// The frozen frame of the output at `origin` as an opaque ARGB8888 buffer
+/// of `width`x`height` pixels, resampled if the capture has another size.
+fn frozen_background(
+    frozen: &[(Box, CaptureResult)],
+    origin: (i32, i32),
+    width: i32,
+    height: i32,
+) -> Option<Vec<u8>> {
+    let (_, capture) = frozen
+        .iter()
+        .find(|(geometry, _)| (geometry.x(), geometry.y()) == origin)?;
+    let (width, height) = (width as u32, height as u32);
+    let resized;
+    let capture = if (capture.width(), capture.height()) == (width, height) {
+        capture
+    } else {
+        resized = capture
+            .resized(width, height, ScaleFilter::Auto)
+            .map_err(|e| log::debug!("Not freezing output at {:?}: {}", origin, e))
+            .ok()?;
+        &resized
+    };
+    Some(
+        capture
+            .data()
+            .chunks_exact(4)
+            .flat_map(|rgba| [rgba[2], rgba[1], rgba[0], 0xff])
+            .collect(),
+    )
+}
+
+/// Composite [`DIM_PIXEL`] over a premultiplied ARGB8888 pixel.
+fn dim(pixel: [u8; 4]) -> [u8; 4] {
+    let keep = 255 - DIM_PIXEL[3] as u32;
+    std::array::from_fn(|i| (DIM_PIXEL[i] as u32 + (pixel[i] as u32 * keep + 127) / 255) as u8)
+}
+
+/// Fill an ARGB8888 buffer of `width`x`height` buffer pixels with the overlay.
+///
+/// `selection` is in surface-local logical coordinates and is multiplied by
+/// `scale` to reach buffer pixels. `background`, a frozen frame of the same
+/// size, is drawn under the overlay when given; otherwise the overlay is
+/// translucent over the live desktop.
+fn draw_overlay(
+    buffer: &mut [u8],
+    width: i32,
+    height: i32,
+    scale: i32,
+    selection: Option<Box>,
+    background: Option<&[u8]>,
+) {
+    let selection = selection.map(|s| {
+        Box::new(
+            s.x() * scale,
+            s.y() * scale,
+            s.width() * scale,
+            s.height() * scale,
+        )
+    });
+    let border = BORDER_WIDTH * scale;
+
+    for (y, row) in buffer
+        .chunks_exact_mut(width as usize * 4)
+        .take(height as usize)
+        .enumerate()
+    {
+        let y = y as i32;
+        for (x, pixel) in row.chunks_exact_mut(4).enumerate() {
+            let base = background.map(|background| {
+                let i = (y as usize * width as usize + x) * 4;
+                [
+                    background[i],
+                    background[i + 1],
+                    background[i + 2],
+                    background[i + 3],
+                ]
+            });
+            let x = x as i32;
+            let color = match selection {
+                Some(s)
+                    if x >= s.x()
+                        && x < s.x() + s.width()
+                        && y >= s.y()
+                        && y < s.y() + s.height() =>
+                {
+                    let inner = x >= s.x() + border
+                        && x < s.x() + s.width() - border
+                        && y >= s.y() + border
+                        && y < s.y() + s.height() - border;
+                    if inner {
+                        base.unwrap_or([0; 4])
+                    } else {
+                        BORDER_PIXEL
+                    }
+                }
+                _ => base.map_or(DIM_PIXEL, dim),
+            };
+            pixel.copy_from_slice(&color);
+        }
+    }
+}
+
+struct SelectionOutput {
+    output: WlOutput,
+    /// Logical position in the global layout.
+    origin: (i32, i32),
+    /// Logical size, used until the layer surface is configured.
+    size: (i32, i32),
+    scale: i32,
+    logical_known: bool,
+}
+
+struct SelectionSurface {
+    surface: WlSurface,
+    layer_surface: ZwlrLayerSurfaceV1,
+    /// Index into `SelectionState::outputs`.
+    output: usize,
+    /// Logical size from the last `configure`, `(0, 0)` until configured.
+    size: (i32, i32),
+    dirty: bool,
+    /// Frozen frame in ARGB8888 at the current buffer size, if freezing.
+    background: Option<Vec<u8>>,
+}
+
+#[derive(Default)]
+struct SelectionState {
+    mode: OverlayMode,
+    compositor: Option<WlCompositor>,
+    shm: Option<WlShm>,
+    layer_shell: Option<ZwlrLayerShellV1>,
+    xdg_output_manager: Option<ZxdgOutputManagerV1>,
+    cursor_shape_manager: Option<WpCursorShapeManagerV1>,
+    seats: Vec<WlSeat>,
+    pointer: Option<WlPointer>,
+    cursor_shape_device: Option<WpCursorShapeDeviceV1>,
+    keyboard: Option<WlKeyboard>,
+    outputs: Vec<SelectionOutput>,
+    surfaces: Vec<SelectionSurface>,
+    /// Global logical pointer position.
+    pointer_position: Option<(f64, f64)>,
+    /// Surface the pointer is currently over, as an index into `surfaces`.
+    pointer_surface: Option<usize>,
+    /// Global logical position where the drag started.
+    drag_start: Option<(f64, f64)>,
+    outcome: Option<Result<Box>>,
+    /// Captures shown under the overlay, with their logical geometry.
+    frozen: Vec<(Box, CaptureResult)>,
+    /// Width to height ratio the selection is held to, if any.
+    aspect_ratio: Option<(u32, u32)>,
+    /// Distance within which the selection snaps to output edges.
+    snap_threshold: u32,
+}
+
+impl SelectionState {
+    fn current_selection(&self) -> Option<Box> {
+        let (start, end) = (self.drag_start?, self.pointer_position?);
+        let (start, end) = if self.snap_threshold > 0 {
+            let outputs: Vec<Box> = self
+                .outputs
+                .iter()
+                .map(|output| {
+                    Box::new(
+                        output.origin.0,
+                        output.origin.1,
+                        output.size.0,
+                        output.size.1,
+                    )
+                })
+                .collect();
+            let threshold = self.snap_threshold as f64;
+            (
+                snap_to_edges(start, &outputs, threshold),
+                snap_to_edges(end, &outputs, threshold),
+            )
+        } else {
+            (start, end)
+        };
+        Some(match self.aspect_ratio {
+            Some(ratio) => constrained_selection_box(start, end, ratio),
+            None => selection_box(start, end),
+        })
+    }
+
+    fn mark_all_dirty(&mut self) {
+        for surface in &mut self.surfaces {
+            surface.dirty = true;
+        }
+    }
+
+    /// Repaint every configured surface whose contents changed.
+    fn redraw(&mut self, qh: &QueueHandle<Self>) -> Result<()> {
+        let selection = self.current_selection();
+        for surface in &mut self.surfaces {
+            if !surface.dirty || surface.size == (0, 0) {
+                continue;
+            }
+            surface.dirty = false;
+
+            let output = &self.outputs[surface.output];
+            let scale = output.scale.max(1);
+            let width = surface.size.0 * scale;
+            let height = surface.size.1 * scale;
+            let stride = width * 4;
+            let size = (stride * height) as usize;
+
+            let file = tempfile::tempfile().map_err(|e| {
+                Error::buffer_creation("failed to create temporary file").with_source(e)
+            })?;
+            file.set_len(size as u64).map_err(|e| {
+                Error::buffer_creation(format!("failed to resize buffer to {} bytes", size))
+                    .with_source(e)
+            })?;
+            let mut mmap = unsafe {
+                memmap2::MmapMut::map_mut(&file).map_err(|e| {
+                    Error::buffer_creation("failed to memory-map buffer").with_source(e)
+                })?
+            };
+            // The probe overlay stays transparent, as the new file is zeroed.
+            if self.mode == OverlayMode::Select {
+                if surface.background.as_ref().map(Vec::len) != Some(size) {
+                    surface.background =
+                        frozen_background(&self.frozen, output.origin, width, height);
+                }
+                draw_overlay(
+                    &mut mmap,
+                    width,
+                    height,
+                    scale,
+                    selection.map(|s| global_to_surface(s, output.origin)),
+                    surface.background.as_deref(),
+                );
+            }
+
+            let shm = self
+                .shm
+                .as_ref()
+                .ok_or_else(|| Error::UnsupportedProtocol("wl_shm not available".to_string()))?;
+            let pool = shm.create_pool(file.as_fd(), size as i32, qh, ());
+            let buffer =
+                pool.create_buffer(0, width, height, stride, wl_shm::Format::Argb8888, qh, ());
+            pool.destroy();
+
+            surface.surface.set_buffer_scale(scale);
+            surface.surface.attach(Some(&buffer), 0, 0);
+            surface.surface.damage_buffer(0, 0, width, height);
+            surface.surface.commit();
+        }
+        Ok(())
+    }
+}
+
+impl Dispatch<WlRegistry, ()> for SelectionState {
+    fn event(
+        state: &mut Self,
+        registry: &WlRegistry,
+        event: wl_registry::Event,
+        _: &(),
+        _conn: &Connection,
+        qh: &QueueHandle<Self>,
+    ) {
+        if let wl_registry::Event::Global {
+            name,
+            interface,
+            version,
+        } = event
+        {
+            match interface.as_str() {
+                "wl_compositor" => {
+                    state.compositor = Some(registry.bind(name, version.min(4), qh, ()));
+                }
+                "wl_shm" => {
+                    state.shm = Some(registry.bind(name, 1, qh, ()));
+                }
+                "wl_seat" => {
+                    state
+                        .seats
+                        .push(registry.bind(name, version.min(5), qh, ()));
+                }
+                "wl_output" => {
+                    let index = state.outputs.len();
+                    let output = registry.bind(name, version.min(3), qh, index);
+                    state.outputs.push(SelectionOutput {
+                        output,
+                        origin: (0, 0),
+                        size: (0, 0),
+                        scale: 1,
+                        logical_known: false,
+                    });
+                }
+                "zwlr_layer_shell_v1" => {
+                    state.layer_shell = Some(registry.bind(name, version.min(4), qh, ()));
+                }
+                "zxdg_output_manager_v1" => {
+                    state.xdg_output_manager = Some(registry.bind(name, version.min(3), qh, ()));
+                }
+                "wp_cursor_shape_manager_v1" => {
+                    state.cursor_shape_manager = Some(registry.bind(name, 1, qh, ()));
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+impl Dispatch<WlOutput, usize> for SelectionState {
+    fn event(
+        state: &mut Self,
+        _output: &WlOutput,
+        event: wl_output::Event,
+        index: &usize,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+    ) {
+        let Some(output) = state.outputs.get_mut(*index) else {
+            return;
+        };
+        match event {
+            wl_output::Event::Geometry { x, y, .. } if !output.logical_known => {
+                output.origin = (x, y);
+            }
+            wl_output::Event::Scale { factor } => {
+                output.scale = factor;
+            }
+            _ => {}
+        }
+    }
+}
+
+impl Dispatch<ZxdgOutputV1, usize> for SelectionState {
+    fn event(
+        state: &mut Self,
+        _xdg_output: &ZxdgOutputV1,
+        event: zxdg_output_v1::Event,
+        index: &usize,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+    ) {
+        let Some(output) = state.outputs.get_mut(*index) else {
+            return;
+        };
+        match event {
+            zxdg_output_v1::Event::LogicalPosition { x, y } => {
+                output.origin = (x, y);
+                output.logical_known = true;
+            }
+            zxdg_output_v1::Event::LogicalSize { width, height } => {
+                output.size = (width, height);
+            }
+            _ => {}
+        }
+    }
+}
+
+impl Dispatch<ZwlrLayerSurfaceV1, usize> for SelectionState {
+    fn event(
+        state: &mut Self,
+        layer_surface: &ZwlrLayerSurfaceV1,
+        event: zwlr_layer_surface_v1::Event,
+        index: &usize,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+    ) {
+        match event {
+            zwlr_layer_surface_v1::Event::Configure {
+                serial,
+                width,
+                height,
+            } => {
+                layer_surface.ack_configure(serial);
+                let fallback = state.outputs[*index].size;
+                if let Some(surface) = state.surfaces.iter_mut().find(|s| s.output == *index) {
+                    surface.size = if width > 0 && height > 0 {
+                        (width as i32, height as i32)
+                    } else {
+                        fallback
+                    };
+                    surface.dirty = true;
+                }
+            }
+            zwlr_layer_surface_v1::Event::Closed => {
+                state.outcome = Some(Err(Error::SelectionCancelled));
+            }
+            _ => {}
+        }
+    }
+}
+
+impl Dispatch<WlSeat, ()> for SelectionState {
+    fn event(
+        state: &mut Self,
+        seat: &WlSeat,
+        event: wl_seat::Event,
+        _: &(),
+        _conn: &Connection,
+        qh: &QueueHandle<Self>,
+    ) {
+        if let wl_seat::Event::Capabilities {
+            capabilities: WEnum::Value(capabilities),
+        } = event
+        {
+            if capabilities.contains(wl_seat::Capability::Pointer) && state.pointer.is_none() {
+                let pointer = seat.get_pointer(qh, ());
+                state.cursor_shape_device = state
+                    .cursor_shape_manager
+                    .as_ref()
+                    .map(|manager| manager.get_pointer(&pointer, qh, ()));
+                state.pointer = Some(pointer);
+            }
+            if capabilities.contains(wl_seat::Capability::Keyboard) && state.keyboard.is_none() {
+                state.keyboard = Some(seat.get_keyboard(qh, ()));
+            }
+        }
+    }
+}
+
+impl Dispatch<WlPointer, ()> for SelectionState {
+    fn event(
+        state: &mut Self,
+        _pointer: &WlPointer,
+        event: wl_pointer::Event,
+        _: &(),
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+    ) {
+        match event {
+            wl_pointer::Event::Enter {
+                serial,
+                surface,
+                surface_x,
+                surface_y,
+            } => {
+                state.pointer_surface = state.surfaces.iter().position(|s| s.surface == surface);
+                if let Some(index) = state.pointer_surface {
+                    let origin = state.outputs[state.surfaces[index].output].origin;
+                    state.pointer_position = Some(surface_to_global(origin, surface_x, surface_y));
+                }
+                if state.mode == OverlayMode::Select {
+                    if let Some(device) = &state.cursor_shape_device {
+                        device.set_shape(serial, wp_cursor_shape_device_v1::Shape::Crosshair);
+                    }
+                }
+            }
+            wl_pointer::Event::Leave { .. } => {
+                state.pointer_surface = None;
+            }
+            wl_pointer::Event::Motion {
+                surface_x,
+                surface_y,
+                ..
+            } => {
+                if let Some(index) = state.pointer_surface {
+                    let origin = state.outputs[state.surfaces[index].output].origin;
+                    state.pointer_position = Some(surface_to_global(origin, surface_x, surface_y));
+                    if state.drag_start.is_some() {
+                        state.mark_all_dirty();
+                    }
+                }
+            }
+            wl_pointer::Event::Button {
+                button: BTN_LEFT,
+                state: WEnum::Value(button_state),
+                ..
+            } => match button_state {
+                wl_pointer::ButtonState::Pressed => {
+                    state.drag_start = state.pointer_position;
+                }
+                wl_pointer::ButtonState::Released => {
+                    if let Some(selection) = state.current_selection() {
+                        state.outcome = Some(if selection.is_empty() {
+                            Err(Error::InvalidRegion("selection is empty".to_string()))
+                        } else {
+                            Ok(selection)
+                        });
+                    }
+                    state.drag_start = None;
+                }
+                _ => {}
+            },
+            _ => {}
+        }
+    }
+}
+
+impl Dispatch<WlKeyboard, ()> for SelectionState {
+    fn event(
+        state: &mut Self,
+        _keyboard: &WlKeyboard,
+        event: wl_keyboard::Event,
+        _: &(),
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+    ) {
+        if let wl_keyboard::Event::Key {
+            key: KEY_ESC,
+            state: WEnum::Value(wl_keyboard::KeyState::Pressed),
+            ..
+        } = event
+        {
+            state.outcome = Some(Err(Error::SelectionCancelled));
+        }
+    }
+}
+
+impl Dispatch<WlBuffer, ()> for SelectionState {
+    fn event(
+        _state: &mut Self,
+        buffer: &WlBuffer,
+        event: <WlBuffer as Proxy>::Event,
+        _: &(),
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+    ) {
+        if let wayland_client::protocol::wl_buffer::Event::Release = event {
+            buffer.destroy();
+        }
+    }
+}
+
+delegate_noop!(SelectionState: WlCompositor);
+delegate_noop!(SelectionState: ignore WlShm);
+delegate_noop!(SelectionState: WlShmPool);
+delegate_noop!(SelectionState: ignore WlSurface);
+delegate_noop!(SelectionState: ZwlrLayerShellV1);
+delegate_noop!(SelectionState: ZxdgOutputManagerV1);
+delegate_noop!(SelectionState: WpCursorShapeManagerV1);
+delegate_noop!(SelectionState: WpCursorShapeDeviceV1);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_surface_to_global_adds_output_origin() {
+        // Output left of the primary one.
+        assert_eq!(surface_to_global((-1920, 0), 100.5, 20.0), (-1819.5, 20.0));
+        assert_eq!(surface_to_global((2560, 300), 0.0, 0.0), (2560.0, 300.0));
+    }
+
+    #[test]
+    fn test_selection_box_normalizes_and_covers_corners() {
+        assert_eq!(
+            selection_box((10.4, 5.0), (20.6, 15.0)),
+            Box::new(10, 5, 11, 10)
+        );
+        // Dragging up and left gives the same box.
+        assert_eq!(
+            selection_box((20.6, 15.0), (10.4, 5.0)),
+            Box::new(10, 5, 11, 10)
+        );
+        assert!(selection_box((3.0, 3.0), (3.0, 8.0)).is_empty());
+    }
+
+    #[test]
+    fn test_constrained_selection_box_keeps_ratio() {
+        // Wider than 16:9: the height follows the width.
+        assert_eq!(
+            constrained_selection_box((100.0, 100.0), (260.0, 110.0), (16, 9)),
+            Box::new(100, 100, 160, 90)
+        );
+        // Taller than 16:9 and dragged up-left: the width follows the height.
+        assert_eq!(
+            constrained_selection_box((100.0, 100.0), (10.0, 0.0), (16, 9)),
+            Box::new(-78, 0, 178, 100)
+        );
+        assert!(constrained_selection_box((5.0, 5.0), (5.0, 5.0), (1, 1)).is_empty());
+    }
+
+    #[test]
+    fn test_snap_to_edges() {
+        let outputs = [Box::new(0, 0, 1920, 1080), Box::new(1920, 0, 1280, 1024)];
+        // Near the shared edge and the bottom of the first output: a corner.
+        assert_eq!(
+            snap_to_edges((1914.5, 1075.0), &outputs, 8.0),
+            (1920.0, 1080.0)
+        );
+        // Too far from any edge.
+        assert_eq!(
+            snap_to_edges((1000.0, 500.0), &outputs, 8.0),
+            (1000.0, 500.0)
+        );
+        // The second output's bottom edge doesn't reach x = 100.
+        assert_eq!(
+            snap_to_edges((100.0, 1020.0), &outputs, 8.0),
+            (100.0, 1020.0)
+        );
+        assert_eq!(snap_to_edges((3.0, 4.0), &outputs, 0.0), (3.0, 4.0));
+    }
+
+    #[test]
+    fn test_selection_options_aspect_ratio() {
+        let options = SelectionOptions::new().aspect_ratio(16, 9);
+        assert_eq!(options.locked_aspect_ratio(), Some((16, 9)));
+        assert_eq!(options.aspect_ratio(0, 9).locked_aspect_ratio(), None);
+        assert_eq!(SelectionOptions::new().locked_aspect_ratio(), None);
+        assert_eq!(SelectionOptions::new().snap_distance(), 0);
+        assert_eq!(SelectionOptions::new().snap_threshold(8).snap_distance(), 8);
+    }
+
+    #[test]
+    fn test_selection_across_outputs() {
+        // Drag from the left output (-1920,0) to the right output (0,0).
+        let start = surface_to_global((-1920, 0), 1820.0, 100.0);
+        let end = surface_to_global((0, 0), 50.0, 200.0);
+        let selection = selection_box(start, end);
+        assert_eq!(selection, Box::new(-100, 100, 150, 100));
+
+        assert_eq!(
+            global_to_surface(selection, (-1920, 0)),
+            Box::new(1820, 100, 150, 100)
+        );
+        assert_eq!(
+            global_to_surface(selection, (0, 0)),
+            Box::new(-100, 100, 150, 100)
+        );
+    }
+
+    #[test]
+    fn test_draw_overlay_scales_selection_to_buffer_pixels() {
+        // 16x16 logical surface at scale 2, selection of 8x8 logical at (2, 2).
+        let (width, height) = (32, 32);
+        let mut buffer = vec![0u8; (width * height * 4) as usize];
+        draw_overlay(
+            &mut buffer,
+            width,
+            height,
+            2,
+            Some(Box::new(2, 2, 8, 8)),
+            None,
+        );
+
+        let pixel = |x: i32, y: i32| {
+            let i = ((y * width + x) * 4) as usize;
+            [buffer[i], buffer[i + 1], buffer[i + 2], buffer[i + 3]]
+        };
+        assert_eq!(pixel(0, 0), DIM_PIXEL);
+        assert_eq!(pixel(3, 10), DIM_PIXEL);
+        assert_eq!(pixel(4, 4), BORDER_PIXEL);
+        assert_eq!(pixel(7, 7), BORDER_PIXEL);
+        assert_eq!(pixel(8, 8), [0; 4]);
+        assert_eq!(pixel(15, 15), [0; 4]);
+        assert_eq!(pixel(16, 16), BORDER_PIXEL);
+        assert_eq!(pixel(19, 19), BORDER_PIXEL);
+        assert_eq!(pixel(20, 20), DIM_PIXEL);
+    }
+
+    #[test]
+    fn test_draw_overlay_over_frozen_frame() {
+        let (width, height) = (8, 8);
+        let background = [0x10, 0x80, 0xff, 0xff].repeat((width * height) as usize);
+        let mut buffer = vec![0u8; background.len()];
+        draw_overlay(
+            &mut buffer,
+            width,
+            height,
+            1,
+            Some(Box::new(2, 2, 6, 6)),
+            Some(&background),
+        );
+
+        let pixel = |x: i32, y: i32| {
+            let i = ((y * width + x) * 4) as usize;
+            [buffer[i], buffer[i + 1], buffer[i + 2], buffer[i + 3]]
+        };
+        // Dimmed but opaque outside, untouched inside the selection.
+        assert_eq!(pixel(0, 0), [0x0a, 0x50, 0x9f, 0xff]);
+        assert_eq!(pixel(2, 2), BORDER_PIXEL);
+        assert_eq!(pixel(4, 4), [0x10, 0x80, 0xff, 0xff]);
+    }
+
+    #[test]
+    fn test_frozen_background_matches_output_and_size() {
+        let capture = CaptureResult::new([1, 2, 3, 4].repeat(4 * 2), 4, 2);
+        let frozen = vec![(Box::new(-4, 0, 4, 2), capture)];
+
+        assert_eq!(
+            frozen_background(&frozen, (-4, 0), 4, 2),
+            Some([3, 2, 1, 0xff].repeat(8))
+        );
+        // Resampled to a HiDPI buffer.
+        assert_eq!(
+            frozen_background(&frozen, (-4, 0), 8, 4).map(|b| b.len()),
+            Some(8 * 4 * 4)
+        );
+        assert_eq!(frozen_background(&frozen, (0, 0), 4, 2), None);
+    }
+}
@@ -0,0 +1,277 @@
+use crate::{
+    Box, CaptureOptions, CaptureParameters, CaptureResult, Error, Grim, MultiOutputCaptureResult,
+    Output, Result,
+};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, TrySendError};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+/// Frames a [`SharedGrim::spawn_capture_stream`] receiver can fall behind by
+/// before new frames are dropped.
+const STREAM_QUEUE_LEN: usize = 2;
+
+/// Cloneable, thread-safe handle to a single [`Grim`] instance.
+///
+/// `Grim` owns per-connection Wayland state and its capture methods take
+/// `&mut self`, so it cannot be used from several threads at once.
+/// `SharedGrim` wraps one instance in an `Arc<Mutex<_>>`: every clone refers
+/// to the same Wayland connection, and requests issued from different threads
+/// are queued on the internal lock and executed one after another.
+///
+/// This is the right model for a screenshot service that receives concurrent
+/// requests but only needs one compositor connection. If captures must really
+/// run in parallel, create a separate [`Grim`] per thread instead.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use grim_rs::SharedGrim;
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let grim = SharedGrim::new()?;
+///
+/// let handles: Vec<_> = (0..4)
+///     .map(|_| {
+///         let grim = grim.clone();
+///         std::thread::spawn(move || grim.capture_all())
+///     })
+///     .collect();
+///
+/// for handle in handles {
+///     let result = handle.join().unwrap()?;
+///     println!("Captured {}x{}", result.width(), result.height());
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct SharedGrim {
+    inner: Arc<Mutex<Grim>>,
+}
+
+impl SharedGrim {
+    /// Connect to the Wayland compositor and wrap the connection in a shared handle.
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`Grim::new`].
+    pub fn new() -> Result<Self> {
+        Ok(Self::from_grim(Grim::new()?))
+    }
+
+    /// Wrap an existing [`Grim`] instance in a shared handle.
+    pub fn from_grim(grim: Grim) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(grim)),
+        }
+    }
+
+    /// Lock the underlying [`Grim`] instance.
+    ///
+    /// The lock is held until the returned guard is dropped, so keep the
+    /// critical section short. A panic in another thread while it held the
+    /// lock does not make the handle unusable: the poisoned guard is
+    /// recovered and the cached output state, which the panic may have left
+    /// half-updated, is marked stale so the next call re-reads it.
+    pub fn lock(&self) -> MutexGuard<'_, Grim> {
+        self.inner.lock().unwrap_or_else(|e| {
+            self.inner.clear_poison();
+            let mut grim = e.into_inner();
+            grim.platform_capture.invalidate_outputs();
+            grim
+        })
+    }
+
+    /// Run `f` with exclusive access to the underlying [`Grim`] instance.
+    ///
+    /// Useful for running several operations (e.g. capture and encode)
+    /// without another thread interleaving its own requests.
+    pub fn with<F, T>(&self, f: F) -> T
+    where
+        F: FnOnce(&mut Grim) -> T,
+    {
+        let mut grim = self.lock();
+        f(&mut grim)
+    }
+
+    /// Get information about available display outputs.
+    ///
+    /// See [`Grim::get_outputs`].
+    pub fn get_outputs(&self) -> Result<Vec<Output>> {
+        self.with(|grim| grim.get_outputs())
+    }
+
+    /// Capture a screenshot as described by `options`.
+    ///
+    /// See [`Grim::capture`].
+    pub fn capture(&self, options: &CaptureOptions) -> Result<CaptureResult> {
+        self.with(|grim| grim.capture(options))
+    }
+
+    /// Capture the entire screen (all outputs).
+    ///
+    /// See [`Grim::capture_all`].
+    pub fn capture_all(&self) -> Result<CaptureResult> {
+        self.with(|grim| grim.capture_all())
+    }
+
+    /// Capture a specific output by name.
+    ///
+    /// See [`Grim::capture_output`].
+    pub fn capture_output(&self, output_name: &str) -> Result<CaptureResult> {
+        self.with(|grim| grim.capture_output(output_name))
+    }
+
+    /// Capture a specific region.
+    ///
+    /// See [`Grim::capture_region`].
+    pub fn capture_region(&self, region: Box) -> Result<CaptureResult> {
+        self.with(|grim| grim.capture_region(region))
+    }
+
+    /// Capture multiple outputs with different parameters.
+    ///
+    /// See [`Grim::capture_outputs`].
+    pub fn capture_outputs(
+        &self,
+        parameters: Vec<CaptureParameters>,
+    ) -> Result<MultiOutputCaptureResult> {
+        self.with(|grim| grim.capture_outputs(parameters))
+    }
+
+    /// Capture an output `fps` times per second on a background thread.
+    ///
+    /// Every frame is a full [`Grim::capture_output`], sent through the
+    /// returned channel, which suits live previews and simple recorders. If
+    /// the receiver falls behind, new frames are dropped rather than queued,
+    /// and a capture that takes longer than the interval delays the next one
+    /// instead of bursting to catch up. The first failed capture is sent as
+    /// an `Err` and ends the stream.
+    ///
+    /// The thread runs until [`CaptureStreamHandle::stop`] is called, the
+    /// handle is dropped, or the receiver is dropped. Captures from other
+    /// clones of this handle are interleaved with the stream's.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::InvalidArgument` if `fps` is 0.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use grim_rs::SharedGrim;
+    ///
+    /// let grim = SharedGrim::new()?;
+    /// let name = grim.get_outputs()?[0].name().to_string();
+    /// let (stream, frames) = grim.spawn_capture_stream(&name, 10)?;
+    /// for frame in frames.iter().take(30) {
+    ///     let frame = frame?;
+    ///     println!("{}x{}", frame.width(), frame.height());
+    /// }
+    /// stream.stop();
+    /// # Ok::<(), grim_rs::Error>(())
+    /// ```
+    pub fn spawn_capture_stream(
+        &self,
+        output_name: &str,
+        fps: u32,
+    ) -> Result<(CaptureStreamHandle, Receiver<Result<CaptureResult>>)> {
+        if fps == 0 {
+            return Err(Error::InvalidArgument(
+                "Capture stream frame rate must be positive".to_string(),
+            ));
+        }
+        let interval = Duration::from_secs(1) / fps;
+        let (sender, receiver) = mpsc::sync_channel(STREAM_QUEUE_LEN);
+        let stop = Arc::new(AtomicBool::new(false));
+
+        let grim = self.clone();
+        let output_name = output_name.to_string();
+        let thread_stop = Arc::clone(&stop);
+        let thread = thread::Builder::new()
+            .name("grim-capture-stream".to_string())
+            .spawn(move || {
+                let mut next_frame = Instant::now();
+                while !thread_stop.load(Ordering::Acquire) {
+                    let result = grim.capture_output(&output_name);
+                    let failed = result.is_err();
+                    match sender.try_send(result) {
+                        Ok(()) => {}
+                        Err(TrySendError::Full(_)) => {
+                            log::debug!("Capture stream receiver is behind, dropping frame");
+                        }
+                        Err(TrySendError::Disconnected(_)) => break,
+                    }
+                    if failed {
+                        break;
+                    }
+
+                    next_frame = (next_frame + interval).max(Instant::now());
+                    // Woken early by `stop`.
+                    while !thread_stop.load(Ordering::Acquire) {
+                        let now = Instant::now();
+                        if now >= next_frame {
+                            break;
+                        }
+                        thread::park_timeout(next_frame - now);
+                    }
+                }
+            })?;
+
+        let handle = CaptureStreamHandle {
+            stop,
+            thread: Some(thread),
+        };
+        Ok((handle, receiver))
+    }
+}
+
+/// Handle to the thread started by [`SharedGrim::spawn_capture_stream`].
+///
+/// Dropping the handle stops the stream, like [`stop`](Self::stop).
+#[derive(Debug)]
+pub struct CaptureStreamHandle {
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl CaptureStreamHandle {
+    /// Stop capturing and wait for the thread to exit.
+    ///
+    /// A capture already in progress is finished first. The receiver then
+    /// yields the frames still queued and reports the channel as closed.
+    pub fn stop(mut self) {
+        self.shutdown();
+    }
+
+    /// Returns whether the capture thread is still running.
+    pub fn is_running(&self) -> bool {
+        self.thread
+            .as_ref()
+            .is_some_and(|thread| !thread.is_finished())
+    }
+
+    fn shutdown(&mut self) {
+        self.stop.store(true, Ordering::Release);
+        if let Some(thread) = self.thread.take() {
+            thread.thread().unpark();
+            if thread.join().is_err() {
+                log::warn!("Capture stream thread panicked");
+            }
+        }
+    }
+}
+
+impl Drop for CaptureStreamHandle {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+impl From<Grim> for SharedGrim {
+    fn from(grim: Grim) -> Self {
+        Self::from_grim(grim)
+    }
+}
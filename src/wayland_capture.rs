@@ -1,5 +1,8 @@
+use crate::error::BoxError;
 use crate::{
-    Box, CaptureParameters, CaptureResult, Error, MultiOutputCaptureResult, Output, Result,
+    Box, CaptureOptions, CaptureParameters, CaptureResult, CompositeBlend, CompositeOptions,
+    CursorImage, Error, MultiOutputCaptureResult, Output, OutputPlacement, OutputTransform, Result,
+    ScaleFilter,
 };
 use wayland_client::{
     protocol::{
@@ -19,13 +22,31 @@ use wayland_protocols_wlr::screencopy::v1::client::{
 
 const ZWLR_SCREENCOPY_FRAME_V1_FLAGS_Y_INVERT: u32 = 1;
 use std::collections::HashMap;
-use std::os::fd::{AsRawFd, BorrowedFd};
+use std::os::fd::{AsFd, AsRawFd, BorrowedFd};
 use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use wayland_client::EventQueue;
+use wayland_protocols::ext::image_capture_source::v1::client::{
+    ext_image_capture_source_v1::ExtImageCaptureSourceV1,
+    ext_output_image_capture_source_manager_v1::ExtOutputImageCaptureSourceManagerV1,
+};
+use wayland_protocols::ext::image_copy_capture::v1::client::{
+    ext_image_copy_capture_frame_v1::ExtImageCopyCaptureFrameV1,
+    ext_image_copy_capture_manager_v1::{self, ExtImageCopyCaptureManagerV1},
+    ext_image_copy_capture_session_v1::ExtImageCopyCaptureSessionV1,
+};
+#[cfg(feature = "dmabuf")]
+use wayland_protocols::wp::linux_dmabuf::zv1::client::{
+    zwp_linux_buffer_params_v1::{self, ZwpLinuxBufferParamsV1},
+    zwp_linux_dmabuf_v1::ZwpLinuxDmabufV1,
+};
 use wayland_protocols::xdg::xdg_output::zv1::client::{
     zxdg_output_manager_v1::ZxdgOutputManagerV1, zxdg_output_v1::ZxdgOutputV1,
 };
 
-const MAX_ATTEMPTS: usize = 100;
+/// How long to wait for the compositor before a capture fails with
+/// [`Error::Timeout`], unless changed with `set_capture_timeout`.
+pub(crate) const DEFAULT_CAPTURE_TIMEOUT: Duration = Duration::from_secs(5);
 
 /// Apply output transformation to width and height.
 ///
@@ -45,15 +66,13 @@ fn apply_output_transform(
     }
 }
 
-/// Safely lock a FrameState mutex, converting poisoned mutex errors to Result.
+/// Safely lock a frame state mutex, converting poisoned mutex errors to Result.
 ///
 /// This helper function provides proper error handling for mutex locks instead of panicking.
-fn lock_frame_state(
-    frame_state: &Arc<Mutex<FrameState>>,
-) -> Result<std::sync::MutexGuard<'_, FrameState>> {
+fn lock_frame_state<T>(frame_state: &Arc<Mutex<T>>) -> Result<std::sync::MutexGuard<'_, T>> {
     frame_state
         .lock()
-        .map_err(|e| Error::FrameCapture(format!("Frame state mutex poisoned: {}", e)))
+        .map_err(|e| Error::frame_capture(format!("Frame state mutex poisoned: {}", e)))
 }
 
 /// Apply transform to captured image data based on rotation and flip.
@@ -186,6 +205,366 @@ fn flip_horizontal(data: &[u8], width: u32, height: u32) -> (Vec<u8>, u32, u32)
     (flipped, width, height)
 }
 
+/// Bring a freshly copied frame into layout orientation.
+///
+/// Applies the output transform and then, if the compositor set the
+/// `y_invert` frame flag, flips the image vertically.
+fn orient_capture(
+    capture: CaptureResult,
+    transform: wayland_client::protocol::wl_output::Transform,
+    flags: u32,
+) -> CaptureResult {
+    let CaptureResult {
+        mut data,
+        mut width,
+        mut height,
+        timestamp,
+    } = capture;
+
+    if !matches!(
+        transform,
+        wayland_client::protocol::wl_output::Transform::Normal
+    ) {
+        (data, width, height) = apply_image_transform(&data, width, height, transform);
+    }
+
+    if (flags & ZWLR_SCREENCOPY_FRAME_V1_FLAGS_Y_INVERT) != 0 {
+        (data, width, height) = flip_vertical(&data, width, height);
+    }
+
+    CaptureResult {
+        data,
+        width,
+        height,
+        timestamp,
+    }
+}
+
+/// Bytes per pixel of the wl_shm formats [`convert_to_rgba`] understands.
+fn shm_bytes_per_pixel(format: ShmFormat) -> Option<u32> {
+    match format {
+        ShmFormat::Xrgb8888
+        | ShmFormat::Argb8888
+        | ShmFormat::Xbgr8888
+        | ShmFormat::Abgr8888
+        | ShmFormat::Rgbx8888
+        | ShmFormat::Rgba8888
+        | ShmFormat::Bgrx8888
+        | ShmFormat::Bgra8888
+        | ShmFormat::Xrgb2101010
+        | ShmFormat::Argb2101010
+        | ShmFormat::Xbgr2101010
+        | ShmFormat::Abgr2101010 => Some(4),
+        ShmFormat::Rgb888 | ShmFormat::Bgr888 => Some(3),
+        ShmFormat::Rgb565 | ShmFormat::Bgr565 => Some(2),
+        _ => None,
+    }
+}
+
+/// The wl_shm format the compositor asked for in its `buffer` event.
+///
+/// Guessing a format would silently produce garbled images, so a frame
+/// without a known format is rejected.
+fn advertised_format(state: &FrameState) -> Result<ShmFormat> {
+    state.format.ok_or_else(|| {
+        Error::UnsupportedBufferFormat(
+            "compositor did not advertise a known wl_shm format".to_string(),
+        )
+    })
+}
+
+/// Row length in bytes for a wl_shm buffer of `format`.
+///
+/// Uses the stride announced by the compositor when it is large enough,
+/// and the tightly packed row length otherwise.
+fn shm_stride(format: ShmFormat, width: u32, announced: u32) -> Result<u32> {
+    let bpp = shm_bytes_per_pixel(format)
+        .ok_or_else(|| Error::UnsupportedBufferFormat(format!("{:?}", format)))?;
+    Ok(announced.max(width * bpp))
+}
+
+/// Convert a wl_shm buffer to tightly packed RGBA in place.
+///
+/// `stride` is the length of a buffer row in bytes; row padding is dropped.
+/// Formats are named after a little-endian integer, so e.g. `Xrgb8888` is
+/// stored as B, G, R, X in memory. 10-bit formats keep the top 8 bits of
+/// each channel, and 5- and 6-bit channels of 16-bit formats are expanded to
+/// the full 8-bit range.
+fn convert_to_rgba(
+    format: ShmFormat,
+    data: &mut Vec<u8>,
+    width: u32,
+    height: u32,
+    stride: u32,
+) -> Result<()> {
+    let convert = pixel_converter(format)?;
+    let bpp = check_shm_buffer(format, data, width, height, stride)?;
+    let (width, height, stride) = (width as usize, height as usize, stride as usize);
+    let row_len = width * bpp;
+
+    if bpp != 4 || stride != row_len {
+        let mut packed = Vec::with_capacity(width * height * 4);
+        for row in data.chunks(stride).take(height) {
+            for px in row[..row_len].chunks_exact(bpp) {
+                packed.extend_from_slice(px);
+                packed.resize(packed.len() + (4 - bpp), 0);
+            }
+        }
+        *data = packed;
+    } else {
+        data.truncate(row_len * height);
+    }
+
+    for px in data.chunks_exact_mut(4) {
+        let converted = convert([px[0], px[1], px[2], px[3]]);
+        px.copy_from_slice(&converted);
+    }
+    Ok(())
+}
+
+/// Convert a wl_shm buffer to RGBA, shrinking it to `target_width`x`target_height`.
+///
+/// Each target pixel is the average of the block of source pixels it covers.
+/// Rows are read straight from `data`, so memory use is proportional to the
+/// target size rather than the buffer. The target must not be larger than
+/// the buffer.
+fn downscale_shm(
+    format: ShmFormat,
+    data: &[u8],
+    width: u32,
+    height: u32,
+    stride: u32,
+    target_width: u32,
+    target_height: u32,
+) -> Result<Vec<u8>> {
+    let convert = pixel_converter(format)?;
+    let bpp = check_shm_buffer(format, data, width, height, stride)?;
+    let (width, height, stride) = (width as usize, height as usize, stride as usize);
+    let (target_width, target_height) = (target_width as usize, target_height as usize);
+    if target_width == 0 || target_height == 0 || target_width > width || target_height > height {
+        return Err(Error::ScalingFailed(format!(
+            "cannot downscale {}x{} to {}x{}",
+            width, height, target_width, target_height
+        )));
+    }
+
+    let span =
+        |i: usize, source: usize, target: usize| (i * source / target, (i + 1) * source / target);
+    let columns: Vec<(usize, usize)> = (0..target_width)
+        .map(|x| span(x, width, target_width))
+        .collect();
+    let mut sums = vec![0u64; target_width * 4];
+    let mut out = Vec::with_capacity(target_width * target_height * 4);
+    for ty in 0..target_height {
+        let (y0, y1) = span(ty, height, target_height);
+        sums.fill(0);
+        for row in data[y0 * stride..].chunks(stride).take(y1 - y0) {
+            for (sum, &(x0, x1)) in sums.chunks_exact_mut(4).zip(&columns) {
+                for px in row[x0 * bpp..x1 * bpp].chunks_exact(bpp) {
+                    let mut raw = [0u8; 4];
+                    raw[..bpp].copy_from_slice(px);
+                    for (total, value) in sum.iter_mut().zip(convert(raw)) {
+                        *total += value as u64;
+                    }
+                }
+            }
+        }
+        for (sum, &(x0, x1)) in sums.chunks_exact(4).zip(&columns) {
+            let count = ((x1 - x0) * (y1 - y0)) as u64;
+            out.extend(sum.iter().map(|total| ((total + count / 2) / count) as u8));
+        }
+    }
+    Ok(out)
+}
+
+/// Largest size with the aspect ratio of `width`x`height` that fits in
+/// `max_width`x`max_height`. Never scales up.
+fn thumbnail_size(width: u32, height: u32, max_width: u32, max_height: u32) -> (u32, u32) {
+    let scale = (max_width as f64 / width as f64)
+        .min(max_height as f64 / height as f64)
+        .min(1.0);
+    let fit = |size: u32, max: u32| ((size as f64 * scale).round() as u32).clamp(1, max.min(size));
+    (fit(width, max_width), fit(height, max_height))
+}
+
+/// Shrink `capture` to fit in `max_width`x`max_height`, keeping its aspect ratio.
+fn shrink_to_fit(capture: CaptureResult, max_width: u32, max_height: u32) -> Result<CaptureResult> {
+    let (width, height) = thumbnail_size(capture.width, capture.height, max_width, max_height);
+    if (width, height) == (capture.width, capture.height) {
+        return Ok(capture);
+    }
+    let scale = width as f64 / capture.width as f64;
+    resize_capture(
+        capture,
+        width,
+        height,
+        filter_type(ScaleFilter::Auto, scale),
+    )
+}
+
+/// Validate the size of a wl_shm buffer and return its bytes per pixel.
+fn check_shm_buffer(
+    format: ShmFormat,
+    data: &[u8],
+    width: u32,
+    height: u32,
+    stride: u32,
+) -> Result<usize> {
+    let bpp = shm_bytes_per_pixel(format)
+        .ok_or_else(|| Error::UnsupportedBufferFormat(format!("{:?}", format)))?
+        as usize;
+    let row_len = width as usize * bpp;
+    if (stride as usize) < row_len || data.len() < stride as usize * height as usize {
+        return Err(Error::frame_capture(format!(
+            "buffer of {} bytes is too small for {}x{} {:?} with stride {}",
+            data.len(),
+            width,
+            height,
+            format,
+            stride
+        )));
+    }
+    Ok(bpp)
+}
+
+/// Per-pixel conversion from `format` to RGBA.
+///
+/// Pixels narrower than 4 bytes are passed zero-padded.
+fn pixel_converter(format: ShmFormat) -> Result<fn([u8; 4]) -> [u8; 4]> {
+    fn alpha2(value: u32) -> u8 {
+        ((value >> 30) * 85) as u8
+    }
+
+    /// Expand the `bits`-wide channel at `shift` to 8 bits.
+    fn channel(value: u16, shift: u32, bits: u32) -> u8 {
+        let v = (value >> shift) & ((1 << bits) - 1);
+        ((v << (8 - bits)) | (v >> (2 * bits - 8))) as u8
+    }
+
+    let convert: fn([u8; 4]) -> [u8; 4] = match format {
+        ShmFormat::Xrgb8888 => |[b, g, r, _]: [u8; 4]| [r, g, b, 255],
+        ShmFormat::Argb8888 => |[b, g, r, a]: [u8; 4]| [r, g, b, a],
+        ShmFormat::Xbgr8888 => |[r, g, b, _]: [u8; 4]| [r, g, b, 255],
+        ShmFormat::Abgr8888 => |[r, g, b, a]: [u8; 4]| [r, g, b, a],
+        ShmFormat::Rgbx8888 => |[_, b, g, r]: [u8; 4]| [r, g, b, 255],
+        ShmFormat::Rgba8888 => |[a, b, g, r]: [u8; 4]| [r, g, b, a],
+        ShmFormat::Bgrx8888 => |[_, r, g, b]: [u8; 4]| [r, g, b, 255],
+        ShmFormat::Bgra8888 => |[a, r, g, b]: [u8; 4]| [r, g, b, a],
+        ShmFormat::Xrgb2101010 => |px: [u8; 4]| {
+            let v = u32::from_le_bytes(px);
+            [(v >> 22) as u8, (v >> 12) as u8, (v >> 2) as u8, 255]
+        },
+        ShmFormat::Argb2101010 => |px: [u8; 4]| {
+            let v = u32::from_le_bytes(px);
+            [(v >> 22) as u8, (v >> 12) as u8, (v >> 2) as u8, alpha2(v)]
+        },
+        ShmFormat::Xbgr2101010 => |px: [u8; 4]| {
+            let v = u32::from_le_bytes(px);
+            [(v >> 2) as u8, (v >> 12) as u8, (v >> 22) as u8, 255]
+        },
+        ShmFormat::Abgr2101010 => |px: [u8; 4]| {
+            let v = u32::from_le_bytes(px);
+            [(v >> 2) as u8, (v >> 12) as u8, (v >> 22) as u8, alpha2(v)]
+        },
+        // 24-bit pixels are widened to [b0, b1, b2, 0] below.
+        ShmFormat::Rgb888 => |[b, g, r, _]: [u8; 4]| [r, g, b, 255],
+        ShmFormat::Bgr888 => |[r, g, b, _]: [u8; 4]| [r, g, b, 255],
+        // 16-bit pixels are widened to [b0, b1, 0, 0] below.
+        ShmFormat::Rgb565 => |[b0, b1, _, _]: [u8; 4]| {
+            let v = u16::from_le_bytes([b0, b1]);
+            [channel(v, 11, 5), channel(v, 5, 6), channel(v, 0, 5), 255]
+        },
+        ShmFormat::Bgr565 => |[b0, b1, _, _]: [u8; 4]| {
+            let v = u16::from_le_bytes([b0, b1]);
+            [channel(v, 0, 5), channel(v, 5, 6), channel(v, 11, 5), 255]
+        },
+        _ => return Err(Error::UnsupportedBufferFormat(format!("{:?}", format))),
+    };
+    Ok(convert)
+}
+
+/// Time of a screencopy `ready` event, split into 64-bit seconds and nanoseconds.
+fn frame_timestamp(tv_sec_hi: u32, tv_sec_lo: u32, tv_nsec: u32) -> Duration {
+    let secs = ((tv_sec_hi as u64) << 32) | tv_sec_lo as u64;
+    Duration::new(secs, tv_nsec)
+}
+
+/// Map an output-local logical region to a crop box in buffer pixels.
+///
+/// Returns `None` if the region does not overlap the buffer.
+fn buffer_crop_box(
+    region: Box,
+    logical_width: i32,
+    logical_height: i32,
+    buffer_width: u32,
+    buffer_height: u32,
+) -> Option<Box> {
+    let scale_x = buffer_width as f64 / logical_width as f64;
+    let scale_y = buffer_height as f64 / logical_height as f64;
+    let x = (region.x() as f64 * scale_x).round() as i32;
+    let y = (region.y() as f64 * scale_y).round() as i32;
+    let right = ((region.x() + region.width()) as f64 * scale_x).round() as i32;
+    let bottom = ((region.y() + region.height()) as f64 * scale_y).round() as i32;
+    Box::new(x, y, right - x, bottom - y).intersection(&Box::new(
+        0,
+        0,
+        buffer_width as i32,
+        buffer_height as i32,
+    ))
+}
+
+/// Fill the `exclusions`, given in layout coordinates, of a capture of the
+/// layout `region` with `color`.
+///
+/// The capture may be larger than `region` on HiDPI outputs, so exclusions
+/// are scaled to buffer pixels the same way crops are. Parts outside the
+/// capture are ignored.
+fn apply_exclusions(
+    mut capture: CaptureResult,
+    region: Box,
+    exclusions: &[Box],
+    color: [u8; 4],
+) -> CaptureResult {
+    for exclusion in exclusions {
+        let local = exclusion.translate(-region.x(), -region.y());
+        if let Some(pixels) = buffer_crop_box(
+            local,
+            region.width(),
+            region.height(),
+            capture.width,
+            capture.height,
+        ) {
+            capture.fill_rect(pixels, color);
+        }
+    }
+    capture
+}
+
+/// Blend `cursor` onto a capture of the layout `region` with its hotspot at
+/// `pointer`, given in layout coordinates.
+fn draw_software_cursor(
+    capture: &mut CaptureResult,
+    region: Box,
+    cursor: &CursorImage,
+    pointer: (f64, f64),
+) -> Result<()> {
+    if region.is_empty() {
+        return Ok(());
+    }
+    let scale_x = capture.width as f64 / region.width() as f64;
+    let scale_y = capture.height as f64 / region.height() as f64;
+    let x = ((pointer.0 - region.x() as f64) * scale_x).floor() as i32;
+    let y = ((pointer.1 - region.y() as f64) * scale_y).floor() as i32;
+    crate::blend_cursor(
+        &mut capture.data,
+        capture.width,
+        capture.height,
+        cursor,
+        x,
+        y,
+    )
+}
+
 /// Flip image vertically.
 fn flip_vertical(data: &[u8], width: u32, height: u32) -> (Vec<u8>, u32, u32) {
     let mut flipped = vec![0u8; (width * height * 4) as usize];
@@ -218,6 +597,57 @@ fn guess_output_logical_geometry(info: &mut OutputInfo) {
     info.logical_scale_known = true;
 }
 
+/// Map a `wl_output` transform to the public [`OutputTransform`].
+fn output_transform(transform: wayland_client::protocol::wl_output::Transform) -> OutputTransform {
+    use wayland_client::protocol::wl_output::Transform;
+    match transform {
+        Transform::_90 => OutputTransform::Rotated90,
+        Transform::_180 => OutputTransform::Rotated180,
+        Transform::_270 => OutputTransform::Rotated270,
+        Transform::Flipped => OutputTransform::Flipped,
+        Transform::Flipped90 => OutputTransform::Flipped90,
+        Transform::Flipped180 => OutputTransform::Flipped180,
+        Transform::Flipped270 => OutputTransform::Flipped270,
+        _ => OutputTransform::Normal,
+    }
+}
+
+/// Placement of a capture of `region` (or the whole output) on `info`.
+fn output_placement(info: &OutputInfo, region: Option<&Box>) -> OutputPlacement {
+    let geometry = match region {
+        Some(region) => *region,
+        None => public_output(info).geometry,
+    };
+    OutputPlacement::new(geometry, info.scale)
+}
+
+/// Build the public description of an output.
+fn public_output(info: &OutputInfo) -> Output {
+    let (x, y, width, height) = if info.logical_scale_known {
+        (
+            info.logical_x,
+            info.logical_y,
+            info.logical_width,
+            info.logical_height,
+        )
+    } else {
+        (info.x, info.y, info.width, info.height)
+    };
+
+    Output {
+        name: info.name.clone(),
+        geometry: Box::new(x, y, width, height),
+        scale: info.scale,
+        description: info.description.clone(),
+        refresh_rate_mhz: (info.refresh_mhz > 0).then_some(info.refresh_mhz),
+        transform: output_transform(info.transform),
+        physical_size_mm: (info.physical_width > 0 && info.physical_height > 0)
+            .then_some((info.physical_width as u32, info.physical_height as u32)),
+        make: info.make.clone(),
+        model: info.model.clone(),
+    }
+}
+
 fn blit_capture(
     dest: &mut [u8],
     dest_width: usize,
@@ -253,7 +683,47 @@ fn blit_capture(
     }
 }
 
-/// Check if outputs have overlapping regions.
+/// Draw `capture` over `dest` at `(offset_x, offset_y)` with straight-alpha
+/// "over" blending. The capture must fit inside `dest`.
+fn blend_capture(
+    dest: &mut [u8],
+    dest_width: usize,
+    capture: &CaptureResult,
+    offset_x: usize,
+    offset_y: usize,
+) {
+    let row_len = capture.width as usize * 4;
+    if row_len == 0 {
+        return;
+    }
+    for (row, src_row) in capture.data.chunks_exact(row_len).enumerate() {
+        let start = ((offset_y + row) * dest_width + offset_x) * 4;
+        for (dst, src) in dest[start..start + row_len]
+            .chunks_exact_mut(4)
+            .zip(src_row.chunks_exact(4))
+        {
+            blend_pixel(dst, src);
+        }
+    }
+}
+
+/// Composite the straight-alpha RGBA pixel `src` over `dst` in place.
+pub(crate) fn blend_pixel(dst: &mut [u8], src: &[u8]) {
+    let src_alpha = src[3] as u32;
+    let dst_weight = dst[3] as u32 * (255 - src_alpha) / 255;
+    let out_alpha = src_alpha + dst_weight;
+    if out_alpha == 0 {
+        dst.copy_from_slice(&[0, 0, 0, 0]);
+        return;
+    }
+    let (color, alpha) = dst.split_at_mut(3);
+    for (dst_channel, &src_channel) in color.iter_mut().zip(&src[..3]) {
+        let value = src_channel as u32 * src_alpha + *dst_channel as u32 * dst_weight;
+        *dst_channel = ((value + out_alpha / 2) / out_alpha) as u8;
+    }
+    alpha[0] = out_alpha as u8;
+}
+
 #[derive(Clone)]
 struct OutputInfo {
     name: String,
@@ -269,614 +739,1044 @@ struct OutputInfo {
     logical_height: i32,
     logical_scale_known: bool,
     description: Option<String>,
+    refresh_mhz: u32,
+    physical_width: i32,
+    physical_height: i32,
+    make: String,
+    model: String,
 }
 
-struct WaylandGlobals {
-    compositor: Option<WlCompositor>,
-    shm: Option<WlShm>,
-    screencopy_manager: Option<ZwlrScreencopyManagerV1>,
-    xdg_output_manager: Option<ZxdgOutputManagerV1>,
-    outputs: Vec<WlOutput>,
-    output_info: HashMap<u32, OutputInfo>,
-    output_xdg_map: HashMap<u32, ZxdgOutputV1>,
+impl OutputInfo {
+    fn new(name: String) -> Self {
+        Self {
+            name,
+            width: 0,
+            height: 0,
+            x: 0,
+            y: 0,
+            scale: 1,
+            transform: wayland_client::protocol::wl_output::Transform::Normal,
+            logical_x: 0,
+            logical_y: 0,
+            logical_width: 0,
+            logical_height: 0,
+            logical_scale_known: false,
+            description: None,
+            refresh_mhz: 0,
+            physical_width: 0,
+            physical_height: 0,
+            make: String::new(),
+            model: String::new(),
+        }
+    }
+
+    fn logical_box(&self) -> Box {
+        Box::new(
+            self.logical_x,
+            self.logical_y,
+            self.logical_width,
+            self.logical_height,
+        )
+    }
 }
 
-pub struct WaylandCapture {
-    _connection: Connection,
-    globals: WaylandGlobals,
+/// Smallest box in the global logical coordinate space that contains every output.
+fn outputs_bounding_box<'a>(infos: impl IntoIterator<Item = &'a OutputInfo>) -> Option<Box> {
+    let boxes: Vec<Box> = infos.into_iter().map(OutputInfo::logical_box).collect();
+    Box::union_all(&boxes)
 }
 
-impl WaylandCapture {
-    pub fn new() -> Result<Self> {
-        let connection = Connection::connect_to_env().map_err(|e| {
-            Error::WaylandConnection(format!("Failed to connect to Wayland: {}", e))
-        })?;
-        let globals = WaylandGlobals {
-            compositor: None,
-            shm: None,
-            screencopy_manager: None,
-            xdg_output_manager: None,
-            outputs: Vec::new(),
-            output_info: HashMap::new(),
-            output_xdg_map: HashMap::new(),
-        };
-        let mut event_queue = connection.new_event_queue();
-        let qh = event_queue.handle();
-        let _registry = connection.display().get_registry(&qh, ());
-        let mut instance = Self {
-            _connection: connection,
-            globals,
-        };
-        event_queue.roundtrip(&mut instance).map_err(|e| {
-            Error::WaylandConnection(format!("Failed to initialize Wayland globals: {}", e))
-        })?;
-        if instance.globals.screencopy_manager.is_none() {
-            return Err(Error::UnsupportedProtocol(
-                "zwlr_screencopy_manager_v1 not available".to_string(),
-            ));
-        }
-        if instance.globals.shm.is_none() {
-            return Err(Error::UnsupportedProtocol(
-                "wl_shm not available".to_string(),
-            ));
+/// Work out which part of `region` every output covers.
+///
+/// Returns `(output index, intersection)` pairs, with the intersection in
+/// global logical coordinates. The intersection's offset from the region
+/// origin is where that output's pixels go in the composite, so outputs are
+/// always placed at their logical position.
+fn composite_plan<'a>(
+    region: Box,
+    infos: impl IntoIterator<Item = &'a OutputInfo>,
+) -> Vec<(usize, Box)> {
+    infos
+        .into_iter()
+        .enumerate()
+        .filter_map(|(index, info)| {
+            info.logical_box()
+                .intersection(&region)
+                .filter(|intersection| !intersection.is_empty())
+                .map(|intersection| (index, intersection))
+        })
+        .collect()
+}
+
+/// Arguments of a `zwlr_screencopy_manager_v1.capture_output_region` request.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct ScreencopyRequest {
+    overlay_cursor: i32,
+    region: Box,
+}
+
+impl ScreencopyRequest {
+    fn new(region: Box, overlay_cursor: bool) -> Self {
+        Self {
+            overlay_cursor: if overlay_cursor { 1 } else { 0 },
+            region,
         }
-        Ok(instance)
     }
+}
 
-    fn refresh_outputs(&mut self) -> Result<()> {
-        self.globals.outputs.clear();
-        self.globals.output_info.clear();
-        self.globals.output_xdg_map.clear();
+/// Build the screencopy requests needed to composite `region`.
+///
+/// Returns `(output index, logical intersection, request)` triples; the
+/// request region is local to the output and in buffer pixels.
+fn composite_requests<'a>(
+    region: Box,
+    infos: &'a [&'a OutputInfo],
+    overlay_cursor: bool,
+) -> Vec<(usize, Box, ScreencopyRequest)> {
+    composite_plan(region, infos.iter().copied())
+        .into_iter()
+        .map(|(index, intersection)| {
+            let info = infos[index];
+            let scale = info.scale as f64;
+            let physical_local_region = Box::new(
+                (((intersection.x() - info.logical_x) as f64) * scale) as i32,
+                (((intersection.y() - info.logical_y) as f64) * scale) as i32,
+                ((intersection.width() as f64) * scale) as i32,
+                ((intersection.height() as f64) * scale) as i32,
+            );
+            (
+                index,
+                intersection,
+                ScreencopyRequest::new(physical_local_region, overlay_cursor),
+            )
+        })
+        .collect()
+}
 
-        let mut event_queue = self._connection.new_event_queue();
-        let qh = event_queue.handle();
+/// Composite the parts of `infos` that overlap `region` into one image.
+///
+/// `capture` is called once per overlapping output with the output's index
+/// and the request for its part of the region, in buffer pixels. Each
+/// capture is scaled back to logical size and copied to the output's logical
+/// position; areas no output covers keep `background`.
+fn composite_outputs<F>(
+    region: Box,
+    infos: &[&OutputInfo],
+    overlay_cursor: bool,
+    background: [u8; 4],
+    mut capture: F,
+) -> Result<CaptureResult>
+where
+    F: FnMut(usize, &ScreencopyRequest) -> Result<CaptureResult>,
+{
+    if region.width() <= 0 || region.height() <= 0 {
+        return Err(Error::InvalidRegion(
+            "Capture region must have positive width and height".to_string(),
+        ));
+    }
 
-        let _registry = self._connection.display().get_registry(&qh, ());
+    let requests = composite_requests(region, infos, overlay_cursor);
+    if requests.is_empty() {
+        return Err(Error::InvalidRegion(
+            "Capture region does not intersect with any output".to_string(),
+        ));
+    }
 
-        event_queue.roundtrip(self).map_err(|e| {
-            Error::WaylandConnection(format!("Failed to refresh Wayland globals: {}", e))
-        })?;
-        if self.globals.output_info.is_empty() {
-            return Err(Error::NoOutputs);
+    let dest_width = region.width() as usize;
+    let dest_height = region.height() as usize;
+    let mut dest = background.repeat(dest_width * dest_height);
+
+    for (index, intersection, request) in &requests {
+        let scale = infos[*index].scale as f64;
+        let mut output_capture = capture(*index, request)?;
+        if scale != 1.0 {
+            output_capture = scale_capture(output_capture, 1.0 / scale, ScaleFilter::Auto)?;
         }
+        let output_capture = fit_capture(
+            output_capture,
+            intersection.width() as u32,
+            intersection.height() as u32,
+        )?;
 
-        for _ in 0..2 {
-            event_queue.roundtrip(self).map_err(|e| {
-                Error::WaylandConnection(format!("Failed to process output events: {}", e))
-            })?;
+        blit_capture(
+            &mut dest,
+            dest_width,
+            dest_height,
+            &output_capture,
+            (intersection.x() - region.x()) as usize,
+            (intersection.y() - region.y()) as usize,
+        );
+    }
+
+    Ok(CaptureResult::new(
+        dest,
+        region.width() as u32,
+        region.height() as u32,
+    ))
+}
+
+/// Reject regions screencopy can't capture from an output.
+fn check_output_region(region: Box) -> Result<()> {
+    if region.width() <= 0 || region.height() <= 0 {
+        return Err(Error::InvalidRegion(
+            "Capture region must have positive width and height".to_string(),
+        ));
+    }
+    if region.x() < 0 || region.y() < 0 {
+        return Err(Error::InvalidRegion(
+            "Capture region origin must be non-negative".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// One frame a capture needs: part of an output, in buffer pixels.
+pub(crate) struct FrameJob {
+    output: WlOutput,
+    request: ScreencopyRequest,
+}
+
+impl FrameJob {
+    fn overlay_cursor(&self) -> bool {
+        self.request.overlay_cursor != 0
+    }
+}
+
+/// Frames to copy for a [`CaptureOptions`] request and how to put them
+/// together.
+///
+/// Built by [`WaylandCapture::plan_capture`]. The frames listed by
+/// [`jobs`](Self::jobs) can be copied in any way, blocking or not, before
+/// [`assemble`](Self::assemble) turns them into the final image.
+pub(crate) struct CapturePlan {
+    /// Logical area covered by the final image.
+    region: Box,
+    target: PlanTarget,
+    overlay_cursor: bool,
+    options: CaptureOptions,
+}
+
+enum PlanTarget {
+    /// A whole output, copied as a single frame.
+    Output(WlOutput, ScreencopyRequest),
+    /// Parts of these outputs, composited at their logical positions.
+    Composite(Vec<(WlOutput, OutputInfo)>),
+}
+
+impl CapturePlan {
+    /// The frames to copy, in the order [`assemble`](Self::assemble) asks
+    /// for them.
+    #[cfg_attr(not(feature = "tokio"), allow(dead_code))]
+    pub(crate) fn jobs(&self) -> Vec<FrameJob> {
+        match &self.target {
+            PlanTarget::Output(output, request) => vec![FrameJob {
+                output: output.clone(),
+                request: *request,
+            }],
+            PlanTarget::Composite(outputs) => {
+                let infos: Vec<&OutputInfo> = outputs.iter().map(|(_, info)| info).collect();
+                composite_requests(self.region, &infos, self.overlay_cursor)
+                    .into_iter()
+                    .map(|(index, _, request)| FrameJob {
+                        output: outputs[index].0.clone(),
+                        request,
+                    })
+                    .collect()
+            }
         }
+    }
 
-        if self.globals.xdg_output_manager.is_none() {
-            for info in self.globals.output_info.values_mut() {
-                if !info.logical_scale_known {
-                    guess_output_logical_geometry(info);
-                }
+    /// Build the final image, calling `capture` for each frame of
+    /// [`jobs`](Self::jobs) in order, then apply exclusions, the software
+    /// cursor and scaling.
+    pub(crate) fn assemble<F>(self, mut capture: F) -> Result<CaptureResult>
+    where
+        F: FnMut(&FrameJob) -> Result<CaptureResult>,
+    {
+        let result = match &self.target {
+            PlanTarget::Output(output, request) => capture(&FrameJob {
+                output: output.clone(),
+                request: *request,
+            })?,
+            PlanTarget::Composite(outputs) => {
+                let infos: Vec<&OutputInfo> = outputs.iter().map(|(_, info)| info).collect();
+                composite_outputs(
+                    self.region,
+                    &infos,
+                    self.overlay_cursor,
+                    self.options.background_fill(),
+                    |index, request| {
+                        capture(&FrameJob {
+                            output: outputs[index].0.clone(),
+                            request: *request,
+                        })
+                    },
+                )?
+            }
+        };
+
+        let options = &self.options;
+        let mut result = apply_exclusions(
+            result,
+            self.region,
+            options.exclusions(),
+            options.exclusion_fill(),
+        );
+        if let Some(cursor) = options.cursor_image() {
+            match crate::region_select::pointer_position() {
+                Ok(pointer) => draw_software_cursor(&mut result, self.region, cursor, pointer)?,
+                Err(e) => log::debug!("Not drawing software cursor: {}", e),
             }
         }
+        match options.scale_factor() {
+            Some(scale) => scale_capture(result, scale, options.filter_type()),
+            None => Ok(result),
+        }
+    }
+}
 
-        Ok(())
+/// Resample `capture` to exactly `width`x`height` if it isn't that size already.
+///
+/// Keeps every output's contribution to a composite exactly as large as its
+/// logical footprint, so neighbouring outputs never overlap or leave gaps
+/// when the buffer size doesn't divide evenly by the output scale.
+fn fit_capture(capture: CaptureResult, width: u32, height: u32) -> Result<CaptureResult> {
+    if capture.width == width && capture.height == height {
+        return Ok(capture);
     }
+    resize_capture(
+        capture,
+        width,
+        height,
+        image::imageops::FilterType::Triangle,
+    )
+}
 
-    fn collect_outputs_snapshot(&self) -> Vec<(WlOutput, OutputInfo)> {
-        self.globals
-            .outputs
-            .iter()
-            .filter_map(|output| {
-                let id = output.id().protocol_id();
-                self.globals
-                    .output_info
-                    .get(&id)
-                    .cloned()
-                    .map(|info| (output.clone(), info))
-            })
-            .collect()
+/// Scale `capture` by `scale`, picking the resampling filter from `filter`.
+///
+/// With [`ScaleFilter::Auto`], 2x, 3x and 4x upscaling takes a fast
+/// integer path that duplicates pixels without resampling.
+pub(crate) fn scale_capture(
+    capture: CaptureResult,
+    scale: f64,
+    filter: ScaleFilter,
+) -> Result<CaptureResult> {
+    if scale == 1.0 {
+        return Ok(capture);
     }
 
-    fn capture_region_for_output(
-        &mut self,
-        output: &WlOutput,
-        region: Box,
-        overlay_cursor: bool,
-    ) -> Result<CaptureResult> {
-        if region.width() <= 0 || region.height() <= 0 {
-            return Err(Error::InvalidRegion(
-                "Capture region must have positive width and height".to_string(),
-            ));
-        }
-        if region.x() < 0 || region.y() < 0 {
-            return Err(Error::InvalidRegion(
-                "Capture region origin must be non-negative".to_string(),
-            ));
-        }
+    let scale_int = scale as u32;
+    if filter == ScaleFilter::Auto
+        && scale > 1.0
+        && (scale - (scale_int as f64)).abs() < 0.01
+        && (2..=4).contains(&scale_int)
+    {
+        return Ok(scale_integer_fast(capture, scale_int));
+    }
 
-        let screencopy_manager =
-            self.globals
-                .screencopy_manager
-                .as_ref()
-                .ok_or(Error::UnsupportedProtocol(
-                    "zwlr_screencopy_manager_v1 not available".to_string(),
-                ))?;
-        let mut event_queue = self._connection.new_event_queue();
-        let qh = event_queue.handle();
-        let frame_state = Arc::new(Mutex::new(FrameState {
-            buffer: None,
-            width: 0,
-            height: 0,
-            format: None,
-            ready: false,
-            flags: 0,
-        }));
-        let frame = screencopy_manager.capture_output_region(
-            if overlay_cursor { 1 } else { 0 },
-            output,
-            region.x(),
-            region.y(),
-            region.width(),
-            region.height(),
-            &qh,
-            frame_state.clone(),
-        );
+    let new_width = ((capture.width as f64) * scale) as u32;
+    let new_height = ((capture.height as f64) * scale) as u32;
+    if new_width == 0 || new_height == 0 {
+        return Err(Error::InvalidRegion(
+            "Scaled dimensions must be positive".to_string(),
+        ));
+    }
 
-        let mut attempts = 0;
-        loop {
-            {
-                let state = lock_frame_state(&frame_state)?;
-                if state.buffer.is_some() || state.ready {
-                    if state.ready && state.buffer.is_none() {
-                        return Err(Error::FrameCapture(
-                            "Frame is ready but buffer was not received".to_string(),
-                        ));
-                    }
-                    break;
-                }
-            }
-            if attempts >= MAX_ATTEMPTS {
-                return Err(Error::FrameCapture(
-                    "Timeout waiting for frame buffer".to_string(),
-                ));
-            }
-            event_queue.blocking_dispatch(self).map_err(|e| {
-                Error::FrameCapture(format!("Failed to dispatch frame events: {}", e))
-            })?;
-            attempts += 1;
-        }
+    resize_capture(capture, new_width, new_height, filter_type(filter, scale))
+}
 
-        let shm = self
-            .globals
-            .shm
-            .as_ref()
-            .ok_or_else(|| Error::UnsupportedProtocol("wl_shm not available".to_string()))?;
+/// Resolve a [`ScaleFilter`] to an `image` filter for the given scale factor.
+pub(crate) fn filter_type(filter: ScaleFilter, scale: f64) -> image::imageops::FilterType {
+    use image::imageops::FilterType;
+    match filter {
+        ScaleFilter::Auto if scale > 1.0 => FilterType::Nearest,
+        ScaleFilter::Auto if scale >= 0.75 => FilterType::Triangle,
+        ScaleFilter::Auto if scale >= 0.5 => FilterType::CatmullRom,
+        ScaleFilter::Auto => FilterType::Lanczos3,
+        ScaleFilter::Nearest => FilterType::Nearest,
+        ScaleFilter::Triangle => FilterType::Triangle,
+        ScaleFilter::CatmullRom => FilterType::CatmullRom,
+        ScaleFilter::Lanczos3 => FilterType::Lanczos3,
+    }
+}
 
-        let (width, height, stride, size, format) = {
-            let state = lock_frame_state(&frame_state)?;
-            if state.width == 0 || state.height == 0 {
-                return Err(Error::CaptureFailed);
-            }
-            let width = state.width;
-            let height = state.height;
-            let stride = width * 4;
-            let size = (stride * height) as usize;
-            let format = state.format.unwrap_or(ShmFormat::Xrgb8888);
-            (width, height, stride, size, format)
-        };
+/// Resample `capture` to `width`x`height` with `filter`.
+pub(crate) fn resize_capture(
+    capture: CaptureResult,
+    width: u32,
+    height: u32,
+    filter: image::imageops::FilterType,
+) -> Result<CaptureResult> {
+    use image::{imageops, ImageBuffer, Rgba};
 
-        let mut tmp_file = tempfile::NamedTempFile::new().map_err(|e| {
-            Error::BufferCreation(format!("failed to create temporary file: {}", e))
-        })?;
-        tmp_file.as_file_mut().set_len(size as u64).map_err(|e| {
-            Error::BufferCreation(format!("failed to resize buffer to {} bytes: {}", size, e))
+    let (old_width, old_height) = (capture.width, capture.height);
+    let img = ImageBuffer::<Rgba<u8>, Vec<u8>>::from_raw(old_width, old_height, capture.data)
+        .ok_or_else(|| {
+            Error::ScalingFailed(format!(
+                "failed to create image buffer for scaling {}x{} -> {}x{}",
+                old_width, old_height, width, height
+            ))
         })?;
-        let mmap = unsafe {
-            memmap2::MmapMut::map_mut(&tmp_file)
-                .map_err(|e| Error::BufferCreation(format!("failed to memory-map buffer: {}", e)))?
-        };
+    let resized = imageops::resize(&img, width, height, filter);
 
-        let pool = shm.create_pool(
-            unsafe { BorrowedFd::borrow_raw(tmp_file.as_file().as_raw_fd()) },
-            size as i32,
-            &qh,
-            (),
-        );
-        let buffer = pool.create_buffer(
-            0,
-            width as i32,
-            height as i32,
-            stride as i32,
-            format,
-            &qh,
-            (),
-        );
-        frame.copy(&buffer);
+    Ok(CaptureResult {
+        data: resized.into_raw(),
+        width,
+        height,
+        timestamp: capture.timestamp,
+    })
+}
 
-        let mut attempts = 0;
-        loop {
-            {
-                let state = lock_frame_state(&frame_state)?;
-                if state.ready {
-                    if state.buffer.is_none() {
-                        return Err(Error::FrameCapture(
-                            "Frame is ready but buffer was not received".to_string(),
-                        ));
-                    }
-                    break;
-                }
-            }
-            if attempts >= MAX_ATTEMPTS {
-                return Err(Error::FrameCapture(
-                    "Timeout waiting for frame capture completion".to_string(),
-                ));
-            }
-            event_queue.blocking_dispatch(self).map_err(|e| {
-                Error::FrameCapture(format!("Failed to dispatch frame events: {}", e))
-            })?;
-            attempts += 1;
-        }
-
-        let mut buffer_data = mmap.to_vec();
-        match format {
-            ShmFormat::Xrgb8888 => {
-                for chunk in buffer_data.chunks_exact_mut(4) {
-                    let b = chunk[0];
-                    let g = chunk[1];
-                    let r = chunk[2];
-                    chunk[0] = r;
-                    chunk[1] = g;
-                    chunk[2] = b;
-                    chunk[3] = 255;
-                }
-            }
-            ShmFormat::Argb8888 => {}
-            _ => {}
-        }
+/// Fast scaling for integer multipliers (2x, 3x, 4x)
+///
+/// Uses nearest neighbor without floating point operations for maximum performance.
+/// Each pixel from the source image is duplicated into a factor×factor block of pixels.
+///
+/// # Performance
+///
+/// This implementation is 20-30x faster than `image::imageops::resize` because it:
+/// - Avoids roundf calls (~258ms for 30M pixels)
+/// - Avoids float→u8 conversion (~241ms)
+/// - Avoids exp calls in interpolation (~223ms)
+/// - Uses simple memory block copying
+pub(crate) fn scale_integer_fast(capture: CaptureResult, factor: u32) -> CaptureResult {
+    let old_width = capture.width as usize;
+    let old_height = capture.height as usize;
+    let new_width = old_width * (factor as usize);
+    let new_height = old_height * (factor as usize);
 
-        let output_id = output.id().protocol_id();
-        let mut final_data = buffer_data;
-        let mut final_width = width;
-        let mut final_height = height;
+    let mut new_data = vec![0u8; new_width * new_height * 4];
 
-        if let Some(info) = self.globals.output_info.get(&output_id) {
-            if !matches!(
-                info.transform,
-                wayland_client::protocol::wl_output::Transform::Normal
-            ) {
-                let (transformed_data, new_width, new_height) =
-                    apply_image_transform(&final_data, final_width, final_height, info.transform);
-                final_data = transformed_data;
-                final_width = new_width;
-                final_height = new_height;
-            }
-        }
+    for old_y in 0..old_height {
+        for old_x in 0..old_width {
+            let old_idx = (old_y * old_width + old_x) * 4;
+            let pixel = [
+                capture.data[old_idx],
+                capture.data[old_idx + 1],
+                capture.data[old_idx + 2],
+                capture.data[old_idx + 3],
+            ];
 
-        let flags = {
-            let state = lock_frame_state(&frame_state)?;
-            state.flags
-        };
+            for dy in 0..factor as usize {
+                for dx in 0..factor as usize {
+                    let new_x = old_x * (factor as usize) + dx;
+                    let new_y = old_y * (factor as usize) + dy;
+                    let new_idx = (new_y * new_width + new_x) * 4;
 
-        if (flags & ZWLR_SCREENCOPY_FRAME_V1_FLAGS_Y_INVERT) != 0 {
-            let (inverted_data, inv_width, inv_height) =
-                flip_vertical(&final_data, final_width, final_height);
-            final_data = inverted_data;
-            final_width = inv_width;
-            final_height = inv_height;
+                    new_data[new_idx..new_idx + 4].copy_from_slice(&pixel);
+                }
+            }
         }
-
-        Ok(CaptureResult {
-            data: final_data,
-            width: final_width,
-            height: final_height,
-        })
     }
 
-    fn composite_region(
-        &mut self,
-        region: Box,
-        outputs: &[(WlOutput, OutputInfo)],
-        overlay_cursor: bool,
-    ) -> Result<CaptureResult> {
-        if region.width() <= 0 || region.height() <= 0 {
-            return Err(Error::InvalidRegion(
-                "Capture region must have positive width and height".to_string(),
-            ));
-        }
-
-        let dest_width = region.width() as usize;
-        let dest_height = region.height() as usize;
-        let mut dest = vec![0u8; dest_width * dest_height * 4];
-        let mut any_capture = false;
-
-        for (output, info) in outputs {
-            let output_box = Box::new(
-                info.logical_x,
-                info.logical_y,
-                info.logical_width,
-                info.logical_height,
-            );
-            if let Some(intersection) = output_box.intersection(&region) {
-                if intersection.width() <= 0 || intersection.height() <= 0 {
-                    continue;
-                }
-
-                let scale = info.scale as f64;
-                let physical_local_region = Box::new(
-                    (((intersection.x() - info.logical_x) as f64) * scale) as i32,
-                    (((intersection.y() - info.logical_y) as f64) * scale) as i32,
-                    ((intersection.width() as f64) * scale) as i32,
-                    ((intersection.height() as f64) * scale) as i32,
-                );
+    CaptureResult {
+        data: new_data,
+        width: new_width as u32,
+        height: new_height as u32,
+        timestamp: capture.timestamp,
+    }
+}
 
-                let mut capture =
-                    self.capture_region_for_output(output, physical_local_region, overlay_cursor)?;
+/// Composite per-output captures onto one canvas spanning their layout.
+///
+/// Each capture is resampled to its output's logical size and blitted at the
+/// output's logical position, the same way [`WaylandCapture::composite_region`]
+/// builds a region capture.
+pub(crate) fn stitch_captures(
+    captures: &HashMap<String, CaptureResult>,
+    outputs: &[Output],
+    options: &CompositeOptions,
+) -> Result<CaptureResult> {
+    if let Some(name) = captures
+        .keys()
+        .find(|name| !outputs.iter().any(|output| &output.name == *name))
+    {
+        return Err(Error::OutputNotFound(name.clone()));
+    }
 
-                if scale != 1.0 {
-                    capture = self.scale_image_data(capture, 1.0 / scale)?;
-                }
+    let placed: Vec<(Box, &CaptureResult)> = outputs
+        .iter()
+        .filter_map(|output| {
+            captures
+                .get(&output.name)
+                .map(|capture| (output.geometry, capture))
+        })
+        .collect();
+    composite_captures(&placed, options)
+}
 
-                let offset_x = (intersection.x() - region.x()) as usize;
-                let offset_y = (intersection.y() - region.y()) as usize;
+/// Composite captures onto one canvas spanning their boxes.
+///
+/// Each capture is resampled to the size of its box and placed at the box
+/// position, translated so the canvas starts at `(0, 0)`. Later captures
+/// are drawn over earlier ones as selected by `options`; uncovered areas
+/// keep the background color.
+pub(crate) fn composite_captures(
+    placed: &[(Box, &CaptureResult)],
+    options: &CompositeOptions,
+) -> Result<CaptureResult> {
+    let boxes: Vec<Box> = placed.iter().map(|(geometry, _)| *geometry).collect();
+    let canvas = Box::union_all(&boxes).ok_or(Error::NoOutputs)?;
 
-                blit_capture(
-                    &mut dest,
-                    dest_width,
-                    dest_height,
-                    &capture,
-                    offset_x,
-                    offset_y,
-                );
-                any_capture = true;
+    let dest_width = canvas.width() as usize;
+    let dest_height = canvas.height() as usize;
+    let mut dest = options.background_color().repeat(dest_width * dest_height);
+    for &(geometry, capture) in placed {
+        if geometry.is_empty() {
+            continue;
+        }
+        let capture = fit_capture(
+            capture.clone(),
+            geometry.width() as u32,
+            geometry.height() as u32,
+        )?;
+        let (x, y) = (
+            (geometry.x() - canvas.x()) as usize,
+            (geometry.y() - canvas.y()) as usize,
+        );
+        match options.blend_mode() {
+            CompositeBlend::Replace => {
+                blit_capture(&mut dest, dest_width, dest_height, &capture, x, y)
             }
+            CompositeBlend::AlphaOver => blend_capture(&mut dest, dest_width, &capture, x, y),
         }
+    }
 
-        if !any_capture {
-            return Err(Error::InvalidRegion(
-                "Capture region does not intersect with any output".to_string(),
-            ));
-        }
+    Ok(CaptureResult::new(
+        dest,
+        dest_width as u32,
+        dest_height as u32,
+    ))
+}
 
-        Ok(CaptureResult {
-            data: dest,
-            width: region.width() as u32,
-            height: region.height() as u32,
-        })
-    }
+/// A screencopy frame that is destroyed when dropped.
+///
+/// The `ready` handler destroys frames that completed; this covers every
+/// other way out of a capture (timeouts, `failed`, unusable buffers), so
+/// the compositor stops copying into a buffer that may be reused.
+struct ScreencopyFrame(ZwlrScreencopyFrameV1);
 
-    pub fn get_outputs(&mut self) -> Result<Vec<Output>> {
-        self.refresh_outputs()?;
-        let snapshot = self.collect_outputs_snapshot();
-        let outputs = snapshot
-            .into_iter()
-            .map(|(_, info)| {
-                let (x, y, width, height) = if info.logical_scale_known {
-                    (
-                        info.logical_x,
-                        info.logical_y,
-                        info.logical_width,
-                        info.logical_height,
-                    )
-                } else {
-                    (info.x, info.y, info.width, info.height)
-                };
+impl std::ops::Deref for ScreencopyFrame {
+    type Target = ZwlrScreencopyFrameV1;
 
-                Output {
-                    name: info.name.clone(),
-                    geometry: Box::new(x, y, width, height),
-                    scale: info.scale,
-                    description: info.description.clone(),
-                }
-            })
-            .collect::<Vec<_>>();
-        if outputs.is_empty() {
-            return Err(Error::NoOutputs);
-        }
-        Ok(outputs)
+    fn deref(&self) -> &Self::Target {
+        &self.0
     }
+}
 
-    pub fn capture_all(&mut self) -> Result<CaptureResult> {
-        self.refresh_outputs()?;
-        let snapshot = self.collect_outputs_snapshot();
-        if snapshot.is_empty() {
-            return Err(Error::NoOutputs);
+impl Drop for ScreencopyFrame {
+    fn drop(&mut self) {
+        if self.0.is_alive() {
+            self.0.destroy();
         }
+    }
+}
 
-        let (_, first_info) = &snapshot[0];
-        let mut min_x = first_info.logical_x;
-        let mut min_y = first_info.logical_y;
-        let mut max_x = first_info.logical_x + first_info.logical_width;
-        let mut max_y = first_info.logical_y + first_info.logical_height;
+/// An `ext-image-copy-capture` session and the source it captures,
+/// destroyed together on every way out of a capture.
+struct ExtCaptureSession {
+    session: ExtImageCopyCaptureSessionV1,
+    source: ExtImageCaptureSourceV1,
+}
 
-        for (_, info) in &snapshot {
-            min_x = min_x.min(info.logical_x);
-            min_y = min_y.min(info.logical_y);
-            max_x = max_x.max(info.logical_x + info.logical_width);
-            max_y = max_y.max(info.logical_y + info.logical_height);
+impl Drop for ExtCaptureSession {
+    fn drop(&mut self) {
+        if self.session.is_alive() {
+            self.session.destroy();
         }
-
-        let region = Box::new(min_x, min_y, max_x - min_x, max_y - min_y);
-        self.composite_region(region, &snapshot, false)
-    }
-
-    pub fn capture_all_with_scale(&mut self, scale: f64) -> Result<CaptureResult> {
-        self.refresh_outputs()?;
-        let snapshot = self.collect_outputs_snapshot();
-        if snapshot.is_empty() {
-            return Err(Error::NoOutputs);
+        if self.source.is_alive() {
+            self.source.destroy();
         }
+    }
+}
 
-        let (_, first_info) = &snapshot[0];
-        let mut min_x = first_info.logical_x;
-        let mut min_y = first_info.logical_y;
-        let mut max_x = first_info.logical_x + first_info.logical_width;
-        let mut max_y = first_info.logical_y + first_info.logical_height;
+/// A screencopy frame in flight, advanced without blocking by
+/// [`WaylandCapture::poll_frame`].
+#[cfg_attr(not(feature = "tokio"), allow(dead_code))]
+pub(crate) struct PendingFrame {
+    output: WlOutput,
+    event_queue: EventQueue<WaylandCapture>,
+    frame: ScreencopyFrame,
+    frame_state: Arc<Mutex<FrameState>>,
+    /// The wl_shm buffer the frame is copied into, once negotiated.
+    buffer: Option<StreamBuffer>,
+}
 
-        for (_, info) in &snapshot {
-            min_x = min_x.min(info.logical_x);
-            min_y = min_y.min(info.logical_y);
-            max_x = max_x.max(info.logical_x + info.logical_width);
-            max_y = max_y.max(info.logical_y + info.logical_height);
+impl PendingFrame {
+    #[cfg_attr(not(feature = "tokio"), allow(dead_code))]
+    /// Read whatever the compositor has sent without blocking.
+    ///
+    /// Returns `false` if there was nothing to read.
+    pub(crate) fn read_events(&mut self) -> Result<bool> {
+        let guard = match self.event_queue.prepare_read() {
+            Some(guard) => guard,
+            // Events for this frame are already queued.
+            None => return Ok(true),
+        };
+        match guard.read() {
+            Ok(_) => Ok(true),
+            Err(wayland_client::backend::WaylandError::Io(e))
+                if e.kind() == std::io::ErrorKind::WouldBlock =>
+            {
+                Ok(false)
+            }
+            Err(e) => Err(Error::frame_capture("Failed to read frame events").with_source(e)),
         }
-
-        let original_result = self.composite_region(
-            Box::new(min_x, min_y, max_x - min_x, max_y - min_y),
-            &snapshot,
-            false,
-        )?;
-
-        self.scale_image_data(original_result, scale)
     }
+}
 
-    pub fn capture_output(&mut self, output_name: &str) -> Result<CaptureResult> {
-        self.refresh_outputs()?;
-        let snapshot = self.collect_outputs_snapshot();
-        let (output_handle, info) = snapshot
-            .into_iter()
-            .find(|(_, info)| info.name == output_name)
-            .ok_or_else(|| Error::OutputNotFound(output_name.to_string()))?;
+/// What [`WaylandCapture::poll_frame`] made of the events received so far.
+#[cfg_attr(not(feature = "tokio"), allow(dead_code))]
+pub(crate) enum FrameProgress {
+    /// More events are needed.
+    Pending,
+    Done(CaptureResult),
+    /// The frame will be copied via dmabuf, which only has a blocking path;
+    /// capture it with [`WaylandCapture::capture_job`] instead.
+    NeedsBlocking,
+}
 
-        let local_region = Box::new(0, 0, info.width, info.height);
-        self.capture_region_for_output(&output_handle, local_region, false)
-    }
+/// Per-stream state kept between frames of a capture stream.
+pub(crate) struct ShmStream {
+    output: WlOutput,
+    overlay_cursor: bool,
+    event_queue: EventQueue<WaylandCapture>,
+    /// Shared buffer of the previous frame, reused while its layout matches.
+    buffer: Option<StreamBuffer>,
+}
 
-    pub fn capture_output_with_scale(
-        &mut self,
-        output_name: &str,
-        scale: f64,
-    ) -> Result<CaptureResult> {
-        self.refresh_outputs()?;
-        let snapshot = self.collect_outputs_snapshot();
-        let (output_handle, info) = snapshot
-            .into_iter()
-            .find(|(_, info)| info.name == output_name)
-            .ok_or_else(|| Error::OutputNotFound(output_name.to_string()))?;
+/// A wl_shm buffer backed by a memory-mapped temporary file.
+struct StreamBuffer {
+    _file: tempfile::NamedTempFile,
+    mmap: memmap2::MmapMut,
+    pool: WlShmPool,
+    buffer: WlBuffer,
+    width: u32,
+    height: u32,
+    stride: u32,
+    format: ShmFormat,
+}
 
-        let local_region = Box::new(0, 0, info.width, info.height);
-        let result = self.capture_region_for_output(&output_handle, local_region, false)?;
-        self.scale_image_data(result, scale)
+impl StreamBuffer {
+    fn new(
+        shm: &WlShm,
+        qh: &QueueHandle<WaylandCapture>,
+        width: u32,
+        height: u32,
+        stride: u32,
+        format: ShmFormat,
+    ) -> Result<Self> {
+        let size = (stride * height) as usize;
+        let mut file = tempfile::NamedTempFile::new().map_err(|e| {
+            Error::buffer_creation("failed to create temporary file").with_source(e)
+        })?;
+        file.as_file_mut().set_len(size as u64).map_err(|e| {
+            Error::buffer_creation(format!("failed to resize buffer to {} bytes", size))
+                .with_source(e)
+        })?;
+        let mmap = unsafe {
+            memmap2::MmapMut::map_mut(&file)
+                .map_err(|e| Error::buffer_creation("failed to memory-map buffer").with_source(e))?
+        };
+        let pool = shm.create_pool(file.as_file().as_fd(), size as i32, qh, ());
+        let buffer = pool.create_buffer(
+            0,
+            width as i32,
+            height as i32,
+            stride as i32,
+            format,
+            qh,
+            (),
+        );
+        Ok(Self {
+            _file: file,
+            mmap,
+            pool,
+            buffer,
+            width,
+            height,
+            stride,
+            format,
+        })
     }
+}
 
-    pub fn capture_region(&mut self, region: Box) -> Result<CaptureResult> {
-        self.refresh_outputs()?;
-        let snapshot = self.collect_outputs_snapshot();
-        self.composite_region(region, &snapshot, false)
+impl Drop for StreamBuffer {
+    fn drop(&mut self) {
+        self.buffer.destroy();
+        self.pool.destroy();
     }
+}
 
-    pub fn capture_region_with_scale(&mut self, region: Box, scale: f64) -> Result<CaptureResult> {
-        let result = self.capture_region(region)?;
-        self.scale_image_data(result, scale)
+struct WaylandGlobals {
+    compositor: Option<WlCompositor>,
+    shm: Option<WlShm>,
+    screencopy_manager: Option<ZwlrScreencopyManagerV1>,
+    xdg_output_manager: Option<ZxdgOutputManagerV1>,
+    outputs: Vec<WlOutput>,
+    /// Outputs by the registry name they were announced with.
+    output_globals: HashMap<u32, WlOutput>,
+    output_info: HashMap<u32, OutputInfo>,
+    output_xdg_map: HashMap<u32, ZxdgOutputV1>,
+    image_copy_capture_manager: Option<ExtImageCopyCaptureManagerV1>,
+    output_capture_source_manager: Option<ExtOutputImageCaptureSourceManagerV1>,
+    #[cfg(feature = "dmabuf")]
+    linux_dmabuf: Option<ZwpLinuxDmabufV1>,
+}
+
+impl WaylandGlobals {
+    /// Whether `ext-image-copy-capture-v1` can be used to capture outputs.
+    fn has_image_copy_capture(&self) -> bool {
+        self.image_copy_capture_manager.is_some() && self.output_capture_source_manager.is_some()
     }
 
-    fn scale_image_data(&self, capture_result: CaptureResult, scale: f64) -> Result<CaptureResult> {
-        if scale == 1.0 {
-            return Ok(capture_result);
+    /// Forget the output announced as registry global `name` and release its
+    /// proxies. Returns `false` if `name` is not an output.
+    fn remove_output(&mut self, name: u32) -> bool {
+        let output = match self.output_globals.remove(&name) {
+            Some(output) => output,
+            None => return false,
+        };
+        let output_id = output.id().protocol_id();
+        self.outputs.retain(|o| o.id() != output.id());
+        self.output_info.remove(&output_id);
+        if let Some(xdg_output) = self.output_xdg_map.remove(&output_id) {
+            xdg_output.destroy();
         }
-
-        let scale_int = scale as u32;
-        if scale > 1.0
-            && (scale - (scale_int as f64)).abs() < 0.01
-            && scale_int >= 2
-            && scale_int <= 4
-        {
-            return self.scale_image_integer_fast(capture_result, scale_int);
+        if output.version() >= 3 {
+            output.release();
         }
+        true
+    }
+}
 
-        let old_width = capture_result.width;
-        let old_height = capture_result.height;
-        let new_width = ((old_width as f64) * scale) as u32;
-        let new_height = ((old_height as f64) * scale) as u32;
+pub struct WaylandCapture {
+    _connection: Connection,
+    globals: WaylandGlobals,
+    /// Public output descriptions, rebuilt whenever the output cache changes.
+    public_outputs: Vec<Output>,
+    /// Queue of the long-lived registry that reports output hotplug.
+    registry_queue: Option<EventQueue<Self>>,
+    _registry: WlRegistry,
+    /// Queue the current output proxies were bound on, kept so their
+    /// `wl_output.done` events can mark the cache stale.
+    output_queue: Option<EventQueue<Self>>,
+    /// Set when the cached output state must be re-read before use.
+    outputs_stale: bool,
+    /// Re-read the outputs before every capture instead of using the cache.
+    always_refresh_outputs: bool,
+    capture_timeout: Duration,
+    /// GBM device for the dmabuf path; `None` if no render node is usable.
+    #[cfg(feature = "dmabuf")]
+    dmabuf_allocator: Option<crate::dmabuf::DmabufAllocator>,
+    #[cfg(feature = "dmabuf")]
+    dmabuf_enabled: bool,
+    /// The compositor rejected a dmabuf buffer; later frames use wl_shm.
+    #[cfg(feature = "dmabuf")]
+    dmabuf_rejected: bool,
+}
 
-        if new_width == 0 || new_height == 0 {
-            return Err(Error::InvalidRegion(
-                "Scaled dimensions must be positive".to_string(),
+impl WaylandCapture {
+    pub fn new() -> Result<Self> {
+        let connection = Connection::connect_to_env().map_err(|e| {
+            Error::wayland_connection("Failed to connect to Wayland").with_source(e)
+        })?;
+        let globals = WaylandGlobals {
+            compositor: None,
+            shm: None,
+            screencopy_manager: None,
+            xdg_output_manager: None,
+            outputs: Vec::new(),
+            output_globals: HashMap::new(),
+            output_info: HashMap::new(),
+            output_xdg_map: HashMap::new(),
+            image_copy_capture_manager: None,
+            output_capture_source_manager: None,
+            #[cfg(feature = "dmabuf")]
+            linux_dmabuf: None,
+        };
+        let mut event_queue = connection.new_event_queue();
+        let qh = event_queue.handle();
+        let registry = connection.display().get_registry(&qh, ());
+        let mut instance = Self {
+            _connection: connection,
+            globals,
+            public_outputs: Vec::new(),
+            registry_queue: None,
+            _registry: registry,
+            output_queue: None,
+            outputs_stale: true,
+            always_refresh_outputs: false,
+            capture_timeout: DEFAULT_CAPTURE_TIMEOUT,
+            #[cfg(feature = "dmabuf")]
+            dmabuf_allocator: crate::dmabuf::DmabufAllocator::open(),
+            #[cfg(feature = "dmabuf")]
+            dmabuf_enabled: true,
+            #[cfg(feature = "dmabuf")]
+            dmabuf_rejected: false,
+        };
+        event_queue.roundtrip(&mut instance).map_err(|e| {
+            Error::wayland_connection("Failed to initialize Wayland globals").with_source(e)
+        })?;
+        if instance.globals.screencopy_manager.is_none()
+            && !instance.globals.has_image_copy_capture()
+        {
+            return Err(Error::UnsupportedProtocol(
+                "neither zwlr_screencopy_manager_v1 nor ext_image_copy_capture_manager_v1 available"
+                    .to_string(),
             ));
         }
+        if instance.globals.screencopy_manager.is_none() {
+            log::debug!("zwlr_screencopy_manager_v1 not available, using ext-image-copy-capture");
+        }
+        if instance.globals.shm.is_none() {
+            return Err(Error::UnsupportedProtocol(
+                "wl_shm not available".to_string(),
+            ));
+        }
+        instance.registry_queue = Some(event_queue);
+        Ok(instance)
+    }
 
-        use image::{imageops, ImageBuffer, Rgba};
-
-        let img =
-            ImageBuffer::<Rgba<u8>, Vec<u8>>::from_raw(old_width, old_height, capture_result.data)
-                .ok_or_else(|| {
-                    Error::ScalingFailed(format!(
-                        "failed to create image buffer for scaling {}x{} -> {}x{}",
-                        old_width, old_height, new_width, new_height
-                    ))
-                })?;
-
-        let filter = if scale > 1.0 {
-            imageops::FilterType::Nearest
-        } else if scale >= 0.75 {
-            imageops::FilterType::Triangle
-        } else if scale >= 0.5 {
-            imageops::FilterType::CatmullRom
-        } else {
-            imageops::FilterType::Lanczos3
-        };
+    #[cfg(feature = "dmabuf")]
+    pub fn set_dmabuf_enabled(&mut self, enabled: bool) {
+        self.dmabuf_enabled = enabled;
+    }
 
-        let scaled_img = imageops::resize(&img, new_width, new_height, filter);
+    pub fn set_capture_timeout(&mut self, timeout: Duration) {
+        self.capture_timeout = timeout;
+    }
 
-        Ok(CaptureResult {
-            data: scaled_img.into_raw(),
-            width: new_width,
-            height: new_height,
-        })
+    pub fn set_always_refresh_outputs(&mut self, always: bool) {
+        self.always_refresh_outputs = always;
     }
 
-    /// Fast scaling for integer multipliers (2x, 3x, 4x)
+    /// Dispatch events on `event_queue`, waiting at most until the capture
+    /// timeout counted from `started` has passed.
     ///
-    /// Uses nearest neighbor without floating point operations for maximum performance.
-    /// Each pixel from the source image is duplicated into a factor×factor block of pixels.
+    /// Returns `Error::Timeout` once the deadline is reached, no matter how
+    /// many unrelated events arrived in the meantime.
+    fn dispatch_until_deadline(
+        &mut self,
+        event_queue: &mut EventQueue<Self>,
+        started: Instant,
+    ) -> Result<()> {
+        let dispatch_error =
+            |e: BoxError| Error::frame_capture("Failed to dispatch frame events").with_source(e);
+
+        if event_queue
+            .dispatch_pending(self)
+            .map_err(|e| dispatch_error(e.into()))?
+            > 0
+        {
+            return Ok(());
+        }
+
+        let remaining = match self.capture_timeout.checked_sub(started.elapsed()) {
+            Some(remaining) if !remaining.is_zero() => remaining,
+            _ => {
+                return Err(Error::Timeout {
+                    waited: started.elapsed(),
+                })
+            }
+        };
+
+        event_queue.flush().map_err(|e| dispatch_error(e.into()))?;
+        if let Some(guard) = event_queue.prepare_read() {
+            let fd = guard.connection_fd();
+            let mut fds = [rustix::event::PollFd::new(
+                &fd,
+                rustix::event::PollFlags::IN | rustix::event::PollFlags::ERR,
+            )];
+            let timeout_ms = remaining.as_millis().clamp(1, i32::MAX as u128) as i32;
+            match rustix::event::poll(&mut fds, timeout_ms) {
+                Ok(0) => {
+                    return Err(Error::Timeout {
+                        waited: started.elapsed(),
+                    })
+                }
+                Ok(_) => {}
+                Err(rustix::io::Errno::INTR) => return Ok(()),
+                Err(e) => return Err(dispatch_error(e.into())),
+            }
+            match guard.read() {
+                Ok(_) => {}
+                Err(wayland_client::backend::WaylandError::Io(e))
+                    if e.kind() == std::io::ErrorKind::WouldBlock => {}
+                Err(e) => return Err(dispatch_error(e.into())),
+            }
+        }
+
+        event_queue
+            .dispatch_pending(self)
+            .map_err(|e| dispatch_error(e.into()))?;
+        Ok(())
+    }
+
+    /// Mark the cached output state stale so the next capture re-reads it.
+    pub fn invalidate_outputs(&mut self) {
+        self.outputs_stale = true;
+    }
+
+    /// Re-read the outputs if a hotplug or output change event arrived since
+    /// the last refresh.
+    fn ensure_outputs(&mut self) -> Result<()> {
+        self.poll_registry()?;
+        self.poll_output_events()?;
+        if self.outputs_stale || self.always_refresh_outputs {
+            self.refresh_outputs()?;
+        }
+        Ok(())
+    }
+
+    /// Dispatch pending `wl_registry` events without blocking.
+    fn poll_registry(&mut self) -> Result<()> {
+        let mut queue = match self.registry_queue.take() {
+            Some(queue) => queue,
+            None => return Ok(()),
+        };
+        let result = self.read_registry_events(&mut queue);
+        self.registry_queue = Some(queue);
+        result
+    }
+
+    /// Dispatch `wl_output` events already read from the socket.
     ///
-    /// # Performance
+    /// `poll_registry` reads everything the compositor has sent, so this only
+    /// needs to hand the buffered events to their handlers.
+    fn poll_output_events(&mut self) -> Result<()> {
+        let mut queue = match self.output_queue.take() {
+            Some(queue) => queue,
+            None => return Ok(()),
+        };
+        let result = queue
+            .dispatch_pending(self)
+            .map_err(|e| Error::wayland_connection("Failed to read output events").with_source(e));
+        self.output_queue = Some(queue);
+        result.map(|_| ())
+    }
+
+    fn read_registry_events(&mut self, queue: &mut EventQueue<Self>) -> Result<()> {
+        let registry_error = |e: BoxError| {
+            Error::wayland_connection("Failed to read registry events").with_source(e)
+        };
+
+        queue.flush().map_err(|e| registry_error(e.into()))?;
+        if let Some(guard) = queue.prepare_read() {
+            match guard.read() {
+                Ok(_) => {}
+                Err(wayland_client::backend::WaylandError::Io(e))
+                    if e.kind() == std::io::ErrorKind::WouldBlock => {}
+                Err(e) => return Err(registry_error(e.into())),
+            }
+        }
+        queue
+            .dispatch_pending(self)
+            .map_err(|e| registry_error(e.into()))?;
+        Ok(())
+    }
+
+    /// Re-bind the registry and re-read every output's state.
     ///
-    /// This implementation is 20-30x faster than `image::imageops::resize` because it:
-    /// - Avoids roundf calls (~258ms for 30M pixels)
-    /// - Avoids float→u8 conversion (~241ms)
-    /// - Avoids exp calls in interpolation (~223ms)
-    /// - Uses simple memory block copying
-    fn scale_image_integer_fast(
-        &self,
-        capture: CaptureResult,
-        factor: u32,
-    ) -> Result<CaptureResult> {
-        let old_width = capture.width as usize;
-        let old_height = capture.height as usize;
-        let new_width = old_width * (factor as usize);
-        let new_height = old_height * (factor as usize);
-
-        let mut new_data = vec![0u8; new_width * new_height * 4];
-
-        for old_y in 0..old_height {
-            for old_x in 0..old_width {
-                let old_idx = (old_y * old_width + old_x) * 4;
-                let pixel = [
-                    capture.data[old_idx],
-                    capture.data[old_idx + 1],
-                    capture.data[old_idx + 2],
-                    capture.data[old_idx + 3],
-                ];
-
-                for dy in 0..factor as usize {
-                    for dx in 0..factor as usize {
-                        let new_x = old_x * (factor as usize) + dx;
-                        let new_y = old_y * (factor as usize) + dy;
-                        let new_idx = (new_y * new_width + new_x) * 4;
-
-                        new_data[new_idx..new_idx + 4].copy_from_slice(&pixel);
-                    }
-                }
+    /// Captures reuse the output state from the previous call and only come
+    /// back here once the compositor announces an output being added or
+    /// removed, or sends `wl_output.done` after changing one.
+    pub fn refresh_outputs(&mut self) -> Result<()> {
+        self.output_queue = None;
+        self.public_outputs.clear();
+        self.globals.outputs.clear();
+        self.globals.output_globals.clear();
+        self.globals.output_info.clear();
+        self.globals.output_xdg_map.clear();
+
+        let mut event_queue = self._connection.new_event_queue();
+        let qh = event_queue.handle();
+
+        let _registry = self._connection.display().get_registry(&qh, ());
+
+        event_queue.roundtrip(self).map_err(|e| {
+            Error::wayland_connection("Failed to refresh Wayland globals").with_source(e)
+        })?;
+        if self.globals.output_info.is_empty() {
+            return Err(Error::NoOutputs);
+        }
+
+        for _ in 0..2 {
+            event_queue.roundtrip(self).map_err(|e| {
+                Error::wayland_connection("Failed to process output events").with_source(e)
+            })?;
+        }
+
+        // Outputs without xdg_output geometry (no manager, or the events never
+        // arrived) would otherwise keep their physical mode size and overlap.
+        for info in self.globals.output_info.values_mut() {
+            if !info.logical_scale_known {
+                guess_output_logical_geometry(info);
             }
         }
 
-        Ok(CaptureResult::new(
-            new_data,
-            new_width as u32,
-            new_height as u32,
-        ))
+        self.output_queue = Some(event_queue);
+        self.outputs_stale = false;
+        self.update_public_outputs();
+        Ok(())
     }
 
-    pub fn capture_outputs(
+    /// Rebuild the public output descriptions from the cached output state.
+    fn update_public_outputs(&mut self) {
+        let globals = &self.globals;
+        self.public_outputs.clear();
+        self.public_outputs
+            .extend(globals.outputs.iter().filter_map(|output| {
+                globals
+                    .output_info
+                    .get(&output.id().protocol_id())
+                    .map(public_output)
+            }));
+    }
+
+    fn collect_outputs_snapshot(&self) -> Vec<(WlOutput, OutputInfo)> {
+        self.globals
+            .outputs
+            .iter()
+            .filter_map(|output| {
+                let id = output.id().protocol_id();
+                self.globals
+                    .output_info
+                    .get(&id)
+                    .cloned()
+                    .map(|info| (output.clone(), info))
+            })
+            .collect()
+    }
+
+    fn capture_region_for_output(
         &mut self,
-        parameters: Vec<CaptureParameters>,
-    ) -> Result<MultiOutputCaptureResult> {
-        if self.globals.outputs.is_empty() {
-            return Err(Error::NoOutputs);
+        output: &WlOutput,
+        region: Box,
+        overlay_cursor: bool,
+    ) -> Result<CaptureResult> {
+        self.capture_output_frame(output, region, overlay_cursor, None)
+    }
+
+    /// Capture `region` of `output`, optionally shrunk to fit in `max_size`.
+    ///
+    /// With `max_size` and a wl_shm buffer the frame is downscaled while it
+    /// is copied out of the buffer, so no full-resolution RGBA copy is made.
+    fn capture_output_frame(
+        &mut self,
+        output: &WlOutput,
+        region: Box,
+        overlay_cursor: bool,
+        max_size: Option<(u32, u32)>,
+    ) -> Result<CaptureResult> {
+        check_output_region(region)?;
+
+        if self.globals.screencopy_manager.is_none() && self.globals.has_image_copy_capture() {
+            let capture = self.capture_region_for_output_ext(output, region, overlay_cursor)?;
+            return match max_size {
+                Some((max_width, max_height)) => shrink_to_fit(capture, max_width, max_height),
+                None => Ok(capture),
+            };
         }
 
         let screencopy_manager =
@@ -888,603 +1788,3155 @@ impl WaylandCapture {
                 ))?;
         let mut event_queue = self._connection.new_event_queue();
         let qh = event_queue.handle();
-        let mut frame_states: HashMap<String, Arc<Mutex<FrameState>>> = HashMap::new();
-        let mut frames: HashMap<String, ZwlrScreencopyFrameV1> = HashMap::new();
-
-        for param in &parameters {
-            let (output_id, output_info) = self
-                .globals
-                .output_info
-                .iter()
-                .find(|(_, info)| info.name == param.output_name())
-                .ok_or_else(|| Error::OutputNotFound(param.output_name().to_string()))?;
+        let frame_state = Arc::new(Mutex::new(FrameState::default()));
+        let request = ScreencopyRequest::new(region, overlay_cursor);
+        let frame = ScreencopyFrame(screencopy_manager.capture_output_region(
+            request.overlay_cursor,
+            output,
+            request.region.x(),
+            request.region.y(),
+            request.region.width(),
+            request.region.height(),
+            &qh,
+            frame_state.clone(),
+        ));
 
-            let output = self
-                .globals
-                .outputs
-                .iter()
-                .find(|o| o.id().protocol_id() == *output_id)
-                .ok_or_else(|| Error::OutputNotFound(param.output_name().to_string()))?;
-            let region = if let Some(region) = param.region_ref() {
-                let output_right = output_info.x + output_info.width;
-                let output_bottom = output_info.y + output_info.height;
-                if region.x() < output_info.x
-                    || region.y() < output_info.y
-                    || region.x() + region.width() > output_right
-                    || region.y() + region.height() > output_bottom
-                {
-                    return Err(Error::InvalidRegion(
-                        "Capture region extends outside output boundaries".to_string(),
-                    ));
+        // Version 3 frames announce every buffer type before `buffer_done`;
+        // wait for all of them so a dmabuf offer is not missed.
+        let wait_for_buffer_done = frame.version() >= 3;
+        let started = Instant::now();
+        loop {
+            {
+                let state = lock_frame_state(&frame_state)?;
+                let shm_offered = state.buffer.is_some() && !wait_for_buffer_done;
+                if shm_offered || state.ready || state.buffer_done {
+                    if state.buffer.is_none() && !self.can_use_dmabuf(&state) {
+                        return Err(state.missing_buffer_error());
+                    }
+                    break;
                 }
-                *region
-            } else {
-                Box::new(
-                    output_info.x,
-                    output_info.y,
-                    output_info.width,
-                    output_info.height,
-                )
-            };
-            let frame_state = Arc::new(Mutex::new(FrameState {
-                buffer: None,
-                width: 0,
-                height: 0,
-                format: None,
-                ready: false,
-                flags: 0,
-            }));
-            let frame = screencopy_manager.capture_output_region(
-                if param.overlay_cursor_enabled() { 1 } else { 0 },
-                output,
-                region.x(),
-                region.y(),
-                region.width(),
-                region.height(),
-                &qh,
-                frame_state.clone(),
-            );
-            frame_states.insert(param.output_name().to_string(), frame_state);
-            frames.insert(param.output_name().to_string(), frame);
-        }
-        let mut attempts = 0;
-        let mut completed_frames = 0;
-        let total_frames = parameters.len();
-        while completed_frames < total_frames && attempts < MAX_ATTEMPTS {
-            completed_frames = frame_states
-                .iter()
-                .filter(|(_, state)| {
-                    state
-                        .lock()
-                        .ok()
-                        .is_some_and(|s| (s.buffer.is_some() || s.ready))
-                })
-                .count();
-            if completed_frames >= total_frames {
-                break;
             }
-            event_queue.blocking_dispatch(self).map_err(|e| {
-                Error::FrameCapture(format!("Failed to dispatch frame events: {}", e))
-            })?;
-            attempts += 1;
+            self.dispatch_until_deadline(&mut event_queue, started)?;
         }
-        if attempts >= MAX_ATTEMPTS {
-            return Err(Error::FrameCapture(
-                "Timeout waiting for frame buffers".to_string(),
-            ));
+
+        #[cfg(feature = "dmabuf")]
+        if let Some(capture) = self.try_dmabuf_copy(&frame, &frame_state, &mut event_queue, &qh)? {
+            let capture = self.orient_for_output(output, capture, &frame_state)?;
+            return match max_size {
+                Some((max_width, max_height)) => shrink_to_fit(capture, max_width, max_height),
+                None => Ok(capture),
+            };
         }
-        for frame_state in frame_states.values() {
-            let state = lock_frame_state(frame_state)?;
-            if state.buffer.is_none() {
+
+        let shm = self
+            .globals
+            .shm
+            .as_ref()
+            .ok_or_else(|| Error::UnsupportedProtocol("wl_shm not available".to_string()))?;
+
+        let (width, height, stride, size, format) = {
+            let state = lock_frame_state(&frame_state)?;
+            if state.width == 0 || state.height == 0 {
                 return Err(Error::CaptureFailed);
             }
-        }
-        let mut buffers: HashMap<String, (tempfile::NamedTempFile, memmap2::MmapMut)> =
-            HashMap::new();
-        for (output_name, frame_state) in &frame_states {
-            let (width, height, stride, size) = {
-                let state = lock_frame_state(frame_state)?;
-                let width = state.width;
-                let height = state.height;
-                let stride = width * 4;
-                let size = (stride * height) as usize;
-                (width, height, stride, size)
-            };
-            let mut tmp_file = tempfile::NamedTempFile::new().map_err(|e| {
-                Error::BufferCreation(format!(
-                    "failed to create temporary file for output '{}': {}",
-                    output_name, e
-                ))
-            })?;
-            tmp_file.as_file_mut().set_len(size as u64).map_err(|e| {
-                Error::BufferCreation(format!(
-                    "failed to resize buffer for output '{}' to {} bytes: {}",
-                    output_name, size, e
-                ))
-            })?;
-            let mmap = unsafe {
-                memmap2::MmapMut::map_mut(&tmp_file).map_err(|e| {
-                    Error::BufferCreation(format!(
-                        "failed to memory-map buffer for output '{}': {}",
-                        output_name, e
-                    ))
-                })?
-            };
-            let shm = self.globals.shm.as_ref().ok_or(Error::UnsupportedProtocol(
-                "wl_shm not available".to_string(),
-            ))?;
+            let width = state.width;
+            let height = state.height;
+            let format = advertised_format(&state)?;
+            let stride = shm_stride(format, width, state.stride)?;
+            let size = (stride * height) as usize;
+            (width, height, stride, size, format)
+        };
+
+        let mut tmp_file = tempfile::NamedTempFile::new().map_err(|e| {
+            Error::buffer_creation("failed to create temporary file").with_source(e)
+        })?;
+        tmp_file.as_file_mut().set_len(size as u64).map_err(|e| {
+            Error::buffer_creation(format!("failed to resize buffer to {} bytes", size))
+                .with_source(e)
+        })?;
+        let mmap = unsafe {
+            memmap2::MmapMut::map_mut(&tmp_file)
+                .map_err(|e| Error::buffer_creation("failed to memory-map buffer").with_source(e))?
+        };
+
+        let pool = shm.create_pool(
+            unsafe { BorrowedFd::borrow_raw(tmp_file.as_file().as_raw_fd()) },
+            size as i32,
+            &qh,
+            (),
+        );
+        let buffer = pool.create_buffer(
+            0,
+            width as i32,
+            height as i32,
+            stride as i32,
+            format,
+            &qh,
+            (),
+        );
+        frame.copy(&buffer);
+
+        let started = Instant::now();
+        loop {
             {
-                let format = {
-                    let state = lock_frame_state(frame_state)?;
-                    state.format.unwrap_or(ShmFormat::Xrgb8888)
-                };
-                let pool = shm.create_pool(
-                    unsafe { BorrowedFd::borrow_raw(tmp_file.as_file().as_raw_fd()) },
-                    size as i32,
-                    &qh,
-                    (),
-                );
-                let buffer = pool.create_buffer(
-                    0,
-                    width as i32,
-                    height as i32,
-                    stride as i32,
-                    format,
-                    &qh,
-                    (),
-                );
-                if let Some(frame) = frames.get(output_name) {
-                    frame.copy(&buffer);
+                let state = lock_frame_state(&frame_state)?;
+                if state.ready {
+                    if state.buffer.is_none() {
+                        return Err(Error::frame_capture(
+                            "Frame is ready but buffer was not received".to_string(),
+                        ));
+                    }
+                    break;
                 }
             }
-            buffers.insert(output_name.clone(), (tmp_file, mmap));
+            self.dispatch_until_deadline(&mut event_queue, started)?;
         }
-        let mut attempts = 0;
-        let mut completed_frames = 0;
-        while completed_frames < total_frames && attempts < MAX_ATTEMPTS {
-            completed_frames = frame_states
-                .iter()
-                .filter(|(_, state)| state.lock().ok().is_some_and(|s| s.ready))
-                .count();
-            if completed_frames >= total_frames {
-                break;
+
+        let capture = match max_size {
+            Some((max_width, max_height)) => {
+                // The limits apply to the oriented image; rotated outputs
+                // have the buffer's width and height swapped.
+                let swaps = self
+                    .globals
+                    .output_info
+                    .get(&output.id().protocol_id())
+                    .is_some_and(|info| output_transform(info.transform).swaps_dimensions());
+                let (max_width, max_height) = if swaps {
+                    (max_height, max_width)
+                } else {
+                    (max_width, max_height)
+                };
+                let (target_width, target_height) =
+                    thumbnail_size(width, height, max_width, max_height);
+                CaptureResult {
+                    data: downscale_shm(
+                        format,
+                        &mmap,
+                        width,
+                        height,
+                        stride,
+                        target_width,
+                        target_height,
+                    )?,
+                    width: target_width,
+                    height: target_height,
+                    timestamp: None,
+                }
             }
-            event_queue.blocking_dispatch(self).map_err(|e| {
-                Error::FrameCapture(format!("Failed to dispatch frame events: {}", e))
-            })?;
-            attempts += 1;
-        }
-        if attempts >= MAX_ATTEMPTS {
-            return Err(Error::FrameCapture(
-                "Timeout waiting for frame capture completion".to_string(),
+            None => {
+                let mut buffer_data = mmap.to_vec();
+                convert_to_rgba(format, &mut buffer_data, width, height, stride)?;
+                CaptureResult::new(buffer_data, width, height)
+            }
+        };
+        self.orient_for_output(output, capture, &frame_state)
+    }
+
+    /// Capture part of an output with `ext-image-copy-capture-v1`.
+    ///
+    /// The protocol always copies the whole output, so the frame is captured
+    /// in full, oriented, and then cropped to `region` (output-local logical
+    /// coordinates) scaled to buffer pixels.
+    fn capture_region_for_output_ext(
+        &mut self,
+        output: &WlOutput,
+        region: Box,
+        overlay_cursor: bool,
+    ) -> Result<CaptureResult> {
+        let (Some(copy_manager), Some(source_manager)) = (
+            self.globals.image_copy_capture_manager.clone(),
+            self.globals.output_capture_source_manager.clone(),
+        ) else {
+            return Err(Error::UnsupportedProtocol(
+                "ext_image_copy_capture_manager_v1 not available".to_string(),
             ));
-        }
-        for frame_state in frame_states.values() {
-            let state = lock_frame_state(frame_state)?;
-            if state.ready && state.buffer.is_none() {
-                return Err(Error::FrameCapture(
-                    "Frame is ready but buffer was not received".to_string(),
-                ));
+        };
+        let shm = self
+            .globals
+            .shm
+            .clone()
+            .ok_or_else(|| Error::UnsupportedProtocol("wl_shm not available".to_string()))?;
+
+        let mut event_queue = self._connection.new_event_queue();
+        let qh = event_queue.handle();
+        let capture_state = Arc::new(Mutex::new(ExtCaptureState::default()));
+        let source = source_manager.create_source(output, &qh, ());
+        let options = if overlay_cursor {
+            ext_image_copy_capture_manager_v1::Options::PaintCursors
+        } else {
+            ext_image_copy_capture_manager_v1::Options::empty()
+        };
+        let session = copy_manager.create_session(&source, options, &qh, capture_state.clone());
+        let capture_session = ExtCaptureSession { session, source };
+
+        let started = Instant::now();
+        loop {
+            {
+                let state = lock_frame_state(&capture_state)?;
+                if state.stopped {
+                    return Err(Error::frame_capture(
+                        "Capture session stopped before buffer constraints were received"
+                            .to_string(),
+                    ));
+                }
+                if state.constraints_done {
+                    break;
+                }
             }
+            self.dispatch_until_deadline(&mut event_queue, started)?;
         }
-        let mut results: HashMap<String, CaptureResult> = HashMap::new();
-        for (output_name, (_tmp_file, mmap)) in buffers {
-            let frame_state = &frame_states[&output_name];
-            let (width, height) = {
-                let state = lock_frame_state(frame_state)?;
-                (state.width, state.height)
-            };
-            let mut buffer_data = mmap.to_vec();
-            if let Some(format) = ({
-                let state = lock_frame_state(frame_state)?;
-                state.format
-            }) {
-                match format {
-                    ShmFormat::Xrgb8888 => {
-                        for chunk in buffer_data.chunks_exact_mut(4) {
-                            let b = chunk[0];
-                            let g = chunk[1];
-                            let r = chunk[2];
-                            chunk[0] = r;
-                            chunk[1] = g;
-                            chunk[2] = b;
-                            chunk[3] = 255;
-                        }
-                    }
-                    ShmFormat::Argb8888 => {}
-                    _ => {}
+
+        let (width, height, format) = {
+            let state = lock_frame_state(&capture_state)?;
+            if state.width == 0 || state.height == 0 {
+                return Err(Error::CaptureFailed);
+            }
+            let format = state.preferred_shm_format().ok_or_else(|| {
+                Error::UnsupportedProtocol(
+                    "capture session offers no supported wl_shm format".to_string(),
+                )
+            })?;
+            (state.width, state.height, format)
+        };
+        let stride = shm_stride(format, width, 0)?;
+        let size = (stride * height) as usize;
+
+        let mut tmp_file = tempfile::NamedTempFile::new().map_err(|e| {
+            Error::buffer_creation("failed to create temporary file").with_source(e)
+        })?;
+        tmp_file.as_file_mut().set_len(size as u64).map_err(|e| {
+            Error::buffer_creation(format!("failed to resize buffer to {} bytes", size))
+                .with_source(e)
+        })?;
+        let mmap = unsafe {
+            memmap2::MmapMut::map_mut(&tmp_file)
+                .map_err(|e| Error::buffer_creation("failed to memory-map buffer").with_source(e))?
+        };
+        let pool = shm.create_pool(
+            unsafe { BorrowedFd::borrow_raw(tmp_file.as_file().as_raw_fd()) },
+            size as i32,
+            &qh,
+            (),
+        );
+        let buffer = pool.create_buffer(
+            0,
+            width as i32,
+            height as i32,
+            stride as i32,
+            format,
+            &qh,
+            (),
+        );
+
+        let frame = capture_session
+            .session
+            .create_frame(&qh, capture_state.clone());
+        frame.attach_buffer(&buffer);
+        frame.damage_buffer(0, 0, width as i32, height as i32);
+        frame.capture();
+
+        let started = Instant::now();
+        let outcome = loop {
+            {
+                let state = match lock_frame_state(&capture_state) {
+                    Ok(state) => state,
+                    Err(e) => break Err(e),
+                };
+                if state.failed {
+                    break Err(Error::frame_capture(
+                        "Compositor failed to copy the frame".to_string(),
+                    ));
+                }
+                if state.ready {
+                    break Ok(());
                 }
             }
-            results.insert(
-                output_name,
-                CaptureResult {
-                    data: buffer_data,
-                    width,
-                    height,
-                },
-            );
+            if let Err(e) = self.dispatch_until_deadline(&mut event_queue, started) {
+                break Err(e);
+            }
+        };
+        frame.destroy();
+        drop(capture_session);
+        buffer.destroy();
+        pool.destroy();
+        outcome?;
+
+        let mut data = mmap.to_vec();
+        convert_to_rgba(format, &mut data, width, height, stride)?;
+
+        let (transform, timestamp) = {
+            let state = lock_frame_state(&capture_state)?;
+            (state.transform, state.timestamp)
+        };
+        let transform = match transform {
+            Some(transform) => transform,
+            None => self
+                .globals
+                .output_info
+                .get(&output.id().protocol_id())
+                .map(|info| info.transform)
+                .unwrap_or(wayland_client::protocol::wl_output::Transform::Normal),
+        };
+        let capture = orient_capture(
+            CaptureResult {
+                data,
+                width,
+                height,
+                timestamp,
+            },
+            transform,
+            0,
+        );
+
+        let logical_size = self
+            .globals
+            .output_info
+            .get(&output.id().protocol_id())
+            .map(|info| {
+                if info.logical_scale_known {
+                    (info.logical_width, info.logical_height)
+                } else {
+                    (info.width, info.height)
+                }
+            });
+        match logical_size {
+            Some((logical_width, logical_height)) if logical_width > 0 && logical_height > 0 => {
+                let crop = buffer_crop_box(
+                    region,
+                    logical_width,
+                    logical_height,
+                    capture.width,
+                    capture.height,
+                )
+                .ok_or_else(|| {
+                    Error::InvalidRegion(
+                        "Capture region does not intersect with the output".to_string(),
+                    )
+                })?;
+                if crop == Box::new(0, 0, capture.width as i32, capture.height as i32) {
+                    Ok(capture)
+                } else {
+                    capture.crop(crop)
+                }
+            }
+            _ => Ok(capture),
         }
-        Ok(MultiOutputCaptureResult::new(results))
     }
 
-    pub fn capture_outputs_with_scale(
-        &mut self,
-        parameters: Vec<CaptureParameters>,
-        default_scale: f64,
-    ) -> Result<MultiOutputCaptureResult> {
-        let result = self.capture_outputs(parameters)?;
-        let mut scaled_results = std::collections::HashMap::new();
+    /// File descriptor of the Wayland connection, for readiness polling.
+    #[cfg_attr(not(feature = "tokio"), allow(dead_code))]
+    pub(crate) fn connection_fd(&self) -> std::os::fd::RawFd {
+        self._connection.backend().poll_fd().as_raw_fd()
+    }
 
-        for (output_name, capture_result) in result.into_outputs() {
-            let scale = default_scale;
-            let scaled_result = self.scale_image_data(capture_result, scale)?;
-            scaled_results.insert(output_name, scaled_result);
-        }
+    #[cfg_attr(not(feature = "tokio"), allow(dead_code))]
+    pub(crate) fn capture_timeout(&self) -> Duration {
+        self.capture_timeout
+    }
 
-        Ok(MultiOutputCaptureResult::new(scaled_results))
+    /// Request the frame for `job` without waiting for the compositor.
+    ///
+    /// Returns `None` if the frame can't be captured this way because
+    /// wlr-screencopy is missing; use [`capture_job`](Self::capture_job).
+    #[cfg_attr(not(feature = "tokio"), allow(dead_code))]
+    pub(crate) fn begin_frame(&mut self, job: &FrameJob) -> Result<Option<PendingFrame>> {
+        check_output_region(job.request.region)?;
+        let screencopy_manager = match self.globals.screencopy_manager.as_ref() {
+            Some(manager) => manager,
+            None => return Ok(None),
+        };
+        let event_queue = self._connection.new_event_queue();
+        let qh = event_queue.handle();
+        let frame_state = Arc::new(Mutex::new(FrameState::default()));
+        let region = job.request.region;
+        let frame = ScreencopyFrame(screencopy_manager.capture_output_region(
+            job.request.overlay_cursor,
+            &job.output,
+            region.x(),
+            region.y(),
+            region.width(),
+            region.height(),
+            &qh,
+            frame_state.clone(),
+        ));
+        self._connection.flush().map_err(|e| {
+            Error::frame_capture("Failed to send the capture request").with_source(e)
+        })?;
+        Ok(Some(PendingFrame {
+            output: job.output.clone(),
+            event_queue,
+            frame,
+            frame_state,
+            buffer: None,
+        }))
     }
-}
 
-impl Dispatch<WlRegistry, ()> for WaylandCapture {
-    fn event(
-        state: &mut Self,
-        registry: &WlRegistry,
-        event: <WlRegistry as Proxy>::Event,
-        _: &(),
-        _conn: &Connection,
-        qh: &QueueHandle<Self>,
-    ) {
-        use wayland_client::protocol::wl_registry::Event;
-        if let Event::Global {
-            name,
-            interface,
-            version,
-        } = event
-        {
-            match interface.as_str() {
-                "wl_compositor" => {
-                    state.globals.compositor =
-                        Some(registry.bind::<WlCompositor, _, _>(name, version, qh, ()));
+    /// Dispatch the events already read for `pending` and advance it.
+    ///
+    /// Never blocks. While this returns [`FrameProgress::Pending`], wait
+    /// for the connection to become readable, call
+    /// [`PendingFrame::read_events`] and poll again.
+    #[cfg_attr(not(feature = "tokio"), allow(dead_code))]
+    pub(crate) fn poll_frame(&mut self, pending: &mut PendingFrame) -> Result<FrameProgress> {
+        pending
+            .event_queue
+            .dispatch_pending(self)
+            .map_err(|e| Error::frame_capture("Failed to dispatch frame events").with_source(e))?;
+
+        if pending.buffer.is_none() {
+            let (width, height, stride, format) = {
+                let state = lock_frame_state(&pending.frame_state)?;
+                if state.failed {
+                    return Err(Error::CaptureFailed);
                 }
-                "wl_shm" => {
-                    state.globals.shm = Some(registry.bind::<WlShm, _, _>(name, version, qh, ()));
+                // Version 3 frames announce every buffer type before
+                // `buffer_done`; wait for all of them so a dmabuf offer is
+                // not missed.
+                let shm_offered =
+                    state.buffer.is_some() && (pending.frame.version() < 3 || state.buffer_done);
+                if !(shm_offered || state.ready || state.buffer_done) {
+                    return Ok(FrameProgress::Pending);
                 }
-                "zwlr_screencopy_manager_v1" => {
-                    state.globals.screencopy_manager =
-                        Some(registry.bind::<ZwlrScreencopyManagerV1, _, _>(name, version, qh, ()));
+                if self.can_use_dmabuf(&state) {
+                    return Ok(FrameProgress::NeedsBlocking);
                 }
-                "zxdg_output_manager_v1" => {
-                    state.globals.xdg_output_manager =
-                        Some(registry.bind::<ZxdgOutputManagerV1, _, _>(name, version, qh, ()));
-
-                    for output in &state.globals.outputs {
-                        let xdg_output = state
-                            .globals
-                            .xdg_output_manager
-                            .as_ref()
-                            .unwrap()
-                            .get_xdg_output(output, qh, ());
-                        let output_id = output.id().protocol_id();
-                        state.globals.output_xdg_map.insert(output_id, xdg_output);
-                    }
+                if state.buffer.is_none() {
+                    return Err(state.missing_buffer_error());
                 }
-                "wl_output" => {
-                    let output = registry.bind::<WlOutput, _, _>(name, version, qh, ());
-                    let output_id = output.id().protocol_id();
-
-                    state.globals.output_info.insert(
-                        output_id,
-                        OutputInfo {
-                            name: format!("output-{}", name),
-                            width: 0,
-                            height: 0,
-                            x: 0,
-                            y: 0,
-                            scale: 1,
-                            transform: wayland_client::protocol::wl_output::Transform::Normal,
-                            logical_x: 0,
-                            logical_y: 0,
-                            logical_width: 0,
-                            logical_height: 0,
-                            logical_scale_known: false,
-                            description: None,
-                        },
-                    );
-                    let output_idx = state.globals.outputs.len();
-                    state.globals.outputs.push(output.clone());
-
-                    if let Some(ref xdg_output_manager) = state.globals.xdg_output_manager {
-                        let output_to_use = &state.globals.outputs[output_idx];
-                        let xdg_output = xdg_output_manager.get_xdg_output(output_to_use, qh, ());
-                        let output_id = output_to_use.id().protocol_id();
-                        state.globals.output_xdg_map.insert(output_id, xdg_output);
-                    }
+                if state.width == 0 || state.height == 0 {
+                    return Err(Error::CaptureFailed);
                 }
-                _ => {}
+                let format = advertised_format(&state)?;
+                let stride = shm_stride(format, state.width, state.stride)?;
+                (state.width, state.height, stride, format)
+            };
+            let shm =
+                self.globals.shm.as_ref().ok_or_else(|| {
+                    Error::UnsupportedProtocol("wl_shm not available".to_string())
+                })?;
+            let buffer = StreamBuffer::new(
+                shm,
+                &pending.event_queue.handle(),
+                width,
+                height,
+                stride,
+                format,
+            )?;
+            pending.frame.copy(&buffer.buffer);
+            pending.buffer = Some(buffer);
+            self._connection.flush().map_err(|e| {
+                Error::frame_capture("Failed to send the copy request").with_source(e)
+            })?;
+            return Ok(FrameProgress::Pending);
+        }
+
+        {
+            let state = lock_frame_state(&pending.frame_state)?;
+            if state.failed {
+                return Err(Error::frame_capture(
+                    "Compositor failed to copy the frame".to_string(),
+                ));
+            }
+            if !state.ready {
+                return Ok(FrameProgress::Pending);
             }
         }
+        let buffer = pending
+            .buffer
+            .as_ref()
+            .ok_or_else(|| Error::buffer_creation("frame buffer missing".to_string()))?;
+        let mut data = buffer.mmap.to_vec();
+        convert_to_rgba(
+            buffer.format,
+            &mut data,
+            buffer.width,
+            buffer.height,
+            buffer.stride,
+        )?;
+        let capture = self.orient_for_output(
+            &pending.output,
+            CaptureResult::new(data, buffer.width, buffer.height),
+            &pending.frame_state,
+        )?;
+        Ok(FrameProgress::Done(capture))
     }
-}
 
-impl Dispatch<WlOutput, ()> for WaylandCapture {
-    fn event(
-        state: &mut Self,
-        output: &WlOutput,
-        event: <WlOutput as Proxy>::Event,
-        _: &(),
-        _conn: &Connection,
-        _qh: &QueueHandle<Self>,
-    ) {
-        use wayland_client::protocol::wl_output::Event;
-        let output_id = output.id().protocol_id();
-        match event {
-            Event::Geometry {
-                x,
-                y,
-                physical_width: _,
-                physical_height: _,
-                subpixel: _,
-                make: _,
-                model: _,
-                transform,
-            } => {
-                if let Some(info) = state.globals.output_info.get_mut(&output_id) {
-                    info.x = x;
-                    info.y = y;
-                    if let wayland_client::WEnum::Value(t) = transform {
-                        info.transform = t;
-                    }
-                    if !info.logical_scale_known {
-                        info.logical_x = x;
-                        info.logical_y = y;
-                    }
+    /// Start a stream of whole-output captures for `output_name`.
+    pub(crate) fn start_stream(
+        &mut self,
+        output_name: &str,
+        overlay_cursor: bool,
+    ) -> Result<ShmStream> {
+        if self.globals.screencopy_manager.is_none() {
+            return Err(Error::UnsupportedProtocol(
+                "capture streams need zwlr_screencopy_manager_v1".to_string(),
+            ));
+        }
+        self.ensure_outputs()?;
+        let (output, _) = self
+            .collect_outputs_snapshot()
+            .into_iter()
+            .find(|(_, info)| info.name == output_name)
+            .ok_or_else(|| Error::OutputNotFound(output_name.to_string()))?;
+        Ok(ShmStream {
+            output,
+            overlay_cursor,
+            event_queue: self._connection.new_event_queue(),
+            buffer: None,
+        })
+    }
+
+    /// Capture the next frame of `stream` into its shared buffer.
+    ///
+    /// After the first frame, frames are requested with `copy_with_damage`
+    /// where supported, so the compositor only answers once the output
+    /// changed.
+    pub(crate) fn next_stream_frame(&mut self, stream: &mut ShmStream) -> Result<CaptureResult> {
+        let screencopy_manager = self.globals.screencopy_manager.clone().ok_or_else(|| {
+            Error::UnsupportedProtocol("zwlr_screencopy_manager_v1 not available".to_string())
+        })?;
+        let shm = self
+            .globals
+            .shm
+            .clone()
+            .ok_or_else(|| Error::UnsupportedProtocol("wl_shm not available".to_string()))?;
+        let qh = stream.event_queue.handle();
+        let frame_state = Arc::new(Mutex::new(FrameState::default()));
+        let frame = ScreencopyFrame(screencopy_manager.capture_output(
+            if stream.overlay_cursor { 1 } else { 0 },
+            &stream.output,
+            &qh,
+            frame_state.clone(),
+        ));
+
+        let started = Instant::now();
+        loop {
+            {
+                let state = lock_frame_state(&frame_state)?;
+                if state.failed {
+                    return Err(Error::CaptureFailed);
+                }
+                if state.buffer.is_some() && (frame.version() < 3 || state.buffer_done) {
+                    break;
+                }
+                if state.buffer_done {
+                    return Err(state.missing_buffer_error());
                 }
             }
-            Event::Mode {
-                flags: _,
-                width,
-                height,
-                refresh: _,
-            } => {
-                log::debug!(
-                    "Mode event for output_id {}: {}x{}",
-                    output_id,
-                    width,
-                    height
-                );
-                if let Some(info) = state.globals.output_info.get_mut(&output_id) {
-                    info.width = width;
-                    info.height = height;
-                    log::debug!("Updated output info: {}x{}", info.width, info.height);
-                    if !info.logical_scale_known {
-                        info.logical_width = width;
-                        info.logical_height = height;
-                    }
+            self.dispatch_until_deadline(&mut stream.event_queue, started)?;
+        }
+
+        let (width, height, stride, format) = {
+            let state = lock_frame_state(&frame_state)?;
+            if state.width == 0 || state.height == 0 {
+                return Err(Error::CaptureFailed);
+            }
+            let format = advertised_format(&state)?;
+            let stride = shm_stride(format, state.width, state.stride)?;
+            (state.width, state.height, stride, format)
+        };
+        let reusable = stream.buffer.as_ref().is_some_and(|buffer| {
+            (buffer.width, buffer.height, buffer.stride, buffer.format)
+                == (width, height, stride, format)
+        });
+        if !reusable {
+            stream.buffer = Some(StreamBuffer::new(&shm, &qh, width, height, stride, format)?);
+        }
+        let buffer = stream
+            .buffer
+            .as_ref()
+            .ok_or_else(|| Error::buffer_creation("stream buffer missing".to_string()))?;
+        // Damage tracking needs a previous frame in the same buffer.
+        if reusable && frame.version() >= 2 {
+            frame.copy_with_damage(&buffer.buffer);
+        } else {
+            frame.copy(&buffer.buffer);
+        }
+
+        let started = Instant::now();
+        loop {
+            {
+                let state = lock_frame_state(&frame_state)?;
+                if state.failed {
+                    return Err(Error::frame_capture(
+                        "Compositor failed to copy the frame".to_string(),
+                    ));
+                }
+                if state.ready {
+                    break;
                 }
             }
-            Event::Scale { factor } => {
-                if let Some(info) = state.globals.output_info.get_mut(&output_id) {
-                    info.scale = factor;
+            self.dispatch_until_deadline(&mut stream.event_queue, started)?;
+        }
+
+        let mut data = buffer.mmap.to_vec();
+        convert_to_rgba(format, &mut data, width, height, stride)?;
+        self.orient_for_output(
+            &stream.output,
+            CaptureResult::new(data, width, height),
+            &frame_state,
+        )
+    }
+
+    /// `capture_outputs` for compositors that only offer
+    /// `ext-image-copy-capture-v1`: captures the outputs one after another.
+    fn capture_outputs_ext(
+        &mut self,
+        parameters: Vec<CaptureParameters>,
+    ) -> Result<MultiOutputCaptureResult> {
+        let mut results = HashMap::new();
+        let mut placements = HashMap::new();
+        for param in &parameters {
+            let (output, info) = self
+                .collect_outputs_snapshot()
+                .into_iter()
+                .find(|(_, info)| info.name == param.output_name())
+                .ok_or_else(|| Error::OutputNotFound(param.output_name().to_string()))?;
+            let region = match param.region_ref() {
+                Some(region) => Box::new(
+                    region.x() - info.x,
+                    region.y() - info.y,
+                    region.width(),
+                    region.height(),
+                ),
+                None => Box::new(0, 0, info.width, info.height),
+            };
+            let capture =
+                self.capture_region_for_output(&output, region, param.overlay_cursor_enabled())?;
+            results.insert(param.output_name().to_string(), capture);
+            placements.insert(
+                param.output_name().to_string(),
+                output_placement(&info, param.region_ref()),
+            );
+        }
+        Ok(MultiOutputCaptureResult::new(results).with_placements(placements))
+    }
+
+    /// Apply the transform of `output` and the frame's flags to a capture.
+    fn orient_for_output(
+        &self,
+        output: &WlOutput,
+        capture: CaptureResult,
+        frame_state: &Arc<Mutex<FrameState>>,
+    ) -> Result<CaptureResult> {
+        let output_id = output.id().protocol_id();
+        let transform = self
+            .globals
+            .output_info
+            .get(&output_id)
+            .map(|info| info.transform)
+            .unwrap_or(wayland_client::protocol::wl_output::Transform::Normal);
+        let (flags, timestamp) = {
+            let state = lock_frame_state(frame_state)?;
+            (state.flags, state.timestamp)
+        };
+
+        let capture = CaptureResult {
+            timestamp,
+            ..capture
+        };
+        Ok(orient_capture(capture, transform, flags))
+    }
+
+    /// Whether a frame without a wl_shm buffer can be copied via dmabuf.
+    #[cfg(feature = "dmabuf")]
+    fn can_use_dmabuf(&self, state: &FrameState) -> bool {
+        self.dmabuf_enabled
+            && !self.dmabuf_rejected
+            && self.globals.linux_dmabuf.is_some()
+            && self.dmabuf_allocator.is_some()
+            && state
+                .dmabuf
+                .is_some_and(|offer| crate::dmabuf::is_supported_format(offer.format))
+    }
+
+    #[cfg(not(feature = "dmabuf"))]
+    fn can_use_dmabuf(&self, _state: &FrameState) -> bool {
+        false
+    }
+
+    /// Copy the frame into a GBM buffer if the compositor offered a dmabuf.
+    ///
+    /// Returns `Ok(None)` when the dmabuf path is unavailable (disabled, no
+    /// `zwp_linux_dmabuf_v1`, no render node, unsupported format, failed
+    /// allocation or buffer rejected by the compositor) so the caller can
+    /// fall back to wl_shm.
+    #[cfg(feature = "dmabuf")]
+    fn try_dmabuf_copy(
+        &mut self,
+        frame: &ZwlrScreencopyFrameV1,
+        frame_state: &Arc<Mutex<FrameState>>,
+        event_queue: &mut EventQueue<Self>,
+        qh: &QueueHandle<Self>,
+    ) -> Result<Option<CaptureResult>> {
+        let offer = {
+            let state = lock_frame_state(frame_state)?;
+            if !self.can_use_dmabuf(&state) {
+                return Ok(None);
+            }
+            match state.dmabuf {
+                Some(offer) => offer,
+                None => return Ok(None),
+            }
+        };
+        let (Some(linux_dmabuf), Some(allocator)) = (
+            self.globals.linux_dmabuf.clone(),
+            self.dmabuf_allocator.as_ref(),
+        ) else {
+            return Ok(None);
+        };
+
+        let buffer = match allocator.allocate(offer.width, offer.height, offer.format) {
+            Ok(buffer) => buffer,
+            Err(e) => {
+                log::debug!("Falling back to wl_shm: {}", e);
+                return Ok(None);
+            }
+        };
+        let fd = buffer.fd()?;
+        let modifier = buffer.modifier()?;
+        let params_state = Arc::new(Mutex::new(DmabufParamsState::default()));
+        let params = linux_dmabuf.create_params(qh, params_state.clone());
+        params.add(
+            fd.as_fd(),
+            0,
+            buffer.offset()?,
+            buffer.stride()?,
+            (modifier >> 32) as u32,
+            modifier as u32,
+        );
+        // Use `create` rather than `create_immed`: the compositor answers
+        // with `created` or `failed` before the frame is copied, so a
+        // rejected buffer can still fall back to wl_shm for this frame.
+        params.create(
+            offer.width as i32,
+            offer.height as i32,
+            buffer.fourcc(),
+            zwp_linux_buffer_params_v1::Flags::empty(),
+        );
+        let started = Instant::now();
+        let created = loop {
+            {
+                let mut state = lock_frame_state(&params_state)?;
+                if state.failed {
+                    break None;
+                }
+                if let Some(wl_buffer) = state.buffer.take() {
+                    break Some(wl_buffer);
                 }
             }
-            Event::Name { name } => {
-                if let Some(info) = state.globals.output_info.get_mut(&output_id) {
-                    info.name = name.clone();
+            if let Err(e) = self.dispatch_until_deadline(event_queue, started) {
+                params.destroy();
+                return Err(e);
+            }
+        };
+        params.destroy();
+        let Some(wl_buffer) = created else {
+            log::debug!("Falling back to wl_shm: compositor rejected the dmabuf");
+            return Ok(None);
+        };
+        frame.copy(&wl_buffer);
+
+        let started = Instant::now();
+        loop {
+            {
+                let state = lock_frame_state(frame_state)?;
+                if state.failed {
+                    wl_buffer.destroy();
+                    return Err(Error::frame_capture(
+                        "Compositor failed to copy frame into dmabuf".to_string(),
+                    ));
+                }
+                if state.ready {
+                    break;
                 }
             }
-            Event::Description { description } => {
-                if let Some(info) = state.globals.output_info.get_mut(&output_id) {
-                    info.description = Some(description);
+            if let Err(e) = self.dispatch_until_deadline(event_queue, started) {
+                wl_buffer.destroy();
+                return Err(e);
+            }
+        }
+
+        let data = match self.dmabuf_allocator.as_ref() {
+            Some(allocator) => buffer.read_rgba(allocator),
+            None => Err(Error::buffer_creation("dmabuf allocator went away")),
+        };
+        wl_buffer.destroy();
+        let data = data?;
+        log::info!("Captured {}x{} frame via dmabuf", offer.width, offer.height);
+        Ok(Some(CaptureResult::new(data, offer.width, offer.height)))
+    }
+
+    pub fn get_outputs(&mut self) -> Result<Vec<Output>> {
+        self.with_outputs(|outputs| outputs.to_vec())
+    }
+
+    /// Lend the cached outputs to `f`, refreshing them first if stale.
+    pub fn with_outputs<F, T>(&mut self, f: F) -> Result<T>
+    where
+        F: FnOnce(&[Output]) -> T,
+    {
+        self.ensure_outputs()?;
+        if self.public_outputs.is_empty() {
+            return Err(Error::NoOutputs);
+        }
+        Ok(f(&self.public_outputs))
+    }
+
+    pub fn capture_all(&mut self) -> Result<CaptureResult> {
+        self.capture_all_with_options(&CaptureOptions::new())
+    }
+
+    pub fn capture_all_with_scale(&mut self, scale: f64) -> Result<CaptureResult> {
+        self.capture_all_with_options(&CaptureOptions::new().scale(scale))
+    }
+
+    pub fn capture_all_with_options(&mut self, options: &CaptureOptions) -> Result<CaptureResult> {
+        self.capture(&CaptureOptions {
+            region: None,
+            output: None,
+            ..options.clone()
+        })
+    }
+
+    pub fn capture(&mut self, options: &CaptureOptions) -> Result<CaptureResult> {
+        let plan = self.plan_capture(options)?;
+        plan.assemble(|job| self.capture_job(job))
+    }
+
+    /// Work out which frames `options` needs, refreshing the output cache
+    /// first if it is stale.
+    pub(crate) fn plan_capture(&mut self, options: &CaptureOptions) -> Result<CapturePlan> {
+        self.ensure_outputs()?;
+        let snapshot = self.collect_outputs_snapshot();
+        // A software cursor replaces the compositor's.
+        let overlay_cursor = options.overlay_cursor_enabled() && options.cursor_image().is_none();
+
+        let (region, target) = match (options.target_output(), options.target_region()) {
+            (Some(output_name), region) => {
+                let (output_handle, info) = snapshot
+                    .into_iter()
+                    .find(|(_, info)| info.name == output_name)
+                    .ok_or_else(|| Error::OutputNotFound(output_name.to_string()))?;
+                match region {
+                    Some(region) => {
+                        let clipped =
+                            region.intersection(&info.logical_box()).ok_or_else(|| {
+                                Error::InvalidRegion(format!(
+                                    "Capture region does not intersect with output '{}'",
+                                    output_name
+                                ))
+                            })?;
+                        (clipped, PlanTarget::Composite(vec![(output_handle, info)]))
+                    }
+                    None => {
+                        let local_region = Box::new(0, 0, info.width, info.height);
+                        (
+                            info.logical_box(),
+                            PlanTarget::Output(
+                                output_handle,
+                                ScreencopyRequest::new(local_region, overlay_cursor),
+                            ),
+                        )
+                    }
                 }
             }
-            _ => {}
+            (None, Some(region)) => (region, PlanTarget::Composite(snapshot)),
+            (None, None) => {
+                let region = outputs_bounding_box(snapshot.iter().map(|(_, info)| info))
+                    .ok_or(Error::NoOutputs)?;
+                (region, PlanTarget::Composite(snapshot))
+            }
+        };
+        Ok(CapturePlan {
+            region,
+            target,
+            overlay_cursor,
+            options: options.clone(),
+        })
+    }
+
+    /// Capture one frame of a [`CapturePlan`], blocking until the
+    /// compositor has copied it.
+    pub(crate) fn capture_job(&mut self, job: &FrameJob) -> Result<CaptureResult> {
+        self.capture_region_for_output(&job.output, job.request.region, job.overlay_cursor())
+    }
+
+    pub fn capture_output(&mut self, output_name: &str) -> Result<CaptureResult> {
+        self.capture_output_with_options(output_name, &CaptureOptions::new())
+    }
+
+    pub fn capture_output_with_scale(
+        &mut self,
+        output_name: &str,
+        scale: f64,
+    ) -> Result<CaptureResult> {
+        self.capture_output_with_options(output_name, &CaptureOptions::new().scale(scale))
+    }
+
+    pub fn capture_output_with_options(
+        &mut self,
+        output_name: &str,
+        options: &CaptureOptions,
+    ) -> Result<CaptureResult> {
+        self.capture(&CaptureOptions {
+            region: None,
+            output: Some(output_name.to_string()),
+            ..options.clone()
+        })
+    }
+
+    pub fn capture_output_thumbnail(
+        &mut self,
+        output_name: &str,
+        max_width: u32,
+        max_height: u32,
+    ) -> Result<CaptureResult> {
+        if max_width == 0 || max_height == 0 {
+            return Err(Error::InvalidRegion(
+                "Thumbnail size must be positive".to_string(),
+            ));
+        }
+        self.ensure_outputs()?;
+        let (output, info) = self
+            .collect_outputs_snapshot()
+            .into_iter()
+            .find(|(_, info)| info.name == output_name)
+            .ok_or_else(|| Error::OutputNotFound(output_name.to_string()))?;
+        let region = Box::new(0, 0, info.width, info.height);
+        self.capture_output_frame(&output, region, false, Some((max_width, max_height)))
+    }
+
+    pub fn capture_region(&mut self, region: Box) -> Result<CaptureResult> {
+        self.capture_region_with_options(region, &CaptureOptions::new())
+    }
+
+    pub fn capture_region_with_scale(&mut self, region: Box, scale: f64) -> Result<CaptureResult> {
+        self.capture_region_with_options(region, &CaptureOptions::new().scale(scale))
+    }
+
+    pub fn capture_region_with_options(
+        &mut self,
+        region: Box,
+        options: &CaptureOptions,
+    ) -> Result<CaptureResult> {
+        self.capture(&CaptureOptions {
+            region: Some(region),
+            output: None,
+            ..options.clone()
+        })
+    }
+
+    pub fn capture_outputs(
+        &mut self,
+        parameters: Vec<CaptureParameters>,
+    ) -> Result<MultiOutputCaptureResult> {
+        self.ensure_outputs()?;
+        if self.globals.outputs.is_empty() {
+            return Err(Error::NoOutputs);
+        }
+
+        if self.globals.screencopy_manager.is_none() && self.globals.has_image_copy_capture() {
+            return self.capture_outputs_ext(parameters);
+        }
+
+        let screencopy_manager =
+            self.globals
+                .screencopy_manager
+                .as_ref()
+                .ok_or(Error::UnsupportedProtocol(
+                    "zwlr_screencopy_manager_v1 not available".to_string(),
+                ))?;
+        let mut event_queue = self._connection.new_event_queue();
+        let qh = event_queue.handle();
+        let mut frame_states: HashMap<String, Arc<Mutex<FrameState>>> = HashMap::new();
+        let mut frames: HashMap<String, ScreencopyFrame> = HashMap::new();
+        let mut placements = HashMap::new();
+
+        for param in &parameters {
+            let (output_id, output_info) = self
+                .globals
+                .output_info
+                .iter()
+                .find(|(_, info)| info.name == param.output_name())
+                .ok_or_else(|| Error::OutputNotFound(param.output_name().to_string()))?;
+
+            let output = self
+                .globals
+                .outputs
+                .iter()
+                .find(|o| o.id().protocol_id() == *output_id)
+                .ok_or_else(|| Error::OutputNotFound(param.output_name().to_string()))?;
+            let region = if let Some(region) = param.region_ref() {
+                let output_right = output_info.x + output_info.width;
+                let output_bottom = output_info.y + output_info.height;
+                if region.x() < output_info.x
+                    || region.y() < output_info.y
+                    || region.x() + region.width() > output_right
+                    || region.y() + region.height() > output_bottom
+                {
+                    return Err(Error::InvalidRegion(
+                        "Capture region extends outside output boundaries".to_string(),
+                    ));
+                }
+                *region
+            } else {
+                Box::new(
+                    output_info.x,
+                    output_info.y,
+                    output_info.width,
+                    output_info.height,
+                )
+            };
+            let frame_state = Arc::new(Mutex::new(FrameState::default()));
+            let request = ScreencopyRequest::new(region, param.overlay_cursor_enabled());
+            let frame = screencopy_manager.capture_output_region(
+                request.overlay_cursor,
+                output,
+                request.region.x(),
+                request.region.y(),
+                request.region.width(),
+                request.region.height(),
+                &qh,
+                frame_state.clone(),
+            );
+            frame_states.insert(param.output_name().to_string(), frame_state);
+            frames.insert(param.output_name().to_string(), ScreencopyFrame(frame));
+            placements.insert(
+                param.output_name().to_string(),
+                output_placement(output_info, param.region_ref()),
+            );
+        }
+        let total_frames = parameters.len();
+        let started = Instant::now();
+        loop {
+            let completed_frames = frame_states
+                .iter()
+                .filter(|(_, state)| {
+                    state
+                        .lock()
+                        .ok()
+                        .is_some_and(|s| s.buffer.is_some() || s.ready || s.buffer_done)
+                })
+                .count();
+            if completed_frames >= total_frames {
+                break;
+            }
+            self.dispatch_until_deadline(&mut event_queue, started)?;
+        }
+        for frame_state in frame_states.values() {
+            let state = lock_frame_state(frame_state)?;
+            if state.buffer.is_none() {
+                if state.dmabuf.is_some() {
+                    return Err(state.missing_buffer_error());
+                }
+                return Err(Error::CaptureFailed);
+            }
+        }
+        let mut buffers: HashMap<String, (tempfile::NamedTempFile, memmap2::MmapMut)> =
+            HashMap::new();
+        for (output_name, frame_state) in &frame_states {
+            let (width, height, stride, size) = {
+                let state = lock_frame_state(frame_state)?;
+                let width = state.width;
+                let height = state.height;
+                let format = advertised_format(&state)?;
+                let stride = shm_stride(format, width, state.stride)?;
+                let size = (stride * height) as usize;
+                (width, height, stride, size)
+            };
+            let mut tmp_file = tempfile::NamedTempFile::new().map_err(|e| {
+                Error::buffer_creation(format!(
+                    "failed to create temporary file for output '{}'",
+                    output_name
+                ))
+                .with_source(e)
+            })?;
+            tmp_file.as_file_mut().set_len(size as u64).map_err(|e| {
+                Error::buffer_creation(format!(
+                    "failed to resize buffer for output '{}' to {} bytes",
+                    output_name, size
+                ))
+                .with_source(e)
+            })?;
+            let mmap = unsafe {
+                memmap2::MmapMut::map_mut(&tmp_file).map_err(|e| {
+                    Error::buffer_creation(format!(
+                        "failed to memory-map buffer for output '{}'",
+                        output_name
+                    ))
+                    .with_source(e)
+                })?
+            };
+            let shm = self.globals.shm.as_ref().ok_or(Error::UnsupportedProtocol(
+                "wl_shm not available".to_string(),
+            ))?;
+            {
+                let format = {
+                    let state = lock_frame_state(frame_state)?;
+                    advertised_format(&state)?
+                };
+                let pool = shm.create_pool(
+                    unsafe { BorrowedFd::borrow_raw(tmp_file.as_file().as_raw_fd()) },
+                    size as i32,
+                    &qh,
+                    (),
+                );
+                let buffer = pool.create_buffer(
+                    0,
+                    width as i32,
+                    height as i32,
+                    stride as i32,
+                    format,
+                    &qh,
+                    (),
+                );
+                if let Some(frame) = frames.get(output_name) {
+                    frame.copy(&buffer);
+                }
+            }
+            buffers.insert(output_name.clone(), (tmp_file, mmap));
+        }
+        let started = Instant::now();
+        loop {
+            let completed_frames = frame_states
+                .iter()
+                .filter(|(_, state)| state.lock().ok().is_some_and(|s| s.ready))
+                .count();
+            if completed_frames >= total_frames {
+                break;
+            }
+            self.dispatch_until_deadline(&mut event_queue, started)?;
+        }
+        for frame_state in frame_states.values() {
+            let state = lock_frame_state(frame_state)?;
+            if state.ready && state.buffer.is_none() {
+                return Err(Error::frame_capture(
+                    "Frame is ready but buffer was not received".to_string(),
+                ));
+            }
+        }
+        let mut results: HashMap<String, CaptureResult> = HashMap::new();
+        for (output_name, (_tmp_file, mmap)) in buffers {
+            let frame_state = &frame_states[&output_name];
+            let (width, height, flags, timestamp) = {
+                let state = lock_frame_state(frame_state)?;
+                (state.width, state.height, state.flags, state.timestamp)
+            };
+            let transform = self
+                .globals
+                .output_info
+                .values()
+                .find(|info| info.name == output_name)
+                .map(|info| info.transform)
+                .unwrap_or(wayland_client::protocol::wl_output::Transform::Normal);
+            let mut buffer_data = mmap.to_vec();
+            let (format, stride) = {
+                let state = lock_frame_state(frame_state)?;
+                let format = advertised_format(&state)?;
+                (format, shm_stride(format, width, state.stride)?)
+            };
+            convert_to_rgba(format, &mut buffer_data, width, height, stride)?;
+            let capture = CaptureResult {
+                data: buffer_data,
+                width,
+                height,
+                timestamp,
+            };
+            results.insert(output_name, orient_capture(capture, transform, flags));
+        }
+        Ok(MultiOutputCaptureResult::new(results).with_placements(placements))
+    }
+
+    pub fn capture_outputs_with_scale(
+        &mut self,
+        parameters: Vec<CaptureParameters>,
+        default_scale: f64,
+    ) -> Result<MultiOutputCaptureResult> {
+        let targets: HashMap<String, (f64, Option<Box>)> = parameters
+            .iter()
+            .map(|param| {
+                (
+                    param.output_name().to_string(),
+                    (
+                        param.scale_factor().unwrap_or(default_scale),
+                        param.region_ref().copied(),
+                    ),
+                )
+            })
+            .collect();
+        let result = self.capture_outputs(parameters)?;
+        let placements = result.placements.clone();
+        let mut scaled_results = std::collections::HashMap::new();
+
+        for (output_name, capture_result) in result.into_outputs() {
+            let (scale, region) = targets
+                .get(&output_name)
+                .copied()
+                .unwrap_or((default_scale, None));
+            let scaled_result = match region {
+                Some(region) => scale_region_capture(capture_result, region, scale)?,
+                None => scale_capture(capture_result, scale, ScaleFilter::Auto)?,
+            };
+            scaled_results.insert(output_name, scaled_result);
+        }
+
+        Ok(MultiOutputCaptureResult::new(scaled_results).with_placements(placements))
+    }
+}
+
+/// Resize a capture of `region` to `region * scale`.
+///
+/// Region captures are sized from the region itself rather than from the
+/// buffer, so HiDPI buffers end up as region * scale like everywhere else.
+fn scale_region_capture(capture: CaptureResult, region: Box, scale: f64) -> Result<CaptureResult> {
+    let width = (region.width() as f64 * scale) as u32;
+    let height = (region.height() as f64 * scale) as u32;
+    if width == 0 || height == 0 {
+        return Err(Error::InvalidRegion(
+            "Scaled dimensions must be positive".to_string(),
+        ));
+    }
+    if capture.width == width && capture.height == height {
+        Ok(capture)
+    } else {
+        resize_capture(
+            capture,
+            width,
+            height,
+            filter_type(ScaleFilter::Auto, scale),
+        )
+    }
+}
+
+impl Dispatch<WlRegistry, ()> for WaylandCapture {
+    fn event(
+        state: &mut Self,
+        registry: &WlRegistry,
+        event: <WlRegistry as Proxy>::Event,
+        _: &(),
+        _conn: &Connection,
+        qh: &QueueHandle<Self>,
+    ) {
+        use wayland_client::protocol::wl_registry::Event;
+        if let Event::GlobalRemove { name } = event {
+            if state.globals.remove_output(name) {
+                log::debug!("Output global {} was removed", name);
+                state.update_public_outputs();
+            }
+            return;
+        }
+        if let Event::Global {
+            name,
+            interface,
+            version,
+        } = event
+        {
+            match interface.as_str() {
+                "wl_compositor" => {
+                    state.globals.compositor =
+                        Some(registry.bind::<WlCompositor, _, _>(name, version, qh, ()));
+                }
+                "wl_shm" => {
+                    state.globals.shm = Some(registry.bind::<WlShm, _, _>(name, version, qh, ()));
+                }
+                "zwlr_screencopy_manager_v1" => {
+                    state.globals.screencopy_manager =
+                        Some(registry.bind::<ZwlrScreencopyManagerV1, _, _>(name, version, qh, ()));
+                }
+                #[cfg(feature = "dmabuf")]
+                "zwp_linux_dmabuf_v1" if version >= 2 => {
+                    state.globals.linux_dmabuf =
+                        Some(registry.bind::<ZwpLinuxDmabufV1, _, _>(name, version.min(3), qh, ()));
+                }
+                "ext_image_copy_capture_manager_v1" => {
+                    state.globals.image_copy_capture_manager =
+                        Some(registry.bind::<ExtImageCopyCaptureManagerV1, _, _>(
+                            name,
+                            version.min(1),
+                            qh,
+                            (),
+                        ));
+                }
+                "ext_output_image_capture_source_manager_v1" => {
+                    state.globals.output_capture_source_manager =
+                        Some(registry.bind::<ExtOutputImageCaptureSourceManagerV1, _, _>(
+                            name,
+                            version.min(1),
+                            qh,
+                            (),
+                        ));
+                }
+                "zxdg_output_manager_v1" => {
+                    state.globals.xdg_output_manager =
+                        Some(registry.bind::<ZxdgOutputManagerV1, _, _>(name, version, qh, ()));
+
+                    for output in &state.globals.outputs {
+                        let xdg_output = state
+                            .globals
+                            .xdg_output_manager
+                            .as_ref()
+                            .unwrap()
+                            .get_xdg_output(output, qh, ());
+                        let output_id = output.id().protocol_id();
+                        state.globals.output_xdg_map.insert(output_id, xdg_output);
+                    }
+                }
+                "wl_output" => {
+                    state.outputs_stale = true;
+                    let output = registry.bind::<WlOutput, _, _>(name, version, qh, ());
+                    let output_id = output.id().protocol_id();
+
+                    state
+                        .globals
+                        .output_info
+                        .insert(output_id, OutputInfo::new(format!("output-{}", name)));
+                    let output_idx = state.globals.outputs.len();
+                    state.globals.outputs.push(output.clone());
+                    state.globals.output_globals.insert(name, output.clone());
+
+                    if let Some(ref xdg_output_manager) = state.globals.xdg_output_manager {
+                        let output_to_use = &state.globals.outputs[output_idx];
+                        let xdg_output = xdg_output_manager.get_xdg_output(output_to_use, qh, ());
+                        let output_id = output_to_use.id().protocol_id();
+                        state.globals.output_xdg_map.insert(output_id, xdg_output);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+impl Dispatch<WlOutput, ()> for WaylandCapture {
+    fn event(
+        state: &mut Self,
+        output: &WlOutput,
+        event: <WlOutput as Proxy>::Event,
+        _: &(),
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+    ) {
+        use wayland_client::protocol::wl_output::Event;
+        let output_id = output.id().protocol_id();
+        match event {
+            Event::Geometry {
+                x,
+                y,
+                physical_width,
+                physical_height,
+                subpixel: _,
+                make,
+                model,
+                transform,
+            } => {
+                if let Some(info) = state.globals.output_info.get_mut(&output_id) {
+                    info.x = x;
+                    info.y = y;
+                    info.physical_width = physical_width.max(0);
+                    info.physical_height = physical_height.max(0);
+                    info.make = make;
+                    info.model = model;
+                    if let wayland_client::WEnum::Value(t) = transform {
+                        info.transform = t;
+                    }
+                    if !info.logical_scale_known {
+                        info.logical_x = x;
+                        info.logical_y = y;
+                    }
+                }
+            }
+            Event::Mode {
+                flags,
+                width,
+                height,
+                refresh,
+            } => {
+                use wayland_client::protocol::wl_output::Mode;
+                // Older compositors advertise every supported mode; only the
+                // current one describes what is actually on screen.
+                if let wayland_client::WEnum::Value(flags) = flags {
+                    if !flags.contains(Mode::Current) {
+                        return;
+                    }
+                }
+                log::debug!(
+                    "Mode event for output_id {}: {}x{}",
+                    output_id,
+                    width,
+                    height
+                );
+                if let Some(info) = state.globals.output_info.get_mut(&output_id) {
+                    info.width = width;
+                    info.height = height;
+                    info.refresh_mhz = refresh.max(0) as u32;
+                    log::debug!("Updated output info: {}x{}", info.width, info.height);
+                    if !info.logical_scale_known {
+                        info.logical_width = width;
+                        info.logical_height = height;
+                    }
+                }
+            }
+            Event::Scale { factor } => {
+                if let Some(info) = state.globals.output_info.get_mut(&output_id) {
+                    info.scale = factor;
+                }
+            }
+            Event::Name { name } => {
+                if let Some(info) = state.globals.output_info.get_mut(&output_id) {
+                    info.name = name.clone();
+                }
+            }
+            Event::Description { description } => {
+                if let Some(info) = state.globals.output_info.get_mut(&output_id) {
+                    info.description = Some(description);
+                }
+            }
+            // Sent after each batch of changes, including the initial one; a
+            // refresh in progress clears the flag again once it finishes.
+            Event::Done => {
+                state.outputs_stale = true;
+            }
+            _ => {}
+        }
+    }
+}
+
+impl Dispatch<WlCompositor, ()> for WaylandCapture {
+    fn event(
+        _state: &mut Self,
+        _proxy: &WlCompositor,
+        _event: <WlCompositor as Proxy>::Event,
+        _data: &(),
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+    ) {
+    }
+}
+
+impl Dispatch<WlShm, ()> for WaylandCapture {
+    fn event(
+        _state: &mut Self,
+        _proxy: &WlShm,
+        _event: <WlShm as Proxy>::Event,
+        _data: &(),
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+    ) {
+    }
+}
+
+impl Dispatch<ZwlrScreencopyManagerV1, ()> for WaylandCapture {
+    fn event(
+        _state: &mut Self,
+        _proxy: &ZwlrScreencopyManagerV1,
+        _event: <ZwlrScreencopyManagerV1 as Proxy>::Event,
+        _data: &(),
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+    ) {
+    }
+}
+
+impl Dispatch<ZwlrScreencopyFrameV1, Arc<Mutex<FrameState>>> for WaylandCapture {
+    fn event(
+        _state: &mut Self,
+        frame: &ZwlrScreencopyFrameV1,
+        event: <ZwlrScreencopyFrameV1 as Proxy>::Event,
+        frame_state: &Arc<Mutex<FrameState>>,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+    ) {
+        use wayland_protocols_wlr::screencopy::v1::client::zwlr_screencopy_frame_v1::Event;
+        match event {
+            Event::Buffer {
+                format,
+                width,
+                height,
+                stride,
+            } => {
+                let mut state = lock_frame_state(frame_state)
+                    .expect("Frame state mutex poisoned in Buffer event");
+                state.width = width;
+                state.height = height;
+                state.stride = stride;
+                match format {
+                    wayland_client::WEnum::Value(val) => state.format = Some(val),
+                    wayland_client::WEnum::Unknown(raw) => {
+                        log::debug!("Compositor offered unknown wl_shm format {:#x}", raw);
+                        state.format = None;
+                    }
+                }
+                state.buffer = Some(vec![0u8; (stride * height) as usize]);
+            }
+            Event::Flags { flags } => {
+                let mut state = lock_frame_state(frame_state)
+                    .expect("Frame state mutex poisoned in Flags event");
+                if let wayland_client::WEnum::Value(val) = flags {
+                    state.flags = val.bits();
+                    log::debug!("Received flags: {:?} (bits: {})", flags, val.bits());
+                }
+            }
+            Event::Ready {
+                tv_sec_hi,
+                tv_sec_lo,
+                tv_nsec,
+            } => {
+                let mut state = lock_frame_state(frame_state)
+                    .expect("Frame state mutex poisoned in Ready event");
+                state.ready = true;
+                state.timestamp = Some(frame_timestamp(tv_sec_hi, tv_sec_lo, tv_nsec));
+                frame.destroy();
+            }
+            Event::Damage {
+                x,
+                y,
+                width,
+                height,
+            } => {
+                log::debug!("Frame damage: {}x{}+{}+{}", width, height, x, y);
+            }
+            Event::Failed => {
+                let mut state = lock_frame_state(frame_state)
+                    .expect("Frame state mutex poisoned in Failed event");
+                state.ready = true;
+                state.failed = true;
+            }
+            Event::LinuxDmabuf {
+                format,
+                width,
+                height,
+            } => {
+                log::debug!(
+                    "Received LinuxDmabuf: format={}, width={}, height={}",
+                    format,
+                    width,
+                    height
+                );
+                let mut state = lock_frame_state(frame_state)
+                    .expect("Frame state mutex poisoned in LinuxDmabuf event");
+                state.dmabuf = Some(DmabufOffer {
+                    format,
+                    width,
+                    height,
+                });
+            }
+            Event::BufferDone => {
+                log::debug!("All buffer types announced");
+                let mut state = lock_frame_state(frame_state)
+                    .expect("Frame state mutex poisoned in BufferDone event");
+                state.buffer_done = true;
+            }
+            _ => {
+                log::warn!("Received unknown event: {:?}", event);
+            }
+        }
+    }
+}
+
+impl Dispatch<ExtImageCopyCaptureManagerV1, ()> for WaylandCapture {
+    fn event(
+        _state: &mut Self,
+        _proxy: &ExtImageCopyCaptureManagerV1,
+        _event: <ExtImageCopyCaptureManagerV1 as Proxy>::Event,
+        _data: &(),
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+    ) {
+    }
+}
+
+impl Dispatch<ExtOutputImageCaptureSourceManagerV1, ()> for WaylandCapture {
+    fn event(
+        _state: &mut Self,
+        _proxy: &ExtOutputImageCaptureSourceManagerV1,
+        _event: <ExtOutputImageCaptureSourceManagerV1 as Proxy>::Event,
+        _data: &(),
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+    ) {
+    }
+}
+
+impl Dispatch<ExtImageCaptureSourceV1, ()> for WaylandCapture {
+    fn event(
+        _state: &mut Self,
+        _proxy: &ExtImageCaptureSourceV1,
+        _event: <ExtImageCaptureSourceV1 as Proxy>::Event,
+        _data: &(),
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+    ) {
+    }
+}
+
+impl Dispatch<ExtImageCopyCaptureSessionV1, Arc<Mutex<ExtCaptureState>>> for WaylandCapture {
+    fn event(
+        _state: &mut Self,
+        _session: &ExtImageCopyCaptureSessionV1,
+        event: <ExtImageCopyCaptureSessionV1 as Proxy>::Event,
+        capture_state: &Arc<Mutex<ExtCaptureState>>,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+    ) {
+        use wayland_protocols::ext::image_copy_capture::v1::client::ext_image_copy_capture_session_v1::Event;
+        let mut state =
+            lock_frame_state(capture_state).expect("Capture state mutex poisoned in session event");
+        match event {
+            Event::BufferSize { width, height } => {
+                state.width = width;
+                state.height = height;
+            }
+            Event::ShmFormat {
+                format: wayland_client::WEnum::Value(format),
+            } => {
+                state.shm_formats.push(format);
+            }
+            Event::Done => {
+                state.constraints_done = true;
+            }
+            Event::Stopped => {
+                state.stopped = true;
+            }
+            _ => {}
+        }
+    }
+}
+
+impl Dispatch<ExtImageCopyCaptureFrameV1, Arc<Mutex<ExtCaptureState>>> for WaylandCapture {
+    fn event(
+        _state: &mut Self,
+        _frame: &ExtImageCopyCaptureFrameV1,
+        event: <ExtImageCopyCaptureFrameV1 as Proxy>::Event,
+        capture_state: &Arc<Mutex<ExtCaptureState>>,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+    ) {
+        use wayland_protocols::ext::image_copy_capture::v1::client::ext_image_copy_capture_frame_v1::Event;
+        let mut state =
+            lock_frame_state(capture_state).expect("Capture state mutex poisoned in frame event");
+        match event {
+            Event::Transform {
+                transform: wayland_client::WEnum::Value(transform),
+            } => {
+                state.transform = Some(transform);
+            }
+            Event::PresentationTime {
+                tv_sec_hi,
+                tv_sec_lo,
+                tv_nsec,
+            } => {
+                state.timestamp = Some(frame_timestamp(tv_sec_hi, tv_sec_lo, tv_nsec));
+            }
+            Event::Ready => {
+                state.ready = true;
+            }
+            Event::Failed { reason } => {
+                log::debug!("ext-image-copy-capture frame failed: {:?}", reason);
+                state.failed = true;
+            }
+            _ => {}
+        }
+    }
+}
+
+impl Dispatch<ZxdgOutputV1, ()> for WaylandCapture {
+    fn event(
+        state: &mut Self,
+        xdg_output: &ZxdgOutputV1,
+        event: <ZxdgOutputV1 as Proxy>::Event,
+        _: &(),
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+    ) {
+        use wayland_protocols::xdg::xdg_output::zv1::client::zxdg_output_v1::Event;
+
+        let xdg_output_id = xdg_output.id().protocol_id();
+
+        let mut found_output_id = None;
+        for (wl_output_id, mapped_xdg_output) in &state.globals.output_xdg_map {
+            if mapped_xdg_output.id().protocol_id() == xdg_output_id {
+                found_output_id = Some(*wl_output_id);
+                break;
+            }
+        }
+
+        if let Some(wl_output_id) = found_output_id {
+            if let Some(info) = state.globals.output_info.get_mut(&wl_output_id) {
+                match event {
+                    Event::LogicalPosition { x, y } => {
+                        info.logical_x = x;
+                        info.logical_y = y;
+                        info.logical_scale_known = true;
+                    }
+                    Event::LogicalSize { width, height } => {
+                        info.logical_width = width;
+                        info.logical_height = height;
+                        info.logical_scale_known = true;
+                    }
+                    Event::Name { name }
+                        if info.name.starts_with("output-") || info.name.is_empty() =>
+                    {
+                        info.name = name.clone();
+                    }
+                    Event::Description { description } => {
+                        info.description = Some(description);
+                    }
+                    Event::Done => {
+                        info.logical_scale_known = true;
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+}
+
+impl Dispatch<WlBuffer, ()> for WaylandCapture {
+    fn event(
+        _state: &mut Self,
+        _proxy: &WlBuffer,
+        _event: <WlBuffer as Proxy>::Event,
+        _data: &(),
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+    ) {
+    }
+}
+
+impl Dispatch<WlShmPool, ()> for WaylandCapture {
+    fn event(
+        _state: &mut Self,
+        _proxy: &WlShmPool,
+        _event: <WlShmPool as Proxy>::Event,
+        _data: &(),
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+    ) {
+    }
+}
+
+#[cfg(feature = "dmabuf")]
+impl Dispatch<ZwpLinuxDmabufV1, ()> for WaylandCapture {
+    fn event(
+        _state: &mut Self,
+        _proxy: &ZwpLinuxDmabufV1,
+        _event: <ZwpLinuxDmabufV1 as Proxy>::Event,
+        _data: &(),
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+    ) {
+    }
+}
+
+#[cfg(feature = "dmabuf")]
+impl Dispatch<ZwpLinuxBufferParamsV1, Arc<Mutex<DmabufParamsState>>> for WaylandCapture {
+    fn event(
+        state: &mut Self,
+        _proxy: &ZwpLinuxBufferParamsV1,
+        event: <ZwpLinuxBufferParamsV1 as Proxy>::Event,
+        data: &Arc<Mutex<DmabufParamsState>>,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+    ) {
+        let Ok(mut params) = data.lock() else {
+            return;
+        };
+        match event {
+            zwp_linux_buffer_params_v1::Event::Created { buffer } => {
+                params.buffer = Some(buffer);
+            }
+            zwp_linux_buffer_params_v1::Event::Failed => {
+                log::warn!("Compositor rejected dmabuf buffer parameters, using wl_shm");
+                params.failed = true;
+                state.dmabuf_rejected = true;
+            }
+            _ => {}
+        }
+    }
+
+    wayland_client::event_created_child!(WaylandCapture, ZwpLinuxBufferParamsV1, [
+        zwp_linux_buffer_params_v1::EVT_CREATED_OPCODE => (WlBuffer, ()),
+    ]);
+}
+
+impl Dispatch<ZxdgOutputManagerV1, ()> for WaylandCapture {
+    fn event(
+        _state: &mut Self,
+        _proxy: &ZxdgOutputManagerV1,
+        _event: <ZxdgOutputManagerV1 as Proxy>::Event,
+        _data: &(),
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+    ) {
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+struct FrameState {
+    buffer: Option<Vec<u8>>,
+    width: u32,
+    height: u32,
+    /// Row length in bytes announced by the compositor.
+    stride: u32,
+    format: Option<ShmFormat>,
+    ready: bool,
+    flags: u32,
+    /// The `linux_dmabuf` buffer offered by the compositor, if any.
+    dmabuf: Option<DmabufOffer>,
+    /// The compositor has announced all buffer types (`buffer_done`).
+    buffer_done: bool,
+    /// The compositor sent `failed` for this frame.
+    #[cfg_attr(not(feature = "dmabuf"), allow(dead_code))]
+    failed: bool,
+    /// Presentation time from the `ready` event (`CLOCK_MONOTONIC`).
+    timestamp: Option<Duration>,
+}
+
+/// State of an `ext-image-copy-capture-v1` session and its single frame.
+#[derive(Debug, Default)]
+struct ExtCaptureState {
+    /// Buffer size from the session's `buffer_size` event.
+    width: u32,
+    height: u32,
+    /// wl_shm formats accepted by the session.
+    shm_formats: Vec<ShmFormat>,
+    /// All buffer constraints have been sent (`done`).
+    constraints_done: bool,
+    /// The session was stopped by the compositor.
+    stopped: bool,
+    /// Buffer transform reported by the frame.
+    transform: Option<wayland_client::protocol::wl_output::Transform>,
+    /// Presentation time from the frame's `presentation_time` event.
+    timestamp: Option<Duration>,
+    ready: bool,
+    failed: bool,
+}
+
+impl ExtCaptureState {
+    /// Pick a wl_shm format we can convert, preferring the common ones.
+    fn preferred_shm_format(&self) -> Option<ShmFormat> {
+        [
+            ShmFormat::Xrgb8888,
+            ShmFormat::Argb8888,
+            ShmFormat::Xbgr8888,
+            ShmFormat::Abgr8888,
+        ]
+        .into_iter()
+        .find(|format| self.shm_formats.contains(format))
+    }
+}
+
+/// Answer to a `zwp_linux_buffer_params_v1.create` request.
+#[cfg(feature = "dmabuf")]
+#[derive(Debug, Default)]
+struct DmabufParamsState {
+    /// The buffer announced by `created`.
+    buffer: Option<WlBuffer>,
+    /// The compositor sent `failed`.
+    failed: bool,
+}
+
+/// Parameters of a `linux_dmabuf` screencopy buffer offer.
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(not(feature = "dmabuf"), allow(dead_code))]
+struct DmabufOffer {
+    /// DRM fourcc format code.
+    format: u32,
+    width: u32,
+    height: u32,
+}
+
+impl FrameState {
+    /// Error for a frame whose buffer negotiation ended without a wl_shm buffer.
+    fn missing_buffer_error(&self) -> Error {
+        if self.dmabuf.is_some() {
+            Error::UnsupportedProtocol("compositor only offers dmabuf buffers".to_string())
+        } else if self.ready {
+            Error::frame_capture("Frame is ready but buffer was not received".to_string())
+        } else {
+            Error::frame_capture("Compositor did not offer a wl_shm buffer".to_string())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn output(name: &str, x: i32, y: i32, width: i32, height: i32) -> OutputInfo {
+        let mut info = OutputInfo::new(name.to_string());
+        info.logical_x = x;
+        info.logical_y = y;
+        info.logical_width = width;
+        info.logical_height = height;
+        info.logical_scale_known = true;
+        info
+    }
+
+    /// Run `composite_outputs` with each output captured as a solid colour
+    /// whose red channel is the output's index plus one.
+    fn composite_solid(region: Box, infos: &[OutputInfo]) -> CaptureResult {
+        composite_solid_on(region, infos, [0, 0, 0, 0])
+    }
+
+    /// Like `composite_solid`, over a `background`-filled destination.
+    fn composite_solid_on(region: Box, infos: &[OutputInfo], background: [u8; 4]) -> CaptureResult {
+        let infos: Vec<&OutputInfo> = infos.iter().collect();
+        composite_outputs(region, &infos, false, background, |index, request| {
+            let (w, h) = (
+                request.region.width() as u32,
+                request.region.height() as u32,
+            );
+            let colour = [index as u8 + 1, 0, 0, 255];
+            Ok(CaptureResult::new(colour.repeat((w * h) as usize), w, h))
+        })
+        .unwrap()
+    }
+
+    fn pixel_owner(image: &CaptureResult, x: u32, y: u32) -> u8 {
+        image.data[((y * image.width + x) * 4) as usize]
+    }
+
+    #[test]
+    fn test_bounding_box_horizontal() {
+        let infos = [
+            output("DP-1", 0, 0, 1920, 1080),
+            output("DP-2", 1920, 0, 2560, 1440),
+        ];
+        let bounds = outputs_bounding_box(&infos).unwrap();
+        assert_eq!(bounds, Box::new(0, 0, 4480, 1440));
+    }
+
+    #[test]
+    fn test_bounding_box_vertical() {
+        let infos = [
+            output("DP-1", 0, 0, 1920, 1080),
+            output("DP-2", 0, 1080, 1920, 1080),
+        ];
+        let bounds = outputs_bounding_box(&infos).unwrap();
+        assert_eq!(bounds, Box::new(0, 0, 1920, 2160));
+    }
+
+    #[test]
+    fn test_bounding_box_negative_coordinates() {
+        let infos = [
+            output("DP-1", -1920, -200, 1920, 1080),
+            output("DP-2", 0, 0, 1920, 1080),
+        ];
+        let bounds = outputs_bounding_box(&infos).unwrap();
+        assert_eq!(bounds, Box::new(-1920, -200, 3840, 1280));
+    }
+
+    #[test]
+    fn test_bounding_box_empty() {
+        assert!(outputs_bounding_box(std::iter::empty()).is_none());
+    }
+
+    #[test]
+    fn test_composite_horizontal_places_outputs_side_by_side() {
+        let infos = [output("DP-1", 0, 0, 40, 20), output("DP-2", 40, 0, 30, 20)];
+        let region = outputs_bounding_box(&infos).unwrap();
+        let image = composite_solid(region, &infos);
+
+        assert_eq!((image.width, image.height), (70, 20));
+        assert_eq!(pixel_owner(&image, 0, 0), 1);
+        assert_eq!(pixel_owner(&image, 39, 19), 1);
+        assert_eq!(pixel_owner(&image, 40, 0), 2);
+        assert_eq!(pixel_owner(&image, 69, 19), 2);
+    }
+
+    #[test]
+    fn test_composite_vertical_places_outputs_stacked() {
+        let infos = [output("DP-1", 0, 0, 30, 20), output("DP-2", 0, 20, 30, 10)];
+        let region = outputs_bounding_box(&infos).unwrap();
+        let image = composite_solid(region, &infos);
+
+        assert_eq!((image.width, image.height), (30, 30));
+        assert_eq!(pixel_owner(&image, 10, 19), 1);
+        assert_eq!(pixel_owner(&image, 10, 20), 2);
+        assert_eq!(pixel_owner(&image, 29, 29), 2);
+    }
+
+    #[test]
+    fn test_composite_l_shaped_leaves_uncovered_area_empty() {
+        let infos = [
+            output("DP-1", 0, 0, 20, 20),
+            output("DP-2", 20, 0, 20, 20),
+            output("DP-3", 0, 20, 20, 20),
+        ];
+        let region = outputs_bounding_box(&infos).unwrap();
+        let image = composite_solid(region, &infos);
+
+        assert_eq!((image.width, image.height), (40, 40));
+        assert_eq!(pixel_owner(&image, 5, 5), 1);
+        assert_eq!(pixel_owner(&image, 25, 5), 2);
+        assert_eq!(pixel_owner(&image, 5, 25), 3);
+        assert_eq!(pixel_owner(&image, 25, 25), 0);
+    }
+
+    #[test]
+    fn test_composite_l_shaped_fills_uncovered_area_with_background() {
+        let infos = [
+            output("DP-1", 0, 0, 20, 20),
+            output("DP-2", 20, 0, 20, 20),
+            output("DP-3", 0, 20, 20, 20),
+        ];
+        let region = outputs_bounding_box(&infos).unwrap();
+        let background = [10, 20, 30, 255];
+        let image = composite_solid_on(region, &infos, background);
+
+        let pixel = |x: u32, y: u32| {
+            let at = ((y * image.width + x) * 4) as usize;
+            [
+                image.data[at],
+                image.data[at + 1],
+                image.data[at + 2],
+                image.data[at + 3],
+            ]
+        };
+        assert_eq!(pixel(5, 5), [1, 0, 0, 255]);
+        assert_eq!(pixel(25, 5), [2, 0, 0, 255]);
+        assert_eq!(pixel(5, 25), [3, 0, 0, 255]);
+        assert_eq!(pixel(20, 20), background);
+        assert_eq!(pixel(39, 39), background);
+    }
+
+    #[test]
+    fn test_composite_scales_hidpi_output_to_logical_size() {
+        let mut hidpi = output("DP-2", 20, 0, 10, 10);
+        hidpi.scale = 2;
+        let infos = [output("DP-1", 0, 0, 20, 10), hidpi];
+        let region = outputs_bounding_box(&infos).unwrap();
+
+        let mut requested = Vec::new();
+        let image = composite_outputs(
+            region,
+            &infos.iter().collect::<Vec<_>>(),
+            true,
+            [0, 0, 0, 0],
+            |index, request| {
+                requested.push((index, *request));
+                let (w, h) = (
+                    request.region.width() as u32,
+                    request.region.height() as u32,
+                );
+                Ok(CaptureResult::new([9; 4].repeat((w * h) as usize), w, h))
+            },
+        )
+        .unwrap();
+
+        assert_eq!((image.width, image.height), (30, 10));
+        assert_eq!(requested.len(), 2);
+        assert_eq!(requested[1].0, 1);
+        assert_eq!(requested[1].1.region, Box::new(0, 0, 20, 20));
+        assert_eq!(requested[1].1.overlay_cursor, 1);
+        assert_eq!(pixel_owner(&image, 29, 9), 9);
+    }
+
+    #[test]
+    fn test_composite_outputs_rejects_uncovered_region() {
+        let infos = [output("DP-1", 0, 0, 20, 10)];
+        let infos: Vec<&OutputInfo> = infos.iter().collect();
+        let mut called = false;
+        let result = composite_outputs(
+            Box::new(100, 100, 5, 5),
+            &infos,
+            false,
+            [0, 0, 0, 0],
+            |_, _| {
+                called = true;
+                Ok(CaptureResult::new(Vec::new(), 0, 0))
+            },
+        );
+        assert!(matches!(result, Err(Error::InvalidRegion(_))));
+        assert!(!called);
+    }
+
+    #[test]
+    fn test_composite_negative_coordinates() {
+        let infos = [
+            output("DP-1", -30, -10, 30, 20),
+            output("DP-2", 0, 0, 20, 20),
+        ];
+        let region = outputs_bounding_box(&infos).unwrap();
+        let image = composite_solid(region, &infos);
+
+        assert_eq!((image.width, image.height), (50, 30));
+        assert_eq!(pixel_owner(&image, 0, 0), 1);
+        assert_eq!(pixel_owner(&image, 29, 19), 1);
+        assert_eq!(pixel_owner(&image, 30, 10), 2);
+        assert_eq!(pixel_owner(&image, 30, 5), 0);
+        assert_eq!(pixel_owner(&image, 10, 25), 0);
+    }
+
+    #[test]
+    fn test_composite_plan_clips_to_region() {
+        let infos = [output("DP-1", 0, 0, 40, 20), output("DP-2", 40, 0, 30, 20)];
+        let plan = composite_plan(Box::new(30, 5, 20, 10), &infos);
+        assert_eq!(
+            plan,
+            vec![(0, Box::new(30, 5, 10, 10)), (1, Box::new(40, 5, 10, 10))]
+        );
+        assert!(composite_plan(Box::new(100, 100, 5, 5), &infos).is_empty());
+    }
+
+    #[test]
+    fn test_screencopy_request_carries_overlay_cursor() {
+        let region = Box::new(1, 2, 3, 4);
+        assert_eq!(ScreencopyRequest::new(region, true).overlay_cursor, 1);
+        assert_eq!(ScreencopyRequest::new(region, false).overlay_cursor, 0);
+        assert_eq!(ScreencopyRequest::new(region, true).region, region);
+    }
+
+    #[test]
+    fn test_composite_requests_propagate_overlay_cursor() {
+        let first = output("DP-1", 0, 0, 40, 20);
+        let mut second = output("DP-2", 40, 0, 30, 20);
+        second.scale = 2;
+        let infos = [&first, &second];
+        let region = Box::new(30, 0, 20, 10);
+
+        for overlay_cursor in [true, false] {
+            let requests = composite_requests(region, &infos, overlay_cursor);
+            assert_eq!(requests.len(), 2);
+            for (_, _, request) in &requests {
+                assert_eq!(request.overlay_cursor, overlay_cursor as i32);
+            }
+        }
+
+        let requests = composite_requests(region, &infos, true);
+        assert_eq!(requests[0].2.region, Box::new(30, 0, 10, 10));
+        assert_eq!(requests[1].2.region, Box::new(0, 0, 20, 20));
+    }
+
+    #[test]
+    fn test_guess_logical_geometry_divides_by_scale() {
+        let mut info = OutputInfo::new("DP-1".to_string());
+        info.x = 1920;
+        info.width = 3840;
+        info.height = 2160;
+        info.scale = 2;
+        guess_output_logical_geometry(&mut info);
+        assert_eq!(info.logical_box(), Box::new(1920, 0, 1920, 1080));
+        assert!(info.logical_scale_known);
+    }
+
+    #[test]
+    fn test_fit_capture_resizes_to_exact_footprint() {
+        let capture = CaptureResult::new(vec![255u8; 5 * 3 * 4], 5, 3);
+        let fitted = fit_capture(capture, 4, 2).unwrap();
+        assert_eq!((fitted.width, fitted.height), (4, 2));
+        assert_eq!(fitted.data.len(), 4 * 2 * 4);
+    }
+
+    #[test]
+    fn test_dmabuf_only_frame_is_unsupported() {
+        let state = FrameState {
+            dmabuf: Some(DmabufOffer {
+                format: 0,
+                width: 1,
+                height: 1,
+            }),
+            buffer_done: true,
+            ..FrameState::default()
+        };
+        match state.missing_buffer_error() {
+            Error::UnsupportedProtocol(msg) => assert!(msg.contains("dmabuf")),
+            other => panic!("unexpected error: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_failed_frame_without_buffer() {
+        let state = FrameState {
+            ready: true,
+            ..FrameState::default()
+        };
+        assert!(matches!(
+            state.missing_buffer_error(),
+            Error::FrameCapture { .. }
+        ));
+    }
+
+    #[test]
+    fn test_orient_capture_swaps_dimensions_for_rotated_output() {
+        use wayland_client::protocol::wl_output::Transform;
+
+        let mut info = OutputInfo::new("DP-2".to_string());
+        info.transform = Transform::_90;
+        let capture = CaptureResult::new(vec![0u8; 4 * 2 * 4], 4, 2);
+        let oriented = orient_capture(capture, info.transform, 0);
+        assert_eq!((oriented.width, oriented.height), (2, 4));
+        assert_eq!(oriented.data.len(), 2 * 4 * 4);
+
+        let capture = CaptureResult::new(vec![0u8; 4 * 2 * 4], 4, 2);
+        let oriented = orient_capture(capture, Transform::Normal, 0);
+        assert_eq!((oriented.width, oriented.height), (4, 2));
+    }
+
+    #[test]
+    fn test_orient_capture_applies_y_invert() {
+        use wayland_client::protocol::wl_output::Transform;
+
+        let mut data = vec![0u8; 2 * 4];
+        data[0] = 1;
+        let capture = CaptureResult::new(data, 1, 2);
+        let oriented = orient_capture(
+            capture,
+            Transform::Normal,
+            ZWLR_SCREENCOPY_FRAME_V1_FLAGS_Y_INVERT,
+        );
+        assert_eq!(oriented.data[0], 0);
+        assert_eq!(oriented.data[4], 1);
+    }
+
+    #[test]
+    fn test_buffer_crop_box_scales_to_buffer_pixels() {
+        // 1920x1080 logical output backed by a 3840x2160 buffer.
+        let crop = buffer_crop_box(Box::new(100, 50, 200, 100), 1920, 1080, 3840, 2160);
+        assert_eq!(crop, Some(Box::new(200, 100, 400, 200)));
+
+        let full = buffer_crop_box(Box::new(0, 0, 1920, 1080), 1920, 1080, 3840, 2160);
+        assert_eq!(full, Some(Box::new(0, 0, 3840, 2160)));
+    }
+
+    #[test]
+    fn test_buffer_crop_box_clips_to_buffer() {
+        let crop = buffer_crop_box(Box::new(1800, 0, 400, 100), 1920, 1080, 1920, 1080);
+        assert_eq!(crop, Some(Box::new(1800, 0, 120, 100)));
+        assert_eq!(
+            buffer_crop_box(Box::new(2000, 0, 10, 10), 1920, 1080, 1920, 1080),
+            None
+        );
+    }
+
+    #[test]
+    fn test_ext_capture_prefers_xrgb() {
+        let state = ExtCaptureState {
+            shm_formats: vec![ShmFormat::Abgr8888, ShmFormat::Xrgb8888],
+            ..ExtCaptureState::default()
+        };
+        assert_eq!(state.preferred_shm_format(), Some(ShmFormat::Xrgb8888));
+
+        let state = ExtCaptureState {
+            shm_formats: vec![ShmFormat::Rgb565],
+            ..ExtCaptureState::default()
+        };
+        assert_eq!(state.preferred_shm_format(), None);
+    }
+
+    fn convert_one(format: ShmFormat, pixel: &[u8]) -> Vec<u8> {
+        let mut data = pixel.to_vec();
+        let bpp = shm_bytes_per_pixel(format).unwrap();
+        convert_to_rgba(format, &mut data, 1, 1, bpp).unwrap();
+        data
+    }
+
+    #[test]
+    fn test_convert_8bit_formats() {
+        // Pixel with R=0x11, G=0x22, B=0x33, A=0x44 in each memory layout.
+        let cases: [(ShmFormat, [u8; 4], [u8; 4]); 8] = [
+            (
+                ShmFormat::Xrgb8888,
+                [0x33, 0x22, 0x11, 0x00],
+                [0x11, 0x22, 0x33, 0xff],
+            ),
+            (
+                ShmFormat::Argb8888,
+                [0x33, 0x22, 0x11, 0x44],
+                [0x11, 0x22, 0x33, 0x44],
+            ),
+            (
+                ShmFormat::Xbgr8888,
+                [0x11, 0x22, 0x33, 0x00],
+                [0x11, 0x22, 0x33, 0xff],
+            ),
+            (
+                ShmFormat::Abgr8888,
+                [0x11, 0x22, 0x33, 0x44],
+                [0x11, 0x22, 0x33, 0x44],
+            ),
+            (
+                ShmFormat::Rgbx8888,
+                [0x00, 0x33, 0x22, 0x11],
+                [0x11, 0x22, 0x33, 0xff],
+            ),
+            (
+                ShmFormat::Rgba8888,
+                [0x44, 0x33, 0x22, 0x11],
+                [0x11, 0x22, 0x33, 0x44],
+            ),
+            (
+                ShmFormat::Bgrx8888,
+                [0x00, 0x11, 0x22, 0x33],
+                [0x11, 0x22, 0x33, 0xff],
+            ),
+            (
+                ShmFormat::Bgra8888,
+                [0x44, 0x11, 0x22, 0x33],
+                [0x11, 0x22, 0x33, 0x44],
+            ),
+        ];
+        for (format, input, expected) in cases {
+            assert_eq!(convert_one(format, &input), expected, "{:?}", format);
+        }
+    }
+
+    #[test]
+    fn test_convert_10bit_formats() {
+        // R=0x3ff, G=0x200, B=0x004, A=0b10.
+        let argb = (0b10u32 << 30) | (0x3ff << 20) | (0x200 << 10) | 0x004;
+        assert_eq!(
+            convert_one(ShmFormat::Argb2101010, &argb.to_le_bytes()),
+            [0xff, 0x80, 0x01, 170]
+        );
+        assert_eq!(
+            convert_one(ShmFormat::Xrgb2101010, &argb.to_le_bytes()),
+            [0xff, 0x80, 0x01, 0xff]
+        );
+
+        let abgr = (0b11u32 << 30) | (0x004 << 20) | (0x200 << 10) | 0x3ff;
+        assert_eq!(
+            convert_one(ShmFormat::Abgr2101010, &abgr.to_le_bytes()),
+            [0xff, 0x80, 0x01, 0xff]
+        );
+        assert_eq!(
+            convert_one(ShmFormat::Xbgr2101010, &abgr.to_le_bytes()),
+            [0xff, 0x80, 0x01, 0xff]
+        );
+    }
+
+    #[test]
+    fn test_convert_24bit_formats_with_padding() {
+        // 2x1 Bgr888 image (bytes R, G, B) with a 2-byte row padding.
+        let mut data = vec![0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0xee, 0xee];
+        convert_to_rgba(ShmFormat::Bgr888, &mut data, 2, 1, 8).unwrap();
+        assert_eq!(data, vec![0x11, 0x22, 0x33, 0xff, 0x44, 0x55, 0x66, 0xff]);
+
+        assert_eq!(
+            convert_one(ShmFormat::Rgb888, &[0x33, 0x22, 0x11]),
+            [0x11, 0x22, 0x33, 0xff]
+        );
+    }
+
+    #[test]
+    fn test_convert_16bit_formats() {
+        let cases: [(ShmFormat, u16, [u8; 4]); 5] = [
+            (ShmFormat::Rgb565, 0xf800, [0xff, 0x00, 0x00, 0xff]),
+            (ShmFormat::Rgb565, 0x07e0, [0x00, 0xff, 0x00, 0xff]),
+            (ShmFormat::Rgb565, 0x001f, [0x00, 0x00, 0xff, 0xff]),
+            (ShmFormat::Bgr565, 0x001f, [0xff, 0x00, 0x00, 0xff]),
+            // R=16/31, G=32/63, B=0.
+            (
+                ShmFormat::Rgb565,
+                (16 << 11) | (32 << 5),
+                [132, 130, 0, 0xff],
+            ),
+        ];
+        for (format, pixel, expected) in cases {
+            assert_eq!(
+                convert_one(format, &pixel.to_le_bytes()),
+                expected,
+                "{:?}",
+                format
+            );
+        }
+
+        // 2x1 Rgb565 image with a 4-byte row padding.
+        let mut data = vec![0x00, 0xf8, 0x1f, 0x00, 0xee, 0xee, 0xee, 0xee];
+        convert_to_rgba(ShmFormat::Rgb565, &mut data, 2, 1, 8).unwrap();
+        assert_eq!(data, vec![0xff, 0, 0, 0xff, 0, 0, 0xff, 0xff]);
+    }
+
+    #[test]
+    fn test_convert_drops_stride_padding() {
+        // 1x2 Xbgr8888 image with 8-byte rows.
+        let mut data = vec![1, 2, 3, 0, 9, 9, 9, 9, 4, 5, 6, 0, 9, 9, 9, 9];
+        convert_to_rgba(ShmFormat::Xbgr8888, &mut data, 1, 2, 8).unwrap();
+        assert_eq!(data, vec![1, 2, 3, 255, 4, 5, 6, 255]);
+    }
+
+    #[test]
+    fn test_convert_unsupported_format() {
+        let mut data = vec![0u8; 2];
+        assert!(matches!(
+            convert_to_rgba(ShmFormat::C8, &mut data, 1, 1, 2),
+            Err(Error::UnsupportedBufferFormat(_))
+        ));
+        assert!(matches!(
+            shm_stride(ShmFormat::C8, 1, 0),
+            Err(Error::UnsupportedBufferFormat(_))
+        ));
+        assert!(matches!(
+            advertised_format(&FrameState::default()),
+            Err(Error::UnsupportedBufferFormat(_))
+        ));
+    }
+
+    #[test]
+    fn test_shm_stride_keeps_padding() {
+        assert_eq!(shm_stride(ShmFormat::Xrgb8888, 3, 16).unwrap(), 16);
+        // A stride too small for the width is not trusted.
+        assert_eq!(shm_stride(ShmFormat::Xrgb8888, 3, 8).unwrap(), 12);
+        assert_eq!(shm_stride(ShmFormat::Bgr888, 3, 0).unwrap(), 9);
+
+        // 3x2 Xrgb8888 with 16-byte rows: each row ends in 4 padding bytes.
+        let mut data = Vec::new();
+        for row in 0..2u8 {
+            for column in 0..3u8 {
+                data.extend_from_slice(&[column, row, 0x80, 0x00]);
+            }
+            data.extend_from_slice(&[0xee; 4]);
+        }
+        convert_to_rgba(ShmFormat::Xrgb8888, &mut data, 3, 2, 16).unwrap();
+        assert_eq!(data.len(), 3 * 2 * 4);
+        assert_eq!(&data[..4], &[0x80, 0, 0, 0xff]);
+        assert_eq!(&data[20..], &[0x80, 1, 2, 0xff]);
+    }
+
+    #[test]
+    fn test_apply_exclusions_scales_and_clamps() {
+        // Layout region starting left of the origin, captured at scale 2.
+        let region = Box::new(-100, 0, 200, 100);
+        let capture = apply_exclusions(
+            solid_capture(400, 200, 200),
+            region,
+            &[Box::new(-50, 10, 20, 20), Box::new(90, 90, 50, 50)],
+            [0, 0, 0, 255],
+        );
+        let pixel = |x: u32, y: u32| {
+            let i = ((y * capture.width + x) * 4) as usize;
+            capture.data[i..i + 4].to_vec()
+        };
+        let black = vec![0, 0, 0, 255];
+        let untouched = vec![200; 4];
+
+        // First exclusion covers buffer pixels (100, 20) to (139, 59).
+        assert_eq!(pixel(100, 20), black);
+        assert_eq!(pixel(139, 59), black);
+        assert_eq!(pixel(99, 20), untouched);
+        assert_eq!(pixel(140, 59), untouched);
+        assert_eq!(pixel(100, 60), untouched);
+
+        // Second exclusion is clamped to the bottom-right corner.
+        assert_eq!(pixel(380, 180), black);
+        assert_eq!(pixel(399, 199), black);
+        assert_eq!(pixel(379, 199), untouched);
+        assert_eq!(pixel(399, 179), untouched);
+    }
+
+    #[test]
+    fn test_apply_exclusions_outside_capture() {
+        let capture = apply_exclusions(
+            solid_capture(10, 10, 50),
+            Box::new(0, 0, 10, 10),
+            &[Box::new(20, 20, 5, 5), Box::new(-5, 0, 5, 10)],
+            [255, 0, 0, 255],
+        );
+        assert_eq!(capture, solid_capture(10, 10, 50));
+    }
+
+    #[test]
+    fn test_software_cursor_maps_pointer_to_buffer_pixels() {
+        // Layout region at (-100, 50) captured at scale 2.
+        let mut capture = solid_capture(400, 200, 0);
+        let cursor = CursorImage::new(CaptureResult::new(vec![255; 2 * 2 * 4], 2, 2), 1, 1);
+        draw_software_cursor(
+            &mut capture,
+            Box::new(-100, 50, 200, 100),
+            &cursor,
+            (-50.0, 60.0),
+        )
+        .unwrap();
+
+        // The hotspot lands on buffer pixel (100, 20).
+        let pixel = |x: u32, y: u32| capture.data[((y * 400 + x) * 4) as usize];
+        assert_eq!(pixel(99, 19), 255);
+        assert_eq!(pixel(100, 20), 255);
+        assert_eq!(pixel(101, 20), 0);
+        assert_eq!(pixel(98, 19), 0);
+    }
+
+    #[test]
+    fn test_frame_timestamp_combines_seconds() {
+        assert_eq!(frame_timestamp(0, 12, 500), Duration::new(12, 500));
+        assert_eq!(frame_timestamp(1, 2, 3), Duration::new((1u64 << 32) + 2, 3));
+    }
+
+    #[test]
+    fn test_derived_captures_keep_timestamp() {
+        let timestamp = Some(Duration::new(5, 250));
+        let capture = CaptureResult {
+            timestamp,
+            ..solid_capture(2, 3, 7)
+        };
+        let rotated = orient_capture(
+            capture.clone(),
+            wayland_client::protocol::wl_output::Transform::_90,
+            ZWLR_SCREENCOPY_FRAME_V1_FLAGS_Y_INVERT,
+        );
+        assert_eq!(rotated.timestamp(), timestamp);
+        assert_eq!(
+            scale_integer_fast(capture.clone(), 2).timestamp(),
+            timestamp
+        );
+        let resized = resize_capture(capture, 1, 1, image::imageops::FilterType::Nearest).unwrap();
+        assert_eq!(resized.timestamp(), timestamp);
+        assert_eq!(solid_capture(1, 1, 0).timestamp(), None);
+    }
+
+    #[test]
+    fn test_thumbnail_size_keeps_aspect_ratio() {
+        assert_eq!(thumbnail_size(1920, 1080, 256, 256), (256, 144));
+        assert_eq!(thumbnail_size(1080, 1920, 256, 256), (144, 256));
+        assert_eq!(thumbnail_size(3840, 2160, 100, 1000), (100, 56));
+        // Never upscales, never collapses to zero.
+        assert_eq!(thumbnail_size(200, 100, 1000, 1000), (200, 100));
+        assert_eq!(thumbnail_size(10000, 1, 100, 100), (100, 1));
+    }
+
+    #[test]
+    fn test_downscale_shm_averages_blocks() {
+        // 4x2 Xrgb8888 with padded stride: left half black, right half white.
+        let stride = 4 * 4 + 8;
+        let mut data = vec![0u8; stride * 2];
+        for row in data.chunks_mut(stride) {
+            row[8..16].fill(255);
+        }
+        let out = downscale_shm(ShmFormat::Xrgb8888, &data, 4, 2, stride as u32, 2, 1).unwrap();
+        assert_eq!(out, vec![0, 0, 0, 255, 255, 255, 255, 255]);
+
+        let out = downscale_shm(ShmFormat::Xrgb8888, &data, 4, 2, stride as u32, 1, 1).unwrap();
+        assert_eq!(out, vec![128, 128, 128, 255]);
+    }
+
+    #[test]
+    fn test_downscale_shm_matches_full_conversion_at_same_size() {
+        let data: Vec<u8> = (0..3 * 2 * 3).map(|i| (i * 13) as u8).collect();
+        let out = downscale_shm(ShmFormat::Bgr888, &data, 3, 2, 9, 3, 2).unwrap();
+        let mut full = data.clone();
+        convert_to_rgba(ShmFormat::Bgr888, &mut full, 3, 2, 9).unwrap();
+        assert_eq!(out, full);
+    }
+
+    #[test]
+    fn test_downscale_shm_rejects_upscaling() {
+        let data = vec![0u8; 4 * 4];
+        assert!(downscale_shm(ShmFormat::Xrgb8888, &data, 2, 2, 8, 4, 4).is_err());
+        assert!(downscale_shm(ShmFormat::Xrgb8888, &data, 2, 2, 8, 0, 1).is_err());
+    }
+
+    fn solid_output(name: &str, geometry: Box, scale: i32) -> Output {
+        Output {
+            name: name.to_string(),
+            geometry,
+            scale,
+            description: None,
+            refresh_rate_mhz: None,
+            transform: OutputTransform::Normal,
+            physical_size_mm: None,
+            make: String::new(),
+            model: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_output_display() {
+        let mut output = solid_output("eDP-1", Box::new(0, 0, 1920, 1080), 2);
+        assert_eq!(output.to_string(), "eDP-1: 1920x1080+0+0 @2x");
+
+        output.geometry = Box::new(-1280, 200, 1280, 1024);
+        output.description = Some("Built-in display".to_string());
+        assert_eq!(
+            output.to_string(),
+            "eDP-1: 1280x1024-1280+200 @2x (Built-in display)"
+        );
+    }
+
+    fn solid_capture(width: u32, height: u32, value: u8) -> CaptureResult {
+        CaptureResult::new(vec![value; (width * height * 4) as usize], width, height)
+    }
+
+    #[test]
+    fn test_stitch_captures_places_outputs_by_geometry() {
+        // A 2x scale output on the left and a 1x output to its right, offset down.
+        let outputs = vec![
+            solid_output("left", Box::new(-2, 0, 2, 2), 2),
+            solid_output("right", Box::new(0, 1, 3, 1), 1),
+        ];
+        let mut captures = HashMap::new();
+        captures.insert("left".to_string(), solid_capture(4, 4, 1));
+        captures.insert("right".to_string(), solid_capture(3, 1, 2));
+
+        let stitched = stitch_captures(&captures, &outputs, &CompositeOptions::default()).unwrap();
+        assert_eq!((stitched.width, stitched.height), (5, 2));
+        let pixel = |x: usize, y: usize| stitched.data[(y * 5 + x) * 4];
+        assert_eq!([pixel(0, 0), pixel(1, 0), pixel(2, 0)], [1, 1, 0]);
+        assert_eq!([pixel(1, 1), pixel(2, 1), pixel(4, 1)], [1, 2, 2]);
+    }
+
+    #[test]
+    fn test_composite_captures_background_and_negative_coordinates() {
+        let left = solid_capture(1, 1, 10);
+        let right = solid_capture(1, 1, 20);
+        // Diagonal layout leaves two uncovered pixels.
+        let placed = [
+            (Box::new(-1, -1, 1, 1), &left),
+            (Box::new(0, 0, 1, 1), &right),
+        ];
+        let options = CompositeOptions::new().background([1, 2, 3, 255]);
+        let canvas = composite_captures(&placed, &options).unwrap();
+        assert_eq!((canvas.width, canvas.height), (2, 2));
+        assert_eq!(&canvas.data[0..4], &[10, 10, 10, 10]);
+        assert_eq!(&canvas.data[4..8], &[1, 2, 3, 255]);
+        assert_eq!(&canvas.data[8..12], &[1, 2, 3, 255]);
+        assert_eq!(&canvas.data[12..16], &[20, 20, 20, 20]);
+    }
+
+    #[test]
+    fn test_composite_captures_overlap_modes() {
+        let bottom = CaptureResult::new([0, 0, 255, 255].repeat(4), 2, 2);
+        let top = CaptureResult::new([255, 0, 0, 128].repeat(4), 2, 2);
+        let placed = [
+            (Box::new(0, 0, 2, 1), &bottom),
+            (Box::new(1, 0, 2, 1), &top),
+        ];
+
+        let replaced = composite_captures(&placed, &CompositeOptions::new()).unwrap();
+        assert_eq!((replaced.width, replaced.height), (3, 1));
+        assert_eq!(&replaced.data[4..8], &[255, 0, 0, 128]);
+
+        let options = CompositeOptions::new().blend(CompositeBlend::AlphaOver);
+        let blended = composite_captures(&placed, &options).unwrap();
+        assert_eq!(&blended.data[0..4], &[0, 0, 255, 255]);
+        assert_eq!(&blended.data[4..8], &[128, 0, 127, 255]);
+        // Over a transparent background the source is kept as is.
+        assert_eq!(&blended.data[8..12], &[255, 0, 0, 128]);
+    }
+
+    #[test]
+    fn test_composite_captures_normalizes_scales() {
+        // A 2x capture of a 2x1 logical box next to a 1x capture.
+        let hidpi = solid_capture(4, 2, 5);
+        let lodpi = solid_capture(1, 1, 6);
+        let placed = [
+            (Box::new(0, 0, 2, 1), &hidpi),
+            (Box::new(2, 0, 1, 1), &lodpi),
+        ];
+        let canvas = composite_captures(&placed, &CompositeOptions::new()).unwrap();
+        assert_eq!((canvas.width, canvas.height), (3, 1));
+        let reds: Vec<u8> = canvas.data.chunks_exact(4).map(|px| px[0]).collect();
+        assert_eq!(reds, vec![5, 5, 6]);
+    }
+
+    #[test]
+    fn test_stitch_captures_unknown_output() {
+        let outputs = vec![solid_output("a", Box::new(0, 0, 1, 1), 1)];
+        let mut captures = HashMap::new();
+        captures.insert("b".to_string(), solid_capture(1, 1, 0));
+        assert!(matches!(
+            stitch_captures(&captures, &outputs, &CompositeOptions::default()),
+            Err(Error::OutputNotFound(name)) if name == "b"
+        ));
+        assert!(matches!(
+            stitch_captures(&HashMap::new(), &outputs, &CompositeOptions::default()),
+            Err(Error::NoOutputs)
+        ));
+    }
+
+    #[test]
+    fn test_output_transform_mapping() {
+        use wayland_client::protocol::wl_output::Transform;
+        let cases = [
+            (Transform::Normal, OutputTransform::Normal),
+            (Transform::_90, OutputTransform::Rotated90),
+            (Transform::_180, OutputTransform::Rotated180),
+            (Transform::_270, OutputTransform::Rotated270),
+            (Transform::Flipped, OutputTransform::Flipped),
+            (Transform::Flipped90, OutputTransform::Flipped90),
+            (Transform::Flipped180, OutputTransform::Flipped180),
+            (Transform::Flipped270, OutputTransform::Flipped270),
+        ];
+        for (wayland, public) in cases {
+            let mapped = output_transform(wayland);
+            assert_eq!(mapped, public);
+            let mut width = 1920;
+            let mut height = 1080;
+            apply_output_transform(wayland, &mut width, &mut height);
+            assert_eq!(mapped.swaps_dimensions(), width == 1080);
         }
     }
-}
 
-impl Dispatch<WlCompositor, ()> for WaylandCapture {
-    fn event(
-        _state: &mut Self,
-        _proxy: &WlCompositor,
-        _event: <WlCompositor as Proxy>::Event,
-        _data: &(),
-        _conn: &Connection,
-        _qh: &QueueHandle<Self>,
-    ) {
+    #[test]
+    fn test_public_output_optional_metadata() {
+        let mut info = OutputInfo::new("DP-1".to_string());
+        let output = public_output(&info);
+        assert_eq!(output.refresh_rate_mhz(), None);
+        assert_eq!(output.physical_size_mm(), None);
+
+        info.refresh_mhz = 143_981;
+        info.physical_width = 600;
+        info.physical_height = 340;
+        let output = public_output(&info);
+        assert_eq!(output.refresh_rate_mhz(), Some(143_981));
+        assert_eq!(output.physical_size_mm(), Some((600, 340)));
+
+        // Projectors report 0x0 mm; a single zero dimension is unknown too.
+        info.physical_height = 0;
+        assert_eq!(public_output(&info).physical_size_mm(), None);
     }
-}
 
-impl Dispatch<WlShm, ()> for WaylandCapture {
-    fn event(
-        _state: &mut Self,
-        _proxy: &WlShm,
-        _event: <WlShm as Proxy>::Event,
-        _data: &(),
-        _conn: &Connection,
-        _qh: &QueueHandle<Self>,
-    ) {
+    #[test]
+    fn test_public_output_make_and_model() {
+        let mut info = OutputInfo::new("DP-1".to_string());
+        assert_eq!(public_output(&info).make(), "");
+
+        info.make = "Dell Inc.".to_string();
+        info.model = "DELL U2720Q".to_string();
+        info.transform = wayland_client::protocol::wl_output::Transform::_90;
+        let output = public_output(&info);
+        assert_eq!(output.make(), "Dell Inc.");
+        assert_eq!(output.model(), "DELL U2720Q");
+        assert_eq!(output.transform(), OutputTransform::Rotated90);
     }
-}
 
-impl Dispatch<ZwlrScreencopyManagerV1, ()> for WaylandCapture {
-    fn event(
-        _state: &mut Self,
-        _proxy: &ZwlrScreencopyManagerV1,
-        _event: <ZwlrScreencopyManagerV1 as Proxy>::Event,
-        _data: &(),
-        _conn: &Connection,
-        _qh: &QueueHandle<Self>,
+    /// A `WaylandCapture` on one end of a socket pair. Requests are only
+    /// buffered, so proxies can be created without a running compositor.
+    fn offline_capture() -> (
+        WaylandCapture,
+        EventQueue<WaylandCapture>,
+        std::os::unix::net::UnixStream,
     ) {
+        let (client, server) = std::os::unix::net::UnixStream::pair().unwrap();
+        let connection = Connection::from_socket(client).unwrap();
+        let event_queue = connection.new_event_queue();
+        let registry = connection.display().get_registry(&event_queue.handle(), ());
+        let capture = WaylandCapture {
+            _connection: connection,
+            globals: WaylandGlobals {
+                compositor: None,
+                shm: None,
+                screencopy_manager: None,
+                xdg_output_manager: None,
+                outputs: Vec::new(),
+                output_globals: HashMap::new(),
+                output_info: HashMap::new(),
+                output_xdg_map: HashMap::new(),
+                image_copy_capture_manager: None,
+                output_capture_source_manager: None,
+                #[cfg(feature = "dmabuf")]
+                linux_dmabuf: None,
+            },
+            public_outputs: Vec::new(),
+            registry_queue: None,
+            _registry: registry,
+            output_queue: None,
+            outputs_stale: false,
+            always_refresh_outputs: false,
+            capture_timeout: DEFAULT_CAPTURE_TIMEOUT,
+            #[cfg(feature = "dmabuf")]
+            dmabuf_allocator: None,
+            #[cfg(feature = "dmabuf")]
+            dmabuf_enabled: true,
+            #[cfg(feature = "dmabuf")]
+            dmabuf_rejected: false,
+        };
+        (capture, event_queue, server)
     }
-}
 
-impl Dispatch<ZwlrScreencopyFrameV1, Arc<Mutex<FrameState>>> for WaylandCapture {
-    fn event(
-        _state: &mut Self,
-        frame: &ZwlrScreencopyFrameV1,
-        event: <ZwlrScreencopyFrameV1 as Proxy>::Event,
-        frame_state: &Arc<Mutex<FrameState>>,
-        _conn: &Connection,
-        _qh: &QueueHandle<Self>,
-    ) {
-        use wayland_protocols_wlr::screencopy::v1::client::zwlr_screencopy_frame_v1::Event;
-        match event {
-            Event::Buffer {
-                format,
-                width,
-                height,
-                stride,
-            } => {
-                let mut state = lock_frame_state(frame_state)
-                    .expect("Frame state mutex poisoned in Buffer event");
-                state.width = width;
-                state.height = height;
-                if let wayland_client::WEnum::Value(val) = format {
-                    state.format = Some(val);
-                }
-                state.buffer = Some(vec![0u8; (stride * height) as usize]);
-            }
-            Event::Flags { flags } => {
-                let mut state = lock_frame_state(frame_state)
-                    .expect("Frame state mutex poisoned in Flags event");
-                if let wayland_client::WEnum::Value(val) = flags {
-                    state.flags = val.bits();
-                    log::debug!("Received flags: {:?} (bits: {})", flags, val.bits());
-                }
-            }
-            Event::Ready {
-                tv_sec_hi: _,
-                tv_sec_lo: _,
-                tv_nsec: _,
-            } => {
-                let mut state = lock_frame_state(frame_state)
-                    .expect("Frame state mutex poisoned in Ready event");
-                state.ready = true;
-                frame.destroy();
-            }
-            Event::Failed => {
-                let mut state = lock_frame_state(frame_state)
-                    .expect("Frame state mutex poisoned in Failed event");
-                state.ready = true;
-            }
-            Event::LinuxDmabuf {
-                format,
-                width,
-                height,
-            } => {
-                // TODO:Обработка LinuxDmabuf - альтернативный способ передачи данных
-                // Пока не поддерживаем, но логируем для отладки
-                log::debug!(
-                    "Received LinuxDmabuf: format={}, width={}, height={}",
-                    format,
-                    width,
-                    height
-                );
-            }
-            Event::BufferDone => {
-                log::debug!("Buffer copy completed");
-            }
-            _ => {
-                log::warn!("Received unknown event: {:?}", event);
-            }
+    #[test]
+    fn test_scale_region_capture_sizes_by_region() {
+        // A 200x100 region on a scale-2 output arrives as a 400x200 buffer.
+        let region = Box::new(0, 0, 200, 100);
+        let hidpi = || CaptureResult::new(vec![0x40; 400 * 200 * 4], 400, 200);
+        for (scale, size) in [(0.5, (100, 50)), (1.0, (200, 100)), (2.0, (400, 200))] {
+            let scaled = scale_region_capture(hidpi(), region, scale).unwrap();
+            assert_eq!((scaled.width, scaled.height), size, "scale {}", scale);
+            assert_eq!(scaled.data.len(), (size.0 * size.1 * 4) as usize);
         }
+
+        assert!(matches!(
+            scale_region_capture(hidpi(), region, 0.001),
+            Err(Error::InvalidRegion(_))
+        ));
     }
-}
 
-impl Dispatch<ZxdgOutputV1, ()> for WaylandCapture {
-    fn event(
-        state: &mut Self,
-        xdg_output: &ZxdgOutputV1,
-        event: <ZxdgOutputV1 as Proxy>::Event,
-        _: &(),
-        _conn: &Connection,
-        _qh: &QueueHandle<Self>,
+    #[test]
+    fn test_global_remove_forgets_output() {
+        use wayland_client::protocol::wl_registry::Event;
+
+        let (mut state, event_queue, _server) = offline_capture();
+        let qh = event_queue.handle();
+        let registry = state._registry.clone();
+        let connection = state._connection.clone();
+        for name in [7, 8] {
+            let global = Event::Global {
+                name,
+                interface: "wl_output".to_string(),
+                version: 4,
+            };
+            <WaylandCapture as Dispatch<WlRegistry, ()>>::event(
+                &mut state,
+                &registry,
+                global,
+                &(),
+                &connection,
+                &qh,
+            );
+        }
+        assert_eq!(state.globals.outputs.len(), 2);
+        state.outputs_stale = false;
+        state.update_public_outputs();
+
+        <WaylandCapture as Dispatch<WlRegistry, ()>>::event(
+            &mut state,
+            &registry,
+            Event::GlobalRemove { name: 7 },
+            &(),
+            &connection,
+            &qh,
+        );
+        assert_eq!(state.globals.outputs.len(), 1);
+        assert_eq!(state.globals.output_info.len(), 1);
+        let remaining = &state.globals.outputs[0];
+        assert_eq!(
+            state.globals.output_info[&remaining.id().protocol_id()].name,
+            "output-8"
+        );
+        let names = state
+            .with_outputs(|outputs| {
+                outputs
+                    .iter()
+                    .map(|output| output.name().to_string())
+                    .collect::<Vec<_>>()
+            })
+            .unwrap();
+        assert_eq!(names, ["output-8"]);
+
+        // Removing a global that isn't an output leaves the outputs alone.
+        assert!(!state.globals.remove_output(42));
+        assert_eq!(state.globals.outputs.len(), 1);
+    }
+
+    /// Announce a global to `state` as if the registry had sent it.
+    fn announce_global(
+        state: &mut WaylandCapture,
+        qh: &QueueHandle<WaylandCapture>,
+        name: u32,
+        interface: &str,
+        version: u32,
     ) {
-        use wayland_protocols::xdg::xdg_output::zv1::client::zxdg_output_v1::Event;
+        let registry = state._registry.clone();
+        let connection = state._connection.clone();
+        <WaylandCapture as Dispatch<WlRegistry, ()>>::event(
+            state,
+            &registry,
+            wayland_client::protocol::wl_registry::Event::Global {
+                name,
+                interface: interface.to_string(),
+                version,
+            },
+            &(),
+            &connection,
+            qh,
+        );
+    }
 
-        let xdg_output_id = xdg_output.id().protocol_id();
+    /// Decode the requests written to `server` as (object id, opcode, argument words).
+    fn sent_requests(server: &mut std::os::unix::net::UnixStream) -> Vec<(u32, u16, Vec<u32>)> {
+        use std::io::Read;
 
-        let mut found_output_id = None;
-        for (wl_output_id, mapped_xdg_output) in &state.globals.output_xdg_map {
-            if mapped_xdg_output.id().protocol_id() == xdg_output_id {
-                found_output_id = Some(*wl_output_id);
-                break;
+        server.set_nonblocking(true).unwrap();
+        let mut bytes = Vec::new();
+        let mut chunk = [0u8; 4096];
+        loop {
+            match server.read(&mut chunk) {
+                Ok(0) => break,
+                Ok(n) => bytes.extend_from_slice(&chunk[..n]),
+                Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => break,
+                Err(e) => panic!("reading requests failed: {}", e),
             }
         }
 
-        if let Some(wl_output_id) = found_output_id {
-            if let Some(info) = state.globals.output_info.get_mut(&wl_output_id) {
-                match event {
-                    Event::LogicalPosition { x, y } => {
-                        info.logical_x = x;
-                        info.logical_y = y;
-                        info.logical_scale_known = true;
-                    }
-                    Event::LogicalSize { width, height } => {
-                        info.logical_width = width;
-                        info.logical_height = height;
-                        info.logical_scale_known = true;
-                    }
-                    Event::Name { name } => {
-                        if info.name.starts_with("output-") || info.name.is_empty() {
-                            info.name = name.clone();
-                        }
-                    }
-                    Event::Description { description } => {
-                        info.description = Some(description);
-                    }
-                    Event::Done => {
-                        info.logical_scale_known = true;
-                    }
-                    _ => {}
-                }
+        let words: Vec<u32> = bytes
+            .chunks_exact(4)
+            .map(|word| u32::from_ne_bytes(word.try_into().unwrap()))
+            .collect();
+        let mut requests = Vec::new();
+        let mut at = 0;
+        while at + 2 <= words.len() {
+            let size = (words[at + 1] >> 16) as usize / 4;
+            let opcode = (words[at + 1] & 0xffff) as u16;
+            requests.push((words[at], opcode, words[at + 2..at + size].to_vec()));
+            at += size;
+        }
+        requests
+    }
+
+    #[test]
+    fn test_stream_frame_timeout_destroys_frame() {
+        let (mut state, event_queue, mut server) = offline_capture();
+        let qh = event_queue.handle();
+        announce_global(&mut state, &qh, 1, "zwlr_screencopy_manager_v1", 3);
+        announce_global(&mut state, &qh, 2, "wl_shm", 1);
+        announce_global(&mut state, &qh, 7, "wl_output", 4);
+        state.outputs_stale = false;
+        state.set_capture_timeout(Duration::from_millis(1));
+
+        // The compositor never answers, so every attempt times out; the
+        // retry must not leave the first frame copying into the buffer.
+        let mut stream = state.start_stream("output-7", false).unwrap();
+        for _ in 0..2 {
+            assert!(matches!(
+                state.next_stream_frame(&mut stream),
+                Err(Error::Timeout { .. })
+            ));
+        }
+        state._connection.flush().unwrap();
+
+        let manager_id = state
+            .globals
+            .screencopy_manager
+            .as_ref()
+            .unwrap()
+            .id()
+            .protocol_id();
+        let requests = sent_requests(&mut server);
+        // zwlr_screencopy_manager_v1.capture_output is opcode 0 and creates
+        // the frame; zwlr_screencopy_frame_v1.destroy is opcode 1.
+        let frames: Vec<u32> = requests
+            .iter()
+            .filter(|(id, opcode, _)| *id == manager_id && *opcode == 0)
+            .map(|(_, _, args)| args[0])
+            .collect();
+        assert_eq!(frames.len(), 2);
+        for frame in frames {
+            assert!(
+                requests
+                    .iter()
+                    .any(|(id, opcode, _)| *id == frame && *opcode == 1),
+                "frame {} was not destroyed",
+                frame
+            );
+        }
+    }
+
+    #[test]
+    fn test_ext_capture_timeout_destroys_session_and_source() {
+        let (mut state, event_queue, mut server) = offline_capture();
+        let qh = event_queue.handle();
+        announce_global(&mut state, &qh, 1, "ext_image_copy_capture_manager_v1", 1);
+        announce_global(
+            &mut state,
+            &qh,
+            2,
+            "ext_output_image_capture_source_manager_v1",
+            1,
+        );
+        announce_global(&mut state, &qh, 3, "wl_shm", 1);
+        announce_global(&mut state, &qh, 7, "wl_output", 4);
+        state.outputs_stale = false;
+        state.set_capture_timeout(Duration::from_millis(1));
+
+        // No buffer constraints ever arrive, so the capture times out
+        // before it gets to its own cleanup.
+        let output = state.globals.outputs[0].clone();
+        assert!(matches!(
+            state.capture_region_for_output_ext(&output, Box::new(0, 0, 4, 2), false),
+            Err(Error::Timeout { .. })
+        ));
+        state._connection.flush().unwrap();
+
+        let source_manager_id = state
+            .globals
+            .output_capture_source_manager
+            .as_ref()
+            .unwrap()
+            .id()
+            .protocol_id();
+        let copy_manager_id = state
+            .globals
+            .image_copy_capture_manager
+            .as_ref()
+            .unwrap()
+            .id()
+            .protocol_id();
+        let requests = sent_requests(&mut server);
+        let created_by = |manager: u32| {
+            requests
+                .iter()
+                .find(|(id, opcode, _)| *id == manager && *opcode == 0)
+                .map(|(_, _, args)| args[0])
+                .unwrap()
+        };
+        let source = created_by(source_manager_id);
+        let session = created_by(copy_manager_id);
+        // ext_image_capture_source_v1.destroy is opcode 0;
+        // ext_image_copy_capture_session_v1.destroy is opcode 1.
+        assert!(requests
+            .iter()
+            .any(|(id, opcode, _)| *id == source && *opcode == 0));
+        assert!(requests
+            .iter()
+            .any(|(id, opcode, _)| *id == session && *opcode == 1));
+    }
+
+    #[test]
+    fn test_poll_frame_negotiates_buffer_then_finishes() {
+        let (mut state, event_queue, _server) = offline_capture();
+        let qh = event_queue.handle();
+        announce_global(&mut state, &qh, 1, "zwlr_screencopy_manager_v1", 3);
+        announce_global(&mut state, &qh, 2, "wl_shm", 1);
+        announce_global(&mut state, &qh, 7, "wl_output", 4);
+        state.outputs_stale = false;
+
+        let job = FrameJob {
+            output: state.globals.outputs[0].clone(),
+            request: ScreencopyRequest::new(Box::new(0, 0, 4, 2), false),
+        };
+        let mut pending = state.begin_frame(&job).unwrap().unwrap();
+        assert!(matches!(
+            state.poll_frame(&mut pending).unwrap(),
+            FrameProgress::Pending
+        ));
+
+        {
+            let mut frame = pending.frame_state.lock().unwrap();
+            frame.width = 4;
+            frame.height = 2;
+            frame.stride = 16;
+            frame.format = Some(ShmFormat::Xrgb8888);
+            frame.buffer = Some(Vec::new());
+            frame.buffer_done = true;
+        }
+        assert!(matches!(
+            state.poll_frame(&mut pending).unwrap(),
+            FrameProgress::Pending
+        ));
+        pending.buffer.as_mut().unwrap().mmap.fill(0x80);
+
+        pending.frame_state.lock().unwrap().ready = true;
+        match state.poll_frame(&mut pending).unwrap() {
+            FrameProgress::Done(capture) => {
+                assert_eq!((capture.width, capture.height), (4, 2));
+                assert_eq!(&capture.data[..4], &[0x80, 0x80, 0x80, 255]);
             }
+            _ => panic!("frame should be done once ready arrives"),
         }
     }
-}
 
-impl Dispatch<WlBuffer, ()> for WaylandCapture {
-    fn event(
-        _state: &mut Self,
-        _proxy: &WlBuffer,
-        _event: <WlBuffer as Proxy>::Event,
-        _data: &(),
-        _conn: &Connection,
-        _qh: &QueueHandle<Self>,
-    ) {
+    #[test]
+    fn test_poll_frame_reports_failed_frame() {
+        let (mut state, event_queue, _server) = offline_capture();
+        let qh = event_queue.handle();
+        announce_global(&mut state, &qh, 1, "zwlr_screencopy_manager_v1", 3);
+        announce_global(&mut state, &qh, 7, "wl_output", 4);
+        state.outputs_stale = false;
+
+        let job = FrameJob {
+            output: state.globals.outputs[0].clone(),
+            request: ScreencopyRequest::new(Box::new(0, 0, 4, 2), false),
+        };
+        let mut pending = state.begin_frame(&job).unwrap().unwrap();
+        pending.frame_state.lock().unwrap().failed = true;
+        assert!(matches!(
+            state.poll_frame(&mut pending),
+            Err(Error::CaptureFailed)
+        ));
+
+        state.globals.screencopy_manager = None;
+        assert!(state.begin_frame(&job).unwrap().is_none());
     }
-}
 
-impl Dispatch<WlShmPool, ()> for WaylandCapture {
-    fn event(
-        _state: &mut Self,
-        _proxy: &WlShmPool,
-        _event: <WlShmPool as Proxy>::Event,
-        _data: &(),
-        _conn: &Connection,
-        _qh: &QueueHandle<Self>,
-    ) {
+    #[cfg(feature = "dmabuf")]
+    #[test]
+    fn test_rejected_dmabuf_params_fall_back_to_shm() {
+        let (mut state, event_queue, _server) = offline_capture();
+        let qh = event_queue.handle();
+        announce_global(&mut state, &qh, 3, "zwp_linux_dmabuf_v1", 3);
+        let params_state = Arc::new(Mutex::new(DmabufParamsState::default()));
+        let params = state
+            .globals
+            .linux_dmabuf
+            .as_ref()
+            .unwrap()
+            .create_params(&qh, params_state.clone());
+
+        let connection = state._connection.clone();
+        <WaylandCapture as Dispatch<ZwpLinuxBufferParamsV1, _>>::event(
+            &mut state,
+            &params,
+            zwp_linux_buffer_params_v1::Event::Failed,
+            &params_state,
+            &connection,
+            &qh,
+        );
+        assert!(params_state.lock().unwrap().failed);
+        assert!(state.dmabuf_rejected);
     }
-}
 
-impl Dispatch<ZxdgOutputManagerV1, ()> for WaylandCapture {
-    fn event(
-        _state: &mut Self,
-        _proxy: &ZxdgOutputManagerV1,
-        _event: <ZxdgOutputManagerV1 as Proxy>::Event,
-        _data: &(),
-        _conn: &Connection,
-        _qh: &QueueHandle<Self>,
-    ) {
+    #[test]
+    fn test_capture_outputs_refreshes_stale_outputs() {
+        let (mut state, event_queue, server) = offline_capture();
+        let qh = event_queue.handle();
+        announce_global(&mut state, &qh, 7, "wl_output", 4);
+        assert!(state.outputs_stale);
+        // With the compositor gone the refresh fails; without one the
+        // capture would go ahead with the stale outputs.
+        drop(server);
+
+        let parameters = vec![CaptureParameters::new("output-7")];
+        assert!(matches!(
+            state.capture_outputs(parameters),
+            Err(Error::WaylandConnection { .. })
+        ));
     }
-}
 
-#[derive(Debug, Clone)]
-struct FrameState {
-    buffer: Option<Vec<u8>>,
-    width: u32,
-    height: u32,
-    format: Option<ShmFormat>,
-    ready: bool,
-    flags: u32,
+    #[test]
+    fn test_output_done_invalidates_cache() {
+        use wayland_client::protocol::wl_registry::Event;
+
+        let (mut state, event_queue, _server) = offline_capture();
+        let qh = event_queue.handle();
+        let registry = state._registry.clone();
+        let connection = state._connection.clone();
+        <WaylandCapture as Dispatch<WlRegistry, ()>>::event(
+            &mut state,
+            &registry,
+            Event::Global {
+                name: 7,
+                interface: "wl_output".to_string(),
+                version: 4,
+            },
+            &(),
+            &connection,
+            &qh,
+        );
+        state.outputs_stale = false;
+
+        let output = state.globals.outputs[0].clone();
+        <WaylandCapture as Dispatch<WlOutput, ()>>::event(
+            &mut state,
+            &output,
+            wayland_client::protocol::wl_output::Event::Scale { factor: 2 },
+            &(),
+            &connection,
+            &qh,
+        );
+        assert!(!state.outputs_stale);
+        <WaylandCapture as Dispatch<WlOutput, ()>>::event(
+            &mut state,
+            &output,
+            wayland_client::protocol::wl_output::Event::Done,
+            &(),
+            &connection,
+            &qh,
+        );
+        assert!(state.outputs_stale);
+
+        state.outputs_stale = false;
+        state.invalidate_outputs();
+        assert!(state.outputs_stale);
+    }
 }
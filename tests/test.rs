@@ -1,4 +1,6 @@
-use grim_rs::{Box as GrimBox, CaptureParameters, CaptureResult, Grim};
+use grim_rs::{
+    Box as GrimBox, CaptureOptions, CaptureParameters, CaptureResult, Grim, ScaleFilter,
+};
 use std::collections::HashMap;
 
 #[test]
@@ -127,6 +129,19 @@ fn test_png_compression_levels() {
     }
 }
 
+#[test]
+fn test_capture_all_to_png_dimensions() {
+    if let Ok(mut grim) = Grim::new() {
+        if let Ok(capture) = grim.capture_all() {
+            let png_data = grim.capture_all_to_png(6).unwrap();
+            assert!(png_data.starts_with(b"\x89PNG\r\n\x1a\n"));
+            let decoded = image::load_from_memory(&png_data).unwrap();
+            assert_eq!(decoded.width(), capture.width());
+            assert_eq!(decoded.height(), capture.height());
+        }
+    }
+}
+
 #[test]
 fn test_ppm_format_generation() {
     let test_data = vec![255u8; 10 * 10 * 4];
@@ -222,6 +237,196 @@ fn test_scale_functionality_validation() {
     }
 }
 
+#[test]
+fn test_capture_with_region_scale_and_cursor() {
+    if let Ok(mut grim) = Grim::new() {
+        let options = CaptureOptions::new()
+            .region(GrimBox::new(0, 0, 200, 100))
+            .scale(0.5)
+            .overlay_cursor(true)
+            .scale_filter(ScaleFilter::Triangle);
+        if let Ok(result) = grim.capture(&options) {
+            assert_eq!(result.width(), 100);
+            assert_eq!(result.height(), 50);
+            assert_eq!(result.data().len(), 100 * 50 * 4);
+        }
+    }
+}
+
+#[test]
+fn test_capture_output_with_region_is_clipped() {
+    if let Ok(mut grim) = Grim::new() {
+        let Ok(outputs) = grim.get_outputs() else {
+            return;
+        };
+        let Some(output) = outputs.first() else {
+            return;
+        };
+        let geometry = output.geometry();
+        // Half of the region hangs off the left edge of the output.
+        let region = GrimBox::new(geometry.x() - 50, geometry.y(), 100, 10);
+        let options = CaptureOptions::new().output(output.name()).region(region);
+        if let Ok(result) = grim.capture(&options) {
+            assert_eq!(result.width(), 50);
+            assert_eq!(result.height(), 10);
+        }
+    }
+}
+
+#[test]
+fn test_capture_all_with_cursor_matches_capture_all_size() {
+    if let Ok(mut grim) = Grim::new() {
+        if let (Ok(plain), Ok(with_cursor)) = (grim.capture_all(), grim.capture_all_with_cursor()) {
+            assert_eq!(plain.width(), with_cursor.width());
+            assert_eq!(plain.height(), with_cursor.height());
+        }
+    }
+}
+
+#[test]
+fn test_capture_region_with_cursor_keeps_region_size() {
+    if let Ok(mut grim) = Grim::new() {
+        let region = GrimBox::new(0, 0, 64, 48);
+        if let Ok(result) = grim.capture_region_with_cursor(region) {
+            assert_eq!(result.width(), 64);
+            assert_eq!(result.height(), 48);
+        }
+    }
+}
+
+#[test]
+fn test_capture_unknown_output_fails() {
+    if let Ok(mut grim) = Grim::new() {
+        let options = CaptureOptions::new().output("no-such-output");
+        assert!(matches!(
+            grim.capture(&options),
+            Err(grim_rs::Error::OutputNotFound(_))
+        ));
+    }
+}
+
+#[test]
+fn test_capture_outputs_with_scale_uses_parameter_scale() {
+    if let Ok(mut grim) = Grim::new() {
+        let outputs = match grim.get_outputs() {
+            Ok(outputs) if !outputs.is_empty() => outputs,
+            _ => return,
+        };
+        let name = outputs[0].name().to_string();
+
+        let full = grim
+            .capture_outputs_with_scale(vec![CaptureParameters::new(name.clone())], 1.0)
+            .unwrap();
+        let half = grim
+            .capture_outputs_with_scale(vec![CaptureParameters::new(name.clone()).scale(0.5)], 1.0)
+            .unwrap();
+
+        let full = full.get(&name).unwrap();
+        let half = half.get(&name).unwrap();
+        assert_eq!(half.width(), (full.width() as f64 * 0.5) as u32);
+        assert_eq!(half.height(), (full.height() as f64 * 0.5) as u32);
+    }
+}
+
+#[test]
+fn test_capture_outputs_records_placements() {
+    if let Ok(mut grim) = Grim::new() {
+        let outputs = match grim.get_outputs() {
+            Ok(outputs) if !outputs.is_empty() => outputs,
+            _ => return,
+        };
+        let parameters = outputs
+            .iter()
+            .map(|output| CaptureParameters::new(output.name()))
+            .collect();
+        if let Ok(results) = grim.capture_outputs_with_scale(parameters, 0.5) {
+            for output in &outputs {
+                let placement = results.placement(output.name()).unwrap();
+                assert_eq!(placement.geometry(), output.geometry());
+                assert_eq!(placement.scale(), output.scale());
+            }
+        }
+    }
+}
+
+#[test]
+fn test_zero_capture_timeout_times_out() {
+    if let Ok(mut grim) = Grim::new() {
+        grim.set_capture_timeout(std::time::Duration::ZERO);
+        assert!(matches!(
+            grim.capture_all(),
+            Err(grim_rs::Error::Timeout { .. }) | Err(grim_rs::Error::NoOutputs)
+        ));
+    }
+}
+
+#[test]
+fn test_with_outputs_matches_get_outputs() {
+    if let Ok(mut grim) = Grim::new() {
+        if let Ok(outputs) = grim.get_outputs() {
+            let names: Vec<String> = outputs.iter().map(|o| o.name().to_string()).collect();
+            let borrowed = grim
+                .with_outputs(|outputs| {
+                    outputs
+                        .iter()
+                        .map(|o| o.name().to_string())
+                        .collect::<Vec<_>>()
+                })
+                .unwrap();
+            assert_eq!(names, borrowed);
+        }
+    }
+}
+
+#[test]
+fn test_refresh_outputs_keeps_output_list() {
+    if let Ok(mut grim) = Grim::new() {
+        if let Ok(before) = grim.get_outputs() {
+            grim.refresh_outputs().unwrap();
+            let after = grim.get_outputs().unwrap();
+            assert_eq!(before, after);
+        }
+    }
+}
+
+#[test]
+fn test_get_output_by_name() {
+    if let Ok(mut grim) = Grim::new() {
+        if let Ok(outputs) = grim.get_outputs() {
+            for output in &outputs {
+                assert_eq!(&grim.get_output(output.name()).unwrap(), output);
+                assert!(grim.has_output(output.name()).unwrap());
+            }
+            assert!(matches!(
+                grim.get_output("nonexistent-output"),
+                Err(grim_rs::Error::OutputNotFound(_))
+            ));
+            assert!(!grim.has_output("nonexistent-output").unwrap());
+        }
+    }
+}
+
+#[test]
+fn test_capture_output_by_index() {
+    if let Ok(mut grim) = Grim::new() {
+        if let Ok(outputs) = grim.get_outputs() {
+            if let Ok(result) = grim.capture_output_by_index(0) {
+                let expected = grim.capture_output(outputs[0].name()).unwrap();
+                assert_eq!(
+                    (result.width(), result.height()),
+                    (expected.width(), expected.height())
+                );
+            }
+            match grim.capture_output_by_index(outputs.len()) {
+                Err(grim_rs::Error::OutputNotFound(message)) => {
+                    assert!(message.contains(&outputs.len().to_string()));
+                }
+                other => panic!("expected OutputNotFound, got {:?}", other.map(|_| ())),
+            }
+        }
+    }
+}
+
 #[test]
 fn test_geometry_bounds_checking() {
     let invalid_box = GrimBox::new(0, 0, -10, 100);
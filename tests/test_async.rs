@@ -0,0 +1,95 @@
+#![cfg(feature = "tokio")]
+
+use grim_rs::{AsyncGrim, Box as GrimBox, CaptureOptions, CaptureParameters, Error, Grim};
+
+fn assert_send_sync<T: Send + Sync>() {}
+
+#[test]
+fn async_grim_is_send_and_sync() {
+    assert_send_sync::<AsyncGrim>();
+}
+
+#[tokio::test]
+async fn async_capture_all() {
+    let grim = match AsyncGrim::new().await {
+        Ok(grim) => grim,
+        Err(_) => return,
+    };
+
+    match grim.capture_all_async().await {
+        Ok(capture) => {
+            assert_eq!(
+                capture.data().len(),
+                (capture.width() * capture.height() * 4) as usize
+            );
+        }
+        Err(Error::NoOutputs) => {}
+        Err(e) => panic!("Unexpected error: {:?}", e),
+    }
+}
+
+#[tokio::test]
+async fn async_capture_output_and_region() {
+    let grim = match AsyncGrim::new().await {
+        Ok(grim) => grim,
+        Err(_) => return,
+    };
+    let outputs = match grim.get_outputs_async().await {
+        Ok(outputs) => outputs,
+        Err(_) => return,
+    };
+    let output = &outputs[0];
+
+    let capture = grim.capture_output_async(output.name()).await.unwrap();
+    assert!(capture.width() > 0 && capture.height() > 0);
+
+    let geometry = output.geometry();
+    let region = GrimBox::new(geometry.x(), geometry.y(), 10, 10);
+    let capture = grim.capture_region_async(region).await.unwrap();
+    assert_eq!(
+        capture.data().len(),
+        (capture.width() * capture.height() * 4) as usize
+    );
+
+    assert!(matches!(
+        grim.capture_output_async("nonexistent-output").await,
+        Err(Error::OutputNotFound(_))
+    ));
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn async_capture_outputs_on_current_thread_runtime() {
+    let grim = match Grim::new() {
+        Ok(grim) => grim.into_async(),
+        Err(_) => return,
+    };
+    let outputs = match grim.get_outputs_async().await {
+        Ok(outputs) => outputs,
+        Err(_) => return,
+    };
+
+    let parameters = outputs
+        .iter()
+        .map(|output| CaptureParameters::new(output.name()))
+        .collect();
+    let results = grim.capture_outputs_async(parameters).await.unwrap();
+    assert_eq!(results.outputs().len(), outputs.len());
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn async_captures_on_one_connection_run_in_turn() {
+    let grim = match AsyncGrim::new().await {
+        Ok(grim) => grim,
+        Err(_) => return,
+    };
+    let other = grim.clone();
+    let half_scale = CaptureOptions::new().scale(0.5);
+
+    let (full, half) = tokio::join!(grim.capture_all_async(), other.capture_async(&half_scale),);
+    let (full, half) = match (full, half) {
+        (Ok(full), Ok(half)) => (full, half),
+        _ => return,
+    };
+    assert_eq!(half.width(), (full.width() as f64 * 0.5) as u32);
+    assert_eq!(half.height(), (full.height() as f64 * 0.5) as u32);
+}
@@ -1,5 +1,5 @@
 use grim_rs::geometry::Box;
-use grim_rs::{CaptureParameters, CaptureResult, MultiOutputCaptureResult};
+use grim_rs::{CaptureParameters, CaptureResult, MultiOutputCaptureResult, OutputPlacement};
 use std::collections::HashMap;
 
 #[test]
@@ -44,7 +44,7 @@ fn capture_parameters_builder_pattern_works() {
 
     assert_eq!(params.output_name(), "HDMI-A-1");
     assert_eq!(params.region_ref(), Some(&region));
-    assert_eq!(params.overlay_cursor_enabled(), true);
+    assert!(params.overlay_cursor_enabled());
     assert_eq!(params.scale_factor(), Some(2.0));
 }
 
@@ -54,7 +54,7 @@ fn capture_parameters_minimal_construction() {
 
     assert_eq!(params.output_name(), "eDP-1");
     assert_eq!(params.region_ref(), None);
-    assert_eq!(params.overlay_cursor_enabled(), false);
+    assert!(!params.overlay_cursor_enabled());
     assert_eq!(params.scale_factor(), None);
 }
 
@@ -79,7 +79,7 @@ fn capture_parameters_partial_builder() {
 
     assert_eq!(params.output_name(), "HDMI-A-2");
     assert_eq!(params.region_ref(), None);
-    assert_eq!(params.overlay_cursor_enabled(), true);
+    assert!(params.overlay_cursor_enabled());
     assert_eq!(params.scale_factor(), None);
 }
 
@@ -141,6 +141,30 @@ fn multi_output_capture_result_into_outputs_consumes() {
     assert!(owned.contains_key("eDP-1"));
 }
 
+#[test]
+fn multi_output_capture_result_placements() {
+    let mut outputs = HashMap::new();
+    outputs.insert(
+        "HDMI-A-1".to_string(),
+        CaptureResult::new(vec![255; 100], 10, 10),
+    );
+
+    let result = MultiOutputCaptureResult::new(outputs.clone());
+    assert!(result.placement("HDMI-A-1").is_none());
+    assert!(result.placements().is_empty());
+
+    let mut placements = HashMap::new();
+    placements.insert(
+        "HDMI-A-1".to_string(),
+        OutputPlacement::new(Box::new(1920, 0, 5, 5), 2),
+    );
+    let result = MultiOutputCaptureResult::new(outputs).with_placements(placements);
+    let placement = result.placement("HDMI-A-1").unwrap();
+    assert_eq!(placement.geometry(), &Box::new(1920, 0, 5, 5));
+    assert_eq!(placement.scale(), 2);
+    assert!(result.get("HDMI-A-1").is_some());
+}
+
 #[test]
 fn capture_result_data_returns_slice() {
     let data = vec![1, 2, 3, 4, 5];
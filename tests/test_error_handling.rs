@@ -1,11 +1,81 @@
-use grim_rs::error::Error;
+use grim_rs::error::{Error, ErrorKind};
 
 #[test]
 fn test_buffer_creation_error_with_context() {
-    let err =
-        Error::BufferCreation("failed to create temporary file: Permission denied".to_string());
-    assert!(err.to_string().contains("Buffer creation failed"));
-    assert!(err.to_string().contains("Permission denied"));
+    let io_err = std::io::Error::new(std::io::ErrorKind::PermissionDenied, "Permission denied");
+    let err = Error::buffer_creation("failed to create temporary file").with_source(io_err);
+    // The cause is reported by `source()`, not repeated in the message.
+    assert_eq!(
+        err.to_string(),
+        "Buffer creation failed: failed to create temporary file"
+    );
+    let source = std::error::Error::source(&err).expect("BufferCreation should keep its cause");
+    assert_eq!(source.to_string(), "Permission denied");
+}
+
+#[test]
+fn test_wayland_errors_expose_source() {
+    use std::error::Error as _;
+
+    let io_err = std::io::Error::new(std::io::ErrorKind::ConnectionRefused, "no socket");
+    let err = Error::wayland_connection("Failed to connect to Wayland").with_source(io_err);
+
+    let source = err
+        .source()
+        .expect("WaylandConnection should keep its cause");
+    let io_source = source
+        .downcast_ref::<std::io::Error>()
+        .expect("source should be the io::Error");
+    assert_eq!(io_source.kind(), std::io::ErrorKind::ConnectionRefused);
+}
+
+#[test]
+fn test_error_without_source() {
+    use std::error::Error as _;
+
+    let err = Error::frame_capture("Compositor failed to capture frame");
+    assert!(err.source().is_none());
+    assert_eq!(
+        err.to_string(),
+        "Frame capture failed: Compositor failed to capture frame"
+    );
+    // Only the Wayland and buffer errors carry a cause.
+    let err = Error::NoOutputs.with_source("ignored");
+    assert!(matches!(err, Error::NoOutputs));
+}
+
+#[test]
+fn test_error_kind() {
+    assert_eq!(
+        Error::wayland_connection("broken pipe").kind(),
+        ErrorKind::Connection
+    );
+    assert_eq!(
+        Error::UnsupportedProtocol("zwlr_screencopy_manager_v1".to_string()).kind(),
+        ErrorKind::Protocol
+    );
+    assert_eq!(Error::frame_capture("failed").kind(), ErrorKind::Capture);
+    assert_eq!(Error::buffer_creation("failed").kind(), ErrorKind::Capture);
+    assert_eq!(
+        Error::Io(std::io::Error::other("disk full")).kind(),
+        ErrorKind::Io
+    );
+    assert_eq!(
+        Error::InvalidGeometry("10,10 bad".to_string()).kind(),
+        ErrorKind::InvalidInput
+    );
+    assert_eq!(
+        Error::InvalidArgument("fps must be positive".to_string()).kind(),
+        ErrorKind::InvalidInput
+    );
+    assert_eq!(
+        Error::Timeout {
+            waited: std::time::Duration::from_secs(1)
+        }
+        .kind(),
+        ErrorKind::Timeout
+    );
+    assert_eq!(Error::SelectionCancelled.kind(), ErrorKind::Cancelled);
 }
 
 #[test]
@@ -20,6 +90,24 @@ fn test_io_with_context_error() {
     assert!(err.to_string().contains("file not found"));
 }
 
+#[test]
+fn test_io_with_context_exposes_source() {
+    use std::error::Error as _;
+
+    let io_err = std::io::Error::new(std::io::ErrorKind::PermissionDenied, "access denied");
+    let err = Error::IoWithContext {
+        operation: "writing PNG data to file".to_string(),
+        source: io_err,
+    };
+
+    let source = err.source().expect("IoWithContext should have a source");
+    let io_source = source
+        .downcast_ref::<std::io::Error>()
+        .expect("source should be the io::Error");
+    assert_eq!(io_source.kind(), std::io::ErrorKind::PermissionDenied);
+    assert_eq!(io_source.to_string(), "access denied");
+}
+
 #[test]
 fn test_transform_not_supported_error() {
     let err = Error::TransformNotSupported("unsupported transform type".to_string());
@@ -46,6 +134,20 @@ fn test_scaling_failed_error() {
     assert!(err.to_string().contains("960x540"));
 }
 
+#[test]
+fn test_timeout_error_reports_elapsed_time() {
+    let err = Error::Timeout {
+        waited: std::time::Duration::from_millis(1500),
+    };
+    assert!(err.to_string().contains("Timed out after 1.5s"));
+    assert!(matches!(err, Error::Timeout { waited } if waited.as_millis() == 1500));
+}
+
+#[test]
+fn test_cancelled_error() {
+    assert_eq!(Error::Cancelled.to_string(), "Operation was cancelled");
+}
+
 #[test]
 fn test_existing_error_types_still_work() {
     let err = Error::NoOutputs;
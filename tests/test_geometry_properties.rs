@@ -12,6 +12,25 @@ proptest! {
         prop_assert_eq!(b.height(), h);
     }
 
+    #[test]
+    fn box_display_round_trips_through_parse(x in -10000i32..10000, y in -10000i32..10000,
+                                             w in 0i32..10000, h in 0i32..10000) {
+        let b = Box::new(x, y, w, h);
+        let parsed: Box = b.to_string().parse().unwrap();
+        prop_assert_eq!(parsed, b);
+    }
+
+    #[test]
+    fn fractional_parse_covers_requested_area(x in -5000.0f64..5000.0, y in -5000.0f64..5000.0,
+                                              w in 0.0f64..5000.0, h in 0.0f64..5000.0) {
+        let parsed: Box = format!("{},{} {}x{}", x, y, w, h).parse().unwrap();
+        prop_assert!(parsed.x() as f64 <= x && parsed.y() as f64 <= y);
+        prop_assert!((parsed.x() + parsed.width()) as f64 >= x + w);
+        prop_assert!((parsed.y() + parsed.height()) as f64 >= y + h);
+        prop_assert!(parsed.width() as f64 <= w + 2.0);
+        prop_assert!(parsed.height() as f64 <= h + 2.0);
+    }
+
     #[test]
     fn box_is_empty_iff_zero_area(x in -1000i32..1000, y in -1000i32..1000,
                                    w in -100i32..100, h in -100i32..100) {
@@ -118,6 +137,59 @@ proptest! {
         prop_assert_eq!(empty2.intersection(&empty3), None);
     }
 
+    #[test]
+    fn union_is_commutative(
+        x1 in -1000i32..1000, y1 in -1000i32..1000, w1 in 1i32..500, h1 in 1i32..500,
+        x2 in -1000i32..1000, y2 in -1000i32..1000, w2 in 1i32..500, h2 in 1i32..500
+    ) {
+        let box1 = Box::new(x1, y1, w1, h1);
+        let box2 = Box::new(x2, y2, w2, h2);
+
+        prop_assert_eq!(box1.union(&box2), box2.union(&box1), "Union should be commutative");
+    }
+
+    #[test]
+    fn union_contains_both_inputs(
+        x1 in -1000i32..1000, y1 in -1000i32..1000, w1 in 1i32..500, h1 in 1i32..500,
+        x2 in -1000i32..1000, y2 in -1000i32..1000, w2 in 1i32..500, h2 in 1i32..500
+    ) {
+        let box1 = Box::new(x1, y1, w1, h1);
+        let box2 = Box::new(x2, y2, w2, h2);
+        let union = box1.union(&box2);
+
+        for b in [box1, box2] {
+            prop_assert!(union.x() <= b.x());
+            prop_assert!(union.y() <= b.y());
+            prop_assert!(union.x() + union.width() >= b.x() + b.width());
+            prop_assert!(union.y() + union.height() >= b.y() + b.height());
+            prop_assert_eq!(union.intersection(&b), Some(b));
+        }
+    }
+
+    #[test]
+    fn union_with_empty_box_is_identity(
+        x1 in -1000i32..1000, y1 in -1000i32..1000, w1 in 1i32..500, h1 in 1i32..500,
+        x2 in -1000i32..1000, y2 in -1000i32..1000, w2 in -100i32..=0, h2 in -100i32..500
+    ) {
+        let box1 = Box::new(x1, y1, w1, h1);
+        let empty = Box::new(x2, y2, w2, h2);
+
+        prop_assert_eq!(box1.union(&empty), box1);
+        prop_assert_eq!(empty.union(&box1), box1);
+    }
+
+    #[test]
+    fn union_all_matches_pairwise_union(
+        boxes in prop::collection::vec(
+            (-1000i32..1000, -1000i32..1000, 1i32..500, 1i32..500)
+                .prop_map(|(x, y, w, h)| Box::new(x, y, w, h)),
+            1..8
+        )
+    ) {
+        let expected = boxes[1..].iter().fold(boxes[0], |acc, b| acc.union(b));
+        prop_assert_eq!(Box::union_all(&boxes), Some(expected));
+    }
+
     #[test]
     fn parse_and_display_roundtrip(x in -1000i32..1000, y in -1000i32..1000,
                                      w in 0i32..500, h in 0i32..500) {
@@ -0,0 +1,97 @@
+use grim_rs::{CaptureStreamHandle, Error, ErrorKind, Grim, SharedGrim};
+use std::thread;
+
+fn assert_send<T: Send>() {}
+fn assert_send_sync<T: Send + Sync>() {}
+
+#[test]
+fn grim_is_send() {
+    assert_send::<Grim>();
+}
+
+#[test]
+fn shared_grim_is_send_and_sync() {
+    assert_send_sync::<SharedGrim>();
+}
+
+#[test]
+fn shared_grim_serializes_concurrent_captures() {
+    let grim = match SharedGrim::new() {
+        Ok(grim) => grim,
+        Err(_) => return,
+    };
+
+    let handles: Vec<_> = (0..4)
+        .map(|_| {
+            let grim = grim.clone();
+            thread::spawn(move || grim.capture_all())
+        })
+        .collect();
+
+    for handle in handles {
+        match handle.join().expect("capture thread panicked") {
+            Ok(capture) => {
+                assert_eq!(
+                    capture.data().len(),
+                    (capture.width() * capture.height() * 4) as usize
+                );
+            }
+            Err(Error::NoOutputs) => {}
+            Err(e) => panic!("Unexpected error: {:?}", e),
+        }
+    }
+}
+
+#[test]
+fn shared_grim_with_gives_exclusive_access() {
+    let grim = match SharedGrim::new() {
+        Ok(grim) => grim,
+        Err(_) => return,
+    };
+
+    let outputs = grim.with(|grim| grim.get_outputs());
+    match outputs {
+        Ok(outputs) => assert!(!outputs.is_empty()),
+        Err(Error::NoOutputs) => {}
+        Err(e) => panic!("Unexpected error: {:?}", e),
+    }
+}
+
+#[test]
+fn capture_stream_handle_is_send() {
+    assert_send::<CaptureStreamHandle>();
+}
+
+#[test]
+fn capture_stream_rejects_zero_fps() {
+    let grim = match SharedGrim::new() {
+        Ok(grim) => grim,
+        Err(_) => return,
+    };
+
+    match grim.spawn_capture_stream("DP-1", 0) {
+        Err(e @ Error::InvalidArgument(_)) => assert_eq!(e.kind(), ErrorKind::InvalidInput),
+        other => panic!("Expected InvalidArgument, got {:?}", other.map(|_| ())),
+    }
+}
+
+#[test]
+fn capture_stream_stops_when_handle_is_dropped() {
+    let grim = match SharedGrim::new() {
+        Ok(grim) => grim,
+        Err(_) => return,
+    };
+    let name = match grim.get_outputs() {
+        Ok(outputs) => outputs[0].name().to_string(),
+        Err(_) => return,
+    };
+
+    let (stream, frames) = grim.spawn_capture_stream(&name, 30).unwrap();
+    let first = frames.recv().expect("stream ended before the first frame");
+    if let Ok(capture) = first {
+        assert!(capture.width() > 0 && capture.height() > 0);
+    }
+    drop(stream);
+    // The thread has exited, so the channel drains and disconnects.
+    assert!(frames.iter().count() <= 2);
+}